@@ -0,0 +1,15 @@
+//! The functions the integration spends most of its time in, the same ones as timed by the
+//! in-app diagnostic; run with `cargo bench`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use coriolis_demo_3d::simulation::kernels;
+
+fn physics_kernels(c: &mut Criterion) {
+    for mut kernel in kernels() {
+        c.bench_function(kernel.name, |b| b.iter(&mut kernel.run));
+    }
+}
+
+criterion_group!(benches, physics_kernels);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: coriolis-demo-3d [scenario.ron] [--start] [--wind FILE] \
+                     [--headless [--duration SECONDS] [--interval SECONDS] [--output FILE]]";
+
+/// Options given on the command line
+pub struct Options {
+    pub scenario: Option<PathBuf>,
+    /// Start the simulation immediately
+    pub autostart: bool,
+    /// GRIB2 or NetCDF file with the wind blowing in the simulation
+    pub wind: Option<PathBuf>,
+    /// Run without a window and write the trajectories out
+    pub headless: bool,
+    /// Simulated time in seconds for the headless mode
+    pub duration: f64,
+    /// Time between the written samples in seconds; every step is written if not given
+    pub interval: Option<f64>,
+    /// File to write the trajectories to instead of stdout
+    pub output: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            scenario: None,
+            autostart: false,
+            wind: None,
+            headless: false,
+            duration: 3600.0,
+            interval: None,
+            output: None,
+        }
+    }
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--start" => options.autostart = true,
+                "--headless" => options.headless = true,
+                "--wind" => {
+                    let path = args.next().ok_or_else(|| missing_value(&arg))?;
+                    options.wind = Some(path.into());
+                }
+                "--duration" => options.duration = parse_number(&arg, args.next())?,
+                "--interval" => options.interval = Some(parse_number(&arg, args.next())?),
+                "--output" => {
+                    let path = args.next().ok_or_else(|| missing_value(&arg))?;
+                    options.output = Some(path.into());
+                }
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                _ if arg.starts_with("--") => {
+                    return Err(format!("unknown option {}\n{}", arg, USAGE));
+                }
+                _ => options.scenario = Some(arg.into()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn missing_value(option: &str) -> String {
+    format!("{} requires a value\n{}", option, USAGE)
+}
+
+fn parse_number(option: &str, value: Option<String>) -> Result<f64, String> {
+    let value = value.ok_or_else(|| missing_value(option))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", option, value))
+}
@@ -0,0 +1,87 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    simulation::{ObjectIntegrator, OMEGA},
+    state::State,
+};
+
+/// Runs the simulation for `duration` seconds without a window, writing the trajectories of
+/// all the objects as CSV to `output` or to stdout
+pub fn run(
+    state: &mut State,
+    duration: f64,
+    interval: Option<f64>,
+    output: Option<&Path>,
+) -> io::Result<()> {
+    // the simulated time would never reach the end
+    let invalid = |message: &str| Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+    if !duration.is_finite() {
+        return invalid("the duration must be a finite number of seconds");
+    }
+    if interval.is_some_and(|interval| !interval.is_finite() || interval < 0.0) {
+        return invalid("the interval must be a finite, non-negative number of seconds");
+    }
+    if !(state.time_step.is_finite() && state.time_step > 0.0) {
+        return invalid("the time step must be a positive number of seconds");
+    }
+
+    let out: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let mut out = BufWriter::new(out);
+
+    writeln!(
+        out,
+        "object,t,lat,lon,elev,vel_e,vel_n,vel_u,acc_coriolis,acc_centrifugal,acc_euler,acc_other"
+    )?;
+
+    let mut integrator = ObjectIntegrator::new(10.0);
+    integrator.method = state.integration;
+    let mut next_sample = 0.0;
+    loop {
+        if state.t >= next_sample {
+            write_sample(&mut out, state)?;
+            next_sample = match interval {
+                Some(interval) => next_sample + interval,
+                None => state.t,
+            };
+        }
+        if state.t >= duration {
+            break;
+        }
+        state.step(&mut integrator);
+    }
+
+    out.flush()
+}
+
+fn write_sample(out: &mut impl Write, state: &State) -> io::Result<()> {
+    for (i, obj) in state.objects.iter().enumerate() {
+        let sample = obj.sample(OMEGA);
+        let (lat, lon, elev) = sample.lat_lon_elev();
+        let (vel_e, vel_n, vel_u) = sample.east_north_up();
+        let forces = sample.forces;
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            i,
+            sample.t,
+            lat,
+            lon,
+            elev,
+            vel_e,
+            vel_n,
+            vel_u,
+            forces.coriolis.norm(),
+            forces.centrifugal.norm(),
+            forces.euler.norm(),
+            forces.other.norm()
+        )?;
+    }
+    Ok(())
+}
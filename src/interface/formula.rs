@@ -0,0 +1,300 @@
+//! A small layout engine for formulas written in a subset of TeX: groups in braces,
+//! superscripts and subscripts, `\frac`, `\sqrt`, Greek letters and a few operators
+
+use std::{iter::Peekable, str::Chars};
+
+use egui::{epaint::Shape, FontId, Pos2, Sense, Stroke, TextStyle, Ui, Vec2};
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Row(Vec<Node>),
+    Scripts {
+        base: Box<Node>,
+        sup: Option<Box<Node>>,
+        sub: Option<Box<Node>>,
+    },
+    Frac(Box<Node>, Box<Node>),
+    Sqrt(Box<Node>),
+}
+
+/// Displays a formula inline in the UI
+pub fn formula(ui: &mut Ui, tex: &str) {
+    let font_size = TextStyle::Body.resolve(ui.style()).size * 1.1;
+    let node = Node::Row(parse_row(&mut tex.chars().peekable()));
+    let layout = layout(ui, &node, font_size);
+
+    let size = Vec2::new(layout.width, layout.ascent + layout.descent);
+    let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+    let origin = Vec2::new(rect.min.x, rect.min.y + layout.ascent);
+    let color = ui.visuals().text_color();
+    let shapes = layout
+        .items
+        .into_iter()
+        .map(|item| {
+            let mut shape = match item {
+                Item::Text(pos, text, size) => {
+                    let galley = ui
+                        .fonts()
+                        .layout_no_wrap(text, FontId::proportional(size), color);
+                    Shape::galley(pos, galley)
+                }
+                Item::Line(points) => Shape::line_segment(points, Stroke::new(1.0, color)),
+            };
+            shape.translate(origin);
+            shape
+        })
+        .collect();
+    ui.painter().extend(shapes);
+}
+
+fn parse_row(chars: &mut Peekable<Chars>) -> Vec<Node> {
+    let mut nodes: Vec<Node> = vec![];
+    while let Some(&c) = chars.peek() {
+        match c {
+            '}' => break,
+            '^' | '_' => {
+                chars.next();
+                let script = Box::new(parse_atom(chars));
+                let base = nodes.pop().unwrap_or_else(|| Node::Text(String::new()));
+                let node = match base {
+                    Node::Scripts { base, sup, sub } if c == '^' && sup.is_none() => {
+                        Node::Scripts {
+                            base,
+                            sup: Some(script),
+                            sub,
+                        }
+                    }
+                    Node::Scripts { base, sup, sub } if c == '_' && sub.is_none() => {
+                        Node::Scripts {
+                            base,
+                            sup,
+                            sub: Some(script),
+                        }
+                    }
+                    base if c == '^' => Node::Scripts {
+                        base: Box::new(base),
+                        sup: Some(script),
+                        sub: None,
+                    },
+                    base => Node::Scripts {
+                        base: Box::new(base),
+                        sup: None,
+                        sub: Some(script),
+                    },
+                };
+                nodes.push(node);
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            _ => nodes.push(parse_atom(chars)),
+        }
+    }
+    nodes
+}
+
+fn parse_atom(chars: &mut Peekable<Chars>) -> Node {
+    match chars.next() {
+        Some('{') => {
+            let row = parse_row(chars);
+            chars.next();
+            Node::Row(row)
+        }
+        Some('\\') => parse_command(chars),
+        Some(c @ ('=' | '+' | '<' | '>')) => Node::Text(format!(" {} ", c)),
+        Some('-') => Node::Text(" − ".to_string()),
+        Some(c) => Node::Text(c.to_string()),
+        None => Node::Text(String::new()),
+    }
+}
+
+fn parse_command(chars: &mut Peekable<Chars>) -> Node {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_alphabetic() {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+    if name.is_empty() {
+        // a single non-letter character, like "\," or "\{"
+        return match chars.next() {
+            Some(',') => Node::Text("\u{2009}".to_string()),
+            Some(c) => Node::Text(c.to_string()),
+            None => Node::Text(String::new()),
+        };
+    }
+
+    let symbol = match name.as_str() {
+        "frac" => {
+            let num = parse_atom(chars);
+            let den = parse_atom(chars);
+            return Node::Frac(Box::new(num), Box::new(den));
+        }
+        "sqrt" => return Node::Sqrt(Box::new(parse_atom(chars))),
+        "sin" | "cos" | "tan" | "ln" | "exp" => return Node::Text(format!("{}\u{2009}", name)),
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "Delta" => "Δ",
+        "varepsilon" | "epsilon" => "ε",
+        "theta" => "θ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "pi" => "π",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "varphi" | "phi" => "φ",
+        "Phi" => "Φ",
+        "omega" => "ω",
+        "Omega" => "Ω",
+        "times" => " × ",
+        "cdot" => "·",
+        "pm" => " ± ",
+        "approx" => " ≈ ",
+        "le" => " ≤ ",
+        "ge" => " ≥ ",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        _ => return Node::Text(name),
+    };
+    Node::Text(symbol.to_string())
+}
+
+enum Item {
+    Text(Pos2, String, f32),
+    Line([Pos2; 2]),
+}
+
+impl Item {
+    fn translate(&mut self, delta: Vec2) {
+        match self {
+            Item::Text(pos, _, _) => *pos += delta,
+            Item::Line(points) => {
+                points[0] += delta;
+                points[1] += delta;
+            }
+        }
+    }
+}
+
+/// A laid out piece of a formula, with coordinates relative to the left end of its baseline
+struct Layout {
+    width: f32,
+    ascent: f32,
+    descent: f32,
+    items: Vec<Item>,
+}
+
+impl Layout {
+    fn empty() -> Self {
+        Self {
+            width: 0.0,
+            ascent: 0.0,
+            descent: 0.0,
+            items: vec![],
+        }
+    }
+
+    /// Adds another layout with its baseline origin at `offset`
+    fn place(&mut self, other: Layout, offset: Vec2) {
+        self.width = self.width.max(offset.x + other.width);
+        self.ascent = self.ascent.max(other.ascent - offset.y);
+        self.descent = self.descent.max(other.descent + offset.y);
+        self.items.extend(other.items.into_iter().map(|mut item| {
+            item.translate(offset);
+            item
+        }));
+    }
+}
+
+fn layout(ui: &Ui, node: &Node, size: f32) -> Layout {
+    match node {
+        Node::Text(text) => {
+            let galley = ui.fonts().layout_no_wrap(
+                text.clone(),
+                FontId::proportional(size),
+                Default::default(),
+            );
+            let height = galley.size().y;
+            // the baseline is at about 80% of the row height
+            Layout {
+                width: galley.size().x,
+                ascent: 0.8 * height,
+                descent: 0.2 * height,
+                items: vec![Item::Text(
+                    Pos2::new(0.0, -0.8 * height),
+                    text.clone(),
+                    size,
+                )],
+            }
+        }
+        Node::Row(nodes) => {
+            let mut result = Layout::empty();
+            for node in nodes {
+                let x = result.width;
+                result.place(layout(ui, node, size), Vec2::new(x, 0.0));
+            }
+            result
+        }
+        Node::Scripts { base, sup, sub } => {
+            let mut result = layout(ui, base, size);
+            let x = result.width;
+            let script_size = size * 0.7;
+            if let Some(sup) = sup {
+                result.place(layout(ui, sup, script_size), Vec2::new(x, -0.45 * size));
+            }
+            if let Some(sub) = sub {
+                result.place(layout(ui, sub, script_size), Vec2::new(x, 0.25 * size));
+            }
+            result
+        }
+        Node::Frac(num, den) => {
+            let num = layout(ui, num, size * 0.9);
+            let den = layout(ui, den, size * 0.9);
+            let width = num.width.max(den.width) + 0.3 * size;
+            let axis = -0.3 * size;
+            let gap = 0.15 * size;
+
+            let mut result = Layout::empty();
+            let num_offset = Vec2::new(0.5 * (width - num.width), axis - gap - num.descent);
+            let den_offset = Vec2::new(0.5 * (width - den.width), axis + gap + den.ascent);
+            result.place(num, num_offset);
+            result.place(den, den_offset);
+            result.items.push(Item::Line([
+                Pos2::new(0.1 * size, axis),
+                Pos2::new(width - 0.1 * size, axis),
+            ]));
+            result.width = width;
+            result
+        }
+        Node::Sqrt(body) => {
+            let body = layout(ui, body, size);
+            let sign_width = 0.5 * size;
+            let top = -body.ascent - 0.1 * size;
+            let bottom = body.descent;
+
+            let mut result = Layout::empty();
+            let body_width = body.width;
+            result.place(body, Vec2::new(sign_width, 0.0));
+            let points = [
+                Pos2::new(0.0, -0.3 * size),
+                Pos2::new(0.2 * sign_width, -0.4 * size),
+                Pos2::new(0.5 * sign_width, bottom),
+                Pos2::new(0.9 * sign_width, top),
+                Pos2::new(sign_width + body_width, top),
+            ];
+            for segment in points.windows(2) {
+                result.items.push(Item::Line([segment[0], segment[1]]));
+            }
+            result.ascent = result.ascent.max(-top);
+            result
+        }
+    }
+}
@@ -0,0 +1,1564 @@
+mod formula;
+
+use egui::{
+    plot::{Bar, BarChart, Legend, Line, MarkerShape, Plot, PlotImage, Points, Value, Values},
+    Color32, Rect, Sense, Ui,
+};
+
+use crate::{
+    renderer::{colormap, CameraAttitude, Renderer},
+    simulation::{
+        coriolis_parameter, foucault_rate, horizontal_deflection, inertial_period, inertial_radius,
+        miss_distance, pos_to_lat_lon_elev, ring_diagnostics, split_at_antimeridian, Energy,
+        Forces, GroundStation, Object, RingSample, OMEGA,
+    },
+    state::{
+        DecimalSeparator, InitialStateDefinition, LinkKind, MapProjection, ObjectDescription,
+        ObjectKind, ObjectKindTag, QuizPhase, State, StateTag, SurfaceMode,
+    },
+};
+use formula::formula;
+
+/// What the user asked to do with an object in the state editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectAction {
+    None,
+    Remove,
+    Duplicate,
+    MoveUp,
+    MoveDown,
+}
+
+pub fn display_object(obj: &mut ObjectDescription, ui: &mut Ui) -> ObjectAction {
+    let mut action = ObjectAction::None;
+
+    ui.horizontal(|ui| {
+        ui.label(format!("{}", obj.kind.as_tag()));
+        ui.add(egui::TextEdit::singleline(&mut obj.name).hint_text("Name"));
+        if ui.button("Remove").clicked() {
+            action = ObjectAction::Remove;
+        }
+        if ui.button("Duplicate").clicked() {
+            action = ObjectAction::Duplicate;
+        }
+        if ui.button("⬆").on_hover_text("Move up").clicked() {
+            action = ObjectAction::MoveUp;
+        }
+        if ui.button("⬇").on_hover_text("Move down").clicked() {
+            action = ObjectAction::MoveDown;
+        }
+    });
+
+    // the position of a satellite follows from its orbital elements
+    if obj.kind.as_tag() != ObjectKindTag::Satellite {
+        ui.horizontal(|ui| {
+            ui.label("Latitude:");
+            ui.text_edit_singleline(&mut obj.lat);
+            ui.label("°");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Longitude:");
+            ui.text_edit_singleline(&mut obj.lon);
+            ui.label("°");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Elevation:");
+            ui.text_edit_singleline(&mut obj.elev);
+            ui.label("m");
+        });
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Trail in the frame:");
+        ui.checkbox(&mut obj.trail_frames.view, "of the view");
+        ui.checkbox(&mut obj.trail_frames.inertial, "inertial");
+        ui.checkbox(&mut obj.trail_frames.earth, "of the Earth");
+    });
+    ui.horizontal(|ui| {
+        ui.label("Trail length:");
+        ui.add(
+            egui::TextEdit::singleline(&mut obj.trail_duration)
+                .hint_text("whole path")
+                .desired_width(80.0),
+        );
+        ui.label("s");
+        ui.checkbox(&mut obj.trail_fade, "Fade out");
+    });
+    ui.horizontal(|ui| {
+        ui.label("Remove");
+        ui.add(
+            egui::TextEdit::singleline(&mut obj.remove_after_landing)
+                .hint_text("never")
+                .desired_width(50.0),
+        );
+        ui.label("s after landing, or above");
+        ui.add(
+            egui::TextEdit::singleline(&mut obj.remove_above)
+                .hint_text("never")
+                .desired_width(50.0),
+        );
+        ui.label("km");
+        ui.checkbox(&mut obj.keep_trail, "Keep the trail");
+    });
+    if obj.kind.as_tag() != ObjectKindTag::Satellite {
+        ui.horizontal(|ui| {
+            ui.label("On hitting the ground:");
+            egui::ComboBox::from_id_source("surface_mode")
+                .selected_text(format!("{}", obj.surface_mode))
+                .show_ui(ui, |ui| {
+                    for mode in SurfaceMode::ALL {
+                        ui.selectable_value(&mut obj.surface_mode, mode, format!("{}", mode));
+                    }
+                });
+            if obj.surface_mode == SurfaceMode::Bounce {
+                ui.label("Restitution:");
+                ui.add(egui::TextEdit::singleline(&mut obj.restitution).desired_width(40.0));
+            }
+        });
+    }
+
+    match &mut obj.kind {
+        ObjectKind::Free {
+            vel_e,
+            vel_n,
+            vel_u,
+            friction,
+            drag,
+            gravity,
+            charge,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Velocity east:");
+                ui.text_edit_singleline(vel_e);
+                ui.label("m/s");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Velocity north:");
+                ui.text_edit_singleline(vel_n);
+                ui.label("m/s");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Velocity up:");
+                ui.text_edit_singleline(vel_u);
+                ui.label("m/s");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Strength of gravity:");
+                ui.text_edit_singleline(gravity);
+                ui.label("g");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Friction coefficient:");
+                ui.text_edit_singleline(friction);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Drag coefficient:");
+                ui.text_edit_singleline(drag);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Charge to mass ratio:");
+                ui.text_edit_singleline(charge);
+                ui.label("C/kg");
+            });
+        }
+        ObjectKind::Cyclone {
+            n_particles,
+            radius,
+            vel,
+            depth,
+            low_radius,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Number of particles:");
+                ui.text_edit_singleline(n_particles);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Radius:");
+                ui.text_edit_singleline(radius);
+                ui.label("km");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Velocity:");
+                ui.text_edit_singleline(vel);
+                ui.label("m/s");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Depth of the low:");
+                ui.text_edit_singleline(depth);
+                ui.label("hPa");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Radius of the low:");
+                ui.text_edit_singleline(low_radius);
+                ui.label("km");
+            });
+        }
+        ObjectKind::ConvergingRing {
+            n_particles,
+            radius,
+            accel,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Number of particles:");
+                ui.text_edit_singleline(n_particles);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Radius:");
+                ui.text_edit_singleline(radius);
+                ui.label("km");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Inward acceleration:");
+                ui.text_edit_singleline(accel);
+                ui.label("m/s²");
+            });
+        }
+        ObjectKind::WeighingScale { vel_e, vel_n } => {
+            ui.horizontal(|ui| {
+                ui.label("Velocity east:");
+                ui.text_edit_singleline(vel_e);
+                ui.label("m/s");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Velocity north:");
+                ui.text_edit_singleline(vel_n);
+                ui.label("m/s");
+            });
+        }
+        ObjectKind::Satellite {
+            semi_major_axis,
+            eccentricity,
+            inclination,
+            raan,
+            arg_perigee,
+            true_anomaly,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Semi-major axis:");
+                ui.text_edit_singleline(semi_major_axis);
+                ui.label("km");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Eccentricity:");
+                ui.text_edit_singleline(eccentricity);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Inclination:");
+                ui.text_edit_singleline(inclination);
+                ui.label("°");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Right ascension of the ascending node:");
+                ui.text_edit_singleline(raan);
+                ui.label("°");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Argument of perigee:");
+                ui.text_edit_singleline(arg_perigee);
+                ui.label("°");
+            });
+            ui.horizontal(|ui| {
+                ui.label("True anomaly:");
+                ui.text_edit_singleline(true_anomaly);
+                ui.label("°");
+            });
+        }
+        ObjectKind::PlumbBob { exaggeration } => {
+            ui.horizontal(|ui| {
+                ui.label("Deviation exaggeration:");
+                ui.text_edit_singleline(exaggeration);
+                ui.label("×");
+            });
+        }
+        ObjectKind::Projectile {
+            speed,
+            azim,
+            angle,
+            drag,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Muzzle velocity:");
+                ui.text_edit_singleline(speed);
+                ui.label("m/s");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Azimuth:");
+                ui.text_edit_singleline(azim);
+                ui.label("°");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Elevation angle:");
+                ui.text_edit_singleline(angle);
+                ui.label("°");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Drag coefficient:");
+                ui.text_edit_singleline(drag);
+            });
+        }
+        ObjectKind::Balloon {
+            volume,
+            payload,
+            drag_area,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Volume:");
+                ui.text_edit_singleline(volume);
+                ui.label("m³");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Payload mass:");
+                ui.text_edit_singleline(payload);
+                ui.label("kg");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Drag area:");
+                ui.text_edit_singleline(drag_area);
+                ui.label("m²");
+            });
+        }
+        ObjectKind::Anticyclone {
+            n_particles,
+            vel,
+            excess,
+            high_radius,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Number of particles:");
+                ui.text_edit_singleline(n_particles);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Velocity:");
+                ui.text_edit_singleline(vel);
+                ui.label("m/s");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Excess pressure of the high:");
+                ui.text_edit_singleline(excess);
+                ui.label("hPa");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Radius of the high:");
+                ui.text_edit_singleline(high_radius);
+                ui.label("km");
+            });
+        }
+        ObjectKind::Foucault { azim, vel } | ObjectKind::Plane { azim, vel } => {
+            ui.horizontal(|ui| {
+                ui.label("Starting azimuth:");
+                ui.text_edit_singleline(azim);
+                ui.label("°");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Starting velocity:");
+                ui.text_edit_singleline(vel);
+                ui.label("m/s");
+            });
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Color:");
+        ui.color_edit_button_rgb(&mut obj.color);
+    });
+
+    ui.separator();
+
+    action
+}
+
+/// The editor of the springs and tethers between the objects of the initial state
+pub fn display_links(state_def: &mut InitialStateDefinition, ui: &mut Ui) {
+    let labels: Vec<_> = state_def
+        .objects
+        .iter()
+        .enumerate()
+        .map(|(index, obj)| {
+            if obj.name.is_empty() {
+                format!("{} #{}", obj.kind.as_tag(), index + 1)
+            } else {
+                obj.name.clone()
+            }
+        })
+        .collect();
+    ui.label("Links the first objects of the descriptions.");
+
+    let mut removed = None;
+    for (index, link) in state_def.links.iter_mut().enumerate() {
+        ui.push_id(index, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("kind")
+                    .selected_text(format!("{}", link.kind))
+                    .show_ui(ui, |ui| {
+                        for kind in LinkKind::ALL {
+                            ui.selectable_value(&mut link.kind, kind, format!("{}", kind));
+                        }
+                    });
+                for (id, end) in [("from", &mut link.from), ("to", &mut link.to)] {
+                    egui::ComboBox::from_id_source(id)
+                        .selected_text(labels.get(*end).map_or("-", String::as_str))
+                        .show_ui(ui, |ui| {
+                            for (i, label) in labels.iter().enumerate() {
+                                ui.selectable_value(end, i, label);
+                            }
+                        });
+                }
+                if ui.button("✖").on_hover_text("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Length:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut link.length)
+                        .hint_text("initial distance")
+                        .desired_width(80.0),
+                );
+                ui.label("m");
+                if link.kind == LinkKind::Spring {
+                    ui.label("Stiffness:");
+                    ui.add(egui::TextEdit::singleline(&mut link.stiffness).desired_width(60.0));
+                    ui.label("1/s²");
+                    ui.label("Damping:");
+                    ui.add(egui::TextEdit::singleline(&mut link.damping).desired_width(60.0));
+                    ui.label("1/s");
+                }
+            });
+        });
+    }
+    if let Some(index) = removed {
+        state_def.links.remove(index);
+    }
+    if ui
+        .add_enabled(state_def.objects.len() > 1, egui::Button::new("Add a link"))
+        .clicked()
+    {
+        state_def.links.push(Default::default());
+    }
+}
+
+/// Simulated time as days, hours, minutes and seconds
+fn format_time(t: f64) -> String {
+    let seconds = t.max(0.0).floor() as u64;
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    format!(
+        "{}d {:02}:{:02}:{:02}",
+        days,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// A single line with the most important quantities: the time, the speed of the simulation and
+/// the position of the selected (or followed) object
+pub fn display_status_bar(state: &State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    ui.horizontal(|ui| {
+        let max_t = state.render_settings.max_t;
+        // while running, the scene may lag behind by a fraction of a time step
+        if max_t < state.t && !state.running {
+            ui.label(format!(
+                "t = {} (of {})",
+                format_time(max_t),
+                format_time(state.t)
+            ));
+        } else {
+            ui.label(format!("t = {}", format_time(max_t)));
+        }
+        ui.separator();
+        if state.precompute.is_some() {
+            ui.label("Precomputing");
+        } else if state.running {
+            if state.speed.measured < 10.0 {
+                ui.label(format!("×{:.1}", separator.number(state.speed.measured)));
+            } else {
+                ui.label(format!("×{:.0}", separator.number(state.speed.measured)));
+            }
+            if let Some(event) = state.speed.slowed_for {
+                ui.colored_label(
+                    Color32::LIGHT_BLUE,
+                    format!("Slow motion: {}", event.describe(&state.objects)),
+                );
+            }
+        } else {
+            ui.colored_label(Color32::YELLOW, "Paused");
+        }
+        if state.recording.is_active() {
+            ui.colored_label(Color32::RED, "● Recording");
+        }
+        if let Some(message) = &state.blowup_message {
+            ui.separator();
+            ui.colored_label(Color32::RED, format!("⚠ {}", message));
+        }
+
+        let followed = (state.camera_state.tag == StateTag::Following)
+            .then_some(state.camera_state.following.obj);
+        let index = state.selected_object.or(followed);
+        if let Some((i, obj)) = index.and_then(|i| state.objects.get(i).map(|obj| (i, obj))) {
+            let sim_state = obj.state_at(max_t);
+            let pos = sim_state.pos().to_omega(OMEGA);
+            let (lat, lon, _) = pos_to_lat_lon_elev(pos.pos());
+            let speed = sim_state
+                .vel()
+                .to_omega(*sim_state.pos(), state.frame_omega())
+                .vel()
+                .norm();
+            ui.separator();
+            ui.label(format!(
+                "{}: {:.2}°, {:.2}°, {:.1} m/s",
+                obj.label(i),
+                separator.number(lat),
+                separator.number(lon),
+                separator.number(speed)
+            ));
+        }
+    });
+}
+
+pub fn display_color_legend(
+    ui: &mut Ui,
+    unit: &str,
+    range: Option<(f64, f64)>,
+    separator: DecimalSeparator,
+) {
+    let (min, max) = match range {
+        Some(range) => range,
+        None => return,
+    };
+    let n_steps = 32;
+    let width = ui.available_width().min(200.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, 12.0), Sense::hover());
+    let step_width = rect.width() / n_steps as f32;
+    for i in 0..n_steps {
+        let color = colormap((i as f64 + 0.5) / n_steps as f64);
+        let step_rect = Rect::from_min_size(
+            rect.min + egui::vec2(i as f32 * step_width, 0.0),
+            egui::vec2(step_width, rect.height()),
+        );
+        ui.painter().rect_filled(
+            step_rect,
+            0.0,
+            Color32::from_rgb(
+                (color[0] * 255.0) as u8,
+                (color[1] * 255.0) as u8,
+                (color[2] * 255.0) as u8,
+            ),
+        );
+    }
+    ui.horizontal(|ui| {
+        ui.label(format!("{:.1}{}", separator.number(min), unit));
+        ui.label("...");
+        ui.label(format!("{:.1}{}", separator.number(max), unit));
+    });
+}
+
+trait Timed {
+    fn t(&self) -> f64;
+}
+
+impl Timed for RingSample {
+    fn t(&self) -> f64 {
+        self.t
+    }
+}
+
+impl Timed for Energy {
+    fn t(&self) -> f64 {
+        self.t
+    }
+}
+
+impl Timed for Forces {
+    fn t(&self) -> f64 {
+        self.t
+    }
+}
+
+fn plot_line<T: Timed>(samples: &[T], name: &str, f: impl Fn(&T) -> f64) -> Line {
+    Line::new(Values::from_values_iter(
+        samples
+            .iter()
+            .map(|sample| Value::new(sample.t(), f(sample))),
+    ))
+    .name(name)
+}
+
+pub fn display_ring_diagnostics(state: &State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    let rings = state.ring_groups();
+    if rings.is_empty() {
+        ui.label("No cyclone or anticyclone rings in the simulation.");
+        return;
+    }
+
+    for (group, members) in rings {
+        let samples = ring_diagnostics(&members, state.render_settings.max_t, 500);
+        ui.collapsing(format!("Ring {}", group), |ui| {
+            if let Some(last) = samples.last() {
+                ui.label(format!(
+                    "Area: {:.0} km²",
+                    separator.number(last.area / 1e6)
+                ));
+                ui.label(format!(
+                    "Circulation: {:.3e} m²/s",
+                    separator.number(last.circulation)
+                ));
+                ui.label(format!(
+                    "Relative vorticity: {:.3e} 1/s",
+                    separator.number(last.rel_vorticity)
+                ));
+                ui.label(format!(
+                    "Absolute vorticity: {:.3e} 1/s",
+                    separator.number(last.abs_vorticity)
+                ));
+                ui.label(format!(
+                    "Divergence: {:.3e} 1/s",
+                    separator.number(last.divergence)
+                ));
+                ui.label(format!(
+                    "Angular momentum (relative/absolute): {:.3e} / {:.3e} m²/s",
+                    separator.number(last.rel_angular_momentum),
+                    separator.number(last.abs_angular_momentum)
+                ));
+            }
+            Plot::new(("ring_area", group))
+                .height(100.0)
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    plot_ui.line(plot_line(&samples, "Area [km²]", |s| s.area / 1e6));
+                });
+            Plot::new(("ring_vorticity", group))
+                .height(150.0)
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    plot_ui.line(plot_line(&samples, "Relative vorticity [1/s]", |s| {
+                        s.rel_vorticity
+                    }));
+                    plot_ui.line(plot_line(&samples, "Absolute vorticity [1/s]", |s| {
+                        s.abs_vorticity
+                    }));
+                    plot_ui.line(plot_line(&samples, "Divergence [1/s]", |s| s.divergence));
+                });
+            Plot::new(("ring_angular_momentum", group))
+                .height(150.0)
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    plot_ui.line(plot_line(&samples, "Relative ang. momentum [m²/s]", |s| {
+                        s.rel_angular_momentum
+                    }));
+                    plot_ui.line(plot_line(&samples, "Absolute ang. momentum [m²/s]", |s| {
+                        s.abs_angular_momentum
+                    }));
+                });
+        });
+    }
+}
+
+pub fn display_energy_plot(
+    obj: &Object,
+    max_t: f64,
+    separator: DecimalSeparator,
+    ui: &mut Ui,
+    id: usize,
+) {
+    let energies = obj.energy_history(max_t, 500);
+    let (first, last) = match (energies.first(), energies.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return,
+    };
+
+    ui.label(format!(
+        "Kinetic: {:.4e} J/kg",
+        separator.number(last.kinetic)
+    ));
+    ui.label(format!(
+        "Gravitational: {:.6e} J/kg",
+        separator.number(last.gravitational)
+    ));
+    ui.label(format!(
+        "Rotational: {:.6e} J/kg",
+        separator.number(last.rotational)
+    ));
+    ui.label(format!(
+        "Total: {:.6e} J/kg (drift {:.3e} J/kg)",
+        separator.number(last.total()),
+        separator.number(last.total() - first.total())
+    ));
+
+    Plot::new(("energy", id))
+        .height(150.0)
+        .legend(Legend::default())
+        .show(ui, |plot_ui| {
+            plot_ui.line(plot_line(&energies, "Kinetic", |e| e.kinetic));
+            plot_ui.line(plot_line(&energies, "Δ Gravitational", |e| {
+                e.gravitational - first.gravitational
+            }));
+            plot_ui.line(plot_line(&energies, "Δ Rotational", |e| {
+                e.rotational - first.rotational
+            }));
+            plot_ui.line(plot_line(&energies, "Δ Total", |e| {
+                e.total() - first.total()
+            }));
+        });
+}
+
+pub fn display_force_plot(
+    obj: &Object,
+    max_t: f64,
+    separator: DecimalSeparator,
+    ui: &mut Ui,
+    id: usize,
+) {
+    let history = obj.force_history(max_t, 500);
+    let last = match history.last() {
+        Some(last) => *last,
+        None => return,
+    };
+
+    ui.label(format!(
+        "Gravity: {:.5} m/s²",
+        separator.number(last.gravity.norm())
+    ));
+    ui.label(format!(
+        "Centrifugal: {:.5} m/s²",
+        separator.number(last.centrifugal.norm())
+    ));
+    ui.label(format!(
+        "Coriolis: {:.3e} m/s²",
+        separator.number(last.coriolis.norm())
+    ));
+    if history.iter().any(|f| f.euler.norm() > 0.0) {
+        ui.label(format!(
+            "Euler: {:.3e} m/s²",
+            separator.number(last.euler.norm())
+        ));
+    }
+    ui.label(format!(
+        "Other: {:.3e} m/s²",
+        separator.number(last.other.norm())
+    ));
+
+    // gravity would dwarf the other forces, so only its deviation from the start is shown
+    let first_gravity = history[0].gravity.norm();
+    Plot::new(("forces", id))
+        .height(150.0)
+        .legend(Legend::default())
+        .show(ui, |plot_ui| {
+            plot_ui.line(plot_line(&history, "Δ Gravity", |f| {
+                f.gravity.norm() - first_gravity
+            }));
+            plot_ui.line(plot_line(&history, "Centrifugal", |f| f.centrifugal.norm()));
+            plot_ui.line(plot_line(&history, "Coriolis", |f| f.coriolis.norm()));
+            plot_ui.line(plot_line(&history, "Euler", |f| f.euler.norm()));
+            plot_ui.line(plot_line(&history, "Other", |f| f.other.norm()));
+        });
+}
+
+/// Accelerations smaller than this, in m/s², are drawn as empty bars in the force balance
+const FORCE_BALANCE_FLOOR: f64 = 1e-8;
+
+/// A bar chart of the sizes of the forces acting on the object on a logarithmic scale
+pub fn display_force_balance(
+    obj: &Object,
+    max_t: f64,
+    separator: DecimalSeparator,
+    ui: &mut Ui,
+    id: usize,
+) {
+    let forces = obj.force_magnitudes(&obj.state_at(max_t));
+    let terms = [
+        ("Gravity", forces.gravity, [0.5, 0.5, 0.0]),
+        ("Centrifugal", forces.centrifugal, [0.3, 1.0, 0.3]),
+        ("Coriolis", forces.coriolis, [0.0, 1.0, 1.0]),
+        ("Drag", forces.drag, [0.8, 0.8, 0.8]),
+        ("Friction", forces.friction, [0.8, 0.5, 0.3]),
+        (
+            "Pressure gradient",
+            forces.pressure_gradient,
+            [0.6, 0.4, 1.0],
+        ),
+        ("Buoyancy", forces.buoyancy, [1.0, 0.6, 0.8]),
+    ];
+    if forces.gravity > 0.0 {
+        ui.label(format!(
+            "Coriolis / gravity: {:.2e}",
+            separator.number(forces.coriolis / forces.gravity)
+        ));
+    }
+
+    let floor = FORCE_BALANCE_FLOOR.log10();
+    let bars = terms
+        .iter()
+        .enumerate()
+        .map(|(i, (name, value, color))| {
+            Bar::new(i as f64, value.max(FORCE_BALANCE_FLOOR).log10() - floor)
+                .base_offset(floor)
+                .name(format!("{}: {:.3e} m/s²", name, value))
+                .fill(color32(*color))
+                .horizontal()
+        })
+        .collect();
+    let names = terms.map(|(name, _, _)| name);
+    Plot::new(("force_balance", id))
+        .height(120.0)
+        .allow_drag(false)
+        .allow_zoom(false)
+        .include_x(floor)
+        .include_x(2.0)
+        .x_axis_formatter(|x, _| {
+            if x.fract() == 0.0 {
+                format!("1e{}", x)
+            } else {
+                String::new()
+            }
+        })
+        .y_axis_formatter(move |y, _| {
+            let index = y.round();
+            match names.get(index as usize) {
+                Some(name) if (y - index).abs() < 1e-6 && index >= 0.0 => name.to_string(),
+                _ => String::new(),
+            }
+        })
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(bars).name("Acceleration [m/s²]"));
+        });
+}
+
+pub fn display_weight_chart(
+    obj: &Object,
+    max_t: f64,
+    separator: DecimalSeparator,
+    ui: &mut Ui,
+    id: usize,
+) {
+    let terms = obj.weight_terms(&obj.state_at(max_t));
+
+    ui.label(format!(
+        "Gravity: {:.5} m/s²",
+        separator.number(terms.gravity)
+    ));
+    ui.label(format!(
+        "Centrifugal: {:.5} m/s²",
+        separator.number(terms.centrifugal)
+    ));
+    ui.label(format!(
+        "Eötvös (Coriolis): {:.5} m/s²",
+        separator.number(terms.eotvos)
+    ));
+    ui.label(format!(
+        "Surface curvature: {:.5} m/s²",
+        separator.number(terms.curvature)
+    ));
+    ui.label(format!(
+        "Apparent weight: {:.5} m/s²",
+        separator.number(terms.total())
+    ));
+
+    // the corrections are tiny compared to gravity, so they are shown in mm/s²
+    let bars = vec![
+        Bar::new(0.0, terms.centrifugal * 1e3).name("Centrifugal"),
+        Bar::new(1.0, terms.eotvos * 1e3).name("Eötvös"),
+        Bar::new(2.0, terms.curvature * 1e3).name("Curvature"),
+        Bar::new(3.0, (terms.total() - terms.gravity) * 1e3).name("Total correction"),
+    ];
+    Plot::new(("weight", id))
+        .height(120.0)
+        .legend(Legend::default())
+        .show(ui, |plot_ui| {
+            plot_ui.bar_chart(BarChart::new(bars).name("Corrections to gravity [mm/s²]"));
+        });
+}
+
+/// The range of headings shown on the heading tape, in degrees to each side
+const TAPE_HALF_RANGE: f64 = 45.0;
+const TAPE_HALF_WIDTH: f32 = 180.0;
+const HORIZON_RADIUS: f32 = 50.0;
+/// Degrees of pitch per pixel in the horizon indicator
+const HORIZON_PITCH_SCALE: f32 = 1.0;
+
+/// Paints a heading tape at the top of the scene and an artificial horizon in its corner
+pub fn draw_attitude_indicator(ui: &Ui, rect: Rect, attitude: CameraAttitude) {
+    let painter = ui.painter();
+    let stroke = egui::Stroke::new(1.5, Color32::WHITE);
+    let font = egui::FontId::proportional(13.0);
+
+    // heading tape
+    let center = egui::pos2(rect.center().x, rect.min.y + 30.0);
+    let px_per_deg = TAPE_HALF_WIDTH / TAPE_HALF_RANGE as f32;
+    painter.rect_filled(
+        Rect::from_center_size(center, egui::vec2(2.0 * TAPE_HALF_WIDTH + 20.0, 40.0)),
+        4.0,
+        Color32::from_black_alpha(120),
+    );
+    let first_tick = ((attitude.heading - TAPE_HALF_RANGE) / 5.0).ceil() as i64 * 5;
+    for tick in (first_tick..=(attitude.heading + TAPE_HALF_RANGE) as i64).step_by(5) {
+        let x = center.x + (tick as f64 - attitude.heading) as f32 * px_per_deg;
+        let heading = tick.rem_euclid(360);
+        let labeled = heading % 15 == 0;
+        let len = if labeled { 8.0 } else { 4.0 };
+        painter.line_segment(
+            [
+                egui::pos2(x, center.y + 8.0),
+                egui::pos2(x, center.y + 8.0 - len),
+            ],
+            stroke,
+        );
+        if labeled {
+            let label = match heading {
+                0 => "N".to_string(),
+                45 => "NE".to_string(),
+                90 => "E".to_string(),
+                135 => "SE".to_string(),
+                180 => "S".to_string(),
+                225 => "SW".to_string(),
+                270 => "W".to_string(),
+                315 => "NW".to_string(),
+                _ => heading.to_string(),
+            };
+            painter.text(
+                egui::pos2(x, center.y - 6.0),
+                egui::Align2::CENTER_BOTTOM,
+                label,
+                font.clone(),
+                Color32::WHITE,
+            );
+        }
+    }
+    painter.line_segment(
+        [
+            egui::pos2(center.x, center.y + 4.0),
+            egui::pos2(center.x, center.y + 16.0),
+        ],
+        egui::Stroke::new(2.0, Color32::YELLOW),
+    );
+    painter.text(
+        egui::pos2(center.x, center.y + 18.0),
+        egui::Align2::CENTER_TOP,
+        format!("{:.0}°", attitude.heading),
+        font.clone(),
+        Color32::YELLOW,
+    );
+
+    // artificial horizon, with the sky above the line and the ground below
+    let center = egui::pos2(
+        rect.max.x - HORIZON_RADIUS - 15.0,
+        rect.max.y - HORIZON_RADIUS - 30.0,
+    );
+    painter.circle_filled(center, HORIZON_RADIUS, Color32::from_rgb(60, 110, 170));
+    let roll = (attitude.roll as f32).to_radians();
+    let along = egui::vec2(roll.cos(), -roll.sin());
+    // the horizon goes down when looking up
+    let offset =
+        (attitude.pitch as f32 / HORIZON_PITCH_SCALE).clamp(-HORIZON_RADIUS, HORIZON_RADIUS);
+    let down = egui::vec2(roll.sin(), roll.cos());
+    let mid = center + down * offset;
+    // the ground is the segment of the disk beyond the horizon line
+    let down_angle = down.y.atan2(down.x);
+    let span = (offset / HORIZON_RADIUS).clamp(-1.0, 1.0).acos();
+    let ground = (0..=32)
+        .map(|i| {
+            let ang = down_angle - span + 2.0 * span * i as f32 / 32.0;
+            center + egui::vec2(ang.cos(), ang.sin()) * HORIZON_RADIUS
+        })
+        .collect();
+    painter.add(egui::Shape::convex_polygon(
+        ground,
+        Color32::from_rgb(120, 85, 50),
+        egui::Stroke::none(),
+    ));
+    let half_chord = (HORIZON_RADIUS.powi(2) - offset.powi(2)).max(0.0).sqrt();
+    painter.line_segment([mid - along * half_chord, mid + along * half_chord], stroke);
+    painter.line_segment(
+        [
+            center - egui::vec2(15.0, 0.0),
+            center + egui::vec2(15.0, 0.0),
+        ],
+        egui::Stroke::new(2.0, Color32::YELLOW),
+    );
+    painter.circle_stroke(center, HORIZON_RADIUS, stroke);
+    painter.text(
+        center + egui::vec2(0.0, HORIZON_RADIUS + 4.0),
+        egui::Align2::CENTER_TOP,
+        format!("pitch {:.0}°, roll {:.0}°", attitude.pitch, attitude.roll),
+        font,
+        Color32::WHITE,
+    );
+}
+
+/// Points of the ground tracks plotted per object on the map
+const MAP_TRACK_SAMPLES: usize = 1000;
+
+fn color32(color: [f32; 3]) -> Color32 {
+    let [r, g, b] = color.map(|c| (c * 255.0) as u8);
+    Color32::from_rgb(r, g, b)
+}
+
+/// The size in pixels of the polar map images
+const POLAR_MAP_SIZE: usize = 512;
+
+/// The background of the map in the given projection: the whole Earth for the equirectangular
+/// map and a hemisphere for the polar ones
+pub fn map_image(projection: MapProjection) -> egui::ColorImage {
+    let image = Renderer::earth_image();
+    let (width, height) = image.dimensions();
+    if projection == MapProjection::Equirectangular {
+        let size = [width as usize, height as usize];
+        return egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+    }
+
+    let mut polar = egui::ColorImage::new([POLAR_MAP_SIZE; 2], Color32::TRANSPARENT);
+    for row in 0..POLAR_MAP_SIZE {
+        for col in 0..POLAR_MAP_SIZE {
+            let to_map = |pixel: usize| 180.0 * (pixel as f64 + 0.5) / POLAR_MAP_SIZE as f64 - 90.0;
+            let (x, y) = (to_map(col), -to_map(row));
+            if x.hypot(y) > 90.0 {
+                continue;
+            }
+            if let Some((lat, lon)) = projection.unproject(x, y) {
+                let u = ((lon + 180.0) / 360.0 * width as f64) as u32;
+                let v = ((90.0 - lat) / 180.0 * height as f64) as u32;
+                let [r, g, b, _] = image.get_pixel(u.min(width - 1), v.min(height - 1)).0;
+                polar.pixels[row * POLAR_MAP_SIZE + col] = Color32::from_rgb(r, g, b);
+            }
+        }
+    }
+    polar
+}
+
+/// A map of the Earth with the ground tracks of the objects up to the displayed time; the
+/// texture has to hold `map_image` for the current projection
+pub fn display_map(state: &mut State, ui: &mut Ui, texture: &egui::TextureHandle) {
+    ui.horizontal(|ui| {
+        ui.label("Projection:");
+        egui::ComboBox::from_id_source("map_projection")
+            .selected_text(format!("{}", state.map_projection))
+            .show_ui(ui, |ui| {
+                for projection in MapProjection::ALL {
+                    ui.selectable_value(
+                        &mut state.map_projection,
+                        projection,
+                        format!("{}", projection),
+                    );
+                }
+            });
+    });
+
+    let projection = state.map_projection;
+    let (half_width, half_height) = match projection {
+        MapProjection::Equirectangular => (180.0, 90.0),
+        MapProjection::NorthPolar | MapProjection::SouthPolar => (90.0, 90.0),
+    };
+    let max_t = state.render_settings.max_t;
+    let state = &*state;
+    Plot::new("map")
+        .data_aspect(1.0)
+        .view_aspect((half_width / half_height) as f32)
+        .include_x(-half_width)
+        .include_x(half_width)
+        .include_y(-half_height)
+        .include_y(half_height)
+        .show(ui, |plot_ui| {
+            plot_ui.image(PlotImage::new(
+                texture,
+                Value::new(0.0, 0.0),
+                egui::vec2(2.0 * half_width as f32, 2.0 * half_height as f32),
+            ));
+            let to_value = |(lat, lon): (f64, f64)| {
+                let (x, y) = projection.project(lat, lon);
+                Value::new(x, y)
+            };
+
+            for (i, obj) in state.objects.iter().enumerate() {
+                if !state.shows_object(i) {
+                    continue;
+                }
+                let color = color32(obj.color());
+                let selected = state.selected_object == Some(i);
+                let track = obj.lat_lon_history(max_t, MAP_TRACK_SAMPLES);
+                // only the equirectangular map has an edge the tracks can cross
+                let segments = if projection == MapProjection::Equirectangular {
+                    split_at_antimeridian(track.iter().map(|(lat, lon)| (*lon, *lat)))
+                        .into_iter()
+                        .map(|segment| segment.into_iter().map(|(lon, lat)| (lat, lon)).collect())
+                        .collect()
+                } else {
+                    vec![track]
+                };
+                for segment in segments {
+                    plot_ui.line(
+                        Line::new(Values::from_values_iter(segment.into_iter().map(to_value)))
+                            .color(color)
+                            .width(if selected { 2.5 } else { 1.5 }),
+                    );
+                }
+                let pos = obj.state_at(max_t).pos().to_omega(OMEGA).pos();
+                let (lat, lon, _) = pos_to_lat_lon_elev(pos);
+                plot_ui.points(
+                    Points::new(Values::from_values(vec![to_value((lat, lon))]))
+                        .name(obj.label(i))
+                        .color(color)
+                        .radius(if selected { 5.0 } else { 3.5 }),
+                );
+            }
+
+            if state.show_ground_stations {
+                let stations = state
+                    .stations
+                    .iter()
+                    .map(|station| to_value((station.lat, station.lon)));
+                plot_ui.points(
+                    Points::new(Values::from_values_iter(stations))
+                        .name("Ground stations")
+                        .shape(MarkerShape::Up)
+                        .color(Color32::WHITE)
+                        .radius(5.0),
+                );
+            }
+            if let Some(reference) = state.render_settings.reference_point {
+                plot_ui.points(
+                    Points::new(Values::from_values(vec![to_value(reference)]))
+                        .name("Reference point")
+                        .shape(MarkerShape::Cross)
+                        .color(Color32::YELLOW)
+                        .radius(6.0),
+                );
+            }
+        });
+}
+
+pub fn display_ground_stations(state: &mut State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    ui.checkbox(
+        &mut state.render_settings.draw_station_cones,
+        "Draw visibility cones",
+    );
+
+    let mut to_remove = None;
+    for (i, station) in state.stations.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("Station {}", i));
+            if ui.button("Remove").clicked() {
+                to_remove = Some(i);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Latitude:");
+            ui.add(egui::Slider::new(&mut station.lat, -90.0..=90.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Longitude:");
+            ui.add(egui::Slider::new(&mut station.lon, -180.0..=180.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Minimum elevation:");
+            ui.add(egui::Slider::new(&mut station.min_elevation, 0.0..=89.0));
+        });
+    }
+    if let Some(i) = to_remove {
+        state.stations.remove(i);
+    }
+    if ui.button("Add station").clicked() {
+        state.stations.push(GroundStation::default());
+    }
+
+    ui.separator();
+    ui.label("Passes");
+
+    let now = state.render_settings.max_t;
+    for (i, station) in state.stations.iter().enumerate() {
+        ui.collapsing(format!("Station {}", i), |ui| {
+            let mut any = false;
+            for (j, obj) in state.objects.iter().enumerate() {
+                for pass in station.passes(obj, obj.time()) {
+                    any = true;
+                    let when = if pass.end < now {
+                        "past"
+                    } else if pass.start > now {
+                        "upcoming"
+                    } else {
+                        "in progress"
+                    };
+                    ui.label(format!(
+                        "{}: t = {:.0} s - {:.0} s, max. elevation {:.1}° at {:.0} s ({})",
+                        obj.label(j),
+                        separator.number(pass.start),
+                        separator.number(pass.end),
+                        separator.number(pass.max_elevation),
+                        separator.number(pass.max_elevation_t),
+                        when
+                    ));
+                }
+            }
+            if !any {
+                ui.label("No passes in the calculated paths.");
+            }
+        });
+    }
+}
+
+pub fn display_calculator(state: &mut State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    let calc = &mut state.calculator;
+    ui.label("Latitude (degrees):");
+    ui.add(egui::Slider::new(&mut calc.lat, -90.0..=90.0));
+    ui.label("Speed (m/s):");
+    ui.add(egui::Slider::new(&mut calc.speed, 1.0..=2000.0).logarithmic(true));
+    ui.label("Direction of motion (degrees from north):");
+    ui.add(egui::Slider::new(&mut calc.azimuth, 0.0..=360.0));
+    ui.label("Flight time (s):");
+    ui.add(egui::Slider::new(&mut calc.flight_time, 1.0..=1e5).logarithmic(true));
+
+    let calc = *calc;
+    let range = calc.speed * calc.flight_time;
+    let deflection = horizontal_deflection(calc.lat, calc.speed, calc.flight_time);
+
+    ui.separator();
+    ui.label(format!("Range: {:.1} km", separator.number(range / 1e3)));
+    formula(ui, r"f = 2\Omega \sin\varphi");
+    ui.label(format!(
+        "Coriolis parameter: {:.3e} 1/s",
+        separator.number(coriolis_parameter(calc.lat))
+    ));
+    formula(ui, r"d = \frac{1}{2} f v t^2");
+    ui.label(format!(
+        "Deflection: {:.1} m {}",
+        separator.number(deflection.abs()),
+        if deflection >= 0.0 {
+            "to the right"
+        } else {
+            "to the left"
+        }
+    ));
+    if deflection.abs() > 0.1 * range {
+        ui.label("The deflection is large compared to the range; the first-order estimate is inaccurate.");
+    }
+    formula(ui, r"T = \frac{2\pi}{|f|}");
+    ui.label(format!(
+        "Inertial period: {:.2} h",
+        separator.number(inertial_period(calc.lat) / 3600.0)
+    ));
+    formula(ui, r"R = \frac{v}{|f|}");
+    ui.label(format!(
+        "Inertial circle radius: {:.1} km",
+        separator.number(inertial_radius(calc.lat, calc.speed) / 1e3)
+    ));
+    formula(ui, r"\omega_F = \Omega \sin\varphi");
+    ui.label(format!(
+        "Foucault pendulum rotation: {:.2}°/h",
+        separator.number(foucault_rate(calc.lat))
+    ));
+
+    if ui.button("Spawn a matching object").clicked() {
+        let azimuth = calc.azimuth.to_radians();
+        state.current_state_def.objects.push(ObjectDescription {
+            lat: calc.lat.to_string(),
+            kind: ObjectKind::Free {
+                vel_e: (calc.speed * azimuth.sin()).to_string(),
+                vel_n: (calc.speed * azimuth.cos()).to_string(),
+                vel_u: "0".to_string(),
+                gravity: "1".to_string(),
+                friction: "0".to_string(),
+                drag: "0".to_string(),
+                charge: "0".to_string(),
+            },
+            ..Default::default()
+        });
+        state.reset_state();
+    }
+}
+
+pub fn display_aiming_tool(state: &mut State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    let tool = &mut state.aiming_tool;
+    egui::Grid::new("aiming_tool").show(ui, |ui| {
+        ui.label("Launch site:");
+        ui.add(
+            egui::DragValue::new(&mut tool.launcher.lat)
+                .clamp_range(-90.0..=90.0)
+                .speed(0.1),
+        );
+        ui.add(
+            egui::DragValue::new(&mut tool.launcher.lon)
+                .clamp_range(-180.0..=180.0)
+                .speed(0.1),
+        );
+        ui.label("°");
+        ui.end_row();
+        ui.label("Target:");
+        ui.add(
+            egui::DragValue::new(&mut tool.target_lat)
+                .clamp_range(-90.0..=90.0)
+                .speed(0.1),
+        );
+        ui.add(
+            egui::DragValue::new(&mut tool.target_lon)
+                .clamp_range(-180.0..=180.0)
+                .speed(0.1),
+        );
+        ui.label("°");
+        ui.end_row();
+    });
+    ui.label("Launch speed (m/s):");
+    ui.add(egui::Slider::new(&mut tool.launcher.speed, 10.0..=3000.0).logarithmic(true));
+    ui.label("Drag coefficient:");
+    ui.add(egui::Slider::new(&mut tool.launcher.drag, 0.0..=1e-3));
+
+    let button = egui::Button::new("Find the aim");
+    if ui.add_enabled(!tool.is_solving(), button).clicked() {
+        tool.solve();
+    }
+    if tool.is_solving() {
+        ui.label("Searching...");
+    }
+
+    let (naive, corrected, iterations) = match tool.result {
+        Some(Ok(result)) => result,
+        Some(Err(err)) => {
+            ui.colored_label(Color32::YELLOW, format!("No solution: {}", err));
+            return;
+        }
+        None => return,
+    };
+    let target = tool.target();
+    let miss = miss_distance(&naive.impact, &target);
+
+    ui.separator();
+    egui::Grid::new("aiming_result")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            ui.label("Azimuth");
+            ui.label("Elevation");
+            ui.label("Flight time");
+            ui.end_row();
+            for (label, shot) in [("Naive", &naive), ("Corrected", &corrected)] {
+                ui.label(label);
+                ui.label(format!("{:.3}°", separator.number(shot.azimuth)));
+                ui.label(format!("{:.3}°", separator.number(shot.elevation)));
+                ui.label(format!("{:.1} s", separator.number(shot.flight_time)));
+                ui.end_row();
+            }
+        });
+    ui.label(format!(
+        "Aim-off: {:+.3}° in azimuth, {:+.3}° in elevation ({} iterations)",
+        separator.number((corrected.azimuth - naive.azimuth + 180.0).rem_euclid(360.0) - 180.0),
+        separator.number(corrected.elevation - naive.elevation),
+        iterations
+    ));
+    ui.label(format!(
+        "The naive shot misses by {:.0} m ({:.0} m east, {:.0} m north)",
+        separator.number(miss.norm()),
+        separator.number(miss.x),
+        separator.number(miss.y)
+    ));
+
+    if ui.button("Fire both shots").clicked() {
+        state.current_state_def.objects = state.aiming_tool.objects(&naive, &corrected);
+        state.current_state_def.preset = None;
+        state.reset_state();
+    }
+}
+
+pub fn display_intercept_tool(state: &mut State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    let labels: Vec<_> = state
+        .objects
+        .iter()
+        .enumerate()
+        .map(|(index, obj)| obj.label(index))
+        .collect();
+    let tool = &mut state.intercept_tool;
+    for (text, index) in [
+        ("Launched object:", &mut tool.chaser),
+        ("Target:", &mut tool.target),
+    ] {
+        ui.horizontal(|ui| {
+            ui.label(text);
+            egui::ComboBox::from_id_source(text)
+                .selected_text(labels.get(*index).map_or("-", String::as_str))
+                .show_ui(ui, |ui| {
+                    for (i, label) in labels.iter().enumerate() {
+                        ui.selectable_value(index, i, label);
+                    }
+                });
+        });
+    }
+    ui.horizontal(|ui| {
+        ui.label("Flight time from");
+        ui.add(
+            egui::DragValue::new(&mut tool.min_time)
+                .clamp_range(1.0..=40000.0)
+                .speed(10.0),
+        );
+        ui.label("to");
+        ui.add(
+            egui::DragValue::new(&mut tool.max_time)
+                .clamp_range(1.0..=40000.0)
+                .speed(10.0),
+        );
+        ui.label("s");
+    });
+    ui.horizontal(|ui| {
+        ui.label("Candidates:");
+        ui.add(egui::Slider::new(&mut tool.candidates, 1..=12));
+    });
+
+    let can_solve = state.precompute.is_none() && !state.intercept_tool.is_solving();
+    if ui
+        .add_enabled(can_solve, egui::Button::new("Find the launches"))
+        .on_hover_text("From the current state of the objects")
+        .clicked()
+    {
+        state.intercept_tool.solve(&state.objects);
+    }
+
+    let tool = &state.intercept_tool;
+    if let Some(message) = &tool.message {
+        ui.colored_label(Color32::YELLOW, message);
+    }
+    if tool.result.is_empty() {
+        return;
+    }
+    let best = tool.best();
+    egui::Grid::new("intercept_result")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Flight time");
+            ui.label("Launch speed");
+            ui.label("Miss");
+            ui.end_row();
+            for (i, interception) in tool.result.iter().enumerate() {
+                let text = format!("{:.0} s", interception.duration);
+                if Some(i) == best {
+                    ui.colored_label(Color32::WHITE, text)
+                        .on_hover_text("The lowest launch speed");
+                } else {
+                    ui.label(text);
+                }
+                ui.label(format!(
+                    "{:.1} m/s",
+                    separator.number(interception.velocity.norm())
+                ));
+                ui.label(format!("{:.1} m", separator.number(interception.miss)));
+                ui.end_row();
+            }
+        });
+    ui.label("The candidate paths are drawn in the scene, the cheapest one in white.");
+}
+
+/// The legend of the runs of an experiment and where they ended up
+pub fn display_experiment(state: &mut State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    let experiment = match state.experiment.as_ref() {
+        Some(experiment) => experiment,
+        None => return,
+    };
+    let unit = experiment.parameter.unit();
+
+    egui::Grid::new("experiment").striped(true).show(ui, |ui| {
+        ui.label("");
+        ui.label(format!("{}", experiment.parameter));
+        ui.label("Final lat");
+        ui.label("Final lon");
+        ui.label("Displacement");
+        ui.end_row();
+        for run in &experiment.runs {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), Sense::hover());
+            ui.painter().rect_filled(rect, 0.0, color32(run.color));
+            ui.label(format!("{:.3}{}", separator.number(run.value), unit));
+            match run.outcome(&state.objects, state.render_settings.max_t) {
+                Some(outcome) => {
+                    ui.label(format!("{:.2}°", separator.number(outcome.lat)));
+                    ui.label(format!("{:.2}°", separator.number(outcome.lon)));
+                    ui.label(format!(
+                        "{:.1} km",
+                        separator.number(outcome.displacement / 1e3)
+                    ));
+                }
+                None => {
+                    ui.label("-");
+                    ui.label("-");
+                    ui.label("-");
+                }
+            }
+            ui.end_row();
+        }
+    });
+    if ui.button("Close").clicked() {
+        state.experiment = None;
+    }
+}
+
+pub fn display_quiz(state: &mut State, ui: &mut Ui) {
+    let separator = state.decimal_separator;
+    let quiz = match state.quiz.as_ref() {
+        Some(quiz) => quiz,
+        None => return,
+    };
+    ui.label(format!(
+        "Score: {} / {}",
+        quiz.score,
+        quiz.current.min(quiz.exercises.len())
+    ));
+
+    let exercise = match (quiz.phase, quiz.exercise()) {
+        (QuizPhase::Finished, _) | (_, None) => {
+            ui.label("All exercises done!");
+            if ui.button("Close").clicked() {
+                state.quiz = None;
+            }
+            return;
+        }
+        (_, Some(exercise)) => exercise.clone(),
+    };
+
+    ui.label(format!(
+        "Exercise {} of {}",
+        quiz.current + 1,
+        quiz.exercises.len()
+    ));
+    ui.label(&exercise.question);
+
+    match quiz.phase {
+        QuizPhase::Asking => {
+            let mut chosen = None;
+            for (i, answer) in exercise.answers.iter().enumerate() {
+                if ui.button(answer).clicked() {
+                    chosen = Some(i);
+                }
+            }
+            if let Some(answer) = chosen {
+                state.answer_exercise(answer);
+            }
+        }
+        QuizPhase::Running(chosen) => {
+            ui.label(format!(
+                "Your answer: {}. Simulating... {:.0}%",
+                exercise.answers.get(chosen).map_or("?", String::as_str),
+                separator.number((state.t / exercise.duration * 100.0).min(100.0))
+            ));
+        }
+        QuizPhase::Answered { chosen, correct } => {
+            let answer_text = |i: Option<usize>| {
+                i.and_then(|i| exercise.answers.get(i))
+                    .map_or("?", String::as_str)
+            };
+            if correct == Some(chosen) {
+                ui.colored_label(Color32::GREEN, "Correct!");
+            } else {
+                ui.colored_label(
+                    Color32::RED,
+                    format!("Wrong - the answer is: {}", answer_text(correct)),
+                );
+            }
+            if let Some(explanation) = &exercise.explanation {
+                ui.label(explanation);
+            }
+            if ui.button("Next").clicked() {
+                state.next_exercise();
+            }
+        }
+        QuizPhase::Finished => (),
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    io::{self, Write},
+    time::Instant,
+};
+
+/// A single change of a control made by the user
+struct Interaction {
+    /// Seconds since the logging was enabled
+    time: f64,
+    control: &'static str,
+    value: String,
+}
+
+/// An opt-in record of the controls touched by the user, for research into how students explore
+/// the simulation. Only the control names, their new values and the times relative to the start
+/// of the recording are stored, nothing that would identify the user.
+pub struct InteractionLog {
+    enabled: bool,
+    start: Instant,
+    interactions: Vec<Interaction>,
+}
+
+impl Default for InteractionLog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: Instant::now(),
+            interactions: vec![],
+        }
+    }
+}
+
+impl InteractionLog {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.enabled && self.interactions.is_empty() {
+            self.start = Instant::now();
+        }
+        self.enabled = enabled;
+    }
+
+    pub fn len(&self) -> usize {
+        self.interactions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interactions.is_empty()
+    }
+
+    /// Records the controls whose values differ between the two snapshots
+    pub fn record_changes(
+        &mut self,
+        before: &[(&'static str, String)],
+        after: &[(&'static str, String)],
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let time = self.start.elapsed().as_secs_f64();
+        for (control, value) in after {
+            let changed = before
+                .iter()
+                .any(|(name, old_value)| name == control && old_value != value);
+            if changed {
+                self.interactions.push(Interaction {
+                    time,
+                    control,
+                    value: value.clone(),
+                });
+            }
+        }
+    }
+
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "time,control,value")?;
+        for interaction in &self.interactions {
+            writeln!(
+                out,
+                "{:.3},{},\"{}\"",
+                interaction.time,
+                interaction.control,
+                interaction.value.replace('"', "\"\"")
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::{self, Write};
+
+use crate::simulation::{Object, OMEGA};
+
+/// Writes the paths of the objects up to the time `max_t` as a KML document, one LineString
+/// placemark per object
+pub fn write_kml(out: &mut impl Write, objects: &[Object], max_t: f64) -> io::Result<()> {
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(out, "<Document>")?;
+    writeln!(out, "<name>Coriolis simulation paths</name>")?;
+
+    for (i, obj) in objects.iter().enumerate() {
+        let [r, g, b] = obj.color();
+        writeln!(out, "<Style id=\"object{}\">", i)?;
+        // KML colors are aabbggrr
+        writeln!(
+            out,
+            "<LineStyle><color>ff{:02x}{:02x}{:02x}</color><width>2</width></LineStyle>",
+            to_byte(b),
+            to_byte(g),
+            to_byte(r)
+        )?;
+        writeln!(out, "</Style>")?;
+
+        writeln!(out, "<Placemark>")?;
+        writeln!(out, "<name>{}</name>", escape(&obj.label(i)))?;
+        if let Some(stats) = obj.path_statistics(max_t) {
+            writeln!(
+                out,
+                "<description>Path length {:.1} km, ground track {:.1} km, great circle {:.1} km, \
+                 average speed {:.1} m/s, total turning {:.1}°</description>",
+                stats.length / 1e3,
+                stats.ground_length / 1e3,
+                stats.straight_distance / 1e3,
+                stats.average_speed,
+                stats.turning
+            )?;
+        }
+        writeln!(out, "<styleUrl>#object{}</styleUrl>", i)?;
+        writeln!(out, "<LineString>")?;
+        writeln!(out, "<tessellate>1</tessellate>")?;
+        writeln!(out, "<altitudeMode>absolute</altitudeMode>")?;
+        writeln!(out, "<coordinates>")?;
+        for sample in obj.samples(OMEGA, max_t) {
+            let (lat, lon, elev) = sample.lat_lon_elev();
+            writeln!(out, "{:.6},{:.6},{:.1}", lon, lat, elev)?;
+        }
+        writeln!(out, "</coordinates>")?;
+        writeln!(out, "</LineString>")?;
+        writeln!(out, "</Placemark>")?;
+    }
+
+    writeln!(out, "</Document>")?;
+    writeln!(out, "</kml>")
+}
+
+fn to_byte(x: f32) -> u8 {
+    (x.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Escapes the characters that have a special meaning in XML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
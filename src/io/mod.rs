@@ -0,0 +1,15 @@
+mod interactions;
+mod kml;
+mod recording;
+mod report;
+mod screenshot;
+mod tle;
+mod wind;
+
+pub use interactions::InteractionLog;
+pub use kml::write_kml;
+pub use recording::Recording;
+pub use report::write_report;
+pub use screenshot::screenshot_path;
+pub use tle::Tle;
+pub use wind::{load_wind_field, WindError};
@@ -0,0 +1,54 @@
+use std::{fs, io, path::Path};
+
+/// Records every rendered frame of the scene to numbered PNG files, for assembling into a video,
+/// e.g. with `ffmpeg -framerate 30 -i frame-%05d.png video.mp4`
+pub struct Recording {
+    /// The frame rate of the video; while recording, every frame advances the simulation by the
+    /// same wall-clock time, so that the video plays at the simulation's speed however long it
+    /// takes to save the frames
+    pub fps: f64,
+    /// The directory the frames are saved in
+    pub directory: String,
+    /// The number of frames saved so far, if recording
+    frames: Option<usize>,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self {
+            fps: 30.0,
+            directory: "recording".to_string(),
+            frames: None,
+        }
+    }
+}
+
+impl Recording {
+    pub fn is_active(&self) -> bool {
+        self.frames.is_some()
+    }
+
+    pub fn start(&mut self) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+        self.frames = Some(0);
+        Ok(())
+    }
+
+    /// Stops the recording, returning the number of frames saved
+    pub fn stop(&mut self) -> usize {
+        self.frames.take().unwrap_or(0)
+    }
+
+    /// The time step of a frame in the video, in seconds
+    pub fn frame_time(&self) -> f64 {
+        1.0 / self.fps.max(1.0)
+    }
+
+    /// The path of the next frame, if recording
+    pub fn next_frame(&mut self) -> Option<String> {
+        let frames = self.frames.as_mut()?;
+        let name = format!("frame-{:05}.png", frames);
+        *frames += 1;
+        Some(Path::new(&self.directory).join(name).display().to_string())
+    }
+}
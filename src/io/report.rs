@@ -0,0 +1,407 @@
+use std::io::{self, Write};
+
+use crate::{
+    simulation::{pos_to_lat_lon_elev, split_at_antimeridian, Object, MIN_FLIGHT_ELEVATION, OMEGA},
+    state::State,
+};
+
+/// At most this many points are drawn per line of a plot
+const MAX_PLOT_POINTS: usize = 500;
+/// Closest approaches are only looked for between this many groups
+const MAX_APPROACH_GROUPS: usize = 12;
+
+const PLOT_WIDTH: f64 = 640.0;
+const PLOT_HEIGHT: f64 = 320.0;
+const PLOT_MARGIN: f64 = 50.0;
+
+/// A line of a plot, possibly broken into separate segments
+struct Series {
+    name: String,
+    color: [f32; 3],
+    segments: Vec<Vec<(f64, f64)>>,
+}
+
+/// Writes a self-contained HTML report of the current run up to the simulated time: the
+/// settings of the simulation, the parameters of the objects, the key events, the lengths of the
+/// paths and plots of the elevation and the ground tracks. Each group of objects is represented
+/// by its first member.
+pub fn write_report(out: &mut impl Write, state: &State) -> io::Result<()> {
+    let max_t = state.render_settings.max_t;
+    let def = &state.current_state_def;
+
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html><head><meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>Coriolis simulation report</title>")?;
+    writeln!(
+        out,
+        "<style>body {{ font-family: sans-serif; max-width: 60em; margin: auto; }} \
+         table {{ border-collapse: collapse; }} \
+         td, th {{ border: 1px solid #999; padding: 2px 8px; text-align: left; }}</style>"
+    )?;
+    writeln!(out, "</head><body>")?;
+    writeln!(out, "<h1>Coriolis simulation report</h1>")?;
+
+    writeln!(out, "<h2>Scenario</h2>")?;
+    writeln!(out, "<table>")?;
+    let scenario = match def.preset {
+        Some(preset) => format!("{}", preset),
+        None => "Custom".to_string(),
+    };
+    table_row(out, &["Scenario", &scenario])?;
+    table_row(out, &["Simulated time", &format!("{:.1} s", max_t)])?;
+    table_row(out, &["Time step", &format!("{} s", state.time_step)])?;
+    table_row(
+        out,
+        &["Integration method", &format!("{}", state.integration)],
+    )?;
+    table_row(
+        out,
+        &[
+            "Rotation of the reference frame",
+            &format!("{} × Earth's", state.omega),
+        ],
+    )?;
+    if let Some(system) = def.two_body {
+        table_row(out, &["Two-body system", &format!("{}", system)])?;
+    }
+    table_row(out, &["Atmosphere", &format!("{}", def.atmosphere)])?;
+    if let Some(ramp) = def.spin_ramp {
+        table_row(
+            out,
+            &[
+                "Earth's rotation",
+                &format!(
+                    "changing to {} × the real rate between {:.1} h and {:.1} h",
+                    ramp.final_rate,
+                    ramp.start / 3600.0,
+                    ramp.end / 3600.0
+                ),
+            ],
+        )?;
+    }
+    writeln!(out, "</table>")?;
+
+    writeln!(out, "<h2>Objects</h2>")?;
+    for (group, desc) in def.objects.iter().enumerate() {
+        let name = if desc.name.is_empty() {
+            format!("Object {}", group)
+        } else {
+            desc.name.clone()
+        };
+        writeln!(
+            out,
+            "<h3><span style=\"color: {}\">■</span> {} ({})</h3>",
+            css_color(desc.color),
+            escape(&name),
+            desc.kind.as_tag()
+        )?;
+        writeln!(out, "<table>")?;
+        for (label, value, unit) in desc.parameters() {
+            table_row(out, &[label, &format!("{} {}", value, unit)])?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    let representatives = representatives(state);
+
+    writeln!(out, "<h2>Key events</h2>")?;
+    let events = key_events(&representatives, max_t);
+    if events.is_empty() {
+        writeln!(out, "<p>None</p>")?;
+    } else {
+        writeln!(out, "<table>")?;
+        writeln!(out, "<tr><th>Time</th><th>Event</th></tr>")?;
+        for (t, event) in events {
+            table_row(out, &[&format!("{:.1} s", t), &event])?;
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    writeln!(out, "<h2>Path statistics</h2>")?;
+    writeln!(out, "<table>")?;
+    writeln!(
+        out,
+        "<tr><th>Object</th><th>Path length</th><th>Ground track</th><th>Great circle</th>\
+         <th>Average speed</th><th>Total turning</th></tr>"
+    )?;
+    for (label, obj) in &representatives {
+        match obj.path_statistics(max_t) {
+            Some(stats) => table_row(
+                out,
+                &[
+                    label,
+                    &format!("{:.1} km", stats.length / 1e3),
+                    &format!("{:.1} km", stats.ground_length / 1e3),
+                    &format!("{:.1} km", stats.straight_distance / 1e3),
+                    &format!("{:.1} m/s", stats.average_speed),
+                    &format!("{:.1}°", stats.turning),
+                ],
+            )?,
+            None => table_row(out, &[label, "-", "-", "-", "-", "-"])?,
+        }
+    }
+    writeln!(out, "</table>")?;
+
+    if let Some(experiment) = &state.experiment {
+        writeln!(out, "<h2>Experiment</h2>")?;
+        writeln!(out, "<table>")?;
+        writeln!(
+            out,
+            "<tr><th>{}</th><th>Final lat</th><th>Final lon</th><th>Displacement</th></tr>",
+            experiment.parameter
+        )?;
+        for run in &experiment.runs {
+            let value = format!("{:.3}{}", run.value, experiment.parameter.unit());
+            match run.outcome(&state.objects, max_t) {
+                Some(outcome) => table_row(
+                    out,
+                    &[
+                        &value,
+                        &format!("{:.2}°", outcome.lat),
+                        &format!("{:.2}°", outcome.lon),
+                        &format!("{:.1} km", outcome.displacement / 1e3),
+                    ],
+                )?,
+                None => table_row(out, &[&value, "-", "-", "-"])?,
+            }
+        }
+        writeln!(out, "</table>")?;
+    }
+
+    writeln!(out, "<h2>Plots</h2>")?;
+    let mut elevation = vec![];
+    let mut ground_track = vec![];
+    for (label, obj) in &representatives {
+        let lat_lon_elev: Vec<_> = decimated_states(obj, max_t)
+            .into_iter()
+            .map(|(t, pos)| (t, pos_to_lat_lon_elev(pos)))
+            .collect();
+        elevation.push(Series {
+            name: label.clone(),
+            color: obj.color(),
+            segments: vec![lat_lon_elev
+                .iter()
+                .map(|(t, (_, _, elev))| (*t, *elev))
+                .collect()],
+        });
+        ground_track.push(Series {
+            name: label.clone(),
+            color: obj.color(),
+            segments: split_at_antimeridian(
+                lat_lon_elev.iter().map(|(_, (lat, lon, _))| (*lon, *lat)),
+            ),
+        });
+    }
+    write_svg_plot(out, "Elevation", "t [s]", "elevation [m]", &elevation)?;
+    write_svg_plot(out, "Ground track", "lon [°]", "lat [°]", &ground_track)?;
+
+    writeln!(out, "</body></html>")
+}
+
+/// The first member of every group, with its label
+fn representatives(state: &State) -> Vec<(String, &Object)> {
+    state
+        .groups()
+        .into_values()
+        .map(|members| {
+            (
+                state.objects[members[0]].label(members[0]),
+                &state.objects[members[0]],
+            )
+        })
+        .collect()
+}
+
+/// Landings and closest approaches between the objects, sorted by time
+fn key_events(objects: &[(String, &Object)], max_t: f64) -> Vec<(f64, String)> {
+    let mut events = vec![];
+
+    for (label, obj) in objects {
+        let landed_at = obj.landed_at().filter(|t| *t <= max_t);
+        // objects starting on the ground "land" right away
+        let airborne = |t: f64| {
+            obj.states_until(t).any(|state| {
+                let (_, _, elev) = pos_to_lat_lon_elev(state.pos().to_omega(OMEGA).pos());
+                elev > MIN_FLIGHT_ELEVATION
+            })
+        };
+        if let Some(t) = landed_at.filter(|t| airborne(*t)) {
+            let (lat, lon, _) = pos_to_lat_lon_elev(obj.state_at(t).pos().to_omega(OMEGA).pos());
+            events.push((t, format!("{} landed at {:.2}°, {:.2}°", label, lat, lon)));
+        }
+    }
+
+    let objects = &objects[..objects.len().min(MAX_APPROACH_GROUPS)];
+    for (i, (label1, obj1)) in objects.iter().enumerate() {
+        for (label2, obj2) in &objects[i + 1..] {
+            let closest = obj1
+                .states_until(max_t)
+                .map(|state| {
+                    let t = state.pos().t();
+                    let pos1 = state.pos().to_omega(OMEGA).pos();
+                    let pos2 = obj2.state_at(t).pos().to_omega(OMEGA).pos();
+                    (t, (pos1 - pos2).norm())
+                })
+                .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+            if let Some((t, distance)) = closest {
+                events.push((
+                    t,
+                    format!(
+                        "Closest approach of {} and {}: {:.3} km",
+                        label1,
+                        label2,
+                        distance / 1e3
+                    ),
+                ));
+            }
+        }
+    }
+
+    events.sort_by(|(t1, _), (t2, _)| t1.total_cmp(t2));
+    events
+}
+
+/// The times and positions in the Earth's frame of at most `MAX_PLOT_POINTS` evenly spaced
+/// states up to `max_t`
+fn decimated_states(obj: &Object, max_t: f64) -> Vec<(f64, nalgebra::Vector3<f64>)> {
+    let states: Vec<_> = obj.states_until(max_t).collect();
+    let stride = states.len().div_ceil(MAX_PLOT_POINTS).max(1);
+    states
+        .iter()
+        .step_by(stride)
+        .map(|state| (state.pos().t(), state.pos().to_omega(OMEGA).pos()))
+        .collect()
+}
+
+fn write_svg_plot(
+    out: &mut impl Write,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    series: &[Series],
+) -> io::Result<()> {
+    let points = series.iter().flat_map(|s| s.segments.iter().flatten());
+    let (mut x_min, mut x_max, mut y_min, mut y_max) = points.fold(
+        (
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(x_min, x_max, y_min, y_max), (x, y)| {
+            (x_min.min(*x), x_max.max(*x), y_min.min(*y), y_max.max(*y))
+        },
+    );
+    if !x_min.is_finite() || !y_min.is_finite() {
+        return Ok(());
+    }
+    // flat lines (up to rounding errors) still need a visible range
+    let is_flat = |min: f64, max: f64| max - min < 1e-6 * min.abs().max(max.abs()).max(1.0);
+    if is_flat(x_min, x_max) {
+        x_min -= 1.0;
+        x_max += 1.0;
+    }
+    if is_flat(y_min, y_max) {
+        y_min -= 1.0;
+        y_max += 1.0;
+    }
+    let plot_w = PLOT_WIDTH - 2.0 * PLOT_MARGIN;
+    let plot_h = PLOT_HEIGHT - 2.0 * PLOT_MARGIN;
+    let to_screen = |(x, y): (f64, f64)| {
+        (
+            PLOT_MARGIN + (x - x_min) / (x_max - x_min) * plot_w,
+            PLOT_MARGIN + (y_max - y) / (y_max - y_min) * plot_h,
+        )
+    };
+
+    writeln!(out, "<h3>{}</h3>", title)?;
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-size=\"11\">",
+        w = PLOT_WIDTH,
+        h = PLOT_HEIGHT
+    )?;
+    writeln!(
+        out,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#999\"/>",
+        PLOT_MARGIN, PLOT_MARGIN, plot_w, plot_h
+    )?;
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\">{:.4}</text><text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.4}</text>",
+        PLOT_MARGIN,
+        PLOT_HEIGHT - PLOT_MARGIN + 14.0,
+        x_min,
+        PLOT_WIDTH - PLOT_MARGIN,
+        PLOT_HEIGHT - PLOT_MARGIN + 14.0,
+        x_max
+    )?;
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.4}</text><text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.4}</text>",
+        PLOT_MARGIN - 4.0,
+        PLOT_HEIGHT - PLOT_MARGIN,
+        y_min,
+        PLOT_MARGIN - 4.0,
+        PLOT_MARGIN + 10.0,
+        y_max
+    )?;
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text><text x=\"{}\" y=\"{}\">{}</text>",
+        PLOT_WIDTH / 2.0,
+        PLOT_HEIGHT - PLOT_MARGIN + 28.0,
+        x_label,
+        PLOT_MARGIN,
+        PLOT_MARGIN - 8.0,
+        y_label
+    )?;
+
+    for (i, s) in series.iter().enumerate() {
+        let color = css_color(s.color);
+        for segment in &s.segments {
+            write!(
+                out,
+                "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"",
+                color
+            )?;
+            for point in segment {
+                let (x, y) = to_screen(*point);
+                write!(out, "{:.1},{:.1} ", x, y)?;
+            }
+            writeln!(out, "\"/>")?;
+        }
+        // the legend in the top left corner of the plot
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>",
+            PLOT_MARGIN + 6.0,
+            PLOT_MARGIN + 12.0 * (i + 1) as f64,
+            color,
+            escape(&s.name)
+        )?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+fn table_row(out: &mut impl Write, cells: &[&str]) -> io::Result<()> {
+    write!(out, "<tr>")?;
+    for cell in cells {
+        write!(out, "<td>{}</td>", escape(cell))?;
+    }
+    writeln!(out, "</tr>")
+}
+
+fn css_color([r, g, b]: [f32; 3]) -> String {
+    let to_byte = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", to_byte(r), to_byte(g), to_byte(b))
+}
+
+/// Escapes the characters that have a special meaning in HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
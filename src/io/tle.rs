@@ -0,0 +1,164 @@
+use std::{f64::consts::PI, fmt};
+
+use crate::simulation::{OrbitalElements, GM};
+
+/// A satellite read from a two-line element set
+#[derive(Debug, Clone)]
+pub struct Tle {
+    /// The name from the optional title line
+    pub name: Option<String>,
+    /// Elements placed in the simulation so that the satellite is over the same point of the
+    /// Earth at t=0 as at the epoch of the element set
+    pub elements: OrbitalElements,
+}
+
+#[derive(Debug, Clone)]
+pub enum TleError {
+    MissingLine(u8),
+    Checksum(u8),
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for TleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TleError::MissingLine(n) => write!(f, "line {} of the element set is missing", n),
+            TleError::Checksum(n) => write!(f, "checksum mismatch in line {}", n),
+            TleError::InvalidField(name) => write!(f, "invalid {}", name),
+        }
+    }
+}
+
+impl Tle {
+    /// Parses a two-line element set, optionally preceded by a title line.
+    ///
+    /// The mean elements are converted to osculating Keplerian elements directly, without SGP4,
+    /// which is accurate enough for showing the ground track over a few orbits. The simulation
+    /// starts with the Greenwich meridian at the vernal equinox, so the right ascension of the
+    /// ascending node is shifted by the sidereal time at the epoch.
+    pub fn parse(text: &str) -> Result<Self, TleError> {
+        let lines: Vec<&str> = text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let line1_idx = lines
+            .iter()
+            .position(|line| line.starts_with("1 "))
+            .ok_or(TleError::MissingLine(1))?;
+        let line1 = lines[line1_idx];
+        let line2 = lines
+            .get(line1_idx + 1)
+            .filter(|line| line.starts_with("2 "))
+            .ok_or(TleError::MissingLine(2))?;
+        let name = line1_idx
+            .checked_sub(1)
+            .map(|idx| lines[idx].trim_start_matches("0 ").trim().to_string());
+
+        verify_checksum(line1, 1)?;
+        verify_checksum(line2, 2)?;
+
+        let epoch_year: i32 = field(line1, 18..20, "epoch year")?;
+        let epoch_day: f64 = field(line1, 20..32, "epoch day")?;
+        let epoch_year = if epoch_year < 57 {
+            2000 + epoch_year
+        } else {
+            1900 + epoch_year
+        };
+
+        let inclination: f64 = field(line2, 8..16, "inclination")?;
+        let raan: f64 = field(line2, 17..25, "right ascension of the ascending node")?;
+        let eccentricity: f64 = line2
+            .get(26..33)
+            .and_then(|s| format!("0.{}", s.trim()).parse().ok())
+            .ok_or(TleError::InvalidField("eccentricity"))?;
+        let arg_perigee: f64 = field(line2, 34..42, "argument of perigee")?;
+        let mean_anomaly: f64 = field(line2, 43..51, "mean anomaly")?;
+        let mean_motion: f64 = field(line2, 52..63, "mean motion")?;
+        if eccentricity >= 1.0 {
+            return Err(TleError::InvalidField("eccentricity"));
+        }
+        if mean_motion <= 0.0 {
+            return Err(TleError::InvalidField("mean motion"));
+        }
+
+        // mean motion is given in revolutions per day
+        let n = mean_motion * 2.0 * PI / 86400.0;
+        let semi_major_axis = (GM / (n * n)).cbrt();
+
+        let gmst = sidereal_time(epoch_year, epoch_day);
+
+        Ok(Self {
+            name,
+            elements: OrbitalElements {
+                semi_major_axis,
+                eccentricity,
+                inclination,
+                raan: (raan - gmst).rem_euclid(360.0),
+                arg_perigee,
+                true_anomaly: true_anomaly(mean_anomaly, eccentricity),
+            },
+        })
+    }
+}
+
+fn field<T: std::str::FromStr>(
+    line: &str,
+    range: std::ops::Range<usize>,
+    name: &'static str,
+) -> Result<T, TleError> {
+    line.get(range)
+        .and_then(|s| s.trim().parse().ok())
+        .ok_or(TleError::InvalidField(name))
+}
+
+/// The last digit of a line is the sum of its digits, with minus signs counting as 1, modulo 10
+fn verify_checksum(line: &str, line_no: u8) -> Result<(), TleError> {
+    let line = line.get(..69).ok_or(TleError::Checksum(line_no))?;
+    let (data, check) = line.split_at(68);
+    let sum: u32 = data
+        .chars()
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum();
+    if check.parse() == Ok(sum % 10) {
+        Ok(())
+    } else {
+        Err(TleError::Checksum(line_no))
+    }
+}
+
+/// Solves Kepler's equation for the true anomaly in degrees, given the mean anomaly in degrees
+fn true_anomaly(mean_anomaly: f64, e: f64) -> f64 {
+    let m = mean_anomaly.to_radians();
+    let mut ecc_anomaly = if e < 0.8 { m } else { PI };
+    for _ in 0..50 {
+        let delta = (ecc_anomaly - e * ecc_anomaly.sin() - m) / (1.0 - e * ecc_anomaly.cos());
+        ecc_anomaly -= delta;
+        if delta.abs() < 1e-12 {
+            break;
+        }
+    }
+    let nu = 2.0
+        * ((1.0 + e).sqrt() * (ecc_anomaly / 2.0).sin())
+            .atan2((1.0 - e).sqrt() * (ecc_anomaly / 2.0).cos());
+    nu.to_degrees().rem_euclid(360.0)
+}
+
+/// Greenwich mean sidereal time in degrees at the given fractional day of the year (1.0 being
+/// January 1st, 0:00 UTC)
+fn sidereal_time(year: i32, day: f64) -> f64 {
+    let is_leap = |y: i32| (y % 4 == 0 && y % 100 != 0) || y % 400 == 0;
+    let year_days = |y: i32| if is_leap(y) { 366.0 } else { 365.0 };
+    let mut days_since_j2000 = day - 1.5;
+    for y in year.min(2000)..year.max(2000) {
+        if year > 2000 {
+            days_since_j2000 += year_days(y);
+        } else {
+            days_since_j2000 -= year_days(y);
+        }
+    }
+    (280.46061837 + 360.98564736629 * days_since_j2000).rem_euclid(360.0)
+}
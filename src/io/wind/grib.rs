@@ -0,0 +1,351 @@
+//! GRIB2 messages with the wind components on regular latitude-longitude grids, packed with the
+//! simple packing (grid template 3.0, data representation template 5.0)
+
+use super::{invalid, unique, ByteReader, WindError};
+use crate::simulation::{pressure_altitude, WindField};
+
+/// The parameter category of the meteorological products containing the wind
+const MOMENTUM: u8 = 2;
+/// The parameter numbers of the eastward and the northward component
+const U_COMPONENT: u8 = 2;
+const V_COMPONENT: u8 = 3;
+
+#[derive(Clone, PartialEq)]
+struct Grid {
+    lats: Vec<f64>,
+    lons: Vec<f64>,
+}
+
+/// One wind component on one level at one time
+struct Record {
+    east: bool,
+    height: f64,
+    /// In seconds since 1970
+    time: f64,
+    grid: Grid,
+    values: Vec<f32>,
+}
+
+/// What a product is, if it's a wind component
+struct Product {
+    east: bool,
+    height: f64,
+    /// From the reference time of the message, in seconds
+    forecast_time: f64,
+}
+
+struct Packing {
+    reference: f32,
+    binary_scale: i32,
+    decimal_scale: i32,
+    bits: usize,
+}
+
+/// A GRIB2 integer of the given number of bits: the highest bit is the sign, the rest the
+/// magnitude
+fn signed(raw: u32, bits: u32) -> i64 {
+    let sign = 1 << (bits - 1);
+    if raw & sign != 0 {
+        -((raw & !sign) as i64)
+    } else {
+        raw as i64
+    }
+}
+
+pub(super) fn read(data: &[u8]) -> Result<WindField, WindError> {
+    let mut records = vec![];
+    let mut start = 0;
+    while let Some(found) = data[start..].windows(4).position(|bytes| bytes == b"GRIB") {
+        let message_start = start + found;
+        let header = ByteReader::new(data, message_start).bytes(16)?;
+        if header[7] != 2 {
+            return Err(WindError::Unsupported(format!(
+                "GRIB edition {}",
+                header[7]
+            )));
+        }
+        let length = u64::from_be_bytes(header[8..16].try_into().unwrap()) as usize;
+        let message = message_start
+            .checked_add(length)
+            .and_then(|end| data.get(message_start..end))
+            .filter(|_| length > 16)
+            .ok_or_else(|| invalid("truncated GRIB message"))?;
+        read_message(message, &mut records)?;
+        start = message_start + length;
+    }
+    assemble(records)
+}
+
+/// Adds the wind components in the message to `records`; a message may repeat the sections
+/// after the first one to hold several fields
+fn read_message(message: &[u8], records: &mut Vec<Record>) -> Result<(), WindError> {
+    let discipline = message[6];
+    let mut reference_time = 0.0;
+    let mut grid = None;
+    let mut product = None;
+    let mut packing = None;
+    let mut bitmap: Option<&[u8]> = None;
+
+    let mut reader = ByteReader::new(message, 16);
+    while message.get(reader.pos..reader.pos + 4) != Some(&b"7777"[..]) {
+        let start = reader.pos;
+        let length = reader.u32()? as usize;
+        let number = reader.u8()?;
+        let section = start
+            .checked_add(length)
+            .and_then(|end| message.get(start..end))
+            .filter(|_| length > 5)
+            .ok_or_else(|| invalid("truncated GRIB section"))?;
+        reader.pos = start + length;
+        match number {
+            1 => reference_time = read_reference_time(section)?,
+            3 => grid = Some(read_grid(section)?),
+            4 => product = read_product(section)?,
+            5 => packing = Some(read_packing(section)?),
+            6 => match section[5] {
+                0 => bitmap = Some(&section[6..]),
+                // the one of the previous field
+                254 => (),
+                255 => bitmap = None,
+                _ => return Err(WindError::Unsupported("predefined bitmaps".to_string())),
+            },
+            7 => {
+                let (grid, product) = match (&grid, &product) {
+                    (Some(grid), Some(product)) if discipline == 0 => (grid, product),
+                    _ => continue,
+                };
+                let packing = packing
+                    .as_ref()
+                    .ok_or_else(|| invalid("data without a representation section"))?;
+                let count = grid.lats.len() * grid.lons.len();
+                records.push(Record {
+                    east: product.east,
+                    height: product.height,
+                    time: reference_time + product.forecast_time,
+                    grid: grid.clone(),
+                    values: unpack(packing, bitmap, &section[5..], count)?,
+                });
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// The reference time of the message in seconds since 1970
+fn read_reference_time(section: &[u8]) -> Result<f64, WindError> {
+    let mut reader = ByteReader::new(section, 12);
+    let year = reader.u16()? as i64;
+    let [month, day, hour, minute, second] = [(); 5].map(|_| reader.u8().map(i64::from));
+    let days = days_from_civil(year, month?, day?);
+    Ok((((days * 24 + hour?) * 60 + minute?) * 60 + second?) as f64)
+}
+
+/// Days from 1970-01-01 to the given date of the Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn read_grid(section: &[u8]) -> Result<Grid, WindError> {
+    let template = ByteReader::new(section, 12).u16()?;
+    if template != 0 {
+        return Err(WindError::Unsupported(format!(
+            "grid template 3.{}",
+            template
+        )));
+    }
+    let mut reader = ByteReader::new(section, 30);
+    let ni = reader.u32()? as usize;
+    let nj = reader.u32()? as usize;
+    let basic_angle = reader.u32()?;
+    let subdivisions = reader.u32()?;
+    let unit = if [basic_angle, subdivisions]
+        .iter()
+        .any(|&value| value == 0 || value == u32::MAX)
+    {
+        1e-6
+    } else {
+        basic_angle as f64 / subdivisions as f64
+    };
+    let angle = |reader: &mut ByteReader| -> Result<f64, WindError> {
+        Ok(signed(reader.u32()?, 32) as f64 * unit)
+    };
+    let la1 = angle(&mut reader)?;
+    let lo1 = angle(&mut reader)?;
+    let _resolution_flags = reader.u8()?;
+    let la2 = angle(&mut reader)?;
+    let mut lo2 = angle(&mut reader)?;
+    let scanning = ByteReader::new(section, 71).u8()?;
+    // only the rows of points along the parallels, all scanned in the same direction
+    if scanning & 0x30 != 0 {
+        return Err(WindError::Unsupported(format!(
+            "scanning mode {:#04x}",
+            scanning
+        )));
+    }
+    if scanning & 0x80 == 0 && lo2 < lo1 {
+        lo2 += 360.0;
+    } else if scanning & 0x80 != 0 && lo2 > lo1 {
+        lo2 -= 360.0;
+    }
+    if ni == 0 || nj == 0 {
+        return Err(invalid("empty grid"));
+    }
+    Ok(Grid {
+        lats: linspace(la1, la2, nj),
+        lons: linspace(lo1, lo2, ni),
+    })
+}
+
+fn linspace(first: f64, last: f64, n: usize) -> Vec<f64> {
+    if n == 1 {
+        return vec![first];
+    }
+    (0..n)
+        .map(|i| first + (last - first) * i as f64 / (n - 1) as f64)
+        .collect()
+}
+
+/// The component and the level of a wind product; `None` for other products
+fn read_product(section: &[u8]) -> Result<Option<Product>, WindError> {
+    let template = ByteReader::new(section, 7).u16()?;
+    // the templates up to 4.15 start with the same fields, the rest may differ
+    if template > 15 {
+        return Ok(None);
+    }
+    let mut reader = ByteReader::new(section, 9);
+    let category = reader.u8()?;
+    let number = reader.u8()?;
+    let east = match (category, number) {
+        (MOMENTUM, U_COMPONENT) => true,
+        (MOMENTUM, V_COMPONENT) => false,
+        _ => return Ok(None),
+    };
+    let mut reader = ByteReader::new(section, 17);
+    let time_unit = match reader.u8()? {
+        0 => 60.0,
+        1 => 3600.0,
+        2 => 86400.0,
+        10 => 3.0 * 3600.0,
+        11 => 6.0 * 3600.0,
+        12 => 12.0 * 3600.0,
+        13 => 1.0,
+        unit => return Err(WindError::Unsupported(format!("time unit {}", unit))),
+    };
+    let forecast_time = signed(reader.u32()?, 32) as f64 * time_unit;
+    let surface = reader.u8()?;
+    let scale = match reader.u8()? {
+        0xff => 0,
+        scale => signed(scale as u32, 8),
+    };
+    let value = signed(reader.u32()?, 32) as f64 / 10.0_f64.powi(scale as i32);
+    let height = match surface {
+        // the ground
+        1 => 0.0,
+        // a pressure level, in pascals
+        100 => pressure_altitude(value / 100.0),
+        // an altitude above the sea level or a height above the ground
+        102 | 103 => value,
+        _ => return Err(WindError::Unsupported(format!("level type {}", surface))),
+    };
+    Ok(Some(Product {
+        east,
+        height,
+        forecast_time,
+    }))
+}
+
+fn read_packing(section: &[u8]) -> Result<Packing, WindError> {
+    let template = ByteReader::new(section, 9).u16()?;
+    if template != 0 {
+        return Err(WindError::Unsupported(format!(
+            "data packing template 5.{}",
+            template
+        )));
+    }
+    let mut reader = ByteReader::new(section, 11);
+    Ok(Packing {
+        reference: f32::from_bits(reader.u32()?),
+        binary_scale: signed(reader.u16()? as u32, 16) as i32,
+        decimal_scale: signed(reader.u16()? as u32, 16) as i32,
+        bits: reader.u8()? as usize,
+    })
+}
+
+/// The values of `count` grid points; only the points present in the bitmap are packed, the
+/// others are NaN
+fn unpack(
+    packing: &Packing,
+    bitmap: Option<&[u8]>,
+    data: &[u8],
+    count: usize,
+) -> Result<Vec<f32>, WindError> {
+    let binary_scale = 2.0_f64.powi(packing.binary_scale);
+    let decimal_scale = 10.0_f64.powi(-packing.decimal_scale);
+    let mut bit = 0;
+    (0..count)
+        .map(|i| {
+            let present = bitmap.is_none_or(|bitmap| {
+                bitmap
+                    .get(i / 8)
+                    .is_some_and(|byte| byte & (0x80 >> (i % 8)) != 0)
+            });
+            if !present {
+                return Ok(f32::NAN);
+            }
+            let packed = read_bits(data, bit, packing.bits)
+                .ok_or_else(|| invalid("too little packed data"))?;
+            bit += packing.bits;
+            let value = (packing.reference as f64 + packed as f64 * binary_scale) * decimal_scale;
+            Ok(value as f32)
+        })
+        .collect()
+}
+
+/// The unsigned number in `bits` bits starting at the bit `start`, counted from the highest
+/// bit of the first byte
+fn read_bits(data: &[u8], start: usize, bits: usize) -> Option<u64> {
+    (start..start + bits).try_fold(0, |value, bit| {
+        let byte = *data.get(bit / 8)?;
+        Some(value << 1 | ((byte >> (7 - bit % 8)) & 1) as u64)
+    })
+}
+
+/// Puts the components together on a grid of the times and the levels of the records
+fn assemble(records: Vec<Record>) -> Result<WindField, WindError> {
+    let grid = match records.first() {
+        Some(record) => record.grid.clone(),
+        None => return Err(invalid("no wind components in the file")),
+    };
+    if records.iter().any(|record| record.grid != grid) {
+        return Err(WindError::Unsupported(
+            "components on different grids".to_string(),
+        ));
+    }
+    if !records.iter().any(|record| record.east) || records.iter().all(|record| record.east) {
+        return Err(invalid(
+            "both the eastward and the northward wind are needed",
+        ));
+    }
+    let times = unique(records.iter().map(|record| record.time).collect());
+    let heights = unique(records.iter().map(|record| record.height).collect());
+    let size = grid.lats.len() * grid.lons.len();
+    let mut east = vec![f32::NAN; times.len() * heights.len() * size];
+    let mut north = east.clone();
+    for record in records {
+        let i_t = times.partition_point(|&t| t < record.time);
+        let i_h = heights.partition_point(|&height| height < record.height);
+        let offset = (i_t * heights.len() + i_h) * size;
+        let values = if record.east { &mut east } else { &mut north };
+        values[offset..offset + size].copy_from_slice(&record.values);
+    }
+    let start = times[0];
+    let times = times.iter().map(|t| t - start).collect();
+    WindField::new(grid.lats, grid.lons, heights, times, east, north)
+        .ok_or_else(|| invalid("the values don't match the grid"))
+}
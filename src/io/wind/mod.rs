@@ -0,0 +1,97 @@
+//! Reading wind grids from weather data files: GRIB2 and classic NetCDF
+
+mod grib;
+mod netcdf;
+
+use std::{fmt, fs, io, path::Path};
+
+use crate::simulation::WindField;
+
+#[derive(Debug)]
+pub enum WindError {
+    Io(io::Error),
+    UnknownFormat,
+    /// A feature of the format that isn't implemented
+    Unsupported(String),
+    Invalid(String),
+}
+
+impl fmt::Display for WindError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindError::Io(err) => write!(f, "couldn't read the file: {}", err),
+            WindError::UnknownFormat => write!(f, "the file is neither GRIB2 nor NetCDF"),
+            WindError::Unsupported(what) => write!(f, "unsupported: {}", what),
+            WindError::Invalid(what) => write!(f, "invalid file: {}", what),
+        }
+    }
+}
+
+fn invalid(what: &str) -> WindError {
+    WindError::Invalid(what.to_string())
+}
+
+/// Reads the eastward and northward wind components from a GRIB2 or a classic NetCDF file.
+/// The first time in the file becomes the start of the simulation, and pressure levels are
+/// placed at the heights they have in the standard atmosphere.
+pub fn load_wind_field<P: AsRef<Path>>(path: P) -> Result<WindField, WindError> {
+    let data = fs::read(path).map_err(WindError::Io)?;
+    if data.starts_with(b"GRIB") {
+        grib::read(&data)
+    } else if data.starts_with(b"CDF") {
+        netcdf::read(&data)
+    } else if data.starts_with(b"\x89HDF") {
+        Err(WindError::Unsupported(
+            "NetCDF-4 files; convert the file to the classic format, e.g. with \
+             `nccopy -k classic`"
+                .to_string(),
+        ))
+    } else {
+        Err(WindError::UnknownFormat)
+    }
+}
+
+/// Reads big-endian values from a byte slice
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], WindError> {
+        let bytes = self
+            .pos
+            .checked_add(n)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| invalid("unexpected end of the data"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, WindError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, WindError> {
+        Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, WindError> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, WindError> {
+        Ok(u64::from_be_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+}
+
+/// Sorts the coordinates and removes the repeated ones
+fn unique(mut values: Vec<f64>) -> Vec<f64> {
+    values.sort_by(f64::total_cmp);
+    values.dedup();
+    values
+}
@@ -0,0 +1,374 @@
+//! Classic NetCDF files (the CDF-1 and CDF-2 formats) with the wind components as variables
+//! over the time, the level, the latitude and the longitude
+
+use super::{invalid, ByteReader, WindError};
+use crate::simulation::{pressure_altitude, WindField};
+
+const NC_DIMENSION: u32 = 0x0a;
+const NC_VARIABLE: u32 = 0x0b;
+const NC_ATTRIBUTE: u32 = 0x0c;
+const NC_CHAR: u32 = 2;
+
+/// The names the reanalyses give to the eastward and the northward wind
+const EAST_NAMES: [&str; 4] = ["u", "u10", "uwnd", "U"];
+const NORTH_NAMES: [&str; 4] = ["v", "v10", "vwnd", "V"];
+
+enum Attribute {
+    Text(String),
+    Numbers(Vec<f64>),
+}
+
+struct Variable {
+    name: String,
+    dims: Vec<usize>,
+    attributes: Vec<(String, Attribute)>,
+    nc_type: u32,
+    /// The size of the data, or of a single record of a record variable, padded to 4 bytes
+    size: usize,
+    begin: usize,
+}
+
+impl Variable {
+    fn number(&self, name: &str) -> Option<f64> {
+        self.attributes.iter().find_map(|(key, value)| match value {
+            Attribute::Numbers(numbers) if key == name => numbers.first().copied(),
+            _ => None,
+        })
+    }
+
+    fn text(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find_map(|(key, value)| match value {
+            Attribute::Text(text) if key == name => Some(text.as_str()),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Time,
+    Level,
+    Lat,
+    Lon,
+}
+
+struct File<'a> {
+    data: &'a [u8],
+    dims: Vec<(String, usize)>,
+    /// The dimension growing with the records, whose length is `records`
+    record_dim: Option<usize>,
+    records: usize,
+    variables: Vec<Variable>,
+    /// The distance between the consecutive records of a record variable
+    record_size: usize,
+}
+
+fn padded(size: usize) -> usize {
+    size.div_ceil(4) * 4
+}
+
+fn type_size(nc_type: u32) -> Result<usize, WindError> {
+    match nc_type {
+        1 | 2 => Ok(1),
+        3 => Ok(2),
+        4 | 5 => Ok(4),
+        6 => Ok(8),
+        _ => Err(WindError::Unsupported(format!("NetCDF type {}", nc_type))),
+    }
+}
+
+/// The first `count` numbers of the given type in `bytes`
+fn decode(bytes: &[u8], nc_type: u32, count: usize) -> Vec<f64> {
+    let size = type_size(nc_type).unwrap_or(1);
+    bytes
+        .chunks_exact(size)
+        .take(count)
+        .map(|chunk| match nc_type {
+            1 => chunk[0] as i8 as f64,
+            3 => i16::from_be_bytes(chunk.try_into().unwrap()) as f64,
+            4 => i32::from_be_bytes(chunk.try_into().unwrap()) as f64,
+            5 => f32::from_be_bytes(chunk.try_into().unwrap()) as f64,
+            6 => f64::from_be_bytes(chunk.try_into().unwrap()),
+            _ => chunk[0] as f64,
+        })
+        .collect()
+}
+
+/// A list of the header: a tag, the number of elements and the elements; an absent list is
+/// written as two zeros
+fn read_list<'a, T>(
+    reader: &mut ByteReader<'a>,
+    tag: u32,
+    mut element: impl FnMut(&mut ByteReader<'a>) -> Result<T, WindError>,
+) -> Result<Vec<T>, WindError> {
+    let found = reader.u32()?;
+    let count = reader.u32()? as usize;
+    if found == 0 && count == 0 {
+        return Ok(vec![]);
+    }
+    if found != tag {
+        return Err(invalid("malformed NetCDF header"));
+    }
+    (0..count).map(|_| element(reader)).collect()
+}
+
+fn read_name(reader: &mut ByteReader) -> Result<String, WindError> {
+    let length = reader.u32()? as usize;
+    let bytes = reader.bytes(padded(length))?;
+    Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+}
+
+fn read_attributes(reader: &mut ByteReader) -> Result<Vec<(String, Attribute)>, WindError> {
+    read_list(reader, NC_ATTRIBUTE, |reader| {
+        let name = read_name(reader)?;
+        let nc_type = reader.u32()?;
+        let count = reader.u32()? as usize;
+        let bytes = reader.bytes(padded(count * type_size(nc_type)?))?;
+        let value = if nc_type == NC_CHAR {
+            let text = String::from_utf8_lossy(&bytes[..count]);
+            Attribute::Text(text.trim_end_matches('\0').to_string())
+        } else {
+            Attribute::Numbers(decode(bytes, nc_type, count))
+        };
+        Ok((name, value))
+    })
+}
+
+fn parse(data: &[u8]) -> Result<File<'_>, WindError> {
+    let mut reader = ByteReader::new(data, 3);
+    let version = reader.u8()?;
+    if version != 1 && version != 2 {
+        return Err(WindError::Unsupported(format!(
+            "NetCDF format version {}",
+            version
+        )));
+    }
+    let records = reader.u32()?;
+    if records == u32::MAX {
+        return Err(WindError::Unsupported(
+            "NetCDF files being written".to_string(),
+        ));
+    }
+    let dims = read_list(&mut reader, NC_DIMENSION, |reader| {
+        Ok((read_name(reader)?, reader.u32()? as usize))
+    })?;
+    let _global_attributes = read_attributes(&mut reader)?;
+    let variables = read_list(&mut reader, NC_VARIABLE, |reader| {
+        let name = read_name(reader)?;
+        let dim_count = reader.u32()? as usize;
+        let dims = (0..dim_count)
+            .map(|_| reader.u32().map(|dim| dim as usize))
+            .collect::<Result<Vec<_>, _>>()?;
+        let attributes = read_attributes(reader)?;
+        let nc_type = reader.u32()?;
+        let size = reader.u32()? as usize;
+        let begin = if version == 1 {
+            reader.u32()? as usize
+        } else {
+            reader.u64()? as usize
+        };
+        Ok(Variable {
+            name,
+            dims,
+            attributes,
+            nc_type,
+            size,
+            begin,
+        })
+    })?;
+
+    if variables
+        .iter()
+        .any(|var| var.dims.iter().any(|&dim| dim >= dims.len()))
+    {
+        return Err(invalid("a variable over an unknown dimension"));
+    }
+    let record_dim = dims.iter().position(|(_, length)| *length == 0);
+    let record_vars: Vec<_> = variables
+        .iter()
+        .filter(|var| record_dim.is_some() && var.dims.first() == record_dim.as_ref())
+        .collect();
+    // a single record variable isn't padded
+    let record_size = match record_vars[..] {
+        [var] => {
+            let count: usize = var.dims[1..].iter().map(|&dim| dims[dim].1).product();
+            count * type_size(var.nc_type)?
+        }
+        _ => record_vars.iter().map(|var| var.size).sum(),
+    };
+    Ok(File {
+        data,
+        dims,
+        record_dim,
+        records: records as usize,
+        variables,
+        record_size,
+    })
+}
+
+impl<'a> File<'a> {
+    fn dim_len(&self, dim: usize) -> usize {
+        if Some(dim) == self.record_dim {
+            self.records
+        } else {
+            self.dims[dim].1
+        }
+    }
+
+    fn variable(&self, name: &str) -> Option<&Variable> {
+        self.variables.iter().find(|var| var.name == name)
+    }
+
+    /// The values of the variable, unpacked, with the missing ones as NaN
+    fn values(&self, var: &Variable) -> Result<Vec<f64>, WindError> {
+        let size = type_size(var.nc_type)?;
+        let is_record = self.record_dim.is_some() && var.dims.first() == self.record_dim.as_ref();
+        let (records, dims) = if is_record {
+            (self.records, &var.dims[1..])
+        } else {
+            (1, &var.dims[..])
+        };
+        let count: usize = dims.iter().map(|&dim| self.dims[dim].1).product();
+        let mut values = Vec::with_capacity(records * count);
+        for record in 0..records {
+            let start = var.begin + record * self.record_size;
+            let bytes = ByteReader::new(self.data, start).bytes(count * size)?;
+            values.extend(decode(bytes, var.nc_type, count));
+        }
+        let scale = var.number("scale_factor").unwrap_or(1.0);
+        let offset = var.number("add_offset").unwrap_or(0.0);
+        let missing = [var.number("_FillValue"), var.number("missing_value")];
+        Ok(values
+            .into_iter()
+            .map(|value| {
+                if missing.contains(&Some(value)) {
+                    f64::NAN
+                } else {
+                    value * scale + offset
+                }
+            })
+            .collect())
+    }
+
+    /// The values of the coordinate variable of the dimension
+    fn coordinates(&self, dim: usize) -> Result<(Vec<f64>, Option<&str>), WindError> {
+        let name = &self.dims[dim].0;
+        let var = self
+            .variable(name)
+            .ok_or_else(|| invalid(&format!("no coordinates for the dimension {}", name)))?;
+        Ok((self.values(var)?, var.text("units")))
+    }
+
+    fn axis(&self, dim: usize) -> Axis {
+        let name = self.dims[dim].0.to_lowercase();
+        let units = self
+            .variable(&self.dims[dim].0)
+            .and_then(|var| var.text("units"))
+            .unwrap_or("");
+        if name.starts_with("lat") || units == "degrees_north" {
+            Axis::Lat
+        } else if name.starts_with("lon") || units == "degrees_east" {
+            Axis::Lon
+        } else if name.contains("time") || units.contains(" since ") {
+            Axis::Time
+        } else {
+            Axis::Level
+        }
+    }
+}
+
+pub(super) fn read(data: &[u8]) -> Result<WindField, WindError> {
+    let file = parse(data)?;
+    let find = |names: &[&str]| {
+        file.variables
+            .iter()
+            .find(|var| names.contains(&var.name.as_str()))
+    };
+    let (east_var, north_var) = match (find(&EAST_NAMES), find(&NORTH_NAMES)) {
+        (Some(east), Some(north)) => (east, north),
+        _ => {
+            return Err(invalid(
+                "both the eastward and the northward wind are needed",
+            ))
+        }
+    };
+    if east_var.dims != north_var.dims {
+        return Err(WindError::Unsupported(
+            "components on different grids".to_string(),
+        ));
+    }
+
+    let dims = &east_var.dims;
+    let axes: Vec<_> = dims.iter().map(|&dim| file.axis(dim)).collect();
+    let dim_of = |axis: Axis| axes.iter().position(|&a| a == axis).map(|i| dims[i]);
+    let layout_ok = matches!(
+        axes[..],
+        [Axis::Lat, Axis::Lon]
+            | [Axis::Time, Axis::Lat, Axis::Lon]
+            | [Axis::Level, Axis::Lat, Axis::Lon]
+            | [Axis::Time, Axis::Level, Axis::Lat, Axis::Lon]
+    );
+    if !layout_ok {
+        let names: Vec<_> = dims.iter().map(|&dim| file.dims[dim].0.as_str()).collect();
+        return Err(WindError::Unsupported(format!(
+            "the dimensions ({})",
+            names.join(", ")
+        )));
+    }
+
+    let (lats, _) = file.coordinates(dim_of(Axis::Lat).unwrap())?;
+    let (lons, _) = file.coordinates(dim_of(Axis::Lon).unwrap())?;
+    let times = match dim_of(Axis::Time) {
+        Some(dim) => {
+            let (times, units) = file.coordinates(dim)?;
+            let unit = time_unit(units.unwrap_or("seconds"))?;
+            let start = times.first().copied().unwrap_or(0.0);
+            times.iter().map(|t| (t - start) * unit).collect()
+        }
+        None => vec![0.0],
+    };
+    let heights = match dim_of(Axis::Level) {
+        Some(dim) => {
+            let (levels, units) = file.coordinates(dim)?;
+            let height = level_height(units.unwrap_or(""))?;
+            levels.into_iter().map(height).collect()
+        }
+        // e.g. "u10" is the wind 10 meters above the ground
+        None => vec![east_var
+            .name
+            .trim_start_matches(char::is_alphabetic)
+            .parse()
+            .unwrap_or(10.0)],
+    };
+    if dims.iter().any(|&dim| file.dim_len(dim) == 0) {
+        return Err(invalid("empty grid"));
+    }
+
+    let to_f32 = |values: Vec<f64>| values.into_iter().map(|value| value as f32).collect();
+    let east = to_f32(file.values(east_var)?);
+    let north = to_f32(file.values(north_var)?);
+    WindField::new(lats, lons, heights, times, east, north)
+        .ok_or_else(|| invalid("the values don't match the grid"))
+}
+
+/// The length of the unit of e.g. "hours since 1900-01-01 00:00:00.0" in seconds
+fn time_unit(units: &str) -> Result<f64, WindError> {
+    match units.split_whitespace().next().unwrap_or("") {
+        "seconds" | "second" | "s" => Ok(1.0),
+        "minutes" | "minute" => Ok(60.0),
+        "hours" | "hour" | "h" => Ok(3600.0),
+        "days" | "day" => Ok(86400.0),
+        unit => Err(WindError::Unsupported(format!("time unit {}", unit))),
+    }
+}
+
+/// The conversion of the values of a level coordinate with the given units to heights in meters
+fn level_height(units: &str) -> Result<fn(f64) -> f64, WindError> {
+    match units {
+        "millibars" | "millibar" | "mbar" | "hPa" => Ok(pressure_altitude),
+        "Pa" => Ok(|pa| pressure_altitude(pa / 100.0)),
+        "m" | "meters" | "metres" => Ok(|height| height),
+        "km" => Ok(|height| height * 1e3),
+        _ => Err(WindError::Unsupported(format!("level units \"{}\"", units))),
+    }
+}
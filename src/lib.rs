@@ -0,0 +1,10 @@
+//! The simulation, its state and the rendering behind the demo, also usable by external tools
+//! and the benchmarks
+
+pub mod cli;
+pub mod headless;
+pub mod interface;
+pub mod io;
+pub mod renderer;
+pub mod simulation;
+pub mod state;
@@ -1,27 +1,87 @@
-mod interface;
-mod renderer;
-mod simulation;
-mod state;
+use std::time::Instant;
 
 use glium::glutin;
-use numeric_algs::integration::RK4Integrator;
 
-use renderer::Renderer;
-
-use crate::{
-    simulation::OMEGA,
-    state::{CameraStateDef, ObjectDescription, ObjectKind, ObjectKindTag, State, StateTag},
+use coriolis_demo_3d::{
+    cli::Options,
+    headless,
+    interface::{
+        display_aiming_tool, display_calculator, display_color_legend, display_energy_plot,
+        display_experiment, display_force_balance, display_force_plot, display_ground_stations,
+        display_intercept_tool, display_links, display_map, display_object, display_quiz,
+        display_ring_diagnostics, display_status_bar, display_weight_chart,
+        draw_attitude_indicator, map_image, ObjectAction,
+    },
+    io::{self, Tle},
+    renderer::Renderer,
+    simulation::{
+        frame_angle, pos_to_lat_lon_elev, run_benchmarks, Atmosphere, IntegrationMethod,
+        ObjectIntegrator, SpinRamp, TwoBodySystem, OMEGA,
+    },
+    state::{
+        fix_all, validate, Background, CameraBookmark, CameraStateDef, ColorMode, DecimalSeparator,
+        FrameRotationStop, MapProjection, ObjectDescription, ObjectKind, ObjectKindTag,
+        PathColorMode, Preset, Projection, Scenario, SpeedMode, State, StateTag, VariedParameter,
+        BUILTIN_FRAME_ROTATIONS, FRAME_ROTATION_FINE_NUDGE, FRAME_ROTATION_NUDGE, SPEED_PRESETS,
+    },
 };
 
-use interface::display_object;
-
 enum EditResult {
     None,
     Ok,
     Cancel,
+    /// Apply the fixes suggested by the warnings
+    Fix,
+    /// Apply the state despite the warnings
+    Proceed,
 }
 
 fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut state = State::default();
+
+    if let Some(path) = &options.scenario {
+        match Scenario::load(path) {
+            Ok(scenario) => state.load_scenario(scenario),
+            Err(err) => {
+                eprintln!("{}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(path) = &options.wind {
+        state.wind_path = path.display().to_string();
+        state.start_loading_wind();
+        state.wait_for_wind();
+        if let Some(message) = &state.wind_message {
+            eprintln!("{}", message);
+        }
+    }
+    if options.autostart {
+        state.running = true;
+    }
+
+    if options.headless {
+        let result = headless::run(
+            &mut state,
+            options.duration,
+            options.interval,
+            options.output.as_deref(),
+        );
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let event_loop = glutin::event_loop::EventLoop::with_user_event();
     let display = create_display(&event_loop);
 
@@ -29,25 +89,52 @@ fn main() {
 
     let mut renderer = Renderer::new(&display);
 
-    let mut state = State::default();
+    let mut integrator = ObjectIntegrator::new(10.0);
 
-    let mut integrator = RK4Integrator::new(10.0);
+    let mut last_frame = Instant::now();
+    // loaded when the map is first shown, and again when its projection changes
+    let mut map_texture: Option<(MapProjection, egui::TextureHandle)> = None;
 
     event_loop.run(move |event, _, control_flow| {
         let mut redraw = || {
             let mut quit = false;
 
-            if state.running {
-                for obj in &mut state.objects {
-                    obj.step(&mut integrator, state.time_step);
-                }
-                state.t += state.time_step;
-                state.ang += state.omega * OMEGA * state.time_step;
+            let now = Instant::now();
+            let wall_dt = if state.recording.is_active() {
+                state.recording.frame_time()
+            } else {
+                (now - last_frame).as_secs_f64()
+            };
+            last_frame = now;
+
+            state.poll_precompute();
+            state.poll_wind();
+            state.aiming_tool.poll();
+            state.intercept_tool.poll();
+            state.update_playback(wall_dt);
+            state.camera_state.update_transition(wall_dt);
+            let t_before = state.t;
+            if state.running && state.precompute.is_none() {
+                integrator.method = state.integration;
+                state.step_frame(&mut integrator, wall_dt);
+                state.update_quiz();
             }
+            state.speed.measure(state.t - t_before, wall_dt);
+            state.update_frame_alpha();
+
+            let controls_before = state.control_values();
 
             let needs_repaint = egui_glium.run(&display, |egui_ctx| {
                 if state.running {
-                    state.render_settings.max_t = state.t;
+                    state.render_settings.max_t = state.render_time();
+                }
+                // the object clicked in the scene in this frame, whose panel gets opened
+                let mut picked_object = None;
+
+                if state.show_status_bar {
+                    egui::TopBottomPanel::bottom("status bar").show(egui_ctx, |ui| {
+                        display_status_bar(&state, ui);
+                    });
                 }
 
                 egui::CentralPanel::default()
@@ -55,7 +142,32 @@ fn main() {
                     .show(egui_ctx, |ui| {
                         let available_size = ui.available_size();
                         let (id, rect) = ui.allocate_space(available_size);
-                        let response = ui.interact(rect, id, egui::Sense::drag());
+                        let response = ui.interact(rect, id, egui::Sense::click_and_drag());
+                        if let Some(pointer) = response
+                            .interact_pointer_pos()
+                            .filter(|_| response.clicked())
+                        {
+                            let pos = pointer - rect.min;
+                            state.selected_object = Renderer::pick_object(
+                                &state,
+                                pos.x,
+                                pos.y,
+                                rect.width(),
+                                rect.height(),
+                            );
+                            picked_object = state.selected_object;
+                        }
+                        if state.render_settings.draw_labels {
+                            draw_labels(ui, rect, &state);
+                        }
+                        if state.render_settings.draw_arrow_labels {
+                            draw_arrow_labels(ui, rect, &state);
+                        }
+                        if let Some(attitude) =
+                            Renderer::camera_attitude(&state).filter(|_| state.show_attitude)
+                        {
+                            draw_attitude_indicator(ui, rect, attitude);
+                        }
                         if ui.input().modifiers.shift {
                             state.camera_state.shift_drag(response.drag_delta());
                         } else {
@@ -63,6 +175,11 @@ fn main() {
                         }
                     });
 
+                // only the scene is rendered, e.g. for compositing in streaming software
+                if state.hide_ui {
+                    return;
+                }
+
                 egui::Window::new("Simulation controls").show(egui_ctx, |ui| {
                     ui.horizontal(|ui| {
                         if state.running {
@@ -83,11 +200,128 @@ fn main() {
                         }
                     });
 
-                    ui.label("Time range to render:");
+                    ui.label("Timeline:");
                     ui.add(egui::Slider::new(
                         &mut state.render_settings.max_t,
                         0.0..=state.t,
                     ));
+                    ui.add_enabled_ui(!state.running, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button("⏮").on_hover_text("Rewind to start").clicked() {
+                                state.render_settings.max_t = 0.0;
+                                state.playback.direction = 0.0;
+                            }
+                            if ui.button("◀").on_hover_text("Play backwards").clicked() {
+                                state.playback.direction = -1.0;
+                            }
+                            if ui.button("⏸").on_hover_text("Stop playback").clicked() {
+                                state.playback.direction = 0.0;
+                            }
+                            if ui.button("▶").on_hover_text("Play").clicked() {
+                                if state.render_settings.max_t >= state.t {
+                                    state.render_settings.max_t = 0.0;
+                                }
+                                state.playback.direction = 1.0;
+                            }
+                            if ui.button("⏭").on_hover_text("Jump to the end").clicked() {
+                                state.render_settings.max_t = state.t;
+                                state.playback.direction = 0.0;
+                            }
+                            ui.add(
+                                egui::DragValue::new(&mut state.playback.rate)
+                                    .clamp_range(1.0..=1e7)
+                                    .speed(10.0),
+                            );
+                            ui.label("s/s");
+                        });
+                        if ui
+                            .button("Continue the simulation from here")
+                            .on_hover_text("Discards everything after the current point")
+                            .clicked()
+                        {
+                            state.rewind_to(state.render_settings.max_t);
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let mut days = state.precompute_until / 86400.0;
+                        ui.add(
+                            egui::DragValue::new(&mut days)
+                                .clamp_range(0.0..=365.0)
+                                .speed(0.1),
+                        );
+                        ui.label("days");
+                        state.precompute_until = days * 86400.0;
+                        if let Some(precompute) = &state.precompute {
+                            ui.add(egui::ProgressBar::new(precompute.progress()).show_percentage());
+                        } else if ui.button("Precompute run").clicked() {
+                            state.start_precompute();
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Decimal separator:");
+                        egui::ComboBox::from_id_source("decimal_separator")
+                            .selected_text(format!("{}", state.decimal_separator))
+                            .show_ui(ui, |ui| {
+                                for option in DecimalSeparator::ALL {
+                                    ui.selectable_value(
+                                        &mut state.decimal_separator,
+                                        option,
+                                        format!("{}", option),
+                                    );
+                                }
+                            });
+                    });
+
+                    ui.collapsing("Performance", |ui| {
+                        let frame_time = egui_ctx.input().unstable_dt;
+                        let separator = state.decimal_separator;
+                        ui.label(format!(
+                            "Frame time: {:.1} ms ({:.0} FPS)",
+                            separator.number(frame_time as f64 * 1e3),
+                            separator.number(1.0 / frame_time as f64)
+                        ));
+                        ui.label(format!(
+                            "Path memory: {:.1} MB",
+                            separator.number(state.path_memory() as f64 / 1024.0 / 1024.0)
+                        ));
+                        ui.horizontal(|ui| {
+                            ui.label("Budget:");
+                            ui.add(
+                                egui::DragValue::new(&mut state.memory_budget.megabytes)
+                                    .clamp_range(16.0..=65536.0)
+                                    .speed(16.0),
+                            );
+                            ui.label("MB");
+                        });
+                        ui.checkbox(
+                            &mut state.memory_budget.auto_decimate,
+                            "Decimate the paths when over budget",
+                        );
+                        if state.memory_budget.decimations > 0 {
+                            ui.label(format!(
+                                "Paths decimated {} time(s) to stay within the budget",
+                                state.memory_budget.decimations
+                            ));
+                        }
+                        if state.memory_budget.exceeded {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                "Memory budget exceeded! Consider pausing or resetting.",
+                            );
+                        }
+                        if ui.button("Run micro-benchmarks").clicked() {
+                            state.benchmarks = run_benchmarks();
+                        }
+                        for result in &state.benchmarks {
+                            ui.label(format!(
+                                "{}: {:.1} ns",
+                                result.name,
+                                state.decimal_separator.number(result.nanos_per_iter)
+                            ));
+                        }
+                    });
                 });
 
                 egui::Window::new("Simulation data").show(egui_ctx, |ui| {
@@ -98,9 +332,23 @@ fn main() {
 
                     ui.label("Rotate the sky (degrees):");
                     ui.add(
-                        egui::Slider::new(&mut state.render_settings.sky_rotation, 0.0..=360.0)
+                        egui::Slider::new(&mut state.celestial.sky_rotation, 0.0..=360.0)
                             .step_by(1.0),
                     );
+                    ui.label("Axial tilt (degrees):");
+                    ui.add(egui::Slider::new(
+                        &mut state.celestial.obliquity,
+                        0.0..=90.0,
+                    ));
+                    ui.checkbox(
+                        &mut state.celestial.precession,
+                        "Precession of the equinoxes",
+                    );
+                    ui.label("Precession rate exaggeration:");
+                    ui.add(
+                        egui::Slider::new(&mut state.celestial.precession_exaggeration, 1.0..=1e8)
+                            .logarithmic(true),
+                    );
 
                     ui.checkbox(&mut state.render_settings.draw_grid, "Draw grid");
                     ui.checkbox(
@@ -108,6 +356,20 @@ fn main() {
                         "Draw solid surface",
                     );
                     ui.checkbox(&mut state.render_settings.use_texture, "Use the texture");
+                    ui.checkbox(&mut state.render_settings.draw_paths, "Draw paths");
+                    ui.checkbox(
+                        &mut state.render_settings.draw_apsides,
+                        "Mark the highest and lowest points",
+                    );
+                    ui.checkbox(
+                        &mut state.render_settings.draw_ground_tracks,
+                        "Draw ground tracks",
+                    );
+                    ui.checkbox(&mut state.render_settings.draw_labels, "Draw object names");
+                    ui.checkbox(
+                        &mut state.render_settings.draw_group_paths,
+                        "Draw group centroids and spread",
+                    );
                     ui.checkbox(
                         &mut state.render_settings.draw_velocities,
                         "Draw velocities",
@@ -118,36 +380,427 @@ fn main() {
                             .logarithmic(true),
                     );
                     ui.checkbox(&mut state.render_settings.draw_forces, "Draw forces");
+                    ui.add_enabled(
+                        state.render_settings.draw_forces,
+                        egui::Checkbox::new(
+                            &mut state.render_settings.draw_euler_force,
+                            "Include the Euler force",
+                        ),
+                    );
                     ui.label("Force scale:");
                     ui.add(
                         egui::Slider::new(&mut state.render_settings.force_scale, 1e2..=1e9)
                             .logarithmic(true),
                     );
+                    ui.add_enabled_ui(
+                        state.render_settings.draw_velocities || state.render_settings.draw_forces,
+                        |ui| {
+                            ui.horizontal(|ui| {
+                                let interval = &mut state.render_settings.path_arrow_interval;
+                                let mut along_path = interval.is_some();
+                                ui.checkbox(&mut along_path, "Also along the paths, every");
+                                let mut n = interval.unwrap_or(20);
+                                ui.add_enabled(
+                                    along_path,
+                                    egui::DragValue::new(&mut n).clamp_range(1..=1000),
+                                );
+                                ui.label("samples");
+                                *interval = along_path.then_some(n);
+                            });
+                            ui.checkbox(
+                                &mut state.render_settings.draw_arrow_labels,
+                                "Print the magnitudes next to the arrows",
+                            );
+                        },
+                    );
+
+                    ui.horizontal(|ui| {
+                        ui.label("Color objects by:");
+                        egui::ComboBox::from_id_source("color_mode")
+                            .selected_text(format!("{}", state.render_settings.color_mode))
+                            .show_ui(ui, |ui| {
+                                for mode in ColorMode::ALL {
+                                    ui.selectable_value(
+                                        &mut state.render_settings.color_mode,
+                                        mode,
+                                        format!("{}", mode),
+                                    );
+                                }
+                            });
+                    });
+                    if state.render_settings.color_mode != ColorMode::Manual {
+                        display_color_legend(
+                            ui,
+                            state.render_settings.color_mode.unit(),
+                            state.color_range(),
+                            state.decimal_separator,
+                        );
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Color paths by:");
+                        egui::ComboBox::from_id_source("path_color_mode")
+                            .selected_text(format!("{}", state.render_settings.path_color_mode))
+                            .show_ui(ui, |ui| {
+                                for mode in PathColorMode::ALL {
+                                    ui.selectable_value(
+                                        &mut state.render_settings.path_color_mode,
+                                        mode,
+                                        format!("{}", mode),
+                                    );
+                                }
+                            });
+                    });
+                    if state.render_settings.path_color_mode != PathColorMode::Object {
+                        display_color_legend(
+                            ui,
+                            state.render_settings.path_color_mode.unit(),
+                            state.path_color_range(),
+                            state.decimal_separator,
+                        );
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Projection:");
+                        egui::ComboBox::from_id_source("projection")
+                            .selected_text(format!("{}", state.render_settings.projection))
+                            .show_ui(ui, |ui| {
+                                for projection in Projection::ALL {
+                                    ui.selectable_value(
+                                        &mut state.render_settings.projection,
+                                        projection,
+                                        format!("{}", projection),
+                                    );
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Background:");
+                        egui::ComboBox::from_id_source("background")
+                            .selected_text(format!("{}", state.render_settings.background))
+                            .show_ui(ui, |ui| {
+                                for background in Background::ALL {
+                                    ui.selectable_value(
+                                        &mut state.render_settings.background,
+                                        background,
+                                        format!("{}", background),
+                                    );
+                                }
+                            });
+                    });
+                    if ui.button("Hide the interface (F10 to show)").clicked() {
+                        state.hide_ui = true;
+                    }
+                    ui.checkbox(&mut state.show_status_bar, "Show the status bar");
+                    ui.checkbox(
+                        &mut state.show_attitude,
+                        "Show the heading and horizon when following objects",
+                    );
 
                     ui.separator();
 
                     ui.label(format!(
                         "Current lat: {:3.1}",
-                        state.camera_state.external.lat.to_degrees()
+                        state
+                            .decimal_separator
+                            .number(state.camera_state.external.lat.to_degrees() as f64)
                     ));
-                    let mut lon = (state.camera_state.external.lon as f64 + state.ang
-                        - OMEGA * state.t)
-                        .to_degrees()
-                        % 360.0;
+                    let camera_lon = if state.camera_state.external.inertial {
+                        state.camera_state.external.lon as f64
+                            - frame_angle(
+                                state.environment.spin,
+                                OMEGA,
+                                state.render_settings.max_t,
+                            )
+                    } else {
+                        state.camera_state.external.lon as f64 + state.ang - OMEGA * state.t
+                    };
+                    let mut lon = camera_lon.to_degrees() % 360.0;
                     if lon > 180.0 {
                         lon -= 360.0;
                     }
                     if lon < -180.0 {
                         lon += 360.0;
                     }
-                    ui.label(format!("Current lon: {:4.1}", lon));
+                    ui.label(format!(
+                        "Current lon: {:4.1}",
+                        state.decimal_separator.number(lon)
+                    ));
+
+                    ui.separator();
+
+                    ui.collapsing("Passive tracers", |ui| {
+                        let settings = &mut state.tracer_settings;
+                        ui.horizontal(|ui| {
+                            ui.label("Count:");
+                            ui.add(
+                                egui::Slider::new(&mut settings.count, 100..=200_000)
+                                    .logarithmic(true),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Center:");
+                            ui.add(
+                                egui::DragValue::new(&mut settings.lat).clamp_range(-90.0..=90.0),
+                            );
+                            ui.label("°,");
+                            ui.add(
+                                egui::DragValue::new(&mut settings.lon).clamp_range(-180.0..=180.0),
+                            );
+                            ui.label("°");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Radius:");
+                            ui.add(
+                                egui::DragValue::new(&mut settings.radius)
+                                    .clamp_range(1.0..=5000.0),
+                            );
+                            ui.label("km");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Velocity E/N:");
+                            ui.add(egui::DragValue::new(&mut settings.vel_e));
+                            ui.add(egui::DragValue::new(&mut settings.vel_n));
+                            ui.label("m/s");
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button("Spawn").clicked() {
+                                state.spawn_tracers();
+                            }
+                            if ui.button("Clear").clicked() {
+                                state.tracers.clear();
+                            }
+                            ui.label(format!("{} tracers", state.tracers.len()));
+                        });
+                    });
+
+                    ui.collapsing("Wind field", |ui| {
+                        ui.label("Real wind grids (GRIB2 or NetCDF) blowing on the drag.");
+                        ui.horizontal(|ui| {
+                            ui.label("File:");
+                            ui.text_edit_singleline(&mut state.wind_path);
+                        });
+                        ui.horizontal(|ui| {
+                            let loading = state.wind_loading.is_some();
+                            if ui
+                                .add_enabled(!loading, egui::Button::new("Load"))
+                                .clicked()
+                            {
+                                state.start_loading_wind();
+                            }
+                            if ui
+                                .add_enabled(!loading, egui::Button::new("Still air"))
+                                .clicked()
+                            {
+                                state.set_wind_field(None);
+                                state.wind_message = None;
+                            }
+                        });
+                        if let Some(message) = &state.wind_message {
+                            ui.label(message);
+                        }
+                    });
+
+                    ui.collapsing("Experiment wizard", |ui| {
+                        ui.label("Runs the current objects several times, varying one parameter.");
+                        let wizard = &mut state.experiment_wizard;
+                        egui::ComboBox::from_label("Varied parameter")
+                            .selected_text(format!("{}", wizard.parameter))
+                            .show_ui(ui, |ui| {
+                                for parameter in VariedParameter::ALL {
+                                    ui.selectable_value(
+                                        &mut wizard.parameter,
+                                        parameter,
+                                        format!("{}", parameter),
+                                    );
+                                }
+                            });
+                        ui.horizontal(|ui| {
+                            ui.label("Runs:");
+                            ui.add(egui::DragValue::new(&mut wizard.runs).clamp_range(2..=20));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("From:");
+                            ui.add(egui::DragValue::new(&mut wizard.from).speed(0.1));
+                            ui.label("to:");
+                            ui.add(egui::DragValue::new(&mut wizard.to).speed(0.1));
+                            ui.label(wizard.parameter.unit());
+                        });
+                        if let Some(message) = &wizard.message {
+                            ui.colored_label(egui::Color32::RED, message);
+                        }
+                        if ui.button("Create runs and precompute").clicked() {
+                            state.start_experiment();
+                        }
+                    });
+
+                    ui.checkbox(&mut state.render_settings.draw_sun, "Draw the Sun");
+                    ui.checkbox(&mut state.render_settings.draw_moon, "Draw the Moon");
+                    ui.label("Moon ecliptic longitude at t=0 (degrees):");
+                    ui.add(egui::Slider::new(&mut state.moon.ecliptic_lon, 0.0..=360.0));
+                    ui.checkbox(
+                        &mut state.render_settings.draw_tides,
+                        "Draw the tidal bulge",
+                    );
+                    ui.label("Tide exaggeration:");
+                    ui.add(
+                        egui::Slider::new(&mut state.render_settings.tide_exaggeration, 1e3..=1e7)
+                            .logarithmic(true),
+                    );
+                    ui.checkbox(
+                        &mut state.render_settings.draw_field_lines,
+                        "Draw magnetic field lines",
+                    );
+                    ui.label("Sun ecliptic longitude at t=0 (degrees):");
+                    ui.add(egui::Slider::new(&mut state.sun.ecliptic_lon, 0.0..=360.0));
+                    ui.checkbox(&mut state.sun.annual_motion, "Annual motion of the Sun");
 
                     ui.separator();
 
                     ui.label("Rotation of the reference frame:");
-                    ui.add(egui::Slider::new(&mut state.omega, 0.0..=1.0));
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(egui::Slider::new(&mut state.omega, 0.0..=1.0).show_value(false))
+                            .changed()
+                        {
+                            state.snap_frame_rotation();
+                        }
+                        ui.add(
+                            egui::DragValue::new(&mut state.omega)
+                                .clamp_range(-10.0..=10.0)
+                                .speed(0.001)
+                                .fixed_decimals(5),
+                        )
+                        .on_hover_text("[ and ] change the value, in finer steps with Shift");
+                    });
+                    let mut remove_stop = None;
+                    ui.horizontal_wrapped(|ui| {
+                        let n_builtin = BUILTIN_FRAME_ROTATIONS.len();
+                        for (i, (name, omega)) in
+                            state.named_frame_rotations().into_iter().enumerate()
+                        {
+                            if ui
+                                .selectable_label(state.omega == omega, &name)
+                                .on_hover_text(format!("{}", omega))
+                                .clicked()
+                            {
+                                state.omega = omega;
+                            }
+                            if i >= n_builtin
+                                && ui.small_button("✖").on_hover_text("Remove").clicked()
+                            {
+                                remove_stop = Some(i - n_builtin);
+                            }
+                        }
+                    });
+                    if let Some(i) = remove_stop {
+                        state.frame_rotation_stops.remove(i);
+                    }
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut state.frame_rotation_name)
+                                .hint_text("Name"),
+                        );
+                        if ui.button("Save the rate").clicked() {
+                            let name = if state.frame_rotation_name.is_empty() {
+                                format!("{:.5}", state.omega)
+                            } else {
+                                std::mem::take(&mut state.frame_rotation_name)
+                            };
+                            state.frame_rotation_stops.push(FrameRotationStop {
+                                name,
+                                omega: state.omega,
+                            });
+                        }
+                    });
                     ui.label("Time step:");
                     ui.add(egui::Slider::new(&mut state.time_step, 1.0..=1000.0).logarithmic(true));
+                    ui.horizontal(|ui| {
+                        ui.label("Simulation speed:");
+                        egui::ComboBox::from_id_source("speed_mode")
+                            .selected_text(format!("{}", state.speed.mode))
+                            .show_ui(ui, |ui| {
+                                for mode in SpeedMode::ALL {
+                                    ui.selectable_value(
+                                        &mut state.speed.mode,
+                                        mode,
+                                        format!("{}", mode),
+                                    );
+                                }
+                            });
+                    });
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Presets:");
+                        for factor in SPEED_PRESETS {
+                            let selected = state.speed.mode == SpeedMode::TimeFactor
+                                && state.speed.time_factor == factor;
+                            if ui
+                                .selectable_label(selected, format!("{}×", factor))
+                                .clicked()
+                            {
+                                state.speed.mode = SpeedMode::TimeFactor;
+                                state.speed.time_factor = factor;
+                            }
+                        }
+                    });
+                    match state.speed.mode {
+                        SpeedMode::StepsPerFrame => {
+                            ui.add(
+                                egui::Slider::new(&mut state.speed.steps_per_frame, 1..=100)
+                                    .logarithmic(true),
+                            );
+                        }
+                        SpeedMode::TimeFactor => {
+                            ui.add(
+                                egui::Slider::new(&mut state.speed.time_factor, 0.1..=1e6)
+                                    .logarithmic(true),
+                            );
+                        }
+                    }
+                    ui.checkbox(
+                        &mut state.speed.interpolate,
+                        "Interpolate the motion between time steps",
+                    );
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut state.speed.slow_motion, "Slow motion near events")
+                            .on_hover_text("Impacts and close approaches of the objects");
+                        ui.add_enabled(
+                            state.speed.slow_motion,
+                            egui::DragValue::new(&mut state.speed.slow_motion_factor)
+                                .clamp_range(0.001..=1.0)
+                                .speed(0.01),
+                        );
+                        ui.label("× speed");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Integration method:");
+                        egui::ComboBox::from_id_source("integration_method")
+                            .selected_text(format!("{}", state.integration))
+                            .show_ui(ui, |ui| {
+                                for method in IntegrationMethod::ALL {
+                                    ui.selectable_value(
+                                        &mut state.integration,
+                                        method,
+                                        format!("{}", method),
+                                    );
+                                }
+                            });
+                    });
+                    ui.checkbox(
+                        &mut state.frame_pacing.enabled,
+                        "Fixed simulated time per frame (for recording)",
+                    );
+                    if state.frame_pacing.enabled {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut state.frame_pacing.seconds_per_frame)
+                                    .clamp_range(1.0..=86400.0)
+                                    .speed(1.0),
+                            );
+                            ui.label("s per frame");
+                        });
+                        ui.label(format!("Frame: {}", state.frame_pacing.frame));
+                    }
 
                     ui.separator();
 
@@ -170,7 +823,200 @@ fn main() {
                                     );
                                 }
                             });
-                        state.camera_state.set_from_def(selected_camera);
+                        let view = Renderer::view(&state);
+                        state.camera_state.switch_to(selected_camera, view);
+                    });
+                    ui.horizontal(|ui| {
+                        let selected: Vec<usize> = state.selected_object.into_iter().collect();
+                        let all: Vec<usize> = (0..state.objects.len()).collect();
+                        let mut fit = None;
+                        if ui
+                            .add_enabled(
+                                !selected.is_empty(),
+                                egui::Button::new("Zoom to the selected object"),
+                            )
+                            .on_disabled_hover_text("Click an object in the scene to select it")
+                            .clicked()
+                        {
+                            fit = state.bounding_sphere(&selected);
+                        }
+                        if ui.button("Fit all objects").clicked() {
+                            fit = state.bounding_sphere(&all);
+                        }
+                        if let Some((center, radius)) = fit {
+                            let view = Renderer::view(&state);
+                            let external = Renderer::external_fitting(&state, &center, radius);
+                            state.camera_state.animate_to(external, view);
+                        }
+                    });
+                    ui.collapsing("Go to a point", |ui| {
+                        let jump = &mut state.camera_jump;
+                        ui.horizontal(|ui| {
+                            ui.label("Latitude:");
+                            ui.add(
+                                egui::DragValue::new(&mut jump.lat)
+                                    .clamp_range(-90.0..=90.0)
+                                    .speed(0.1)
+                                    .suffix("°"),
+                            );
+                            ui.label("Longitude:");
+                            ui.add(
+                                egui::DragValue::new(&mut jump.lon)
+                                    .clamp_range(-180.0..=180.0)
+                                    .speed(0.1)
+                                    .suffix("°"),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Altitude:");
+                            ui.add(
+                                egui::DragValue::new(&mut jump.altitude)
+                                    .clamp_range(0.0..=2e6)
+                                    .speed(10.0)
+                                    .suffix(" km"),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            let go = ui.button("Go").clicked();
+                            let selected = state.selected_object.and_then(|i| state.objects.get(i));
+                            let go_to_selected = ui
+                                .add_enabled(
+                                    selected.is_some(),
+                                    egui::Button::new("Go above the selected object"),
+                                )
+                                .clicked();
+                            if let Some(obj) = selected.filter(|_| go_to_selected) {
+                                let pos = obj
+                                    .state_at(state.render_settings.max_t)
+                                    .pos()
+                                    .to_omega(OMEGA);
+                                let (lat, lon, _) = pos_to_lat_lon_elev(pos.pos());
+                                state.camera_jump.lat = lat;
+                                state.camera_jump.lon = lon;
+                            }
+                            if go || go_to_selected {
+                                let jump = state.camera_jump;
+                                let view = Renderer::view(&state);
+                                let external = Renderer::external_above_lat_lon(
+                                    &state,
+                                    jump.lat,
+                                    jump.lon,
+                                    jump.altitude * 1e3,
+                                );
+                                state.camera_state.animate_to(external, view);
+                            }
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Reset orientation (Home)").clicked() {
+                            let view = Renderer::view(&state);
+                            state.camera_state.reset_orientation(view);
+                        }
+                        ui.checkbox(&mut state.camera_state.north_up, "Keep north up");
+                    });
+                    ui.horizontal(|ui| {
+                        let orbit = &mut state.camera_state.auto_orbit;
+                        ui.checkbox(&mut orbit.enabled, "Orbit automatically")
+                            .on_hover_text(
+                                "Slowly circles the Earth, or the object the camera follows; \
+                                 pauses for a few seconds whenever you move the camera",
+                            );
+                        ui.add(
+                            egui::DragValue::new(&mut orbit.rate)
+                                .speed(0.1)
+                                .clamp_range(-90.0..=90.0)
+                                .suffix("°/s"),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut orbit.radius)
+                                .speed(100.0)
+                                .clamp_range(1.0..=2e6)
+                                .suffix(" km"),
+                        );
+                    });
+                    if state.camera_state.tag == StateTag::External {
+                        let view = Renderer::view(&state);
+                        if ui
+                            .checkbox(
+                                &mut state.camera_state.external.inertial,
+                                "Fixed relative to the stars",
+                            )
+                            .on_hover_text("The Earth and the frame rotate beneath the camera")
+                            .changed()
+                        {
+                            state.camera_state.start_transition(view);
+                        }
+                    }
+                    if state.camera_state.tag == StateTag::Following {
+                        let following = &mut state.camera_state.following;
+                        ui.checkbox(&mut following.first_person, "First-person view");
+                        ui.add_enabled_ui(!following.first_person, |ui| {
+                            ui.horizontal(|ui| {
+                                let mut km = following.distance / 1e3;
+                                ui.label("Distance:");
+                                ui.add(
+                                    egui::DragValue::new(&mut km)
+                                        .clamp_range(1.0..=50000.0)
+                                        .speed(10.0),
+                                );
+                                ui.label("km");
+                                following.distance = km * 1e3;
+                            });
+                            ui.label("Drag the scene to look around the object");
+                        });
+                    }
+
+                    ui.collapsing("Camera bookmarks", |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Animation time:");
+                            ui.add(
+                                egui::DragValue::new(&mut state.camera_state.animation_time)
+                                    .clamp_range(0.0..=60.0)
+                                    .speed(0.1),
+                            );
+                            ui.label("s");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut state.bookmark_name)
+                                    .hint_text("Name"),
+                            );
+                            let can_save = state.camera_state.tag == StateTag::External;
+                            if ui
+                                .add_enabled(can_save, egui::Button::new("Save the view"))
+                                .on_disabled_hover_text("Only the external camera can be saved")
+                                .clicked()
+                            {
+                                let name = if state.bookmark_name.is_empty() {
+                                    format!("View {}", state.camera_bookmarks.len() + 1)
+                                } else {
+                                    std::mem::take(&mut state.bookmark_name)
+                                };
+                                let bookmark =
+                                    CameraBookmark::new(name, &state.camera_state.external);
+                                state.camera_bookmarks.push(bookmark);
+                            }
+                        });
+                        let mut go_to = None;
+                        let mut remove = None;
+                        for (i, bookmark) in state.camera_bookmarks.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.button(&bookmark.name).clicked() {
+                                    go_to = Some(i);
+                                }
+                                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                        }
+                        if let Some(i) = go_to {
+                            let view = Renderer::view(&state);
+                            let bookmark = state.camera_bookmarks[i].clone();
+                            state.camera_state.go_to_bookmark(&bookmark, view);
+                        }
+                        if let Some(i) = remove {
+                            state.camera_bookmarks.remove(i);
+                        }
                     });
 
                     ui.separator();
@@ -179,23 +1025,346 @@ fn main() {
                         state.new_state_def = Some(state.current_state_def.clone());
                     }
 
-                    ui.label("Objects");
+                    if let Some(notes) = state.current_state_def.preset.and_then(|p| p.notes()) {
+                        ui.collapsing("About this scenario", |ui| {
+                            ui.label(notes);
+                        });
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("KML file:");
+                        ui.text_edit_singleline(&mut state.kml_path);
+                        if ui.button("Export paths").clicked() {
+                            state.export_message = Some(export_kml(&state));
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Report file:");
+                        ui.text_edit_singleline(&mut state.report_path);
+                        if ui.button("Export report").clicked() {
+                            state.export_message = Some(export_report(&state));
+                        }
+                    });
+                    if ui.button("Screenshot (F12)").clicked() {
+                        state.screenshot_requested = true;
+                    }
+                    let mut recording = state.recording.is_active();
+                    ui.checkbox(&mut recording, "Record every frame");
+                    if recording && !state.recording.is_active() {
+                        if let Err(err) = state.recording.start() {
+                            state.export_message = Some(format!("Recording failed: {}", err));
+                        }
+                    } else if !recording && state.recording.is_active() {
+                        let frames = state.recording.stop();
+                        state.export_message = Some(format!(
+                            "Recorded {} frames to {}",
+                            frames, state.recording.directory
+                        ));
+                    }
+                    ui.add_enabled_ui(!state.recording.is_active(), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Frames directory:");
+                            ui.text_edit_singleline(&mut state.recording.directory);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Frame rate:");
+                            ui.add(
+                                egui::DragValue::new(&mut state.recording.fps)
+                                    .clamp_range(1.0..=120.0)
+                                    .suffix(" fps"),
+                            );
+                        });
+                    });
+                    if let Some(message) = &state.export_message {
+                        ui.label(message);
+                    }
+
+                    let mut log_enabled = state.interaction_log.is_enabled();
+                    ui.checkbox(&mut log_enabled, "Record interactions (for research)");
+                    state.interaction_log.set_enabled(log_enabled);
+                    if log_enabled {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} recorded", state.interaction_log.len()));
+                            if ui.button("Export CSV").clicked() {
+                                state.export_message = Some(export_interactions(&state));
+                            }
+                        });
+                    }
+
+                    ui.checkbox(&mut state.show_ring_diagnostics, "Show ring diagnostics");
+                    ui.checkbox(&mut state.show_ground_stations, "Show ground stations");
+                    ui.checkbox(&mut state.show_calculator, "Show Coriolis calculator");
+                    ui.checkbox(&mut state.show_aiming_tool, "Show aim-off calculator");
+                    ui.checkbox(&mut state.show_intercept_tool, "Show interception solver");
+                    ui.checkbox(&mut state.show_map, "Show the map");
+
+                    let mut use_reference = state.render_settings.reference_point.is_some();
+                    ui.checkbox(&mut use_reference, "Report ranges from a reference point");
+                    if !use_reference {
+                        state.render_settings.reference_point = None;
+                    } else {
+                        let (lat, lon) = state
+                            .render_settings
+                            .reference_point
+                            .get_or_insert((0.0, 0.0));
+                        ui.horizontal(|ui| {
+                            ui.label("Latitude:");
+                            ui.add(
+                                egui::DragValue::new(lat)
+                                    .clamp_range(-90.0..=90.0)
+                                    .speed(0.1)
+                                    .suffix("°"),
+                            );
+                            ui.label("Longitude:");
+                            ui.add(
+                                egui::DragValue::new(lon)
+                                    .clamp_range(-180.0..=180.0)
+                                    .speed(0.1)
+                                    .suffix("°"),
+                            );
+                        });
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.label("Objects");
+                        let any_hidden = state
+                            .objects
+                            .iter()
+                            .any(|obj| obj.is_hidden() || obj.is_path_hidden());
+                        if any_hidden && ui.button("Show all").clicked() {
+                            for i in 0..state.objects.len() {
+                                state.set_visibility(i, true, true);
+                            }
+                        }
+                    });
+                    let mut follow = None;
+                    // (object, shown, path shown) changed by the user
+                    let mut visibility = None;
+                    let max_t = state.render_settings.max_t;
+                    let separator = state.decimal_separator;
                     ui.indent(0u64, |ui| {
                         for (i, obj) in state.objects.iter().enumerate() {
-                            ui.collapsing(format!("Object {}", i), |ui| {
-                                let status =
-                                    obj.status(state.omega * OMEGA, &state.render_settings);
+                            let mut header = egui::RichText::new(obj.label(i));
+                            if state.selected_object == Some(i) {
+                                header = header.strong();
+                            }
+                            if obj.is_hidden() {
+                                header = header.weak();
+                            }
+                            let header = egui::CollapsingHeader::new(header)
+                                .id_source(("object", i))
+                                .open(picked_object.filter(|picked| *picked == i).map(|_| true));
+                            header.show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    let mut shown = !obj.is_hidden();
+                                    let mut path_shown = !obj.is_path_hidden();
+                                    let changed = ui.checkbox(&mut shown, "Show").changed()
+                                        | ui.checkbox(&mut path_shown, "Show the path").changed();
+                                    if changed {
+                                        visibility = Some((i, shown, path_shown));
+                                    }
+                                });
+                                let status = obj.status(
+                                    state.frame_omega(),
+                                    &state.sun,
+                                    &state.celestial,
+                                    &state.render_settings,
+                                );
                                 for text in status {
                                     ui.label(text);
                                 }
+                                if ui.button("Follow with the camera").clicked() {
+                                    follow = Some(i);
+                                }
+                                ui.collapsing("Energy", |ui| {
+                                    display_energy_plot(obj, max_t, separator, ui, i);
+                                });
+                                ui.collapsing("Forces", |ui| {
+                                    display_force_plot(obj, max_t, separator, ui, i);
+                                });
+                                ui.collapsing("Force balance", |ui| {
+                                    display_force_balance(obj, max_t, separator, ui, i);
+                                });
+                                if obj.is_weighing_scale() {
+                                    ui.collapsing("Apparent weight", |ui| {
+                                        display_weight_chart(obj, max_t, separator, ui, i);
+                                    });
+                                }
                             });
                         }
                     });
+                    if let Some((i, shown, path_shown)) = visibility {
+                        state.set_visibility(i, shown, path_shown);
+                    }
+                    if let Some(obj) = follow {
+                        let view = Renderer::view(&state);
+                        state
+                            .camera_state
+                            .switch_to(CameraStateDef::Following(obj), view);
+                    }
                 });
 
+                if state.show_ring_diagnostics {
+                    egui::Window::new("Ring diagnostics").show(egui_ctx, |ui| {
+                        display_ring_diagnostics(&state, ui);
+                    });
+                }
+
+                if state.quiz.is_some() {
+                    egui::Window::new("Exercise").show(egui_ctx, |ui| {
+                        display_quiz(&mut state, ui);
+                    });
+                }
+
+                if state.experiment.is_some() {
+                    egui::Window::new("Experiment").show(egui_ctx, |ui| {
+                        display_experiment(&mut state, ui);
+                    });
+                }
+
+                if state.show_calculator {
+                    egui::Window::new("Coriolis calculator").show(egui_ctx, |ui| {
+                        display_calculator(&mut state, ui);
+                    });
+                }
+
+                if state.show_aiming_tool {
+                    egui::Window::new("Aim-off calculator").show(egui_ctx, |ui| {
+                        display_aiming_tool(&mut state, ui);
+                    });
+                }
+
+                if state.show_intercept_tool {
+                    egui::Window::new("Interception").show(egui_ctx, |ui| {
+                        display_intercept_tool(&mut state, ui);
+                    });
+                }
+
+                if state.show_map {
+                    let projection = state.map_projection;
+                    if map_texture.as_ref().map(|(loaded, _)| *loaded) != Some(projection) {
+                        let texture = egui_ctx.load_texture("earth map", map_image(projection));
+                        map_texture = Some((projection, texture));
+                    }
+                    if let Some((_, texture)) = &map_texture {
+                        egui::Window::new("Map")
+                            .default_width(600.0)
+                            .show(egui_ctx, |ui| {
+                                display_map(&mut state, ui, texture);
+                            });
+                    }
+                }
+
+                if state.show_ground_stations {
+                    egui::Window::new("Ground stations").show(egui_ctx, |ui| {
+                        display_ground_stations(&mut state, ui);
+                    });
+                }
+
                 let mut edit_result = EditResult::None;
                 if let Some(ref mut new_state_def) = state.new_state_def {
                     egui::Window::new("Editing state").show(egui_ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Preset:");
+                            let mut selected_preset = None;
+                            egui::ComboBox::from_id_source("preset")
+                                .selected_text("Choose...")
+                                .show_ui(ui, |ui| {
+                                    for preset in Preset::ALL {
+                                        ui.selectable_value(
+                                            &mut selected_preset,
+                                            Some(preset),
+                                            format!("{}", preset),
+                                        );
+                                    }
+                                });
+                            if let Some(preset) = selected_preset {
+                                new_state_def.objects = preset.objects();
+                                new_state_def.preset = Some(preset);
+                                new_state_def.two_body = preset.two_body();
+                                if preset == Preset::ConvergingRing {
+                                    state.show_ring_diagnostics = true;
+                                }
+                                if let Some(time_step) = preset.time_step() {
+                                    state.time_step = time_step;
+                                }
+                            }
+                        });
+                        if let Some(notes) = new_state_def.preset.and_then(|p| p.notes()) {
+                            ui.label(notes);
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Co-rotating frame:");
+                            let selected = match new_state_def.two_body {
+                                Some(system) => format!("{}", system),
+                                None => "Earth only".to_string(),
+                            };
+                            egui::ComboBox::from_id_source("two_body")
+                                .selected_text(selected)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(
+                                        &mut new_state_def.two_body,
+                                        None,
+                                        "Earth only",
+                                    );
+                                    for system in TwoBodySystem::ALL {
+                                        ui.selectable_value(
+                                            &mut new_state_def.two_body,
+                                            Some(system),
+                                            format!("{}", system),
+                                        );
+                                    }
+                                });
+                        });
+                        let mut vary_spin = new_state_def.spin_ramp.is_some();
+                        ui.checkbox(&mut vary_spin, "Change the Earth's rotation during the run");
+                        if vary_spin != new_state_def.spin_ramp.is_some() {
+                            new_state_def.spin_ramp = vary_spin.then(SpinRamp::default);
+                        }
+                        if let Some(ramp) = new_state_def.spin_ramp.as_mut() {
+                            ui.horizontal(|ui| {
+                                ui.label("Final rate:");
+                                ui.add(
+                                    egui::DragValue::new(&mut ramp.final_rate)
+                                        .clamp_range(-10.0..=10.0)
+                                        .speed(0.01),
+                                );
+                                ui.label("× the real one");
+                            });
+                            ui.horizontal(|ui| {
+                                let mut start = ramp.start / 3600.0;
+                                let mut end = ramp.end / 3600.0;
+                                ui.label("Changing from");
+                                ui.add(
+                                    egui::DragValue::new(&mut start)
+                                        .clamp_range(0.0..=8760.0)
+                                        .speed(0.1),
+                                );
+                                ui.label("to");
+                                ui.add(
+                                    egui::DragValue::new(&mut end)
+                                        .clamp_range(start..=8760.0)
+                                        .speed(0.1),
+                                );
+                                ui.label("hours");
+                                ramp.start = start * 3600.0;
+                                ramp.end = end.max(start) * 3600.0;
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Atmosphere:");
+                            egui::ComboBox::from_id_source("atmosphere")
+                                .selected_text(format!("{}", new_state_def.atmosphere))
+                                .show_ui(ui, |ui| {
+                                    for atmosphere in Atmosphere::ALL {
+                                        ui.selectable_value(
+                                            &mut new_state_def.atmosphere,
+                                            atmosphere,
+                                            format!("{}", atmosphere),
+                                        );
+                                    }
+                                });
+                        });
                         ui.horizontal(|ui| {
                             ui.label("Object to add:");
                             egui::ComboBox::from_label("")
@@ -226,6 +1395,36 @@ fn main() {
                                         ObjectKindTag::Plane,
                                         format!("{}", ObjectKindTag::Plane),
                                     );
+                                    ui.selectable_value(
+                                        &mut new_state_def.selected_kind,
+                                        ObjectKindTag::ConvergingRing,
+                                        format!("{}", ObjectKindTag::ConvergingRing),
+                                    );
+                                    ui.selectable_value(
+                                        &mut new_state_def.selected_kind,
+                                        ObjectKindTag::PlumbBob,
+                                        format!("{}", ObjectKindTag::PlumbBob),
+                                    );
+                                    ui.selectable_value(
+                                        &mut new_state_def.selected_kind,
+                                        ObjectKindTag::WeighingScale,
+                                        format!("{}", ObjectKindTag::WeighingScale),
+                                    );
+                                    ui.selectable_value(
+                                        &mut new_state_def.selected_kind,
+                                        ObjectKindTag::Satellite,
+                                        format!("{}", ObjectKindTag::Satellite),
+                                    );
+                                    ui.selectable_value(
+                                        &mut new_state_def.selected_kind,
+                                        ObjectKindTag::Balloon,
+                                        format!("{}", ObjectKindTag::Balloon),
+                                    );
+                                    ui.selectable_value(
+                                        &mut new_state_def.selected_kind,
+                                        ObjectKindTag::Projectile,
+                                        format!("{}", ObjectKindTag::Projectile),
+                                    );
                                 });
                             if ui.button("Add").clicked() {
                                 let new_object_kind = match new_state_def.selected_kind {
@@ -234,6 +1433,16 @@ fn main() {
                                     ObjectKindTag::Anticyclone => ObjectKind::default_anticyclone(),
                                     ObjectKindTag::Foucault => ObjectKind::default_foucault(),
                                     ObjectKindTag::Plane => ObjectKind::default_plane(),
+                                    ObjectKindTag::ConvergingRing => {
+                                        ObjectKind::default_converging_ring()
+                                    }
+                                    ObjectKindTag::PlumbBob => ObjectKind::default_plumb_bob(),
+                                    ObjectKindTag::WeighingScale => {
+                                        ObjectKind::default_weighing_scale()
+                                    }
+                                    ObjectKindTag::Satellite => ObjectKind::default_satellite(),
+                                    ObjectKindTag::Balloon => ObjectKind::default_balloon(),
+                                    ObjectKindTag::Projectile => ObjectKind::default_projectile(),
                                 };
                                 let new_object = ObjectDescription {
                                     kind: new_object_kind,
@@ -242,21 +1451,82 @@ fn main() {
                                 new_state_def.objects.push(new_object);
                             }
                         });
+                        ui.collapsing("Import two-line element set", |ui| {
+                            ui.add(
+                                egui::TextEdit::multiline(&mut new_state_def.tle)
+                                    .font(egui::TextStyle::Monospace)
+                                    .desired_rows(3)
+                                    .desired_width(f32::INFINITY),
+                            );
+                            ui.horizontal(|ui| {
+                                if ui.button("Import").clicked() {
+                                    match Tle::parse(&new_state_def.tle) {
+                                        Ok(tle) => {
+                                            new_state_def.objects.push(ObjectDescription {
+                                                kind: ObjectKind::satellite(&tle.elements),
+                                                ..Default::default()
+                                            });
+                                            new_state_def.tle_message = Some(format!(
+                                                "Imported {}",
+                                                tle.name.as_deref().unwrap_or("satellite")
+                                            ));
+                                        }
+                                        Err(err) => {
+                                            new_state_def.tle_message =
+                                                Some(format!("Error: {}", err));
+                                        }
+                                    }
+                                }
+                                if let Some(message) = &new_state_def.tle_message {
+                                    ui.label(message);
+                                }
+                            });
+                        });
                         ui.separator();
-                        let mut to_remove: Option<usize> = None;
+                        let mut object_action = None;
                         egui::ScrollArea::vertical()
                             .max_height(300.0)
                             .show(ui, |ui| {
                                 for (index, obj) in new_state_def.objects.iter_mut().enumerate() {
-                                    if display_object(obj, ui) {
-                                        to_remove = Some(index);
+                                    let action =
+                                        ui.push_id(index, |ui| display_object(obj, ui)).inner;
+                                    if action != ObjectAction::None {
+                                        object_action = Some((index, action));
                                     }
                                 }
                             });
-                        if let Some(index) = to_remove {
-                            new_state_def.objects.remove(index);
+                        match object_action {
+                            Some((index, ObjectAction::Remove)) => {
+                                new_state_def.remove_object(index);
+                            }
+                            Some((index, ObjectAction::Duplicate)) => {
+                                let copy = new_state_def.objects[index].clone();
+                                new_state_def.insert_object(index + 1, copy);
+                            }
+                            Some((index, ObjectAction::MoveUp)) if index > 0 => {
+                                new_state_def.swap_objects(index - 1, index);
+                            }
+                            Some((index, ObjectAction::MoveDown))
+                                if index + 1 < new_state_def.objects.len() =>
+                            {
+                                new_state_def.swap_objects(index, index + 1);
+                            }
+                            _ => (),
                         }
+                        ui.collapsing("Springs and tethers", |ui| {
+                            display_links(new_state_def, ui);
+                        });
                         ui.separator();
+                        if state.t > 0.0 {
+                            ui.checkbox(
+                                &mut state.soft_start,
+                                "Start the new and changed objects at the current time",
+                            )
+                            .on_hover_text(
+                                "Otherwise they are simulated from the start up to now. \
+                                 Resetting starts all the objects at 0.",
+                            );
+                        }
                         ui.horizontal(|ui| {
                             if ui.button("OK").clicked() {
                                 edit_result = EditResult::Ok;
@@ -265,22 +1535,70 @@ fn main() {
                                 edit_result = EditResult::Cancel;
                             }
                         });
+                        if !state.input_warnings.is_empty() {
+                            ui.separator();
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                "The objects might not behave as expected:",
+                            );
+                            for warning in &state.input_warnings {
+                                ui.label(format!("• {}", warning.message));
+                            }
+                            ui.horizontal(|ui| {
+                                let can_fix = state.input_warnings.iter().any(|w| w.can_fix());
+                                if ui
+                                    .add_enabled(can_fix, egui::Button::new("Fix the inputs"))
+                                    .clicked()
+                                {
+                                    edit_result = EditResult::Fix;
+                                }
+                                if ui.button("Apply anyway").clicked() {
+                                    edit_result = EditResult::Proceed;
+                                }
+                            });
+                        }
                     });
                 }
                 match edit_result {
                     EditResult::None => (),
                     EditResult::Cancel => {
                         state.new_state_def = None;
+                        state.input_warnings.clear();
                     }
                     EditResult::Ok => {
+                        state.input_warnings = state
+                            .new_state_def
+                            .as_ref()
+                            .map(|def| validate(def, state.time_step, state.decimal_separator))
+                            .unwrap_or_default();
+                        if state.input_warnings.is_empty() {
+                            if let Some(new_state) = state.new_state_def.take() {
+                                state.apply_state_def(new_state);
+                            }
+                        }
+                    }
+                    EditResult::Fix => {
+                        if let Some(new_state) = state.new_state_def.as_mut() {
+                            let separator = state.decimal_separator;
+                            let warnings = validate(new_state, state.time_step, separator);
+                            fix_all(&warnings, new_state, &mut state.time_step);
+                            state.input_warnings = validate(new_state, state.time_step, separator);
+                        }
+                    }
+                    EditResult::Proceed => {
+                        state.input_warnings.clear();
                         if let Some(new_state) = state.new_state_def.take() {
-                            state.current_state_def = new_state;
-                            state.reset_state();
+                            state.apply_state_def(new_state);
                         }
                     }
                 }
             });
 
+            let controls_after = state.control_values();
+            state
+                .interaction_log
+                .record_changes(&controls_before, &controls_after);
+
             let needs_repaint = needs_repaint || true;
 
             *control_flow = if quit {
@@ -302,6 +1620,20 @@ fn main() {
                 // draw here
                 renderer.draw(&display, &mut target, &state);
 
+                if state.screenshot_requested {
+                    state.screenshot_requested = false;
+                    state.export_message = Some(save_screenshot(&display, &target));
+                }
+                if let Some(path) = state.recording.next_frame() {
+                    if let Err(err) = save_frame(&display, &target, &path) {
+                        let frames = state.recording.stop();
+                        state.export_message = Some(format!(
+                            "Recording stopped after {} frames: {}",
+                            frames, err
+                        ));
+                    }
+                }
+
                 egui_glium.paint(&display, &mut target);
 
                 target.finish().unwrap();
@@ -321,6 +1653,64 @@ fn main() {
                     WindowEvent::MouseWheel { delta, .. } => {
                         state.camera_state.scroll(delta);
                     }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            glutin::event::KeyboardInput {
+                                state: glutin::event::ElementState::Pressed,
+                                virtual_keycode: Some(glutin::event::VirtualKeyCode::F10),
+                                ..
+                            },
+                        ..
+                    } => {
+                        state.hide_ui = !state.hide_ui;
+                    }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            glutin::event::KeyboardInput {
+                                state: glutin::event::ElementState::Pressed,
+                                virtual_keycode: Some(glutin::event::VirtualKeyCode::F12),
+                                ..
+                            },
+                        ..
+                    } => {
+                        state.screenshot_requested = true;
+                    }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            glutin::event::KeyboardInput {
+                                state: glutin::event::ElementState::Pressed,
+                                virtual_keycode: Some(glutin::event::VirtualKeyCode::Home),
+                                ..
+                            },
+                        ..
+                    } if !egui_glium.egui_ctx.wants_keyboard_input() => {
+                        let view = Renderer::view(&state);
+                        state.camera_state.reset_orientation(view);
+                    }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            glutin::event::KeyboardInput {
+                                state: glutin::event::ElementState::Pressed,
+                                virtual_keycode:
+                                    Some(
+                                        key @ (glutin::event::VirtualKeyCode::LBracket
+                                        | glutin::event::VirtualKeyCode::RBracket),
+                                    ),
+                                ..
+                            },
+                        ..
+                    } if !egui_glium.egui_ctx.wants_keyboard_input() => {
+                        let step = if egui_glium.egui_ctx.input().modifiers.shift {
+                            FRAME_ROTATION_FINE_NUDGE
+                        } else {
+                            FRAME_ROTATION_NUDGE
+                        };
+                        if key == glutin::event::VirtualKeyCode::LBracket {
+                            state.nudge_frame_rotation(-step);
+                        } else {
+                            state.nudge_frame_rotation(step);
+                        }
+                    }
                     _ => (),
                 }
 
@@ -334,6 +1724,118 @@ fn main() {
     });
 }
 
+/// Paints the names of the named objects next to them in the scene
+fn draw_labels(ui: &egui::Ui, rect: egui::Rect, state: &State) {
+    let omega = state.frame_omega();
+    for (index, obj) in state.objects.iter().enumerate() {
+        let name = match obj.name() {
+            Some(name) => name,
+            None => continue,
+        };
+        if !state.shows_object(index) {
+            continue;
+        }
+        let sim_state = obj.state_at(state.render_settings.max_t);
+        let pos = sim_state.pos().to_omega(omega).pos();
+        if let Some((x, y)) = Renderer::screen_position(state, &pos, rect.width(), rect.height()) {
+            let [r, g, b] = obj.color();
+            ui.painter().text(
+                rect.min + egui::vec2(x + 8.0, y),
+                egui::Align2::LEFT_CENTER,
+                name,
+                egui::FontId::proportional(14.0),
+                egui::Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8),
+            );
+        }
+    }
+}
+
+/// Prints the magnitude of each drawn velocity and force at the tip of its arrow
+fn draw_arrow_labels(ui: &egui::Ui, rect: egui::Rect, state: &State) {
+    let omega = state.frame_omega();
+    let settings = &state.render_settings;
+    let colors = state.object_colors();
+    for (index, obj) in state.objects.iter().enumerate() {
+        if !state.shows_object(index) {
+            continue;
+        }
+        for sim_state in obj.arrow_states(settings) {
+            for arrow in obj.arrows(&sim_state, omega, colors[index], settings) {
+                let tip = arrow.origin + arrow.vector;
+                let (x, y) =
+                    match Renderer::screen_position(state, &tip, rect.width(), rect.height()) {
+                        Some(pos) => pos,
+                        None => continue,
+                    };
+                let magnitude = if (1e-2..1e5).contains(&arrow.magnitude) {
+                    format!("{:.2}", arrow.magnitude)
+                } else {
+                    format!("{:.2e}", arrow.magnitude)
+                };
+                let [r, g, b] = arrow.color;
+                ui.painter().text(
+                    rect.min + egui::vec2(x + 4.0, y),
+                    egui::Align2::LEFT_CENTER,
+                    format!("{} {}", magnitude, arrow.unit),
+                    egui::FontId::proportional(12.0),
+                    egui::Color32::from_rgb(
+                        (r * 255.0) as u8,
+                        (g * 255.0) as u8,
+                        (b * 255.0) as u8,
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn export_kml(state: &State) -> String {
+    let result = std::fs::File::create(&state.kml_path).and_then(|file| {
+        let mut out = std::io::BufWriter::new(file);
+        io::write_kml(&mut out, &state.objects, state.render_settings.max_t)
+    });
+    match result {
+        Ok(()) => format!("Paths exported to {}", state.kml_path),
+        Err(err) => format!("Export failed: {}", err),
+    }
+}
+
+fn export_report(state: &State) -> String {
+    let result = std::fs::File::create(&state.report_path).and_then(|file| {
+        let mut out = std::io::BufWriter::new(file);
+        io::write_report(&mut out, state)
+    });
+    match result {
+        Ok(()) => format!("Report exported to {}", state.report_path),
+        Err(err) => format!("Export failed: {}", err),
+    }
+}
+
+fn save_screenshot(display: &glium::Display, target: &glium::Frame) -> String {
+    let path = io::screenshot_path();
+    match save_frame(display, target, &path) {
+        Ok(()) => format!("Screenshot saved to {}", path),
+        Err(err) => format!("Screenshot failed: {}", err),
+    }
+}
+
+fn save_frame(display: &glium::Display, target: &glium::Frame, path: &str) -> Result<(), String> {
+    Renderer::capture(display, target)
+        .and_then(|image| image.save(path).map_err(|err| err.to_string()))
+}
+
+fn export_interactions(state: &State) -> String {
+    let path = "interactions.csv";
+    let result = std::fs::File::create(path).and_then(|file| {
+        let mut out = std::io::BufWriter::new(file);
+        state.interaction_log.write_csv(&mut out)
+    });
+    match result {
+        Ok(()) => format!("Interactions exported to {}", path),
+        Err(err) => format!("Export failed: {}", err),
+    }
+}
+
 fn create_display(event_loop: &glutin::event_loop::EventLoop<()>) -> glium::Display {
     let window_builder = glutin::window::WindowBuilder::new()
         .with_resizable(true)
@@ -341,7 +1843,9 @@ fn create_display(event_loop: &glutin::event_loop::EventLoop<()>) -> glium::Disp
             width: 800.0,
             height: 600.0,
         })
-        .with_title("Coriolis Demo 3D");
+        .with_title("Coriolis Demo 3D")
+        // needed for the transparent background mode
+        .with_transparent(true);
 
     let context_builder = glutin::ContextBuilder::new()
         .with_depth_buffer(24)
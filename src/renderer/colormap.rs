@@ -0,0 +1,28 @@
+/// Sample points of the colormap (an approximation of viridis)
+const STOPS: [[f32; 3]; 5] = [
+    [0.267, 0.005, 0.329],
+    [0.229, 0.322, 0.546],
+    [0.128, 0.567, 0.551],
+    [0.369, 0.789, 0.383],
+    [0.993, 0.906, 0.144],
+];
+
+/// Maps a value from the range [0, 1] to a color
+pub fn colormap(x: f64) -> [f32; 3] {
+    let x = if x.is_finite() {
+        x.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let scaled = x * (STOPS.len() - 1) as f64;
+    let index = (scaled.floor() as usize).min(STOPS.len() - 2);
+    let frac = (scaled - index as f64) as f32;
+
+    let c1 = STOPS[index];
+    let c2 = STOPS[index + 1];
+    [
+        c1[0] + (c2[0] - c1[0]) * frac,
+        c1[1] + (c2[1] - c1[1]) * frac,
+        c1[2] + (c2[2] - c1[2]) * frac,
+    ]
+}
@@ -5,7 +5,7 @@ use glium::{
     VertexBuffer,
 };
 
-use super::{TexturedVertex, Vertex};
+use super::{Instance, TexturedVertex, Vertex};
 use crate::simulation::lat_lon_elev_to_vec3;
 
 pub trait VertexLike: glium::Vertex {
@@ -118,6 +118,56 @@ impl<T: VertexLike> Mesh<T> {
         Mesh { vertices, indices }
     }
 
+    /// A part of the unit sphere between the given latitudes and longitudes in degrees,
+    /// parametrized like `solid_sphere`, with the texture coordinates spanning the whole part
+    pub fn sphere_patch(
+        display: &Display,
+        lat_range: (f64, f64),
+        lon_range: (f64, f64),
+        n_divisions: u32,
+    ) -> Mesh<T> {
+        let (lat_min, lat_max) = lat_range;
+        let (lon_min, lon_max) = lon_range;
+        Self::grid(display, n_divisions, |u, v| {
+            let lat = (lat_min + (lat_max - lat_min) * v).to_radians();
+            let lon = (lon_min + (lon_max - lon_min) * u).to_radians();
+            let x = lat.cos() * lon.cos();
+            let y = lat.cos() * lon.sin();
+            let z = lat.sin();
+            T::from_position_and_tex(y as f32, z as f32, x as f32, u as f32, v as f32)
+        })
+    }
+
+    /// A square grid of `n_divisions` by `n_divisions` cells, with the vertex at the relative
+    /// position (u, v) in the grid, both going from 0 to 1, given by `vertex`
+    pub fn grid<F: Fn(f64, f64) -> T>(display: &Display, n_divisions: u32, vertex: F) -> Mesh<T> {
+        let mut vertices = vec![];
+        for v_index in 0..=n_divisions {
+            let v = v_index as f64 / n_divisions as f64;
+            for u_index in 0..=n_divisions {
+                let u = u_index as f64 / n_divisions as f64;
+                vertices.push(vertex(u, v));
+            }
+        }
+
+        let row_len = n_divisions + 1;
+        let indices = (0..n_divisions)
+            .map(|v_index| {
+                let strip: Vec<u32> = (0..row_len)
+                    .flat_map(|u_index| {
+                        let index = v_index * row_len + u_index;
+                        [index + row_len, index]
+                    })
+                    .collect();
+                IndexBuffer::new(display, index::PrimitiveType::TriangleStrip, &strip).unwrap()
+            })
+            .collect();
+
+        let vertices = VertexBuffer::new(display, &vertices).unwrap();
+
+        Mesh { vertices, indices }
+    }
+
     pub fn ellipsoid(display: &Display) -> Mesh<T> {
         let n_meridians = 24;
         let n_parallels = 12;
@@ -295,4 +345,26 @@ impl<T: VertexLike> Mesh<T> {
                 .unwrap();
         }
     }
+
+    /// Draws a copy of the mesh for every instance, with the transformation and color given
+    /// per instance instead of in the uniforms
+    pub fn draw_instanced(
+        &self,
+        target: &mut Frame,
+        program: &Program,
+        instances: &VertexBuffer<Instance>,
+        draw_parameters: &DrawParameters,
+    ) {
+        for index_buffer in &self.indices {
+            target
+                .draw(
+                    (&self.vertices, instances.per_instance().unwrap()),
+                    index_buffer,
+                    program,
+                    &glium::uniforms::EmptyUniforms,
+                    draw_parameters,
+                )
+                .unwrap();
+        }
+    }
 }
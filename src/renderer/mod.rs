@@ -1,20 +1,53 @@
+mod colormap;
 mod cubemap;
 mod mesh;
+mod patch;
+mod paths;
+mod tiles;
 
 use std::io::Cursor;
 
 use glium::{
-    implement_vertex, index, uniform, uniforms::Uniforms, Display, DrawParameters, Frame, Program,
-    Surface, VertexBuffer,
+    implement_vertex, index, uniform, uniforms::Uniforms, vertex::VertexBufferSlice, Display,
+    DrawParameters, Frame, Program, Surface, VertexBuffer,
 };
-use nalgebra::{Matrix4, Point3, Vector3};
+use nalgebra::{Isometry3, Matrix4, Point3, Translation3, Unit, Vector3, Vector4};
 
 use crate::{
-    simulation::{surface_normal, OMEGA, R_EQU, R_POL},
-    State, StateTag,
+    simulation::{
+        dipole_field_line, lat_lon_elev_to_vec3, rotation, surface_normal, tide_amplitude,
+        GroupPath, Link, TwoBodySystem, MOON_DISTANCE, MOON_GM, MOON_RADIUS, OMEGA, R_EQU, R_POL,
+        SUN_DISTANCE, SUN_GM,
+    },
+    state::{
+        Background, ExternalState, Projection, State, StateTag, MAX_CAMERA_DISTANCE,
+        MIN_CAMERA_DISTANCE,
+    },
 };
+pub use colormap::colormap;
 use cubemap::Cubemap;
 pub use mesh::Mesh;
+use patch::{PatchTexture, SurfacePatch};
+use paths::{PathBuffers, PathVertex, ScreenScale};
+use tiles::TexturePyramid;
+
+/// The distance at which the Sun is drawn; it has to fit within the far clipping plane
+const SUN_DRAW_DISTANCE: f64 = 5e8;
+/// The radius of the drawn Sun, so that it has the correct angular size at `SUN_DRAW_DISTANCE`
+const SUN_DRAW_RADIUS: f32 = 2.3e6;
+/// The slant range up to which the ground station visibility cones are drawn
+const STATION_CONE_RANGE: f64 = 2e6;
+/// How far from an object's marker, in pixels, a click still picks it
+const PICK_TOLERANCE: f64 = 8.0;
+/// How much room is left around a sphere fitted in the view
+const FIT_MARGIN: f64 = 1.2;
+/// The global sphere is drawn slightly below the surface, so that it doesn't hide the objects
+/// and paths lying on the surface
+const SURFACE_SCALE: f64 = 0.9999;
+/// The detailed texture tiles are drawn this much above the global sphere, relative to its size
+const TILE_LIFT: f64 = 5e-5;
+/// The distance determining the scale of the orthographic projection in the first-person view
+const FIRST_PERSON_ORTHO_DISTANCE: f32 = 1e6;
 
 const VERTEX_SHADER_SRC: &'static str = r#"
     #version 140
@@ -42,6 +75,78 @@ const FRAGMENT_SHADER_SRC: &'static str = r#"
     }
 "#;
 
+/// Draws many copies of a mesh at once, each with its own transformation and color
+const INSTANCED_VERTEX_SHADER_SRC: &str = r#"
+    #version 140
+
+    in vec3 position;
+    in mat4 instance_matrix;
+    in vec3 instance_color;
+
+    out vec3 in_color;
+
+    void main() {
+        gl_Position = instance_matrix * vec4(position, 1.0);
+        in_color = instance_color;
+    }
+"#;
+
+const PATH_VERTEX_SHADER_SRC: &str = r#"
+    #version 140
+
+    in vec3 position;
+    in float time;
+    in float value;
+
+    uniform mat4 matrix;
+    uniform vec3 color;
+    uniform float brightness;
+    uniform bool colored;
+    uniform vec2 value_range;
+    uniform bool fade;
+    uniform vec2 time_range;
+    out vec4 v_color;
+
+    // the same stops as in colormap.rs
+    vec3 colormap(float x) {
+        vec3 stops[5] = vec3[](
+            vec3(0.267, 0.005, 0.329),
+            vec3(0.229, 0.322, 0.546),
+            vec3(0.128, 0.567, 0.551),
+            vec3(0.369, 0.789, 0.383),
+            vec3(0.993, 0.906, 0.144)
+        );
+        float scaled = clamp(x, 0.0, 1.0) * 4.0;
+        int index = min(int(floor(scaled)), 3);
+        return mix(stops[index], stops[index + 1], scaled - float(index));
+    }
+
+    void main() {
+        gl_Position = matrix * vec4(position, 1.0);
+        vec3 rgb = color;
+        if (colored) {
+            float span = value_range.y - value_range.x;
+            rgb = colormap(span > 0.0 ? (value - value_range.x) / span : 0.5);
+        }
+        float alpha = 1.0;
+        if (fade && time_range.y > time_range.x) {
+            alpha = clamp((time - time_range.x) / (time_range.y - time_range.x), 0.0, 1.0);
+        }
+        v_color = vec4(rgb * brightness, alpha);
+    }
+"#;
+
+const PATH_FRAGMENT_SHADER_SRC: &str = r#"
+    #version 140
+
+    in vec4 v_color;
+    out vec4 color;
+
+    void main() {
+        color = v_color;
+    }
+"#;
+
 const TEXTURED_VERTEX_SHADER_SRC: &'static str = r#"
     #version 140
 
@@ -70,6 +175,38 @@ const TEXTURED_FRAGMENT_SHADER_SRC: &'static str = r#"
     }
 "#;
 
+const TIDE_VERTEX_SHADER_SRC: &str = r#"
+    #version 140
+
+    in vec3 position;
+
+    uniform mat4 matrix;
+    uniform vec3 radii;
+    uniform vec3 moon_dir;
+    uniform vec3 sun_dir;
+    uniform float moon_amplitude;
+    uniform float sun_amplitude;
+
+    void main() {
+        float cos_moon = dot(position, moon_dir);
+        float cos_sun = dot(position, sun_dir);
+        float height = moon_amplitude * (3.0 * cos_moon * cos_moon - 1.0) / 2.0
+            + sun_amplitude * (3.0 * cos_sun * cos_sun - 1.0) / 2.0;
+        vec3 pos = position * radii + position * height;
+        gl_Position = matrix * vec4(pos, 1.0);
+    }
+"#;
+
+const TIDE_FRAGMENT_SHADER_SRC: &str = r#"
+    #version 140
+
+    out vec4 color;
+
+    void main() {
+        color = vec4(0.2, 0.4, 1.0, 0.35);
+    }
+"#;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Vertex {
     pub position: [f32; 3],
@@ -81,15 +218,41 @@ pub struct TexturedVertex {
     pub tex_coords: [f32; 2],
 }
 
+/// The per-instance attributes of an instanced mesh
+#[derive(Debug, Clone, Copy)]
+pub struct Instance {
+    pub instance_matrix: [[f32; 4]; 4],
+    pub instance_color: [f32; 3],
+}
+
 implement_vertex!(Vertex, position);
 implement_vertex!(TexturedVertex, position, tex_coords);
+implement_vertex!(Instance, instance_matrix, instance_color);
+
+/// The orientation of the camera relative to the local horizon, in degrees
+#[derive(Debug, Clone, Copy)]
+pub struct CameraAttitude {
+    /// The direction of view, clockwise from north
+    pub heading: f64,
+    /// The angle of the direction of view above the horizon
+    pub pitch: f64,
+    /// The bank of the camera, positive with its right side below the horizon
+    pub roll: f64,
+}
 
 pub struct Renderer {
     program: Program,
+    path_program: Program,
+    instanced_program: Program,
     textured_program: Program,
+    tide_program: Program,
     tex_earth: glium::Texture2d,
+    texture_pyramid: TexturePyramid,
+    surface_patch: SurfacePatch,
+    path_buffers: PathBuffers,
     earth_solid_sphere: Mesh<TexturedVertex>,
     earth_grid: Mesh<Vertex>,
+    ocean_shell: Mesh<Vertex>,
     object_solid_sphere: Mesh<Vertex>,
     arrow: Mesh<Vertex>,
     cubemap: Cubemap,
@@ -125,13 +288,18 @@ fn galactic_matrix() -> Matrix4<f32> {
 }
 
 impl Renderer {
-    pub fn new(display: &Display) -> Self {
-        let image = image::load(
+    /// The global equirectangular map of the Earth
+    pub fn earth_image() -> image::RgbaImage {
+        image::load(
             Cursor::new(&include_bytes!("media/earth.jpg")[..]),
             image::ImageFormat::Jpeg,
         )
         .unwrap()
-        .to_rgba8();
+        .to_rgba8()
+    }
+
+    pub fn new(display: &Display) -> Self {
+        let image = Self::earth_image();
         let image_dimensions = image.dimensions();
         let image =
             glium::texture::RawImage2d::from_raw_rgba_reversed(&image.into_raw(), image_dimensions);
@@ -140,6 +308,20 @@ impl Renderer {
         Renderer {
             program: Program::from_source(display, VERTEX_SHADER_SRC, FRAGMENT_SHADER_SRC, None)
                 .unwrap(),
+            path_program: Program::from_source(
+                display,
+                PATH_VERTEX_SHADER_SRC,
+                PATH_FRAGMENT_SHADER_SRC,
+                None,
+            )
+            .unwrap(),
+            instanced_program: Program::from_source(
+                display,
+                INSTANCED_VERTEX_SHADER_SRC,
+                FRAGMENT_SHADER_SRC,
+                None,
+            )
+            .unwrap(),
             textured_program: Program::from_source(
                 display,
                 TEXTURED_VERTEX_SHADER_SRC,
@@ -147,95 +329,406 @@ impl Renderer {
                 None,
             )
             .unwrap(),
+            tide_program: Program::from_source(
+                display,
+                TIDE_VERTEX_SHADER_SRC,
+                TIDE_FRAGMENT_SHADER_SRC,
+                None,
+            )
+            .unwrap(),
             tex_earth,
+            texture_pyramid: TexturePyramid::new(),
+            surface_patch: Default::default(),
+            path_buffers: Default::default(),
             earth_solid_sphere: Mesh::solid_sphere(display, 120, 240),
             earth_grid: Mesh::ellipsoid(display),
+            ocean_shell: Mesh::solid_sphere(display, 60, 120),
             arrow: Mesh::arrow(display),
             object_solid_sphere: Mesh::solid_sphere(display, 12, 24),
             cubemap: Cubemap::new(display),
         }
     }
 
-    pub fn view_external(state: &State) -> (Matrix4<f32>, Matrix4<f32>, Matrix4<f32>) {
-        let omega = OMEGA * state.omega;
+    pub fn view_external(state: &State) -> Isometry3<f64> {
+        let omega = state.frame_omega();
+        let external = &state.camera_state.external;
 
-        let dist = state.camera_state.external.distance;
-        let lat = state.camera_state.external.lat;
-        let lon = state.camera_state.external.lon;
+        let view_rot = if external.inertial {
+            // undo the rotation of the frame, so that the stars stay still
+            rotation::about_x(external.lat as f64)
+                * rotation::about_y(-external.lon as f64)
+                * rotation::frame_rotation(
+                    state.environment.spin,
+                    omega,
+                    0.0,
+                    state.render_settings.max_t,
+                )
+        } else {
+            let camera_ang = state.ang - omega * state.t;
+            rotation::about_x(external.lat as f64)
+                * rotation::about_y(-external.lon as f64 - camera_ang)
+        };
+        let camera_orient =
+            rotation::about_y(external.turn as f64) * rotation::about_x(external.tilt as f64);
+        let view_trans = Vector3::new(0.0, 0.0, -external.distance as f64);
 
-        let camera_ang = state.ang - omega * state.t;
+        let view = Isometry3::from_parts(
+            Translation3::from(camera_orient * view_trans),
+            camera_orient * view_rot,
+        );
+        if !state.camera_state.north_up {
+            return view;
+        }
+        // keep the direction of view, but turn the camera about it so that the Earth's axis
+        // points up; there is no such orientation when looking along the axis
+        let camera = view.inverse();
+        let eye = Point3::from(camera.translation.vector);
+        let front = camera * -Vector3::z();
+        if front.cross(&Vector3::y()).norm() < 1e-6 {
+            return view;
+        }
+        Isometry3::look_at_rh(&eye, &(eye + front), &Vector3::y())
+    }
 
-        let view_rot = Matrix4::new_rotation(Vector3::new(lat as f32, 0.0, 0.0))
-            * Matrix4::new_rotation(Vector3::new(0.0, -lon - camera_ang as f32, 0.0));
-        let view_trans = Matrix4::new_translation(&Vector3::new(0.0, 0.0, -dist));
-        let camera_orient =
-            Matrix4::new_rotation(Vector3::new(0.0, state.camera_state.external.turn, 0.0))
-                * Matrix4::new_rotation(Vector3::new(state.camera_state.external.tilt, 0.0, 0.0));
+    /// The setting of the external camera looking at a sphere given in the frame the scene is
+    /// viewed in, from right above its center and from such a distance that the sphere fills
+    /// the view
+    pub fn external_fitting(state: &State, center: &Vector3<f64>, radius: f64) -> ExternalState {
+        let half_fov = (state.render_settings.fov as f64).to_radians() / 2.0;
+        let distance = center.norm() + FIT_MARGIN * radius / half_fov.sin();
+        Self::external_above(state, center, distance)
+    }
+
+    /// The setting of the external camera looking straight down at the Earth's center from the
+    /// given distance from it, above the point given in the frame the scene is viewed in
+    pub fn external_above(state: &State, point: &Vector3<f64>, distance: f64) -> ExternalState {
+        let external = state.camera_state.external;
+        let omega = state.frame_omega();
+
+        // the direction of the camera from the Earth's center, in the frame the camera angles
+        // refer to
+        let dir = if external.inertial {
+            rotation::frame_rotation(
+                state.environment.spin,
+                omega,
+                0.0,
+                state.render_settings.max_t,
+            ) * point
+        } else {
+            rotation::about_y(omega * state.t - state.ang) * point
+        };
+        let (lat, lon) = if dir.norm() > 1e3 {
+            let dir = dir.normalize();
+            (dir.y.asin() as f32, dir.x.atan2(dir.z) as f32)
+        } else {
+            // centered on the Earth, so any direction will do
+            (external.lat, external.lon)
+        };
 
-        (view_rot, view_trans, camera_orient)
+        ExternalState {
+            lat,
+            lon,
+            tilt: 0.0,
+            turn: 0.0,
+            distance: (distance as f32).clamp(MIN_CAMERA_DISTANCE, MAX_CAMERA_DISTANCE),
+            inertial: external.inertial,
+        }
     }
 
-    pub fn view_following(
+    /// The setting of the external camera above the given point of the surface, at the given
+    /// altitude in meters
+    pub fn external_above_lat_lon(
         state: &State,
-        earth_rot: &Matrix4<f32>,
-    ) -> (Matrix4<f32>, Matrix4<f32>, Matrix4<f32>) {
-        let follow_obj = state.camera_state.following.obj;
-        if follow_obj >= state.objects.len() {
-            return Self::view_external(state);
-        }
+        lat: f64,
+        lon: f64,
+        altitude: f64,
+    ) -> ExternalState {
+        let point = rotation::frame_rotation(
+            state.environment.spin,
+            OMEGA,
+            state.frame_omega(),
+            state.render_settings.max_t,
+        ) * lat_lon_elev_to_vec3(lat, lon, 0.0);
+        Self::external_above(state, &point, point.norm() + altitude)
+    }
+
+    /// The chase camera: looking at the followed object with the local vertical up, from
+    /// behind it with respect to its horizontal direction of motion, turned by the azimuth and
+    /// raised by the elevation. In the first-person mode, the camera is at the object and looks
+    /// forward.
+    pub fn view_following(state: &State) -> Isometry3<f64> {
+        let following = &state.camera_state.following;
+        let obj = match state.objects.get(following.obj) {
+            Some(obj) => obj,
+            None => return Self::view_external(state),
+        };
+        let omega = state.frame_omega();
 
-        let sim_state = state.objects[follow_obj].last_sim_state(state.render_settings.max_t);
+        // interpolated, so that the camera moves smoothly even with large time steps
+        let sim_state = obj.state_at(state.render_settings.max_t);
 
         let pos = sim_state.pos().to_omega(OMEGA);
         let vel = sim_state.vel().to_omega(pos, OMEGA).vel();
         let up = surface_normal(&pos.pos());
-        let front = (vel - vel.dot(&up) * up).normalize();
-
-        let pos = pos.pos();
-        let view_trans = earth_rot.transpose()
-            * Matrix4::new_translation(&Vector3::new(
-                -pos[0] as f32,
-                -pos[1] as f32,
-                -pos[2] as f32,
-            ))
-            * earth_rot;
-        let view_rot = Matrix4::identity();
-
-        let camera_orient = earth_rot.transpose()
-            * Matrix4::look_at_rh(
-                &Point3::new(0.0, 0.0, 0.0),
-                &Point3::new(front[0] as f32, front[1] as f32, front[2] as f32),
-                &Vector3::new(up[0] as f32, up[1] as f32, up[2] as f32),
-            )
-            * earth_rot;
+        let horizontal = vel - vel.dot(&up) * up;
+        let front = if horizontal.norm() > 1e-6 {
+            horizontal.normalize()
+        } else {
+            // at rest, look north (or along +Z at the poles)
+            let north = Vector3::y() - up * up.y;
+            if north.norm() > 1e-6 {
+                north.normalize()
+            } else {
+                Vector3::z()
+            }
+        };
 
-        (view_rot, view_trans, camera_orient)
+        let up = pos.dir_to_omega(up, omega);
+        let front = pos.dir_to_omega(front, omega);
+        let target = Point3::from(pos.to_omega(omega).pos());
+        if following.first_person {
+            return Isometry3::look_at_rh(&target, &(target + front), &up);
+        }
+
+        let elevation = following.elevation as f64;
+        let behind =
+            rotation::about_axis(&Unit::new_normalize(up), following.azimuth as f64) * -front;
+        let eye =
+            target + (behind * elevation.cos() + up * elevation.sin()) * following.distance as f64;
+        Isometry3::look_at_rh(&eye, &target, &up)
     }
 
-    pub fn draw(&mut self, display: &Display, target: &mut Frame, state: &State) {
-        target.clear_color(0.0, 0.0, 0.02, 1.0);
-        target.clear_depth(1.0);
+    /// Where the camera is looking relative to the local horizon, for the cameras following
+    /// objects
+    pub fn camera_attitude(state: &State) -> Option<CameraAttitude> {
+        if state.camera_state.tag != StateTag::Following {
+            return None;
+        }
+        // the local directions in the Earth's frame
+        let to_earth = rotation::frame_rotation(
+            state.environment.spin,
+            state.frame_omega(),
+            OMEGA,
+            state.render_settings.max_t,
+        ) * Self::view(state).inverse();
+        let position = to_earth.translation.vector;
+        let front = to_earth * -Vector3::z();
+        let camera_up = to_earth * Vector3::y();
 
-        let (width, height) = target.get_dimensions();
-        let aspect = width as f32 / height as f32;
+        let up = surface_normal(&position);
+        let east = Vector3::y().cross(&up);
+        if east.norm() < 1e-10 {
+            return None;
+        }
+        let east = east.normalize();
+        let north = up.cross(&east);
+        let right = front.cross(&camera_up);
 
-        let omega = OMEGA * state.omega;
-        // how much has Earth rotated since t=0
-        let earth_ang = (OMEGA - omega) * state.render_settings.max_t;
-        // how much has the frame rotated with respect to the sky
-        let skybox_ang = -omega * state.render_settings.max_t + state.render_settings.sky_rotation.to_radians();
+        Some(CameraAttitude {
+            heading: front
+                .dot(&east)
+                .atan2(front.dot(&north))
+                .to_degrees()
+                .rem_euclid(360.0),
+            pitch: front.dot(&up).clamp(-1.0, 1.0).asin().to_degrees(),
+            roll: (-right.dot(&up)).atan2(camera_up.dot(&up)).to_degrees(),
+        })
+    }
+
+    /// How much has Earth rotated since t=0 relative to the frame the scene is viewed in
+    fn earth_rotation(state: &State) -> Matrix4<f32> {
+        rotation::to_matrix(&rotation::frame_rotation(
+            state.environment.spin,
+            OMEGA,
+            state.frame_omega(),
+            state.render_settings.max_t,
+        ))
+    }
 
-        let earth_rotation = Matrix4::new_rotation(Vector3::new(0.0, earth_ang as f32, 0.0));
-        let skybox_rotation = Matrix4::new_rotation(Vector3::new(0.0, skybox_ang as f32, 0.0));
+    fn perspective(state: &State, aspect: f32) -> Matrix4<f32> {
+        Matrix4::new_perspective(aspect, state.render_settings.fov.to_radians(), 1000.0, 1e9)
+    }
+
+    /// Half the height of the orthographic view, in meters: the same as the height seen in
+    /// perspective at the distance the camera is focused on
+    fn ortho_half_height(state: &State) -> f32 {
+        let camera = &state.camera_state;
+        let distance = match camera.tag {
+            StateTag::External => camera.external.distance,
+            StateTag::Following if camera.following.first_person => FIRST_PERSON_ORTHO_DISTANCE,
+            StateTag::Following => camera.following.distance,
+        };
+        distance * (state.render_settings.fov.to_radians() / 2.0).tan()
+    }
+
+    /// How large the pixels of a screen of the given height are in the scene
+    fn screen_scale(state: &State, height: f32) -> ScreenScale {
+        let eye = Self::view(state).inverse().translation.vector;
+        match state.render_settings.projection {
+            Projection::Perspective => ScreenScale {
+                eye,
+                pixel: (state.render_settings.fov as f64).to_radians() / height as f64,
+                perspective: true,
+            },
+            Projection::Orthographic => ScreenScale {
+                eye,
+                pixel: 2.0 * Self::ortho_half_height(state) as f64 / height as f64,
+                perspective: false,
+            },
+        }
+    }
 
-        let galactic_pole_rot = galactic_matrix();
+    fn projection(state: &State, aspect: f32) -> Matrix4<f32> {
+        match state.render_settings.projection {
+            Projection::Perspective => Self::perspective(state, aspect),
+            Projection::Orthographic => {
+                let half_height = Self::ortho_half_height(state);
+                let half_width = half_height * aspect;
+                Matrix4::new_orthographic(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    1000.0,
+                    1e9,
+                )
+            }
+        }
+    }
 
-        let perspective = Matrix4::new_perspective(aspect, state.render_settings.fov.to_radians(), 1000.0, 1e9);
-        let (view_rot, view_trans, camera_orient) = match state.camera_state.tag {
+    /// The view for the current camera state, blended with the one the camera was switched
+    /// from during a transition
+    pub fn view(state: &State) -> Isometry3<f64> {
+        let view = match state.camera_state.tag {
             StateTag::External => Self::view_external(state),
-            StateTag::Following => Self::view_following(state, &earth_rotation),
+            StateTag::Following => Self::view_following(state),
+        };
+        match &state.camera_state.transition {
+            Some(transition) => {
+                // blend the camera poses rather than the views, so that the eye moves along
+                // a straight line
+                let s =
+                    transition.progress * transition.progress * (3.0 - 2.0 * transition.progress);
+                let from = transition.from.inverse();
+                let to = view.inverse();
+                let translation = from.translation.vector.lerp(&to.translation.vector, s);
+                let rotation = rotation::slerp(&from.rotation, &to.rotation, s);
+                Isometry3::from_parts(Translation3::from(translation), rotation).inverse()
+            }
+            None => view,
+        }
+    }
+
+    /// Where a point given in the frame the scene is viewed in appears on a screen of the given
+    /// size, with the origin in the top left corner; `None` if it's behind the camera
+    pub fn screen_position(
+        state: &State,
+        point: &Vector3<f64>,
+        width: f32,
+        height: f32,
+    ) -> Option<(f32, f32)> {
+        let matrix = Self::projection(state, width / height)
+            * Self::view(state).to_homogeneous().cast::<f32>();
+        let clip = matrix * point.cast::<f32>().push(1.0);
+        if clip.w <= 0.0 {
+            return None;
+        }
+        let (x, y) = (clip.x / clip.w, clip.y / clip.w);
+        Some(((x + 1.0) / 2.0 * width, (1.0 - y) / 2.0 * height))
+    }
+
+    /// The object under a point of a screen of the given size, with the origin in the top left
+    /// corner, found by casting a ray from the camera through the point. Objects hidden behind
+    /// the Earth are skipped, and small markers can be picked within a few pixels.
+    pub fn pick_object(state: &State, x: f32, y: f32, width: f32, height: f32) -> Option<usize> {
+        let matrix = Self::projection(state, width / height)
+            * Self::view(state).to_homogeneous().cast::<f32>();
+        let inverse = matrix.cast::<f64>().try_inverse()?;
+        let ndc_x = 2.0 * x as f64 / width as f64 - 1.0;
+        let ndc_y = 1.0 - 2.0 * y as f64 / height as f64;
+        let unproject = |z: f64| {
+            let point = inverse * Vector4::new(ndc_x, ndc_y, z, 1.0);
+            point.xyz() / point.w
         };
-        let matrix = perspective * camera_orient * view_trans * view_rot;
+        let origin = unproject(-1.0);
+        let dir = (unproject(1.0) - origin).normalize();
+
+        // the tolerance in meters at the given distance along the ray
+        let screen = Self::screen_scale(state, height);
+        let tolerance = |along: f64| PICK_TOLERANCE * screen.pixel_size(along);
+        let earth_hit = ray_sphere(&origin, &dir, &Vector3::zeros(), R_POL);
+        let omega = state.frame_omega();
+
+        state
+            .objects
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| state.shows_object(*index))
+            .filter_map(|(index, obj)| {
+                let pos = obj
+                    .state_at(state.render_settings.max_t)
+                    .pos()
+                    .to_omega(omega)
+                    .pos();
+                let along = (pos - origin).dot(&dir);
+                let radius = (obj.radius() as f64).max(tolerance(along));
+                let dist = ray_sphere(&origin, &dir, &pos, radius)?;
+                match earth_hit {
+                    Some(earth_dist) if earth_dist < dist => None,
+                    _ => Some((index, dist)),
+                }
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+    }
+
+    /// Copies what has been drawn so far in the frame into an image
+    pub fn capture(display: &Display, target: &Frame) -> Result<image::RgbaImage, String> {
+        let (width, height) = target.get_dimensions();
+        let texture = glium::texture::Texture2d::empty_with_format(
+            display,
+            glium::texture::UncompressedFloatFormat::U8U8U8U8,
+            glium::texture::MipmapsOption::NoMipmap,
+            width,
+            height,
+        )
+        .map_err(|err| err.to_string())?;
+        target.fill(
+            &texture.as_surface(),
+            glium::uniforms::MagnifySamplerFilter::Nearest,
+        );
+        let raw: glium::texture::RawImage2d<u8> = texture.read();
+        let mut image = image::RgbaImage::from_raw(width, height, raw.data.into_owned())
+            .ok_or_else(|| "unexpected size of the image data".to_string())?;
+        // OpenGL stores the rows bottom to top
+        image::imageops::flip_vertical_in_place(&mut image);
+        Ok(image)
+    }
+
+    pub fn draw(&mut self, display: &Display, target: &mut Frame, state: &State) {
+        let (r, g, b, a) = state.render_settings.background.clear_color();
+        target.clear_color(r, g, b, a);
+        target.clear_depth(1.0);
+
+        let (width, height) = target.get_dimensions();
+        let aspect = width as f32 / height as f32;
+
+        let omega = state.frame_omega();
+        let earth_rotation = Self::earth_rotation(state);
+        // orientation of the sky with respect to the frame
+        let skybox_rotation = (state.celestial.equatorial_to_rotating(
+            state.render_settings.max_t,
+            omega,
+            state.environment.spin,
+        ) * state
+            .celestial
+            .sky_to_equatorial(state.render_settings.max_t))
+        .to_homogeneous()
+        .cast::<f32>();
+
+        let projection = Self::projection(state, aspect);
+        let view = Self::view(state);
+        let matrix = projection * view.to_homogeneous().cast::<f32>();
 
         let draw_parameters = glium::DrawParameters {
             depth: glium::draw_parameters::Depth {
@@ -247,19 +740,34 @@ impl Renderer {
             ..Default::default()
         };
 
-        self.cubemap.draw(
-            target,
-            &(perspective * camera_orient * view_rot * skybox_rotation * galactic_pole_rot),
-            &draw_parameters,
-        );
+        if state.render_settings.background == Background::Stars {
+            // the stars are infinitely far away, so they are always seen in perspective
+            self.cubemap.draw(
+                target,
+                &(Self::perspective(state, aspect)
+                    * rotation::to_matrix(&view.rotation)
+                    * skybox_rotation
+                    * galactic_matrix()),
+                &draw_parameters,
+            );
+        }
 
         if state.render_settings.draw_solid_surface {
             let scaling = Matrix4::new_nonuniform_scaling(&Vector3::new(
-                (R_EQU * 0.9999) as f32,
-                (R_POL * 0.9999) as f32,
-                (R_EQU * 0.9999) as f32,
+                (R_EQU * SURFACE_SCALE) as f32,
+                (R_POL * SURFACE_SCALE) as f32,
+                (R_EQU * SURFACE_SCALE) as f32,
             ));
 
+            // the position of the camera in the Earth's frame
+            let earth_rotation_f64 = rotation::frame_rotation(
+                state.environment.spin,
+                OMEGA,
+                omega,
+                state.render_settings.max_t,
+            );
+            let camera = earth_rotation_f64.inverse() * view.inverse().translation.vector;
+
             if state.render_settings.use_texture {
                 let uniforms = uniform! {
                     matrix: *(matrix * earth_rotation * scaling).as_ref(),
@@ -272,6 +780,16 @@ impl Renderer {
                     &uniforms,
                     &draw_parameters,
                 );
+
+                let tile_scaling = scaling.prepend_scaling((1.0 + TILE_LIFT) as f32);
+                for tile in self.texture_pyramid.tiles_around(display, &camera) {
+                    let uniforms = uniform! {
+                        matrix: *(matrix * earth_rotation * tile_scaling).as_ref(),
+                        tex: &tile.texture,
+                    };
+                    tile.mesh
+                        .draw(target, &self.textured_program, &uniforms, &draw_parameters);
+                }
             } else {
                 let uniforms = uniform! {
                     matrix: *(matrix * earth_rotation * scaling).as_ref(),
@@ -281,6 +799,44 @@ impl Renderer {
                 self.earth_solid_sphere
                     .draw(target, &self.program, &uniforms, &draw_parameters);
             }
+
+            if let Some(patch) = self
+                .surface_patch
+                .update(display, &camera, &self.texture_pyramid)
+            {
+                // composed in double precision, as the patch is tiny compared to the distances
+                // from the origin
+                let model_view = view.to_homogeneous()
+                    * earth_rotation_f64.to_homogeneous()
+                    * Translation3::from(patch.center).to_homogeneous();
+                let patch_matrix = projection * model_view.cast::<f32>();
+                if state.render_settings.use_texture {
+                    let texture = match patch.texture {
+                        PatchTexture::Tile(key) => self
+                            .texture_pyramid
+                            .tile(key)
+                            .map_or(&self.tex_earth, |tile| &tile.texture),
+                        PatchTexture::Global => &self.tex_earth,
+                    };
+                    let uniforms = uniform! {
+                        matrix: *patch_matrix.as_ref(),
+                        tex: texture
+                            .sampled()
+                            .wrap_function(glium::uniforms::SamplerWrapFunction::Repeat),
+                    };
+                    patch
+                        .mesh
+                        .draw(target, &self.textured_program, &uniforms, &draw_parameters);
+                } else {
+                    let uniforms = uniform! {
+                        matrix: *patch_matrix.as_ref(),
+                        color: [0.1_f32, 0.25, 0.1],
+                    };
+                    patch
+                        .mesh
+                        .draw(target, &self.program, &uniforms, &draw_parameters);
+                }
+            }
         };
 
         if state.render_settings.draw_grid {
@@ -293,9 +849,114 @@ impl Renderer {
                 .draw(target, &self.program, &uniforms, &draw_parameters);
         }
 
+        let two_body = state.current_state_def.two_body;
+
+        // in a two-body scenario the other body is drawn where the simulation puts it instead
+        if state.render_settings.draw_sun && two_body != Some(TwoBodySystem::SunEarth) {
+            let sun_pos = state.sun.direction(
+                state.render_settings.max_t,
+                omega,
+                state.environment.spin,
+                &state.celestial,
+            ) * SUN_DRAW_DISTANCE;
+            let sun_matrix = matrix
+                .prepend_translation(&Vector3::new(
+                    sun_pos.x as f32,
+                    sun_pos.y as f32,
+                    sun_pos.z as f32,
+                ))
+                .prepend_scaling(SUN_DRAW_RADIUS);
+            let uniforms = uniform! {
+                matrix: *sun_matrix.as_ref(),
+                color: [1.0_f32, 0.95, 0.6],
+            };
+            self.object_solid_sphere
+                .draw(target, &self.program, &uniforms, &draw_parameters);
+        }
+
+        if state.render_settings.draw_moon && two_body != Some(TwoBodySystem::EarthMoon) {
+            let moon_pos = state.moon.direction(
+                state.render_settings.max_t,
+                omega,
+                state.environment.spin,
+                &state.celestial,
+            ) * MOON_DISTANCE;
+            let moon_matrix = matrix
+                .prepend_translation(&Vector3::new(
+                    moon_pos.x as f32,
+                    moon_pos.y as f32,
+                    moon_pos.z as f32,
+                ))
+                .prepend_scaling(MOON_RADIUS as f32);
+            let uniforms = uniform! {
+                matrix: *moon_matrix.as_ref(),
+                color: [0.7_f32, 0.7, 0.7],
+            };
+            self.object_solid_sphere
+                .draw(target, &self.program, &uniforms, &draw_parameters);
+        }
+
+        if let Some(system) = two_body {
+            let frame_matrix = matrix
+                * rotation::to_matrix(&rotation::frame_rotation(
+                    state.environment.spin,
+                    system.mean_motion(),
+                    omega,
+                    state.render_settings.max_t,
+                ));
+
+            let (other_pos, other_radius, other_color) = match system {
+                TwoBodySystem::EarthMoon => {
+                    (system.other_pos(), MOON_RADIUS as f32, [0.7, 0.7, 0.7])
+                }
+                TwoBodySystem::SunEarth => (
+                    system.other_pos().normalize() * SUN_DRAW_DISTANCE,
+                    SUN_DRAW_RADIUS,
+                    [1.0, 0.95, 0.6],
+                ),
+            };
+            let other_matrix = frame_matrix
+                .prepend_translation(&other_pos.cast::<f32>())
+                .prepend_scaling(other_radius);
+            let uniforms = uniform! {
+                matrix: *other_matrix.as_ref(),
+                color: other_color,
+            };
+            self.object_solid_sphere
+                .draw(target, &self.program, &uniforms, &draw_parameters);
+
+            // the Sun-Earth Lagrange points lie far beyond the far clipping plane, so their
+            // depth is clamped to it instead of them being clipped away
+            let lagrange_parameters = glium::DrawParameters {
+                depth: glium::draw_parameters::Depth {
+                    clamp: glium::draw_parameters::DepthClamp::Clamp,
+                    ..draw_parameters.depth
+                },
+                ..draw_parameters.clone()
+            };
+            for point in system.lagrange_points() {
+                let point_matrix = frame_matrix
+                    .prepend_translation(&point.cast::<f32>())
+                    .prepend_scaling((system.distance() * 5e-3) as f32);
+                let uniforms = uniform! {
+                    matrix: *point_matrix.as_ref(),
+                    color: [0.2_f32, 1.0, 1.0],
+                };
+                self.object_solid_sphere.draw(
+                    target,
+                    &self.program,
+                    &uniforms,
+                    &lagrange_parameters,
+                );
+            }
+        }
+
         let obj_ang = 0.0;
         let obj_rotation = Matrix4::new_rotation(Vector3::new(0.0, obj_ang as f32, 0.0));
 
+        // taken out for the time of drawing, as the painter borrows the renderer
+        let mut path_buffers = std::mem::take(&mut self.path_buffers);
+        path_buffers.begin_frame(display, state, Self::screen_scale(state, height as f32));
         let mut painter = Painter {
             display,
             renderer: self,
@@ -304,22 +965,258 @@ impl Renderer {
                 line_width: Some(6.0),
                 ..draw_parameters.clone()
             },
+            spheres: vec![],
+            arrows: vec![],
         };
 
+        if state.render_settings.draw_field_lines {
+            let uniforms = uniform! {
+                matrix: *(matrix * earth_rotation).as_ref(),
+                color: [0.8_f32, 0.4, 1.0],
+            };
+            for l_shell in [1.5, 2.0, 3.0, 5.0] {
+                for lon in (0..360).step_by(30) {
+                    let line = dipole_field_line(l_shell, lon as f64, 100);
+                    painter.path(&uniforms, &to_f32(&line));
+                }
+            }
+        }
+
+        if state.render_settings.draw_station_cones {
+            let uniforms = uniform! {
+                matrix: *(matrix * earth_rotation).as_ref(),
+                color: [0.3_f32, 1.0, 0.5],
+            };
+            for station in &state.stations {
+                let apex = station.pos();
+                let rim = station.cone_rim(STATION_CONE_RANGE, 36);
+                painter.path(&uniforms, &to_f32(&rim));
+                for point in rim.iter().step_by(3) {
+                    painter.path(&uniforms, &to_f32(&[apex, *point]));
+                }
+            }
+        }
+
+        if !state.tracers.is_empty() {
+            let uniforms = uniform! {
+                matrix: *(matrix * earth_rotation).as_ref(),
+                color: [0.6_f32, 0.9, 1.0],
+            };
+            let points: Vec<_> = state.tracers.positions().collect();
+            painter.points(&uniforms, &to_f32(&points));
+        }
+
+        let colors = state.object_colors();
+
         for (index, obj) in state.objects.iter().enumerate() {
-            if index == state.camera_state.following.obj
-                && state.camera_state.tag == StateTag::Following
-            {
+            if !state.shows_object(index) {
                 continue;
             }
             obj.draw(
                 &mut painter,
                 omega,
                 &(matrix * obj_rotation),
+                colors[index],
                 &state.render_settings,
             );
         }
+        // the opaque markers go first, so that the blended paths are drawn over them
+        painter.flush_instances();
+
+        for constraint in &state.constraints {
+            let (obj_a, obj_b) = match (
+                state.objects.get(constraint.a),
+                state.objects.get(constraint.b),
+            ) {
+                (Some(obj_a), Some(obj_b)) => (obj_a, obj_b),
+                _ => continue,
+            };
+            let [a, b] = [obj_a, obj_b].map(|obj| {
+                obj.state_at(state.render_settings.max_t)
+                    .pos()
+                    .to_omega(omega)
+                    .pos()
+            });
+            let color = match constraint.link {
+                Link::Spring { .. } => [1.0_f32, 0.8, 0.3],
+                Link::Tether => [0.8, 0.8, 0.8],
+            };
+            let uniforms = uniform! {
+                matrix: *(matrix * obj_rotation).as_ref(),
+                color: color,
+            };
+            painter.path(&uniforms, &to_f32(&[a, b]));
+        }
+
+        for (index, obj) in state.objects.iter().enumerate() {
+            if !state.shows_object(index) {
+                continue;
+            }
+            path_buffers.draw(
+                &mut painter,
+                &(matrix * obj_rotation),
+                index,
+                obj,
+                colors[index],
+            );
+        }
+        for (index, removed) in state.removed_objects.iter().enumerate() {
+            let obj = &removed.obj;
+            if !obj.keeps_trail() {
+                continue;
+            }
+            path_buffers.draw(
+                &mut painter,
+                &(matrix * obj_rotation),
+                state.objects.len() + index,
+                obj,
+                obj.color(),
+            );
+        }
+        path_buffers.end_frame();
+
+        if state.show_intercept_tool {
+            let tool = &state.intercept_tool;
+            let best = tool.best();
+            for (i, interception) in tool.result.iter().enumerate() {
+                let path: Vec<_> = interception
+                    .path
+                    .iter()
+                    .map(|pos| pos.to_omega(omega).pos())
+                    .collect();
+                let brightness = if Some(i) == best { 1.0_f32 } else { 0.4 };
+                let uniforms = uniform! {
+                    matrix: *(matrix * obj_rotation).as_ref(),
+                    color: [brightness, brightness, brightness],
+                };
+                painter.path(&uniforms, &to_f32(&path));
+            }
+        }
+
+        if let Some(obj) = state
+            .selected_object
+            .and_then(|index| state.objects.get(index))
+            .filter(|obj| !obj.is_hidden())
+        {
+            let pos = obj
+                .state_at(state.render_settings.max_t)
+                .pos()
+                .to_omega(omega)
+                .pos();
+            let uniforms = uniform! {
+                matrix: *(matrix * obj_rotation).as_ref(),
+                color: [1.0_f32, 1.0, 1.0],
+            };
+            for ring in selection_rings(&pos, obj.radius() as f64 * 2.5) {
+                painter.path(&uniforms, &to_f32(&ring));
+            }
+        }
+
+        if state.render_settings.draw_tides {
+            let exaggeration = state.render_settings.tide_exaggeration;
+            let moon_dir = state.moon.direction(
+                state.render_settings.max_t,
+                omega,
+                state.environment.spin,
+                &state.celestial,
+            );
+            let sun_dir = state.sun.direction(
+                state.render_settings.max_t,
+                omega,
+                state.environment.spin,
+                &state.celestial,
+            );
+            let uniforms = uniform! {
+                matrix: *(matrix * obj_rotation).as_ref(),
+                radii: [R_EQU as f32, R_POL as f32, R_EQU as f32],
+                moon_dir: [moon_dir.x as f32, moon_dir.y as f32, moon_dir.z as f32],
+                sun_dir: [sun_dir.x as f32, sun_dir.y as f32, sun_dir.z as f32],
+                moon_amplitude: (tide_amplitude(MOON_GM, MOON_DISTANCE) * exaggeration) as f32,
+                sun_amplitude: (tide_amplitude(SUN_GM, SUN_DISTANCE) * exaggeration) as f32,
+            };
+            let tide_parameters = glium::DrawParameters {
+                blend: glium::Blend::alpha_blending(),
+                depth: glium::draw_parameters::Depth {
+                    test: glium::draw_parameters::DepthTest::IfLessOrEqual,
+                    write: false,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            painter.renderer.ocean_shell.draw(
+                painter.target,
+                &painter.renderer.tide_program,
+                &uniforms,
+                &tide_parameters,
+            );
+        }
+
+        if state.render_settings.draw_group_paths {
+            for members in state.groups().values().filter(|members| members.len() > 1) {
+                let objects: Vec<_> = members.iter().map(|i| &state.objects[*i]).collect();
+                let group_path = GroupPath::new(&objects, omega, state.render_settings.max_t);
+                let (left, right) = group_path.envelope();
+
+                let color = colors[members[0]];
+                let uniforms = uniform! {
+                    matrix: *(matrix * obj_rotation).as_ref(),
+                    color: [color[0] * 0.5, color[1] * 0.5, color[2] * 0.5],
+                };
+                painter.ribbon(&uniforms, &to_f32(&left), &to_f32(&right));
+
+                let uniforms = uniform! {
+                    matrix: *(matrix * obj_rotation).as_ref(),
+                    color: [1.0_f32, 1.0, 1.0],
+                };
+                painter.path(&uniforms, &to_f32(&group_path.centroid));
+            }
+        }
+
+        self.path_buffers = path_buffers;
+    }
+}
+
+/// The distance along the ray to its first intersection with a sphere in front of the origin;
+/// `dir` has to be normalized
+fn ray_sphere(
+    origin: &Vector3<f64>,
+    dir: &Vector3<f64>,
+    center: &Vector3<f64>,
+    radius: f64,
+) -> Option<f64> {
+    let to_origin = origin - center;
+    let b = to_origin.dot(dir);
+    let disc = b * b - to_origin.norm_squared() + radius * radius;
+    if disc < 0.0 {
+        return None;
     }
+    [-b - disc.sqrt(), -b + disc.sqrt()]
+        .into_iter()
+        .find(|dist| *dist > 0.0)
+}
+
+/// Three perpendicular circles around a point, marking the selected object
+fn selection_rings(center: &Vector3<f64>, radius: f64) -> [Vec<Vector3<f64>>; 3] {
+    let circle = |axis_a: Vector3<f64>, axis_b: Vector3<f64>| {
+        (0..=36)
+            .map(|i| {
+                let ang = (i as f64 * 10.0).to_radians();
+                center + (axis_a * ang.cos() + axis_b * ang.sin()) * radius
+            })
+            .collect()
+    };
+    [
+        circle(Vector3::x(), Vector3::y()),
+        circle(Vector3::y(), Vector3::z()),
+        circle(Vector3::z(), Vector3::x()),
+    ]
+}
+
+fn to_f32(points: &[Vector3<f64>]) -> Vec<Vector3<f32>> {
+    points
+        .iter()
+        .map(|p| Vector3::new(p.x as f32, p.y as f32, p.z as f32))
+        .collect()
 }
 
 pub struct Painter<'a, 'b, 'c, 'd, 'e> {
@@ -327,19 +1224,25 @@ pub struct Painter<'a, 'b, 'c, 'd, 'e> {
     renderer: &'b Renderer,
     target: &'c mut Frame,
     draw_parameters: &'d DrawParameters<'e>,
+    /// Object markers and arrows waiting to be drawn in a single call each
+    spheres: Vec<Instance>,
+    arrows: Vec<Instance>,
 }
 
 impl<'a, 'b, 'c, 'd, 'e> Painter<'a, 'b, 'c, 'd, 'e> {
-    pub fn solid_sphere<U: Uniforms>(&mut self, uniforms: &U) {
-        self.renderer.object_solid_sphere.draw(
-            self.target,
-            &self.renderer.program,
-            uniforms,
-            self.draw_parameters,
-        );
+    /// Queues a solid sphere to be drawn with the next `flush_instances`
+    pub fn solid_sphere(&mut self, matrix: &Matrix4<f32>, color: [f32; 3]) {
+        self.spheres.push(Instance {
+            instance_matrix: (*matrix).into(),
+            instance_color: color,
+        });
     }
 
     pub fn path<U: Uniforms>(&mut self, uniforms: &U, path: &[Vector3<f32>]) {
+        // a line strip needs at least two vertices
+        if path.len() < 2 {
+            return;
+        }
         let vertex_buffer = VertexBuffer::new(
             self.display,
             &path
@@ -363,12 +1266,115 @@ impl<'a, 'b, 'c, 'd, 'e> Painter<'a, 'b, 'c, 'd, 'e> {
             .unwrap();
     }
 
-    pub fn arrow<U: Uniforms>(&mut self, uniforms: &U) {
-        self.renderer.arrow.draw(
-            self.target,
-            &self.renderer.program,
-            uniforms,
-            self.draw_parameters,
-        );
+    /// Draws a line through vertices of an object's path, blended with what is behind it
+    pub fn path_vertices<U: Uniforms>(
+        &mut self,
+        uniforms: &U,
+        vertices: VertexBufferSlice<'_, PathVertex>,
+    ) {
+        let index_buffer = index::NoIndices(index::PrimitiveType::LineStrip);
+
+        self.target
+            .draw(
+                vertices,
+                index_buffer,
+                &self.renderer.path_program,
+                uniforms,
+                &glium::DrawParameters {
+                    blend: glium::Blend::alpha_blending(),
+                    ..self.draw_parameters.clone()
+                },
+            )
+            .unwrap();
+    }
+
+    pub fn points<U: Uniforms>(&mut self, uniforms: &U, points: &[Vector3<f32>]) {
+        let vertex_buffer = VertexBuffer::new(
+            self.display,
+            &points
+                .iter()
+                .map(|pos| Vertex {
+                    position: [pos.x, pos.y, pos.z],
+                })
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let index_buffer = index::NoIndices(index::PrimitiveType::Points);
+
+        self.target
+            .draw(
+                &vertex_buffer,
+                index_buffer,
+                &self.renderer.program,
+                uniforms,
+                &glium::DrawParameters {
+                    point_size: Some(2.0),
+                    ..self.draw_parameters.clone()
+                },
+            )
+            .unwrap();
+    }
+
+    /// Draws a filled band between two lines with the same number of points
+    pub fn ribbon<U: Uniforms>(
+        &mut self,
+        uniforms: &U,
+        left: &[Vector3<f32>],
+        right: &[Vector3<f32>],
+    ) {
+        let vertices: Vec<_> = left
+            .iter()
+            .zip(right)
+            .flat_map(|(l, r)| {
+                [
+                    Vertex {
+                        position: [l.x, l.y, l.z],
+                    },
+                    Vertex {
+                        position: [r.x, r.y, r.z],
+                    },
+                ]
+            })
+            .collect();
+        let vertex_buffer = VertexBuffer::new(self.display, &vertices).unwrap();
+        let index_buffer = index::NoIndices(index::PrimitiveType::TriangleStrip);
+
+        self.target
+            .draw(
+                &vertex_buffer,
+                index_buffer,
+                &self.renderer.program,
+                uniforms,
+                self.draw_parameters,
+            )
+            .unwrap();
+    }
+
+    /// Queues an arrow to be drawn with the next `flush_instances`
+    pub fn arrow(&mut self, matrix: &Matrix4<f32>, color: [f32; 3]) {
+        self.arrows.push(Instance {
+            instance_matrix: (*matrix).into(),
+            instance_color: color,
+        });
+    }
+
+    /// Draws all the queued spheres and arrows, one call per mesh
+    pub fn flush_instances(&mut self) {
+        for (mesh, instances) in [
+            (&self.renderer.object_solid_sphere, &mut self.spheres),
+            (&self.renderer.arrow, &mut self.arrows),
+        ] {
+            if instances.is_empty() {
+                continue;
+            }
+            let instance_buffer = VertexBuffer::new(self.display, instances).unwrap();
+            mesh.draw_instanced(
+                self.target,
+                &self.renderer.instanced_program,
+                &instance_buffer,
+                self.draw_parameters,
+            );
+            instances.clear();
+        }
     }
 }
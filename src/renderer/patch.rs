@@ -0,0 +1,149 @@
+use nalgebra::Vector3;
+
+use super::{
+    tiles::{TexturePyramid, TileKey},
+    Mesh, TexturedVertex, SURFACE_SCALE,
+};
+use crate::simulation::{lat_lon_elev_to_vec3, pos_to_lat_lon_elev, R_EQU, R_POL};
+
+/// The patch is only drawn when the camera is lower than this, in meters
+const MAX_ALTITUDE: f64 = 5e4;
+/// How far the patch reaches beyond the horizon seen from the camera
+const HORIZON_MARGIN: f64 = 1.2;
+/// The smallest half-size of the patch, in meters
+const MIN_HALF_SIZE: f64 = 5e3;
+/// The patch is drawn this far below the surface, so that the objects and paths lying on the
+/// surface stay visible, in meters
+const PATCH_DEPTH: f64 = 1.0;
+/// The outer part of the patch, as a fraction of its half-size, sloping down to the global
+/// sphere
+const SKIRT: f64 = 0.2;
+const N_DIVISIONS: u32 = 64;
+/// The patch is rebuilt when the point under the camera moves by this fraction of its
+/// half-size, or when its size should change by more than this factor
+const REBUILD_SHIFT: f64 = 0.1;
+const REBUILD_SCALE: f64 = 1.25;
+
+/// The latitude of the unit sphere scaled into the global ellipsoid passing through the point
+/// at the given geodetic latitude, both in degrees; the textures are mapped along it
+fn parametric_lat(lat: f64) -> f64 {
+    (R_POL / R_EQU * lat.to_radians().tan()).atan().to_degrees()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchTexture {
+    /// The global map
+    Global,
+    /// A detailed tile containing the whole patch
+    Tile(TileKey),
+}
+
+/// A detailed piece of the surface around the point under the camera, drawn at the actual
+/// height of the surface instead of the slightly lowered global sphere, so that the views from
+/// near the ground look right
+pub struct Patch {
+    /// The point of the surface under the camera, in the Earth's frame; the vertices are
+    /// relative to it to keep the precision of single floats
+    pub center: Vector3<f64>,
+    pub texture: PatchTexture,
+    pub mesh: Mesh<TexturedVertex>,
+    lat: f64,
+    lon: f64,
+    /// Half the size of the patch, in degrees of latitude
+    half_size: f64,
+}
+
+#[derive(Default)]
+pub struct SurfacePatch {
+    current: Option<Patch>,
+}
+
+impl SurfacePatch {
+    /// Rebuilds the patch if the camera (given in the Earth's frame) moved too far from it;
+    /// `None` if the camera is too high for the patch to be needed
+    pub fn update(
+        &mut self,
+        display: &glium::Display,
+        camera: &Vector3<f64>,
+        pyramid: &TexturePyramid,
+    ) -> Option<&Patch> {
+        let (lat, lon, altitude) = pos_to_lat_lon_elev(*camera);
+        let horizon = (2.0 * R_EQU * altitude.max(0.0)).sqrt();
+        let half_size = (horizon * HORIZON_MARGIN).max(MIN_HALF_SIZE) / R_EQU;
+        let half_size = half_size.to_degrees();
+        // the grid of meridians and parallels degenerates at the poles
+        if altitude > MAX_ALTITUDE || lat.abs() + half_size > 89.0 {
+            self.current = None;
+            return None;
+        }
+        let lon_half_size = half_size / lat.to_radians().cos();
+        let lat_range = (
+            parametric_lat(lat - half_size),
+            parametric_lat(lat + half_size),
+        );
+        let lon_range = (lon - lon_half_size, lon + lon_half_size);
+        let texture = pyramid
+            .tile_containing(lat_range, lon_range)
+            .map_or(PatchTexture::Global, |(key, _)| PatchTexture::Tile(key));
+
+        let up_to_date = self.current.as_ref().is_some_and(|patch| {
+            let shift = (patch.lat - lat)
+                .abs()
+                .max((patch.lon - lon).abs() * lat.to_radians().cos());
+            let scale = patch.half_size / half_size;
+            patch.texture == texture
+                && shift < REBUILD_SHIFT * patch.half_size
+                && (1.0 / REBUILD_SCALE..REBUILD_SCALE).contains(&scale)
+        });
+        if !up_to_date {
+            let tex_rect = match texture {
+                PatchTexture::Global => ((-90.0, 90.0), (-180.0, 180.0)),
+                PatchTexture::Tile(key) => pyramid
+                    .tile(key)
+                    .map(|tile| (tile.lat_range, tile.lon_range))
+                    .unwrap_or(((-90.0, 90.0), (-180.0, 180.0))),
+            };
+            self.current = Some(Patch::new(display, lat, lon, half_size, texture, tex_rect));
+        }
+        self.current.as_ref()
+    }
+}
+
+impl Patch {
+    fn new(
+        display: &glium::Display,
+        lat: f64,
+        lon: f64,
+        half_size: f64,
+        texture: PatchTexture,
+        ((tex_lat_min, tex_lat_max), (tex_lon_min, tex_lon_max)): ((f64, f64), (f64, f64)),
+    ) -> Self {
+        let center = lat_lon_elev_to_vec3(lat, lon, 0.0);
+        let lon_half_size = half_size / lat.to_radians().cos();
+        let mesh = Mesh::grid(display, N_DIVISIONS, |u, v| {
+            let (x, y) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+            let vertex_lat = lat + half_size * y;
+            let vertex_lon = lon + lon_half_size * x;
+            // 1 inside, going down to 0 at the edge, where the patch meets the global sphere
+            let rim = 1.0 - x.abs().max(y.abs());
+            let blend = (rim / SKIRT).min(1.0);
+            let scale = 1.0 - (1.0 - blend) * (1.0 - SURFACE_SCALE);
+            let pos = lat_lon_elev_to_vec3(vertex_lat, vertex_lon, -PATCH_DEPTH) * scale - center;
+            // the global map repeats beyond the antimeridian
+            let tex_u = (vertex_lon - tex_lon_min) / (tex_lon_max - tex_lon_min);
+            let tex_v = (parametric_lat(vertex_lat) - tex_lat_min) / (tex_lat_max - tex_lat_min);
+            TexturedVertex {
+                position: [pos.x as f32, pos.y as f32, pos.z as f32],
+                tex_coords: [tex_u as f32, tex_v as f32],
+            }
+        });
+        Self {
+            center,
+            texture,
+            mesh,
+            lat,
+            lon,
+            half_size,
+        }
+    }
+}
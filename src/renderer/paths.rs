@@ -0,0 +1,668 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    iter,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use glium::{implement_vertex, uniform, Display, VertexBuffer};
+use nalgebra::{Matrix4, Vector3};
+
+use super::Painter;
+use crate::{
+    simulation::{earth_radius, rotation, Object, SimState, SpinRamp, OMEGA},
+    state::{PathColorMode, State},
+};
+
+/// The smallest number of vertices a path buffer is created with room for
+const MIN_CAPACITY: usize = 1024;
+/// How far the drawn paths may deviate from the stored ones at full detail, in meters, so that
+/// points on nearly straight stretches can be left out; each coarser level of detail doubles it
+const MAX_PATH_DEVIATION: f64 = 1.0;
+/// How far the drawn paths may deviate from the stored ones on the screen, in pixels
+const MAX_PIXEL_DEVIATION: f64 = 0.5;
+const MAX_DETAIL_LEVEL: u32 = 24;
+/// How far, in levels, the ideal level of detail may drift out of the one of a path before it's
+/// rebuilt, so that small camera moves don't rebuild it back and forth
+const DETAIL_HYSTERESIS: f64 = 0.25;
+/// The distance of the camera from a path is measured again when the camera has moved by this
+/// fraction of it
+const CAMERA_MOVE_FRACTION: f64 = 0.25;
+/// The most points left out in a row, which bounds the work for each new point
+const MAX_DROPPED_POINTS: usize = 256;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PathVertex {
+    pub position: [f32; 3],
+    /// Relative to the start of the buffer
+    pub time: f32,
+    /// The quantity the path is colored by, if any
+    pub value: f32,
+}
+
+implement_vertex!(PathVertex, position, time, value);
+
+/// How large the pixels of the screen are in the scene
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenScale {
+    /// The position of the camera in the frame of the view
+    pub eye: Vector3<f64>,
+    /// The size of a pixel, per meter of distance from the camera in perspective, or in meters
+    /// in the orthographic projection
+    pub pixel: f64,
+    pub perspective: bool,
+}
+
+impl ScreenScale {
+    /// The size of a pixel in meters at the given distance from the camera
+    pub fn pixel_size(&self, distance: f64) -> f64 {
+        if self.perspective {
+            self.pixel * distance
+        } else {
+            self.pixel
+        }
+    }
+
+    /// The level of detail a path at the given distance needs, as a fraction of a level, so
+    /// that the levels above it can be told apart
+    fn detail(&self, distance: f64) -> f64 {
+        (self.pixel_size(distance) * MAX_PIXEL_DEVIATION / MAX_PATH_DEVIATION).log2()
+    }
+}
+
+/// The level for `detail`, 0 being the full detail
+fn detail_level(detail: f64) -> u32 {
+    detail.floor().clamp(0.0, MAX_DETAIL_LEVEL as f64) as u32
+}
+
+/// Which of an object's paths is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PathLayer {
+    /// The trail in the frame of the view
+    View,
+    Inertial,
+    Earth,
+    GroundTrack,
+}
+
+impl PathLayer {
+    /// The angular velocity of the frame the points of the layer are fixed in
+    fn frame_omega(self, view_omega: f64) -> f64 {
+        match self {
+            PathLayer::View => view_omega,
+            PathLayer::Inertial => 0.0,
+            PathLayer::Earth | PathLayer::GroundTrack => OMEGA,
+        }
+    }
+
+    fn brightness(self) -> f32 {
+        match self {
+            PathLayer::View => 1.0,
+            PathLayer::Inertial | PathLayer::Earth => 0.6,
+            PathLayer::GroundTrack => 0.5,
+        }
+    }
+
+    /// The point of the layer for the state, in the frame rotating with `frame_omega`; the
+    /// ground track is the point of the surface directly below the state (along the radius)
+    fn point(self, state: &SimState, frame_omega: f64) -> Vector3<f64> {
+        let pos = state.pos().to_omega(frame_omega).pos();
+        match self {
+            PathLayer::GroundTrack => {
+                let r = pos.norm();
+                pos * earth_radius((pos.y / r).asin()) / r
+            }
+            _ => pos,
+        }
+    }
+}
+
+/// The states of the path from the sample at `from` on, with the states interpolated between
+/// the samples
+fn smooth_states(path: &VecDeque<SimState>, from: usize) -> Vec<SimState> {
+    let mut states = vec![];
+    for index in from..path.len() {
+        if index > 0 {
+            states.extend(
+                path[index - 1]
+                    .smooth_segment(&path[index])
+                    .into_iter()
+                    .skip(1),
+            );
+        }
+        states.push(path[index]);
+    }
+    states
+}
+
+fn same_sample(a: &SimState, b: &SimState) -> bool {
+    a.pos().t() == b.pos().t() && a.pos().pos() == b.pos().pos()
+}
+
+/// The distance from a point to the segment between `a` and `b`
+fn distance_to_segment(point: &Vector3<f64>, a: &Vector3<f64>, b: &Vector3<f64>) -> f64 {
+    let ab = b - a;
+    let s = if ab.norm_squared() > 0.0 {
+        ((point - a).dot(&ab) / ab.norm_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point - (a + ab * s)).norm()
+}
+
+/// The vertices of a path, without the points it goes (nearly) straight through: a new point
+/// replaces the last vertex if all the points since the vertex before stay within
+/// `tolerance` of the line to the new point
+struct PathPoints {
+    /// In meters
+    tolerance: f64,
+    vertices: Vec<PathVertex>,
+    /// The times of the vertices, at full precision
+    times: Vec<f64>,
+    /// The positions of the last two vertices, at full precision
+    anchor: Option<Vector3<f64>>,
+    last: Option<Vector3<f64>>,
+    /// The points dropped since the anchor, including the last vertex replaced
+    dropped: Vec<Vector3<f64>>,
+}
+
+impl PathPoints {
+    fn new(level: u32) -> Self {
+        Self {
+            tolerance: MAX_PATH_DEVIATION * 2.0_f64.powi(level as i32),
+            vertices: vec![],
+            times: vec![],
+            anchor: None,
+            last: None,
+            dropped: vec![],
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    fn push(&mut self, point: Vector3<f64>, vertex: PathVertex, t: f64) {
+        let straight = match (self.anchor, self.last) {
+            (Some(anchor), Some(last)) => {
+                self.dropped.len() < MAX_DROPPED_POINTS
+                    && iter::once(&last)
+                        .chain(&self.dropped)
+                        .all(|p| distance_to_segment(p, &anchor, &point) <= self.tolerance)
+            }
+            _ => false,
+        };
+        if straight {
+            self.dropped.extend(self.last);
+            *self.vertices.last_mut().unwrap() = vertex;
+            *self.times.last_mut().unwrap() = t;
+        } else {
+            self.dropped.clear();
+            self.anchor = self.last;
+            self.vertices.push(vertex);
+            self.times.push(t);
+        }
+        self.last = Some(point);
+    }
+}
+
+/// The vertices of one path of an object and what they were computed for; built on the
+/// worker thread, then kept up to date on the render thread
+struct PathGeometry {
+    layer: PathLayer,
+    frame_omega: f64,
+    color_mode: PathColorMode,
+    level: u32,
+    /// Where the camera was, in the frame of the points, when its distance from the path was
+    /// last measured, and the distance
+    measured_from: (Vector3<f64>, f64),
+    points: PathPoints,
+    /// The times in the vertices are relative to this one
+    t_origin: f64,
+    /// The last stored sample added
+    last_sample: SimState,
+}
+
+impl PathGeometry {
+    /// The geometry of the whole stored path of the job, which mustn't be empty, in the detail
+    /// needed when seen from the job's camera position
+    fn new(job: &PathJob) -> Self {
+        let path = &job.path;
+        let distance = path
+            .iter()
+            .map(|state| (job.layer.point(state, job.frame_omega) - job.eye).norm())
+            .fold(f64::INFINITY, f64::min);
+        let level = detail_level(job.screen.detail(distance));
+        let mut geometry = Self {
+            layer: job.layer,
+            frame_omega: job.frame_omega,
+            color_mode: job.color_mode,
+            level,
+            measured_from: (job.eye, distance),
+            points: PathPoints::new(level),
+            t_origin: path[0].pos().t(),
+            last_sample: path[path.len() - 1],
+        };
+        geometry.add(&smooth_states(path, 0));
+        geometry
+    }
+
+    fn vertex(&self, state: &SimState) -> PathVertex {
+        self.vertex_at(&self.layer.point(state, self.frame_omega), state)
+    }
+
+    fn vertex_at(&self, point: &Vector3<f64>, state: &SimState) -> PathVertex {
+        PathVertex {
+            position: *point.cast::<f32>().as_ref(),
+            time: (state.pos().t() - self.t_origin) as f32,
+            value: Object::path_attribute(state, self.color_mode).unwrap_or(0.0) as f32,
+        }
+    }
+
+    fn add(&mut self, states: &[SimState]) {
+        for state in states {
+            let point = self.layer.point(state, self.frame_omega);
+            let vertex = self.vertex_at(&point, state);
+            self.points.push(point, vertex, state.pos().t());
+        }
+    }
+
+    /// Whether the level of detail still suits the view from `eye` (in the frame of the points);
+    /// the distance from the path is only measured again once the camera has moved enough
+    fn detail_fits(&mut self, eye: &Vector3<f64>, screen: &ScreenScale) -> bool {
+        let (from, distance) = self.measured_from;
+        let distance = if (eye - from).norm() < CAMERA_MOVE_FRACTION * distance {
+            distance
+        } else {
+            let distance = self
+                .points
+                .vertices
+                .iter()
+                .map(|vertex| (Vector3::from(vertex.position).cast::<f64>() - eye).norm())
+                .fold(f64::INFINITY, f64::min);
+            self.measured_from = (*eye, distance);
+            distance
+        };
+        let detail = screen.detail(distance);
+        let level = self.level as f64;
+        // the full detail and the coarsest level also cover everything beyond them
+        (self.level == 0 || detail >= level - DETAIL_HYSTERESIS)
+            && (self.level == MAX_DETAIL_LEVEL || detail < level + 1.0 + DETAIL_HYSTERESIS)
+    }
+
+    /// Adds the samples stored since the last update and returns the index of the first vertex
+    /// that changed, `None` if the path changed in another way (e.g. it was rewound)
+    fn update(&mut self, path: &VecDeque<SimState>) -> Option<usize> {
+        let last_t = self.last_sample.pos().t();
+        let last_index = path.partition_point(|state| state.pos().t() < last_t);
+        match path.get(last_index) {
+            Some(sample) if same_sample(sample, &self.last_sample) => (),
+            _ => return None,
+        }
+        // the last vertex may move
+        let start = self.points.len() - 1;
+        if last_index + 1 < path.len() {
+            // the states start with the ones interpolated after the last sample already added
+            let states = smooth_states(path, last_index + 1);
+            self.add(&states);
+            self.last_sample = path[path.len() - 1];
+        }
+        Some(start)
+    }
+}
+
+/// A request to build the geometry of a path on the worker thread
+struct PathJob {
+    key: (usize, PathLayer),
+    /// Tells the result apart from the ones of the earlier requests for the same path
+    serial: u64,
+    layer: PathLayer,
+    frame_omega: f64,
+    color_mode: PathColorMode,
+    path: VecDeque<SimState>,
+    /// The position of the camera in the frame of the points
+    eye: Vector3<f64>,
+    screen: ScreenScale,
+}
+
+struct PathResult {
+    key: (usize, PathLayer),
+    serial: u64,
+    geometry: PathGeometry,
+}
+
+/// The thread building the paths that have to be created anew, so that long paths don't hold
+/// up the frames; the render thread only uploads the finished vertices
+struct PathWorker {
+    jobs: Sender<PathJob>,
+    results: Receiver<PathResult>,
+}
+
+impl PathWorker {
+    fn spawn() -> Self {
+        let (jobs, job_receiver) = mpsc::channel::<PathJob>();
+        let (result_sender, results) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(job) = job_receiver.recv() {
+                // only the latest request for each path is worth building
+                let mut latest = HashMap::new();
+                for job in iter::once(job).chain(job_receiver.try_iter()) {
+                    latest.insert(job.key, job);
+                }
+                for job in latest.into_values() {
+                    let result = PathResult {
+                        key: job.key,
+                        serial: job.serial,
+                        geometry: PathGeometry::new(&job),
+                    };
+                    if result_sender.send(result).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Self { jobs, results }
+    }
+}
+
+/// One path of an object kept on the GPU; new samples are written after the ones already
+/// there, instead of uploading the whole path every frame
+struct PathBuffer {
+    geometry: PathGeometry,
+    vertices: VertexBuffer<PathVertex>,
+    /// Drawn in the current frame; buffers that weren't are dropped
+    used: bool,
+}
+
+impl PathBuffer {
+    fn new(display: &Display, geometry: PathGeometry) -> Self {
+        let capacity = (2 * geometry.points.len()).max(MIN_CAPACITY);
+        let mut buffer = Self {
+            geometry,
+            vertices: VertexBuffer::empty_dynamic(display, capacity).unwrap(),
+            used: false,
+        };
+        buffer.upload(0);
+        buffer
+    }
+
+    /// Writes the vertices from the index `start` on to the GPU
+    fn upload(&mut self, start: usize) {
+        let points = &self.geometry.points;
+        self.vertices
+            .slice_mut(start..points.len())
+            .unwrap()
+            .write(&points.vertices[start..]);
+    }
+
+    /// Writes the samples stored since the last update, moving the path to a larger buffer if
+    /// there is no more room. Returns false if the path changed in another way (e.g. it was
+    /// rewound), so that it has to be built anew.
+    fn update(&mut self, display: &Display, path: &VecDeque<SimState>) -> bool {
+        let start = match self.geometry.update(path) {
+            Some(start) => start,
+            None => return false,
+        };
+        let len = self.geometry.points.len();
+        if len > self.vertices.len() {
+            self.vertices = VertexBuffer::empty_dynamic(display, 2 * len).unwrap();
+            self.upload(0);
+        } else {
+            self.upload(start);
+        }
+        true
+    }
+}
+
+/// The appearance of the paths common to all objects in the current frame
+#[derive(Debug, Clone, Copy)]
+struct PathStyle {
+    spin: Option<SpinRamp>,
+    view_omega: f64,
+    max_t: f64,
+    draw_trails: bool,
+    draw_ground_tracks: bool,
+    color_mode: PathColorMode,
+    /// The range of the quantity the paths are colored by
+    color_range: Option<(f64, f64)>,
+    screen: ScreenScale,
+}
+
+/// The trails and ground tracks of the objects, kept on the GPU between frames
+pub struct PathBuffers {
+    buffers: HashMap<(usize, PathLayer), PathBuffer>,
+    /// The paths being built on the worker thread, with the serial numbers of the latest
+    /// requests and the frames and coloring they were requested for
+    pending: HashMap<(usize, PathLayer), (u64, f64, PathColorMode)>,
+    next_serial: u64,
+    /// Started with the first request
+    worker: Option<PathWorker>,
+    style: PathStyle,
+}
+
+impl Default for PathBuffers {
+    fn default() -> Self {
+        Self {
+            buffers: HashMap::new(),
+            pending: HashMap::new(),
+            next_serial: 0,
+            worker: None,
+            style: PathStyle {
+                spin: None,
+                view_omega: 0.0,
+                max_t: 0.0,
+                draw_trails: false,
+                draw_ground_tracks: false,
+                color_mode: PathColorMode::Object,
+                color_range: None,
+                screen: ScreenScale {
+                    eye: Vector3::zeros(),
+                    pixel: 0.0,
+                    perspective: false,
+                },
+            },
+        }
+    }
+}
+
+impl PathBuffers {
+    /// Takes over the paths finished on the worker thread and sets up the style of the frame
+    pub fn begin_frame(&mut self, display: &Display, state: &State, screen: ScreenScale) {
+        if let Some(worker) = &self.worker {
+            for result in worker.results.try_iter() {
+                let latest = self
+                    .pending
+                    .get(&result.key)
+                    .is_some_and(|(serial, ..)| *serial == result.serial);
+                if latest {
+                    self.pending.remove(&result.key);
+                    let buffer = PathBuffer::new(display, result.geometry);
+                    self.buffers.insert(result.key, buffer);
+                }
+            }
+        }
+
+        let settings = &state.render_settings;
+        self.style = PathStyle {
+            spin: state.environment.spin,
+            view_omega: state.frame_omega(),
+            max_t: settings.max_t,
+            draw_trails: settings.draw_paths,
+            draw_ground_tracks: settings.draw_ground_tracks,
+            color_mode: settings.path_color_mode,
+            color_range: state.path_color_range(),
+            screen,
+        };
+        for buffer in self.buffers.values_mut() {
+            buffer.used = false;
+        }
+    }
+
+    /// Drops the buffers of the paths that are no longer drawn
+    pub fn end_frame(&mut self) {
+        self.buffers.retain(|_, buffer| buffer.used);
+    }
+
+    /// Draws the trails and the ground track of the object with the given index
+    pub fn draw(
+        &mut self,
+        painter: &mut Painter<'_, '_, '_, '_, '_>,
+        matrix: &Matrix4<f32>,
+        index: usize,
+        obj: &Object,
+        color: [f32; 3],
+    ) {
+        if obj.is_path_hidden() {
+            return;
+        }
+        let style = self.style;
+        let frames = obj.trail_frames();
+        let mut layers = vec![];
+        if style.draw_trails {
+            if frames.view {
+                layers.push(PathLayer::View);
+            }
+            if frames.inertial && style.view_omega != 0.0 {
+                layers.push(PathLayer::Inertial);
+            }
+            if frames.earth && style.view_omega != OMEGA {
+                layers.push(PathLayer::Earth);
+            }
+        }
+        if style.draw_ground_tracks {
+            layers.push(PathLayer::GroundTrack);
+        }
+        for layer in layers {
+            self.draw_layer(painter, matrix, (index, layer), obj, color);
+        }
+    }
+
+    fn draw_layer(
+        &mut self,
+        painter: &mut Painter<'_, '_, '_, '_, '_>,
+        matrix: &Matrix4<f32>,
+        key: (usize, PathLayer),
+        obj: &Object,
+        color: [f32; 3],
+    ) {
+        let style = self.style;
+        let layer = key.1;
+        let path = obj.path();
+        if path.is_empty() {
+            return;
+        }
+
+        let frame_omega = layer.frame_omega(style.view_omega);
+        let max_t = style.max_t;
+        let eye = rotation::frame_rotation(style.spin, frame_omega, style.view_omega, max_t)
+            .inverse_transform_vector(&style.screen.eye);
+        // a buffer in another frame or coloring, or of a path that was replaced (e.g. rewound),
+        // has to be built anew, and one in the wrong detail refined; either is still drawn as it
+        // is until the new one is ready
+        let up_to_date = self.buffers.get_mut(&key).is_some_and(|buffer| {
+            buffer.geometry.frame_omega == frame_omega
+                && buffer.geometry.color_mode == style.color_mode
+                && buffer.update(painter.display, path)
+                && buffer.geometry.detail_fits(&eye, &style.screen)
+        });
+        if !up_to_date {
+            self.request(key, frame_omega, path, &eye);
+        }
+        let buffer = match self.buffers.get_mut(&key) {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        buffer.used = true;
+        let geometry = &buffer.geometry;
+        // a stale buffer is drawn in the frame it was built in
+        let rotation =
+            rotation::frame_rotation(style.spin, geometry.frame_omega, style.view_omega, max_t);
+
+        // the stored samples up to `max_t` are in the buffer, the rest of the way to the exact
+        // position at `max_t` is drawn separately
+        let trail_start = obj.trail_start(max_t).max(path[0].pos().t());
+        let last_index = path.partition_point(|state| state.pos().t() <= max_t);
+        let last_sample = path[last_index.max(1) - 1];
+        let start = geometry.points.times.partition_point(|t| *t < trail_start);
+        let end = geometry
+            .points
+            .times
+            .partition_point(|t| *t <= last_sample.pos().t());
+        let end_state = obj.state_at(max_t);
+        let mut head = last_sample.smooth_segment(&end_state);
+        head.push(end_state);
+        head.retain(|state| state.pos().t() >= trail_start);
+
+        let (colored, value_range) = match style.color_range {
+            Some((min, max))
+                if style.color_mode != PathColorMode::Object
+                    && geometry.color_mode == style.color_mode =>
+            {
+                (true, [min as f32, max as f32])
+            }
+            _ => (false, [0.0, 0.0]),
+        };
+        let uniforms = uniform! {
+            matrix: *(matrix * rotation::to_matrix(&rotation)).as_ref(),
+            color: color,
+            brightness: layer.brightness(),
+            colored: colored,
+            value_range: value_range,
+            fade: obj.trail_fades(),
+            time_range: [
+                (trail_start - geometry.t_origin) as f32,
+                (max_t - geometry.t_origin) as f32,
+            ],
+        };
+
+        if end > start + 1 {
+            painter.path_vertices(&uniforms, buffer.vertices.slice(start..end).unwrap());
+        }
+        // the last sample may have been left out of the buffer, so the head continues from the
+        // last vertex drawn from it
+        let head: Vec<_> = geometry.points.vertices[start.min(end)..end]
+            .last()
+            .copied()
+            .into_iter()
+            .chain(head.iter().map(|state| geometry.vertex(state)))
+            .collect();
+        if head.len() > 1 {
+            let head = VertexBuffer::new(painter.display, &head).unwrap();
+            painter.path_vertices(&uniforms, head.slice(..).unwrap());
+        }
+    }
+
+    /// Asks the worker thread to build the path anew, unless it's already doing that for the
+    /// same frame and coloring
+    fn request(
+        &mut self,
+        key: (usize, PathLayer),
+        frame_omega: f64,
+        path: &VecDeque<SimState>,
+        eye: &Vector3<f64>,
+    ) {
+        let color_mode = self.style.color_mode;
+        let requested = self
+            .pending
+            .get(&key)
+            .is_some_and(|&(_, omega, mode)| omega == frame_omega && mode == color_mode);
+        if requested {
+            return;
+        }
+        let serial = self.next_serial;
+        self.next_serial += 1;
+        self.pending.insert(key, (serial, frame_omega, color_mode));
+        let job = PathJob {
+            key,
+            serial,
+            layer: key.1,
+            frame_omega,
+            color_mode,
+            path: path.clone(),
+            eye: *eye,
+            screen: self.style.screen,
+        };
+        let worker = self.worker.get_or_insert_with(PathWorker::spawn);
+        let _ = worker.jobs.send(job);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::HashMap,
+    f64::consts::PI,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use glium::{texture::RawImage2d, Display, Texture2d};
+use image::RgbaImage;
+use nalgebra::Vector3;
+
+use super::{Mesh, TexturedVertex};
+use crate::simulation::{pos_to_lat_lon_elev, R_EQU};
+
+/// Where the detailed tiles of the Earth's texture are looked for, next to the executable or
+/// else in the working directory. Level `n` is the equirectangular map cut into `2^n` rows and
+/// `2^(n+1)` columns of square tiles, stored as `<level>/<row>_<col>.jpg` with row 0 at the
+/// north pole and column 0 at 180°W.
+const TILES_DIR: &str = "textures/earth";
+/// The deepest level looked for; its tiles are about 40 km across
+const MAX_LEVEL: u32 = 9;
+/// A tile is used when it's at least this many times wider than the camera's altitude, so
+/// that the tiles around the camera fill the view
+const TILE_TO_ALTITUDE: f64 = 4.0;
+/// The angle between the vertices of a tile's mesh, in degrees; small enough that the patches
+/// stay above the global sphere they are drawn over
+const PATCH_STEP: f64 = 0.5;
+/// Above this many loaded tiles, the ones out of view are dropped
+const MAX_CACHED_TILES: usize = 36;
+
+pub type TileKey = (u32, u32, u32);
+
+pub struct Tile {
+    pub texture: Texture2d,
+    pub mesh: Mesh<TexturedVertex>,
+    /// The latitudes and longitudes covered by the tile, in degrees
+    pub lat_range: (f64, f64),
+    pub lon_range: (f64, f64),
+}
+
+impl Tile {
+    fn contains(&self, lat_range: (f64, f64), lon_range: (f64, f64)) -> bool {
+        self.lat_range.0 <= lat_range.0
+            && lat_range.1 <= self.lat_range.1
+            && self.lon_range.0 <= lon_range.0
+            && lon_range.1 <= self.lon_range.1
+    }
+}
+
+/// Texture tiles of increasing resolution for close-ups of the surface, loaded from disk as the
+/// camera gets near them
+pub struct TexturePyramid {
+    /// The directory the tiles were found in
+    dir: PathBuf,
+    /// The number of levels found on disk
+    levels: u32,
+    /// Loaded tiles; `None` for the ones that are missing or failed to load
+    cache: HashMap<TileKey, Option<Tile>>,
+    /// Tiles being read and decoded on background threads
+    loading: HashMap<TileKey, JoinHandle<Option<RgbaImage>>>,
+}
+
+impl TexturePyramid {
+    pub fn new() -> Self {
+        let dir = Self::tiles_dir();
+        let levels = (1..=MAX_LEVEL)
+            .take_while(|level| Self::level_dir(&dir, *level).is_dir())
+            .count() as u32;
+        Self {
+            dir,
+            levels,
+            cache: HashMap::new(),
+            loading: HashMap::new(),
+        }
+    }
+
+    fn tiles_dir() -> PathBuf {
+        let next_to_exe = std::env::current_exe()
+            .ok()
+            .and_then(|exe| Some(exe.parent()?.join(TILES_DIR)));
+        match next_to_exe {
+            Some(dir) if dir.is_dir() => dir,
+            _ => PathBuf::from(TILES_DIR),
+        }
+    }
+
+    fn level_dir(dir: &Path, level: u32) -> PathBuf {
+        dir.join(level.to_string())
+    }
+
+    /// The size of the tiles of a level, in degrees
+    fn tile_size(level: u32) -> f64 {
+        180.0 / f64::from(1u32 << level)
+    }
+
+    /// The deepest level whose tiles are wide enough for the camera at the given altitude
+    fn level_for_altitude(&self, altitude: f64) -> Option<u32> {
+        let tiles_in_half_circle = PI * R_EQU / (TILE_TO_ALTITUDE * altitude.max(1.0));
+        let level = tiles_in_half_circle
+            .log2()
+            .floor()
+            .min(f64::from(self.levels));
+        (level >= 1.0).then_some(level as u32)
+    }
+
+    /// The loaded tiles around the point under the camera, given in the Earth's frame; starts
+    /// loading the missing ones in the background
+    pub fn tiles_around(&mut self, display: &Display, camera: &Vector3<f64>) -> Vec<&Tile> {
+        let (lat, lon, elev) = pos_to_lat_lon_elev(*camera);
+        let level = match self.level_for_altitude(elev) {
+            Some(level) => level,
+            None => return vec![],
+        };
+        let size = Self::tile_size(level);
+        let rows = 1i64 << level;
+        let cols = 2 * rows;
+        let row = (((90.0 - lat) / size) as i64).clamp(0, rows - 1);
+        let col = (((lon + 180.0) / size) as i64).rem_euclid(cols);
+
+        let mut keys = vec![];
+        for row in (row - 1).max(0)..=(row + 1).min(rows - 1) {
+            for col in col - 1..=col + 1 {
+                let key = (level, row as u32, col.rem_euclid(cols) as u32);
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+
+        if self.cache.len() + keys.len() > MAX_CACHED_TILES {
+            self.cache.retain(|key, _| keys.contains(key));
+        }
+        // the threads of the tiles no longer needed are left to finish on their own
+        self.loading.retain(|key, _| keys.contains(key));
+        for key in &keys {
+            if !self.cache.contains_key(key) && !self.loading.contains_key(key) {
+                let path = self.tile_path(*key);
+                let handle = thread::spawn(move || Some(image::open(path).ok()?.to_rgba8()));
+                self.loading.insert(*key, handle);
+            }
+        }
+
+        let finished: Vec<_> = self
+            .loading
+            .iter()
+            .filter(|(_, handle)| handle.is_finished())
+            .map(|(key, _)| *key)
+            .collect();
+        for key in finished {
+            let image = self
+                .loading
+                .remove(&key)
+                .and_then(|handle| handle.join().ok());
+            let tile = image
+                .flatten()
+                .and_then(|image| Self::make_tile(display, key, image));
+            self.cache.insert(key, tile);
+        }
+
+        keys.iter()
+            .filter_map(|key| self.cache.get(key)?.as_ref())
+            .collect()
+    }
+
+    /// The most detailed loaded tile covering the whole area
+    pub fn tile_containing(
+        &self,
+        lat_range: (f64, f64),
+        lon_range: (f64, f64),
+    ) -> Option<(TileKey, &Tile)> {
+        self.cache
+            .iter()
+            .filter_map(|(key, tile)| tile.as_ref().map(|tile| (*key, tile)))
+            .filter(|(_, tile)| tile.contains(lat_range, lon_range))
+            .max_by_key(|((level, _, _), _)| *level)
+    }
+
+    pub fn tile(&self, key: TileKey) -> Option<&Tile> {
+        self.cache.get(&key)?.as_ref()
+    }
+
+    fn tile_path(&self, (level, row, col): TileKey) -> PathBuf {
+        Self::level_dir(&self.dir, level).join(format!("{}_{}.jpg", row, col))
+    }
+
+    fn make_tile(display: &Display, (level, row, col): TileKey, image: RgbaImage) -> Option<Tile> {
+        let dimensions = image.dimensions();
+        let image = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
+        let texture = Texture2d::new(display, image).ok()?;
+
+        let size = Self::tile_size(level);
+        let lat_max = 90.0 - size * f64::from(row);
+        let lon_min = size * f64::from(col) - 180.0;
+        let n_divisions = (size / PATCH_STEP).ceil() as u32;
+        let lat_range = (lat_max - size, lat_max);
+        let lon_range = (lon_min, lon_min + size);
+        let mesh = Mesh::sphere_patch(display, lat_range, lon_range, n_divisions);
+        Some(Tile {
+            texture,
+            mesh,
+            lat_range,
+            lon_range,
+        })
+    }
+}
@@ -0,0 +1,268 @@
+//! Solving for the launch direction of a projectile that lands on a given point, with the full
+//! dynamics of the simulation (Coriolis force, drag)
+
+use std::fmt;
+
+use nalgebra::{Matrix2, Matrix3, Vector2, Vector3};
+
+use super::{
+    east_north, great_circle, lat_lon_elev_to_vec3, pos_to_lat_lon_elev, surface_normal, Object,
+    ObjectIntegrator, Position, Velocity, OMEGA,
+};
+
+/// Time step of the simulated shots, in seconds
+const SHOT_TIME_STEP: f64 = 0.5;
+/// Shots that haven't landed after this time, in seconds, are given up
+const MAX_FLIGHT_TIME: f64 = 3600.0;
+/// Near the ground the steps shrink so that the landing point is exact; this is the shortest
+const MIN_SHOT_STEP: f64 = 1e-3;
+/// The range of launch elevations searched, in degrees; only the flat trajectories are used
+const MIN_ELEVATION: f64 = 0.5;
+const MAX_ELEVATION: f64 = 45.0;
+/// Number of halvings of the elevation interval when looking for the naive solution
+const BISECTION_STEPS: usize = 30;
+/// Step of the finite differences of the landing point, in degrees
+const AIM_DELTA: f64 = 1e-3;
+/// A shot landing closer than this to the target, in meters, is a hit
+const HIT_TOLERANCE: f64 = 1.0;
+const MAX_ITERATIONS: usize = 30;
+/// Time step of the simulated interceptors and their targets, in seconds
+const INTERCEPT_TIME_STEP: f64 = 1.0;
+/// Step of the finite differences of the interceptor's end point, in m/s
+const VELOCITY_DELTA: f64 = 0.1;
+/// An interceptor passing closer than this to the target, in meters, reaches it
+const INTERCEPT_TOLERANCE: f64 = 10.0;
+/// Standard gravity, for the first guess of the launch velocity
+const G0: f64 = 9.81;
+
+/// A launch from a point on the surface and where it lands
+#[derive(Debug, Clone, Copy)]
+pub struct Shot {
+    /// Launch direction, in degrees clockwise from north
+    pub azimuth: f64,
+    /// Launch angle above the horizon, in degrees
+    pub elevation: f64,
+    /// The landing point in the Earth's frame
+    pub impact: Vector3<f64>,
+    pub flight_time: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AimError {
+    /// Even the longest shot falls short of the target
+    OutOfRange,
+    /// A shot didn't land in time
+    NoLanding,
+    /// The iterations didn't bring the shots onto the target
+    NoConvergence,
+}
+
+impl fmt::Display for AimError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AimError::OutOfRange => write!(f, "the target is out of range"),
+            AimError::NoLanding => write!(f, "a shot didn't land within an hour"),
+            AimError::NoConvergence => write!(f, "the aim didn't converge on the target"),
+        }
+    }
+}
+
+/// The launch site and the parameters of the projectile
+#[derive(Debug, Clone, Copy)]
+pub struct Launcher {
+    /// Latitude and longitude of the launch site, in degrees
+    pub lat: f64,
+    pub lon: f64,
+    /// Launch speed relative to the ground, in m/s
+    pub speed: f64,
+    pub drag: f64,
+}
+
+impl Launcher {
+    /// The projectile leaving the launch site in the given direction (in degrees)
+    pub fn projectile(&self, azimuth: f64, elevation: f64) -> Object {
+        let (az, el) = (azimuth.to_radians(), elevation.to_radians());
+        let pos = Position::from_lat_lon_elev(self.lat, self.lon, 0.0);
+        let vel = Velocity::from_east_north_up(
+            pos,
+            self.speed * el.cos() * az.sin(),
+            self.speed * el.cos() * az.cos(),
+            self.speed * el.sin(),
+        );
+        Object::new(pos, vel).with_drag(self.drag)
+    }
+
+    /// Simulates the shot in the given direction up to its landing
+    pub fn fire(&self, azimuth: f64, elevation: f64) -> Result<Shot, AimError> {
+        let mut obj = self.projectile(azimuth, elevation);
+        let mut integrator = ObjectIntegrator::new(SHOT_TIME_STEP);
+        while obj.landed_at().is_none() {
+            if obj.time() > MAX_FLIGHT_TIME {
+                return Err(AimError::NoLanding);
+            }
+            let pos = obj.pos().to_omega(OMEGA);
+            let (_, _, elev) = pos_to_lat_lon_elev(pos.pos());
+            let (_, _, v_up) = obj.vel().to_east_north_up(obj.pos());
+            // aim the last step at the ground
+            let dt = if v_up < 0.0 {
+                (elev / -v_up).clamp(MIN_SHOT_STEP, SHOT_TIME_STEP)
+            } else {
+                SHOT_TIME_STEP
+            };
+            obj.step(&mut integrator, dt);
+        }
+        Ok(Shot {
+            azimuth,
+            elevation,
+            impact: obj.pos().to_omega(OMEGA).pos(),
+            flight_time: obj.time(),
+        })
+    }
+
+    fn site(&self) -> Vector3<f64> {
+        lat_lon_elev_to_vec3(self.lat, self.lon, 0.0)
+    }
+
+    /// The shot aimed along the great circle to the target, with the elevation only adjusted so
+    /// that it flies the right distance; the rotation of the Earth makes it miss sideways
+    pub fn naive(&self, target: &Vector3<f64>) -> Result<Shot, AimError> {
+        let (distance, azimuth) = great_circle(&self.site(), target);
+        let range = |shot: &Shot| great_circle(&self.site(), &shot.impact).0;
+
+        let longest = self.fire(azimuth, MAX_ELEVATION)?;
+        if range(&longest) < distance {
+            return Err(AimError::OutOfRange);
+        }
+        let (mut low, mut high) = (MIN_ELEVATION, MAX_ELEVATION);
+        let mut shot = longest;
+        for _ in 0..BISECTION_STEPS {
+            let mid = 0.5 * (low + high);
+            shot = self.fire(azimuth, mid)?;
+            if range(&shot) < distance {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(shot)
+    }
+
+    /// The shot that lands on the target, found with Newton's method starting from the naive
+    /// aim, with the number of iterations it took
+    pub fn correct(&self, naive: &Shot, target: &Vector3<f64>) -> Result<(Shot, usize), AimError> {
+        let (mut azimuth, mut elevation) = (naive.azimuth, naive.elevation);
+        for iteration in 0..MAX_ITERATIONS {
+            let shot = self.fire(azimuth, elevation)?;
+            let miss = miss_distance(&shot.impact, target);
+            if miss.norm() < HIT_TOLERANCE {
+                return Ok((shot, iteration));
+            }
+            let d_azimuth =
+                miss_distance(&self.fire(azimuth + AIM_DELTA, elevation)?.impact, target);
+            let d_elevation =
+                miss_distance(&self.fire(azimuth, elevation + AIM_DELTA)?.impact, target);
+            let jacobian = Matrix2::from_columns(&[
+                (d_azimuth - miss) / AIM_DELTA,
+                (d_elevation - miss) / AIM_DELTA,
+            ]);
+            let correction = jacobian.try_inverse().ok_or(AimError::NoConvergence)? * -miss;
+            azimuth = (azimuth + correction.x).rem_euclid(360.0);
+            elevation = (elevation + correction.y).clamp(MIN_ELEVATION, 90.0 - MIN_ELEVATION);
+        }
+        Err(AimError::NoConvergence)
+    }
+}
+
+/// The east and north components of the distance from the target to the landing point, in
+/// meters
+pub fn miss_distance(impact: &Vector3<f64>, target: &Vector3<f64>) -> Vector2<f64> {
+    let (east, north) = east_north(target);
+    let diff = impact - target;
+    Vector2::new(diff.dot(&east), diff.dot(&north))
+}
+
+/// A launch that brings one object to the position of another one
+#[derive(Debug, Clone)]
+pub struct Interception {
+    /// Flight time of the interceptor, in seconds
+    pub duration: f64,
+    /// Launch velocity relative to the Earth, in the Earth's frame
+    pub velocity: Vector3<f64>,
+    /// The remaining distance from the target at the end of the flight, in meters
+    pub miss: f64,
+    pub path: Vec<Position>,
+}
+
+/// Advances a copy of the object by `duration` seconds
+fn fly(mut obj: Object, duration: f64) -> Object {
+    let mut integrator = ObjectIntegrator::new(INTERCEPT_TIME_STEP);
+    let end = obj.time() + duration;
+    while obj.time() < end {
+        let dt = INTERCEPT_TIME_STEP.min(end - obj.time());
+        obj.step(&mut integrator, dt);
+    }
+    obj
+}
+
+/// Launch velocities that make `chaser`, set off from its current state, meet `target` after
+/// each of `candidates` flight times spread evenly over `window` (in seconds), found with the
+/// shooting method; the flight times for which the shooting didn't converge are left out
+pub fn intercept(
+    chaser: &Object,
+    target: &Object,
+    window: (f64, f64),
+    candidates: usize,
+) -> Vec<Interception> {
+    let (min_duration, max_duration) = window;
+    let t0 = chaser.time();
+    // the target is simulated ahead once, to be looked up at all the flight times
+    let target = fly(target.without_history(), t0 + max_duration - target.time());
+    (0..candidates)
+        .filter_map(|i| {
+            let fraction = if candidates > 1 {
+                i as f64 / (candidates - 1) as f64
+            } else {
+                1.0
+            };
+            let duration = min_duration + (max_duration - min_duration) * fraction;
+            let aim = target.state_at(t0 + duration).pos().to_omega(OMEGA).pos();
+            shoot(chaser, &aim, duration)
+        })
+        .collect()
+}
+
+/// Finds the launch velocity of `chaser` that brings it to `aim` (in the Earth's frame) after
+/// `duration` seconds
+fn shoot(chaser: &Object, aim: &Vector3<f64>, duration: f64) -> Option<Interception> {
+    let end = |vel: Vector3<f64>| {
+        fly(chaser.launched(vel), duration)
+            .pos()
+            .to_omega(OMEGA)
+            .pos()
+    };
+    let start = chaser.pos().to_omega(OMEGA).pos();
+    // a straight line, corrected for the fall under uniform gravity
+    let mut vel = (aim - start) / duration + 0.5 * G0 * duration * surface_normal(&start);
+    for _ in 0..MAX_ITERATIONS {
+        let reached = end(vel);
+        let miss = reached - aim;
+        if miss.norm() < INTERCEPT_TOLERANCE {
+            let obj = fly(chaser.launched(vel), duration);
+            return Some(Interception {
+                duration,
+                velocity: vel,
+                miss: miss.norm(),
+                path: obj
+                    .states_until(f64::INFINITY)
+                    .map(|state| *state.pos())
+                    .collect(),
+            });
+        }
+        let columns: Vec<_> = (0..3)
+            .map(|k| (end(vel + Vector3::ith(k, VELOCITY_DELTA)) - reached) / VELOCITY_DELTA)
+            .collect();
+        let jacobian = Matrix3::from_columns(&columns);
+        vel -= jacobian.try_inverse()? * miss;
+    }
+    None
+}
@@ -0,0 +1,32 @@
+//! First-order analytic estimates of the Coriolis effects, for comparison with the simulation
+
+use std::f64::consts::PI;
+
+use super::OMEGA;
+
+/// The Coriolis parameter f = 2Ω sin(latitude), in 1/s
+pub fn coriolis_parameter(lat: f64) -> f64 {
+    2.0 * OMEGA * lat.to_radians().sin()
+}
+
+/// Sideways deflection of an object moving horizontally with constant speed for the given time,
+/// in meters; positive means to the right of the direction of motion
+pub fn horizontal_deflection(lat: f64, speed: f64, time: f64) -> f64 {
+    0.5 * coriolis_parameter(lat) * speed * time * time
+}
+
+/// Period of inertial oscillations, in seconds (infinite at the equator)
+pub fn inertial_period(lat: f64) -> f64 {
+    2.0 * PI / coriolis_parameter(lat).abs()
+}
+
+/// Radius of the inertial circle for the given speed, in meters
+pub fn inertial_radius(lat: f64, speed: f64) -> f64 {
+    speed / coriolis_parameter(lat).abs()
+}
+
+/// Rate of rotation of the Foucault pendulum's plane of swing, in degrees per hour; positive
+/// means clockwise when viewed from above
+pub fn foucault_rate(lat: f64) -> f64 {
+    OMEGA.to_degrees() * 3600.0 * lat.to_radians().sin()
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The specific gas constant of dry air, in J/(kg·K)
+const R_AIR: f64 = 287.053;
+/// The standard gravity the geopotential heights are measured with, in m/s²
+const G0: f64 = 9.80665;
+/// The Earth's radius used for converting between geometric and geopotential heights, in m
+const R_GEOPOTENTIAL: f64 = 6_356_766.0;
+
+/// The layers of the International Standard Atmosphere up to 86 km: the geopotential height of
+/// the base in m, the temperature gradient in K/m, and the temperature in K and the pressure in
+/// Pa at the base. Above the last one the temperature is taken as constant.
+const LAYERS: [(f64, f64, f64, f64); 8] = [
+    (0.0, -0.0065, 288.15, 101_325.0),
+    (11_000.0, 0.0, 216.65, 22_632.06),
+    (20_000.0, 0.001, 216.65, 5_474.889),
+    (32_000.0, 0.0028, 228.65, 868.0187),
+    (47_000.0, 0.0, 270.65, 110.9063),
+    (51_000.0, -0.0028, 270.65, 66.938_87),
+    (71_000.0, -0.002, 214.65, 3.956_42),
+    (84_852.0, 0.0, 186.946, 0.373_38),
+];
+
+/// How the density of the air changes with the elevation, for the drag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Atmosphere {
+    /// The layered International Standard Atmosphere
+    #[default]
+    Standard,
+    /// A single exponential with a scale height of 8 km; much too dense above about 20 km
+    Exponential,
+}
+
+impl Atmosphere {
+    pub const ALL: [Atmosphere; 2] = [Atmosphere::Standard, Atmosphere::Exponential];
+
+    /// The density of the air at the elevation `elev` above the sea level, in kg/m³
+    pub fn air_density(self, elev: f64) -> f64 {
+        match self {
+            Atmosphere::Standard => {
+                let (temperature, pressure) = standard_atmosphere(elev);
+                pressure / (R_AIR * temperature)
+            }
+            Atmosphere::Exponential => 1.225 * (-0.000125 * elev).exp(),
+        }
+    }
+}
+
+impl fmt::Display for Atmosphere {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Atmosphere::Standard => write!(f, "International Standard Atmosphere"),
+            Atmosphere::Exponential => write!(f, "Exponential (8 km scale height)"),
+        }
+    }
+}
+
+/// The layer of the standard atmosphere containing the geopotential height `h`
+fn layer(h: f64) -> (f64, f64, f64, f64) {
+    let index = LAYERS.partition_point(|&(base, ..)| base <= h);
+    LAYERS[index.saturating_sub(1)]
+}
+
+/// The temperature in K and the pressure in Pa of the standard atmosphere at the elevation
+/// `elev` in meters
+pub fn standard_atmosphere(elev: f64) -> (f64, f64) {
+    let h = R_GEOPOTENTIAL * elev / (R_GEOPOTENTIAL + elev);
+    let (base, lapse, base_temperature, base_pressure) = layer(h);
+    let temperature = base_temperature + lapse * (h - base);
+    let pressure = if lapse == 0.0 {
+        base_pressure * (-G0 * (h - base) / (R_AIR * base_temperature)).exp()
+    } else {
+        base_pressure * (base_temperature / temperature).powf(G0 / (R_AIR * lapse))
+    };
+    (temperature, pressure)
+}
+
+/// The elevation in meters at which the pressure of the standard atmosphere is `hpa`
+/// hectopascals
+pub fn pressure_altitude(hpa: f64) -> f64 {
+    let pressure = hpa * 100.0;
+    // the pressures fall with the height
+    let index = LAYERS.partition_point(|&(.., base_pressure)| base_pressure >= pressure);
+    let (base, lapse, base_temperature, base_pressure) = LAYERS[index.saturating_sub(1)];
+    let h = if lapse == 0.0 {
+        base - R_AIR * base_temperature / G0 * (pressure / base_pressure).ln()
+    } else {
+        let temperature = base_temperature * (pressure / base_pressure).powf(-R_AIR * lapse / G0);
+        base + (temperature - base_temperature) / lapse
+    };
+    R_GEOPOTENTIAL * h / (R_GEOPOTENTIAL - h)
+}
@@ -0,0 +1,88 @@
+use std::{hint::black_box, time::Instant};
+
+use super::{pos_to_lat_lon_elev, Object, Position, Velocity, OMEGA};
+
+/// Average duration of a single call of one of the physics kernels
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub name: &'static str,
+    pub nanos_per_iter: f64,
+}
+
+/// One of the physics kernels, with its inputs prepared
+pub struct Kernel {
+    pub name: &'static str,
+    pub run: Box<dyn FnMut()>,
+}
+
+const ITERATIONS: u32 = 100_000;
+
+fn measure(name: &'static str, mut f: impl FnMut()) -> BenchmarkResult {
+    // warm up the caches and the branch predictor
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    BenchmarkResult {
+        name,
+        nanos_per_iter: start.elapsed().as_secs_f64() * 1e9 / ITERATIONS as f64,
+    }
+}
+
+/// The functions the integration spends most of its time in, shared by the in-app diagnostic
+/// and the criterion benchmarks
+pub fn kernels() -> Vec<Kernel> {
+    let pos = Position::from_lat_lon_elev(45.0, 10.0, 1000.0);
+    let vel = Velocity::from_east_north_up(pos, 100.0, 50.0, 10.0);
+    let free = Object::new(pos, vel);
+    let const_alt = Object::new(pos, vel)
+        .with_const_alt(1000.0)
+        .with_friction(1e-4);
+    let mut inertial_pos = pos;
+    inertial_pos.increase_time(3600.0);
+
+    let kernel = |name, run: Box<dyn FnMut()>| Kernel { name, run };
+    vec![
+        kernel(
+            "Derivative (free flight)",
+            Box::new(move || {
+                black_box(black_box(&free).derivative());
+            }),
+        ),
+        kernel(
+            "Derivative (constant altitude)",
+            Box::new(move || {
+                black_box(black_box(&const_alt).derivative());
+            }),
+        ),
+        kernel(
+            "Position frame conversion",
+            Box::new(move || {
+                black_box(black_box(inertial_pos).to_omega(0.0));
+            }),
+        ),
+        kernel(
+            "Velocity frame conversion",
+            Box::new(move || {
+                black_box(black_box(vel).to_omega(inertial_pos, 0.0));
+            }),
+        ),
+        kernel(
+            "Cartesian to geodetic",
+            Box::new(move || {
+                black_box(pos_to_lat_lon_elev(black_box(pos.to_omega(OMEGA).pos())));
+            }),
+        ),
+    ]
+}
+
+/// Times the kernels, so that the performance can be compared between machines and versions
+pub fn run_benchmarks() -> Vec<BenchmarkResult> {
+    kernels()
+        .into_iter()
+        .map(|kernel| measure(kernel.name, kernel.run))
+        .collect()
+}
@@ -0,0 +1,109 @@
+//! Springs and tethers linking pairs of objects, so that they can't be advanced independently
+
+use nalgebra::Vector3;
+use numeric_algs::integration::Integrator;
+
+use super::Object;
+
+/// How many times the tethers are enforced in turn after each step; tethers sharing an object
+/// disturb each other, and each pass brings them closer to their lengths
+const TETHER_PASSES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Link {
+    /// Pulls the ends towards the rest length, with `stiffness` in 1/s² and `damping` in 1/s,
+    /// both per unit mass
+    Spring { stiffness: f64, damping: f64 },
+    /// Keeps the ends from getting further apart than the length, but lets them come closer
+    Tether,
+}
+
+/// A link between the objects with the indices `a` and `b`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constraint {
+    pub a: usize,
+    pub b: usize,
+    /// The rest length of the spring or the length of the tether, in meters
+    pub length: f64,
+    pub link: Link,
+}
+
+impl Constraint {
+    /// The acceleration of the `a` end, in the inertial frame; the `b` end gets the opposite
+    fn spring_acceleration(&self, objects: &[Object]) -> Vector3<f64> {
+        let (stiffness, damping) = match self.link {
+            Link::Spring { stiffness, damping } => (stiffness, damping),
+            Link::Tether => return Vector3::zeros(),
+        };
+        let (pos_a, vel_a) = objects[self.a].inertial_state();
+        let (pos_b, vel_b) = objects[self.b].inertial_state();
+        let offset = pos_b - pos_a;
+        let distance = offset.norm();
+        if distance == 0.0 {
+            return Vector3::zeros();
+        }
+        let dir = offset / distance;
+        let stretching = (vel_b - vel_a).dot(&dir);
+        (stiffness * (distance - self.length) + damping * stretching) * dir
+    }
+
+    /// Moves a stretched tether's ends back to its length and stops them from moving further
+    /// apart; the ends that aren't free to move (e.g. anchored ones) stay put
+    fn enforce_tether(&self, objects: &mut [Object]) {
+        if self.link != Link::Tether {
+            return;
+        }
+        let (pos_a, vel_a) = objects[self.a].inertial_state();
+        let (pos_b, vel_b) = objects[self.b].inertial_state();
+        let offset = pos_b - pos_a;
+        let distance = offset.norm();
+        if distance <= self.length {
+            return;
+        }
+        let weight = |obj: &Object| if obj.is_free_flight() { 1.0 } else { 0.0 };
+        let (weight_a, weight_b) = (weight(&objects[self.a]), weight(&objects[self.b]));
+        let total = weight_a + weight_b;
+        if total == 0.0 {
+            return;
+        }
+        let dir = offset / distance;
+        let stretch = (distance - self.length) * dir;
+        let separation = (vel_b - vel_a).dot(&dir).max(0.0) * dir;
+        objects[self.a].shift_inertial(stretch * weight_a / total, separation * weight_a / total);
+        objects[self.b].shift_inertial(-stretch * weight_b / total, -separation * weight_b / total);
+    }
+}
+
+/// Advances all the objects by a step of `dt`, with the springs acting on them during the step
+/// and the tethers enforced after it
+pub fn step_linked(
+    objects: &mut [Object],
+    constraints: &[Constraint],
+    integrator: &mut impl Integrator<Object>,
+    dt: f64,
+) {
+    if !constraints.is_empty() {
+        let mut accelerations = vec![Vector3::zeros(); objects.len()];
+        for constraint in constraints {
+            let acc = constraint.spring_acceleration(objects);
+            accelerations[constraint.a] += acc;
+            accelerations[constraint.b] -= acc;
+        }
+        for (obj, acc) in objects.iter_mut().zip(accelerations) {
+            obj.set_link_acceleration(acc);
+        }
+    }
+    for obj in objects.iter_mut() {
+        obj.step(integrator, dt);
+    }
+    if constraints
+        .iter()
+        .any(|constraint| constraint.link == Link::Tether)
+    {
+        for _ in 0..TETHER_PASSES {
+            for constraint in constraints {
+                constraint.enforce_tether(objects);
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use nalgebra::Vector3;
+
+use super::{Atmosphere, PressureSystem, SpinRamp, WindField};
+
+/// The conditions the objects move in. Every object and the tracers hold the same one, shared,
+/// so that the objects integrated on background threads see exactly what the rest does.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    /// How the Earth's rotation changes over the run; `None` keeps it constant
+    pub spin: Option<SpinRamp>,
+    /// The wind used in the drag computation; `None` for still air
+    pub wind: Option<Arc<WindField>>,
+    /// The pressure systems acting on the objects at a constant altitude
+    pub pressure_systems: Vec<PressureSystem>,
+    /// How the density of the air changes with the elevation
+    pub atmosphere: Atmosphere,
+}
+
+impl Environment {
+    /// The density of the air at the elevation `elev` above the sea level, in kg/m³
+    pub fn air_density(&self, elev: f64) -> f64 {
+        self.atmosphere.air_density(elev)
+    }
+
+    /// The wind at the point given in the Earth's frame at the time `t`, as a vector in that
+    /// frame; `None` in still air
+    pub fn wind_velocity(&self, pos: &Vector3<f64>, t: f64) -> Option<Vector3<f64>> {
+        self.wind.as_ref().map(|field| field.velocity(pos, t))
+    }
+
+    /// The total pressure-gradient acceleration at the point given in the Earth's frame, in that
+    /// frame; `None` if there are no pressure systems
+    pub fn pressure_gradient(&self, pos: &Vector3<f64>, elev: f64) -> Option<Vector3<f64>> {
+        (!self.pressure_systems.is_empty()).then(|| {
+            let density = self.air_density(elev);
+            self.pressure_systems
+                .iter()
+                .map(|system| system.acceleration(pos, density))
+                .sum()
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use std::f64::consts::PI;
+
+use nalgebra::{Rotation3, Vector3};
+
+use super::{spin::frame_angle, SpinRamp};
+
+/// Obliquity of the ecliptic in degrees
+pub const OBLIQUITY: f64 = 23.439;
+/// Period of the precession of the equinoxes in seconds
+pub const PRECESSION_PERIOD: f64 = 25_772.0 * 365.25 * 86400.0;
+/// Length of the sidereal year in seconds
+pub const YEAR: f64 = 365.256363 * 86400.0;
+
+/// The orientation of the Earth's rotation axis and the rotating frames relative to the celestial
+/// sphere.
+///
+/// Three kinds of coordinates are involved, all with the same handedness as the simulation
+/// coordinates:
+/// - ecliptic: the Y axis points to the ecliptic pole, the Z axis to the vernal equinox,
+/// - equatorial: inertial, the Y axis is the Earth's rotation axis, the Z axis points to the
+///   vernal equinox,
+/// - rotating: the simulation coordinates of the frame rotating with some angular velocity; they
+///   coincide with the equatorial coordinates rotated by `sky_rotation` at t=0.
+#[derive(Debug, Clone, Copy)]
+pub struct CelestialFrame {
+    /// Tilt of the rotation axis relative to the ecliptic pole, in degrees
+    pub obliquity: f64,
+    /// Rotation of the celestial sphere about the Earth's axis at t=0, in degrees
+    pub sky_rotation: f64,
+    pub precession: bool,
+    /// How many times faster than in reality the axis precesses
+    pub precession_exaggeration: f64,
+}
+
+impl Default for CelestialFrame {
+    fn default() -> Self {
+        Self {
+            obliquity: OBLIQUITY,
+            sky_rotation: 0.0,
+            precession: false,
+            precession_exaggeration: 1e6,
+        }
+    }
+}
+
+impl CelestialFrame {
+    /// A unit vector in ecliptic coordinates at the given ecliptic longitude and latitude (degrees)
+    pub fn ecliptic_direction(lon: f64, lat: f64) -> Vector3<f64> {
+        let lon = lon.to_radians();
+        let lat = lat.to_radians();
+        Vector3::new(lat.cos() * lon.sin(), lat.sin(), lat.cos() * lon.cos())
+    }
+
+    pub fn ecliptic_to_equatorial(&self) -> Rotation3<f64> {
+        ecliptic_to_equatorial(self.obliquity)
+    }
+
+    /// Transforms the equatorial coordinates of the real sky (in which the star map is defined) to
+    /// the equatorial coordinates of the simulated Earth at time `t`, accounting for the
+    /// configured obliquity and the precession
+    pub fn sky_to_equatorial(&self, t: f64) -> Rotation3<f64> {
+        let precession = if self.precession {
+            let ang = 2.0 * PI * t * self.precession_exaggeration / PRECESSION_PERIOD;
+            Rotation3::from_axis_angle(&Vector3::y_axis(), ang)
+        } else {
+            Rotation3::identity()
+        };
+        ecliptic_to_equatorial(self.obliquity)
+            * precession
+            * ecliptic_to_equatorial(OBLIQUITY).inverse()
+    }
+
+    /// Transforms the equatorial coordinates to the coordinates of the frame rotating with `omega`
+    /// at time `t`, with the Earth's rotation following `spin`
+    pub fn equatorial_to_rotating(
+        &self,
+        t: f64,
+        omega: f64,
+        spin: Option<SpinRamp>,
+    ) -> Rotation3<f64> {
+        let ang = self.sky_rotation.to_radians() - frame_angle(spin, omega, t);
+        Rotation3::from_axis_angle(&Vector3::y_axis(), ang)
+    }
+
+    pub fn ecliptic_to_rotating(
+        &self,
+        t: f64,
+        omega: f64,
+        spin: Option<SpinRamp>,
+    ) -> Rotation3<f64> {
+        self.equatorial_to_rotating(t, omega, spin) * self.ecliptic_to_equatorial()
+    }
+}
+
+fn ecliptic_to_equatorial(obliquity: f64) -> Rotation3<f64> {
+    // the ecliptic pole is the celestial pole tilted towards RA = 18h
+    Rotation3::from_axis_angle(&Vector3::z_axis(), obliquity.to_radians())
+}
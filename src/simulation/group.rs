@@ -0,0 +1,204 @@
+use nalgebra::Vector3;
+
+use super::{pos_to_lat_lon_elev, surface_normal, Object, SimState, OMEGA};
+
+/// The most samples of a group path, so that long runs don't make it expensive to draw
+const MAX_GROUP_PATH_SAMPLES: usize = 2000;
+
+/// The collective path of a group of objects: the centroid of the members at every sample
+/// and the RMS distance of the members from it
+pub struct GroupPath {
+    pub centroid: Vec<Vector3<f64>>,
+    pub spread: Vec<f64>,
+}
+
+impl GroupPath {
+    /// Calculates the group path in the frame rotating with `omega`, up to the time `max_t`,
+    /// at evenly spaced samples. The members are assumed to have been created at the same
+    /// time, so that their path samples correspond to the same moments.
+    pub fn new(members: &[&Object], omega: f64, max_t: f64) -> Self {
+        let indices = sample_indices(members, max_t, MAX_GROUP_PATH_SAMPLES);
+        let n = members.len() as f64;
+
+        let mut centroid = Vec::with_capacity(indices.len());
+        let mut spread = Vec::with_capacity(indices.len());
+
+        for i in indices {
+            let positions: Vec<_> = members
+                .iter()
+                .map(|obj| obj.nth_state(i).pos().to_omega(omega).pos())
+                .collect();
+            let center = positions
+                .iter()
+                .fold(Vector3::zeros(), |acc, pos| acc + pos)
+                / n;
+            let variance = positions
+                .iter()
+                .map(|pos| (pos - center).norm_squared())
+                .sum::<f64>()
+                / n;
+            centroid.push(center);
+            spread.push(variance.sqrt());
+        }
+
+        Self { centroid, spread }
+    }
+
+    /// The edges of the ±1σ envelope around the centroid, spread horizontally
+    /// perpendicular to the direction of motion
+    pub fn envelope(&self) -> (Vec<Vector3<f64>>, Vec<Vector3<f64>>) {
+        let mut left = Vec::with_capacity(self.centroid.len());
+        let mut right = Vec::with_capacity(self.centroid.len());
+        let mut side = Vector3::zeros();
+
+        for (i, (center, sigma)) in self.centroid.iter().zip(&self.spread).enumerate() {
+            let tangent = if i + 1 < self.centroid.len() {
+                self.centroid[i + 1] - center
+            } else if i > 0 {
+                center - self.centroid[i - 1]
+            } else {
+                Vector3::zeros()
+            };
+            let new_side = tangent.cross(center);
+            if new_side.norm() > 1e-9 {
+                side = new_side.normalize();
+            }
+            left.push(center + side * *sigma);
+            right.push(center - side * *sigma);
+        }
+
+        (left, right)
+    }
+}
+
+/// Aggregate quantities describing a ring of particles at a single moment
+#[derive(Debug, Clone, Copy)]
+pub struct RingSample {
+    pub t: f64,
+    /// Area enclosed by the ring, in m²
+    pub area: f64,
+    /// Circulation of the velocity relative to the surface around the ring, in m²/s
+    pub circulation: f64,
+    /// Mean relative vorticity inside the ring, in 1/s
+    pub rel_vorticity: f64,
+    /// Relative vorticity plus the Coriolis parameter at the ring's center, in 1/s
+    pub abs_vorticity: f64,
+    /// Relative rate of change of the enclosed area, in 1/s
+    pub divergence: f64,
+    /// Total angular momentum per unit mass of the particles about the local vertical through
+    /// the ring's center, relative to the surface, in m²/s
+    pub rel_angular_momentum: f64,
+    /// The same, including the rotation of the Earth (conserved under central forces)
+    pub abs_angular_momentum: f64,
+}
+
+/// Calculates the ring diagnostics over time for a group of particles, treating the members,
+/// in order, as the vertices of a polygon. At most `max_samples` evenly spaced samples up to
+/// the time `max_t` are returned.
+pub fn ring_diagnostics(members: &[&Object], max_t: f64, max_samples: usize) -> Vec<RingSample> {
+    if members.len() < 3 {
+        return vec![];
+    }
+    let mut samples: Vec<RingSample> = sample_indices(members, max_t, max_samples)
+        .into_iter()
+        .map(|i| {
+            let states: Vec<_> = members.iter().map(|obj| obj.nth_state(i)).collect();
+            ring_sample(&states)
+        })
+        .collect();
+
+    for i in 0..samples.len() {
+        let prev = samples[i.saturating_sub(1)];
+        let next = samples[(i + 1).min(samples.len() - 1)];
+        let dt = next.t - prev.t;
+        let area = samples[i].area;
+        if dt > 0.0 && area > 1.0 {
+            samples[i].divergence = (next.area - prev.area) / dt / area;
+        }
+    }
+
+    samples
+}
+
+/// The indices of at most about `max_samples` evenly spaced states common to all the members,
+/// up to the time `max_t`
+fn sample_indices(members: &[&Object], max_t: f64, max_samples: usize) -> Vec<usize> {
+    let len = members
+        .iter()
+        .map(|obj| obj.count_states_until(max_t))
+        .min()
+        .unwrap_or(0);
+    let stride = (len / max_samples.max(1)).max(1);
+    let mut indices: Vec<_> = (0..len).step_by(stride).collect();
+    // the group's path reaches up to the latest state
+    if len > 0 && !(len - 1).is_multiple_of(stride) {
+        indices.push(len - 1);
+    }
+    indices
+}
+
+fn ring_sample(states: &[SimState]) -> RingSample {
+    let positions: Vec<_> = states
+        .iter()
+        .map(|state| state.pos().to_omega(OMEGA).pos())
+        .collect();
+    let center = positions
+        .iter()
+        .fold(Vector3::zeros(), |acc, pos| acc + pos)
+        / states.len() as f64;
+
+    // the local horizontal plane at the center of the ring
+    let up = surface_normal(&center);
+    let lon = center.x.atan2(center.z);
+    let east = Vector3::new(lon.cos(), 0.0, -lon.sin());
+    let north = up.cross(&east);
+
+    let coords: Vec<_> = positions
+        .iter()
+        .map(|pos| (pos - center).dot(&east))
+        .zip(positions.iter().map(|pos| (pos - center).dot(&north)))
+        .collect();
+    let vels: Vec<_> = states
+        .iter()
+        .map(|state| {
+            let vel = state.vel().to_omega(*state.pos(), OMEGA).vel();
+            (vel.dot(&east), vel.dot(&north))
+        })
+        .collect();
+
+    let n = coords.len();
+    let mut area = 0.0;
+    let mut circulation = 0.0;
+    let mut angular_momentum = 0.0;
+    let mut moment_of_inertia = 0.0;
+    for k in 0..n {
+        let (x1, y1) = coords[k];
+        let (x2, y2) = coords[(k + 1) % n];
+        let (u1, v1) = vels[k];
+        let (u2, v2) = vels[(k + 1) % n];
+        area += 0.5 * (x1 * y2 - x2 * y1);
+        circulation += 0.5 * ((u1 + u2) * (x2 - x1) + (v1 + v2) * (y2 - y1));
+        angular_momentum += x1 * v1 - y1 * u1;
+        moment_of_inertia += x1 * x1 + y1 * y1;
+    }
+    // make the orientation counterclockwise when viewed from above
+    if area < 0.0 {
+        area = -area;
+        circulation = -circulation;
+    }
+
+    let rel_vorticity = if area > 1.0 { circulation / area } else { 0.0 };
+    let (lat, _, _) = pos_to_lat_lon_elev(center);
+    let coriolis_param = 2.0 * OMEGA * lat.to_radians().sin();
+
+    RingSample {
+        t: states[0].pos().t(),
+        area,
+        circulation,
+        rel_vorticity,
+        abs_vorticity: rel_vorticity + coriolis_param,
+        divergence: 0.0,
+        rel_angular_momentum: angular_momentum,
+        abs_angular_momentum: angular_momentum + 0.5 * coriolis_param * moment_of_inertia,
+    }
+}
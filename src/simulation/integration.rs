@@ -0,0 +1,110 @@
+use std::fmt;
+
+use nalgebra::SVector;
+use numeric_algs::{
+    integration::{Integrator, RK4Integrator, StepSize},
+    State,
+};
+use serde::{Deserialize, Serialize};
+
+use super::Object;
+
+/// The numerical method used for advancing the objects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntegrationMethod {
+    #[default]
+    RungeKutta4,
+    /// Velocity Verlet; conserves energy over many orbits, but is only second-order accurate
+    Leapfrog,
+}
+
+impl fmt::Display for IntegrationMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntegrationMethod::RungeKutta4 => write!(f, "Runge-Kutta 4"),
+            IntegrationMethod::Leapfrog => write!(f, "Leapfrog (symplectic)"),
+        }
+    }
+}
+
+impl IntegrationMethod {
+    pub const ALL: [IntegrationMethod; 2] =
+        [IntegrationMethod::RungeKutta4, IntegrationMethod::Leapfrog];
+}
+
+/// The kick-drift-kick leapfrog scheme.
+///
+/// Objects in free flight are integrated in the inertial frame, where the forces don't depend on
+/// the velocity and the scheme is symplectic; the Coriolis force of a rotating frame would make
+/// the energy drift again.
+pub struct Leapfrog {
+    default_step: f64,
+}
+
+impl Leapfrog {
+    pub fn new(default_step: f64) -> Self {
+        Self { default_step }
+    }
+}
+
+impl Integrator<Object> for Leapfrog {
+    fn propagate_in_place<D>(&mut self, start: &mut Object, diff_eq: D, step: StepSize)
+    where
+        D: Fn(&Object) -> SVector<f64, 7>,
+    {
+        let dt = match step {
+            StepSize::UseDefault => self.default_step,
+            StepSize::Step(dt) => dt,
+        };
+        let frame_omega = start.pos().omega();
+        if start.is_free_flight() {
+            start.change_frame(0.0);
+        }
+
+        let kick = |obj: &mut Object, amount: f64| {
+            let mut derivative = diff_eq(obj);
+            for i in [0, 1, 2, 6] {
+                derivative[i] = 0.0;
+            }
+            obj.shift_in_place(&derivative, amount);
+        };
+        kick(start, dt / 2.0);
+        let mut derivative = diff_eq(start);
+        for i in 3..6 {
+            derivative[i] = 0.0;
+        }
+        start.shift_in_place(&derivative, dt);
+        kick(start, dt / 2.0);
+
+        start.change_frame(frame_omega);
+    }
+}
+
+/// Advances objects with the method chosen at the moment
+pub struct ObjectIntegrator {
+    pub method: IntegrationMethod,
+    rk4: RK4Integrator,
+    leapfrog: Leapfrog,
+}
+
+impl ObjectIntegrator {
+    pub fn new(default_step: f64) -> Self {
+        Self {
+            method: IntegrationMethod::default(),
+            rk4: RK4Integrator::new(default_step),
+            leapfrog: Leapfrog::new(default_step),
+        }
+    }
+}
+
+impl Integrator<Object> for ObjectIntegrator {
+    fn propagate_in_place<D>(&mut self, start: &mut Object, diff_eq: D, step: StepSize)
+    where
+        D: Fn(&Object) -> SVector<f64, 7>,
+    {
+        match self.method {
+            IntegrationMethod::RungeKutta4 => self.rk4.propagate_in_place(start, diff_eq, step),
+            IntegrationMethod::Leapfrog => self.leapfrog.propagate_in_place(start, diff_eq, step),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use nalgebra::Vector3;
+
+use super::R_EQU;
+
+/// Magnitude of the Earth's magnetic field at the magnetic equator on the surface, in teslas
+pub const B0: f64 = 3.12e-5;
+
+/// The magnetic field of a dipole aligned with the rotation axis, in teslas. `pos` is in the
+/// Earth's frame; the dipole moment points south, like the Earth's.
+pub fn dipole_field(pos: &Vector3<f64>) -> Vector3<f64> {
+    let r = pos.norm();
+    let r_hat = pos / r;
+    let m_hat = Vector3::new(0.0, -1.0, 0.0);
+    let coeff = B0 * (R_EQU / r).powi(3);
+    coeff * (3.0 * m_hat.dot(&r_hat) * r_hat - m_hat)
+}
+
+/// Points of the dipole field line crossing the magnetic equator at `l_shell` Earth radii, in
+/// the meridian plane at longitude `lon` (degrees), limited to the part above the surface
+pub fn dipole_field_line(l_shell: f64, lon: f64, n_points: usize) -> Vec<Vector3<f64>> {
+    let lon = lon.to_radians();
+    // the field line reaches the surface at the latitude where cos²(lat) = 1/L
+    let max_lat = (1.0 / l_shell).sqrt().acos();
+    (0..=n_points)
+        .map(|i| {
+            let lat = -max_lat + 2.0 * max_lat * i as f64 / n_points as f64;
+            let r = l_shell * R_EQU * lat.cos() * lat.cos();
+            Vector3::new(
+                r * lat.cos() * lon.sin(),
+                r * lat.sin(),
+                r * lat.cos() * lon.cos(),
+            )
+        })
+        .collect()
+}
@@ -1,12 +1,57 @@
+mod aiming;
+mod analytic;
+mod atmosphere;
+mod bench;
+mod constraint;
+mod environment;
+mod frames;
+mod group;
+mod integration;
+mod magnetic;
+mod moon;
 mod object;
+mod orbit;
 mod position;
+mod pressure;
+pub mod rotation;
+mod samples;
+mod spin;
+mod station;
+mod sun;
+mod tracers;
+mod two_body;
 mod velocity;
+mod wind;
 
 use nalgebra::Vector3;
 
-pub use object::Object;
+pub use aiming::{intercept, miss_distance, AimError, Interception, Launcher, Shot};
+pub use analytic::{
+    coriolis_parameter, foucault_rate, horizontal_deflection, inertial_period, inertial_radius,
+};
+pub use atmosphere::{pressure_altitude, Atmosphere};
+pub use bench::{kernels, run_benchmarks, BenchmarkResult, Kernel};
+pub use constraint::{step_linked, Constraint, Link};
+pub use environment::Environment;
+pub use frames::{CelestialFrame, YEAR};
+pub use group::{ring_diagnostics, GroupPath, RingSample};
+pub use integration::{IntegrationMethod, ObjectIntegrator};
+pub use magnetic::{dipole_field, dipole_field_line};
+pub use moon::{Moon, MOON_DISTANCE, MOON_GM, MOON_RADIUS};
+pub use object::{
+    Completion, Energy, Forces, Object, SimState, SurfaceInteraction, MIN_FLIGHT_ELEVATION,
+};
+pub use orbit::{state_from_inertial, OrbitPrediction, OrbitalElements};
 pub use position::Position;
+pub use pressure::PressureSystem;
+pub use samples::Sample;
+pub use spin::{frame_angle, SpinRamp};
+pub use station::GroundStation;
+pub use sun::{Sun, SUN_DISTANCE, SUN_GM};
+pub use tracers::Tracers;
+pub use two_body::TwoBodySystem;
 pub use velocity::Velocity;
+pub use wind::WindField;
 
 /// Earth's angular speed in radians per second
 pub const OMEGA: f64 = 7.29212351699e-5;
@@ -90,6 +135,49 @@ pub fn earth_radius(lat_r_gc: f64) -> f64 {
     1.0 / (x * x + y * y).sqrt()
 }
 
+/// The local east and north directions on the sphere at the point given in the Earth's frame.
+/// At a pole they are those of the meridian of the point, like in the launches from there, so
+/// that they are never undefined.
+pub fn east_north(pos: &Vector3<f64>) -> (Vector3<f64>, Vector3<f64>) {
+    let lon = pos.x.atan2(pos.z);
+    let east = Vector3::new(lon.cos(), 0.0, -lon.sin());
+    let north = pos.normalize().cross(&east);
+    (east, north)
+}
+
+/// Distance along the surface in meters and initial bearing in degrees clockwise from north
+/// of the great circle between two positions in the Earth's frame
+pub fn great_circle(from: &Vector3<f64>, to: &Vector3<f64>) -> (f64, f64) {
+    let lat_gc = |pos: &Vector3<f64>| (pos.y / pos.norm()).asin();
+    let radius = 0.5 * (earth_radius(lat_gc(from)) + earth_radius(lat_gc(to)));
+    let distance = from.angle(to) * radius;
+
+    let (east, north) = east_north(from);
+    let up = from.normalize();
+    let dir = to - up * to.dot(&up);
+    let bearing = dir.dot(&east).atan2(dir.dot(&north)).to_degrees();
+
+    (distance, bearing.rem_euclid(360.0))
+}
+
+/// Splits a ground track into segments wherever it crosses the ±180° meridian, so that the
+/// crossings aren't drawn as lines across the whole map
+pub fn split_at_antimeridian(points: impl Iterator<Item = (f64, f64)>) -> Vec<Vec<(f64, f64)>> {
+    let mut segments: Vec<Vec<(f64, f64)>> = vec![vec![]];
+    for (lon, lat) in points {
+        let segment = segments.last_mut().unwrap();
+        if segment
+            .last()
+            .is_some_and(|(last_lon, _)| (lon - last_lon).abs() > 180.0)
+        {
+            segments.push(vec![(lon, lat)]);
+        } else {
+            segment.push((lon, lat));
+        }
+    }
+    segments
+}
+
 pub fn surface_normal(pos: &Vector3<f64>) -> Vector3<f64> {
     let v = Vector3::new(
         pos.x / R_EQU / R_EQU,
@@ -108,6 +196,8 @@ pub fn r_curv(pos: &Vector3<f64>) -> f64 {
     coeff * coeff * coeff / R_EQU / R_POL
 }
 
-pub fn air_density(elev: f64) -> f64 {
-    1.225 * (-0.000125 * elev).exp()
+/// Amplitude of the equilibrium tide raised by a body, in meters: the height of the tidal
+/// bulge is `amplitude * (3 cos²θ - 1) / 2`, where θ is the angle from the sub-body point
+pub fn tide_amplitude(body_gm: f64, distance: f64) -> f64 {
+    body_gm / GM * R_EQU.powi(4) / distance.powi(3)
 }
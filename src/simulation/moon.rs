@@ -0,0 +1,40 @@
+use nalgebra::Vector3;
+
+use super::{CelestialFrame, SpinRamp};
+
+/// The Moon's mass multiplied by G in m³/s²
+pub const MOON_GM: f64 = 4.9048695e12;
+/// Mean distance between the Earth and the Moon in meters
+pub const MOON_DISTANCE: f64 = 384_400e3;
+/// The Moon's radius in meters
+pub const MOON_RADIUS: f64 = 1_737.4e3;
+/// Sidereal orbital period of the Moon in seconds
+pub const MOON_PERIOD: f64 = 27.321661 * 86400.0;
+
+/// A simple model of the Moon: a circular orbit in the plane of the ecliptic
+#[derive(Debug, Clone, Copy)]
+pub struct Moon {
+    /// Ecliptic longitude of the Moon at t=0 in degrees
+    pub ecliptic_lon: f64,
+}
+
+impl Default for Moon {
+    fn default() -> Self {
+        Self { ecliptic_lon: 90.0 }
+    }
+}
+
+impl Moon {
+    /// Unit vector pointing towards the Moon at time `t` in the frame rotating with `omega`, with
+    /// the Earth's rotation following `spin`
+    pub fn direction(
+        &self,
+        t: f64,
+        omega: f64,
+        spin: Option<SpinRamp>,
+        frame: &CelestialFrame,
+    ) -> Vector3<f64> {
+        let lon = self.ecliptic_lon + 360.0 * t / MOON_PERIOD;
+        frame.ecliptic_to_rotating(t, omega, spin) * CelestialFrame::ecliptic_direction(lon, 0.0)
+    }
+}
@@ -1,6 +1,5 @@
-use std::{collections::VecDeque, iter, rc::Rc};
+use std::{collections::VecDeque, iter, mem, sync::Arc};
 
-use glium::uniform;
 use nalgebra::{Matrix4, SVector, Vector3};
 use numeric_algs::{
     integration::{Integrator, StepSize},
@@ -8,17 +7,109 @@ use numeric_algs::{
 };
 
 use super::{
-    air_density, earth_radius, pos_to_lat_lon_elev, r_curv, surface_normal, Position, Velocity, GM,
-    OMEGA,
+    dipole_field, earth_radius, east_north, great_circle, lat_lon_elev_to_vec3, orbit::OrbitClass,
+    pos_to_lat_lon_elev, r_curv, rotation, samples::thin_out, spin::frame_rate, surface_normal,
+    CelestialFrame, Environment, OrbitPrediction, Position, Sample, Sun, TwoBodySystem, Velocity,
+    GM, OMEGA,
+};
+use crate::{
+    renderer::Painter,
+    state::{ColorMode, PathColorMode, RenderSettings, TrailFrames},
 };
-use crate::{renderer::Painter, state::RenderSettings};
 
 const MAX_PATH_LEN: usize = 50000;
+/// Maximum change of the direction of motion along a single drawn path segment, in radians
+const MAX_SEGMENT_TURN: f64 = 0.05;
+/// Maximum number of segments a path between two consecutive samples is split into
+const MAX_SUBDIVISIONS: usize = 16;
+/// Size of the markers of the apsides relative to the object's marker
+const APSIS_MARKER_SCALE: f32 = 0.4;
+/// Objects that never got higher than this (in meters) aren't considered to have flown
+pub const MIN_FLIGHT_ELEVATION: f64 = 1.0;
+/// Simulated time between the first checkpoints of an object, in seconds; it doubles every
+/// time they are thinned out
+const CHECKPOINT_INTERVAL: f64 = 600.0;
+/// Most checkpoints kept per object; when there would be more, every other one is dropped
+const MAX_CHECKPOINTS: usize = 64;
+/// The largest ageostrophic acceleration, relative to the Coriolis force, at which the motion
+/// still counts as geostrophic
+const GEOSTROPHIC_TOLERANCE: f64 = 0.1;
+
+/// An additional force depending on the position; `Send` so that objects can be integrated on
+/// background threads
+type Attractor = dyn Fn(Position) -> Vector3<f64> + Send + Sync;
 
 #[derive(Debug, Clone, Copy)]
 enum ObjectState {
     FreeFlight,
     ConstantAltitude(f64),
+    /// Fixed to the Earth's surface
+    Anchored,
+}
+
+/// What happens to an object in free flight when it hits the surface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceInteraction {
+    /// Keeps sliding along the surface
+    Stick,
+    /// Bounces off, the vertical velocity reversed and multiplied by the restitution
+    /// coefficient
+    Bounce { restitution: f64 },
+    /// Comes to rest where it hit the surface
+    Stop,
+}
+
+/// When an object is done and is taken out of the simulation, so that the finished ones don't
+/// keep being stepped
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Completion {
+    /// Remove the object this many seconds after it came to the ground
+    pub after_landing: Option<f64>,
+    /// Remove the object once it gets higher than this elevation, in meters
+    pub max_elevation: Option<f64>,
+    /// Keep drawing the trail of the removed object
+    pub keep_trail: bool,
+}
+
+/// Bounces slower than this (in m/s) end the bouncing, so that it doesn't go on indefinitely
+const MIN_BOUNCE_SPEED: f64 = 0.5;
+
+/// Objects further than this from the Earth's center (in meters, about 100 AU) are considered
+/// to have been flung away by a numerical blow-up
+const MAX_SANE_DISTANCE: f64 = 1.5e13;
+/// Objects faster than this (in m/s, the speed of light) are considered to have blown up
+const MAX_SANE_SPEED: f64 = 3e8;
+
+/// The integration of an object produced an impossible state, e.g. NaN or a speed above the
+/// speed of light
+#[derive(Debug, Clone, Copy)]
+pub struct Blowup {
+    /// The time of the last valid state, where the object was stopped
+    pub t: f64,
+    /// The term of the acceleration that was non-finite or the largest in that state
+    pub term: &'static str,
+    /// Its magnitude, in m/s²
+    pub magnitude: f64,
+}
+
+impl Blowup {
+    pub fn describe(&self) -> String {
+        format!(
+            "the {} term was {:.3e} m/s² at t = {:.0} s",
+            self.term, self.magnitude, self.t
+        )
+    }
+}
+
+/// The changing part of the state of an object, from which the integration continues exactly
+/// as it went the first time
+#[derive(Debug, Clone, Copy)]
+struct Checkpoint {
+    sim_state: SimState,
+    state: ObjectState,
+    landed_at: Option<f64>,
+    first_impact: Option<(f64, Vector3<f64>)>,
+    link_acceleration: Vector3<f64>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -36,33 +127,269 @@ impl SimState {
         &self.vel
     }
 
-    fn coriolis_counteraction(&self) -> Vector3<f64> {
-        let pos = self.pos.to_omega(OMEGA);
-        let vel = self.vel.to_omega(self.pos, OMEGA);
+    /// The states from this one up to the `next` one (without it), with interpolated states
+    /// inserted where the direction of motion changes noticeably, so that curved paths don't
+    /// look polygonal with large time steps
+    pub fn smooth_segment(&self, next: &SimState) -> Vec<SimState> {
+        let turn = self.vel.vel().angle(&next.vel.vel());
+        let subdivisions = if turn.is_finite() {
+            ((turn / MAX_SEGMENT_TURN).ceil() as usize).clamp(1, MAX_SUBDIVISIONS)
+        } else {
+            1
+        };
+        let dt = next.pos.t() - self.pos.t();
+        iter::once(*self)
+            .chain((1..subdivisions).map(|i| {
+                let t = self.pos.t() + dt * i as f64 / subdivisions as f64;
+                self.interpolate(next, t)
+            }))
+            .collect()
+    }
 
-        let up = surface_normal(&pos.pos());
-        let coriolis = vel.coriolis();
+    /// The state at the time `t` between this state and the `next` one, from cubic Hermite
+    /// interpolation of the positions with the velocities as tangents
+    pub fn interpolate(&self, next: &SimState, t: f64) -> SimState {
+        let dt = next.pos.t() - self.pos.t();
+        if dt <= 0.0 {
+            return *self;
+        }
+        let s = ((t - self.pos.t()) / dt).clamp(0.0, 1.0);
+        let (p0, p1) = (self.pos.pos(), next.pos.pos());
+        let (v0, v1) = (self.vel.vel(), next.vel.vel());
+
+        let h00 = 2.0 * s * s * s - 3.0 * s * s + 1.0;
+        let h10 = s * s * s - 2.0 * s * s + s;
+        let h01 = -2.0 * s * s * s + 3.0 * s * s;
+        let h11 = s * s * s - s * s;
+        let pos = h00 * p0 + h10 * dt * v0 + h01 * p1 + h11 * dt * v1;
+
+        // derivatives of the basis functions with respect to s
+        let d00 = 6.0 * s * s - 6.0 * s;
+        let d10 = 3.0 * s * s - 4.0 * s + 1.0;
+        let d11 = 3.0 * s * s - 2.0 * s;
+        let vel = (d00 * (p0 - p1)) / dt + d10 * v0 + d11 * v1;
+
+        let mut result = *self;
+        result.pos.increase(pos - p0);
+        result.pos.increase_time(s * dt);
+        result.vel.increase(vel - v0);
+        result
+    }
+
+    /// Computes the quantities shared by the force terms; the elevation requires an iterative
+    /// solve, so it is only computed if asked for
+    fn context<'a>(&self, env: &'a Environment, with_elevation: bool) -> ForceContext<'a> {
+        let pos_earth = self.pos.to_omega(OMEGA);
+        let vel_earth = self.vel.to_omega(self.pos, OMEGA);
+        let t = self.pos.t();
+        let o = frame_rate(env.spin, OMEGA, t) - frame_rate(env.spin, self.pos.omega(), t);
+        ForceContext {
+            env,
+            pos_earth,
+            vel_earth,
+            vel: self.vel.to_omega(self.pos, self.pos.omega()).vel(),
+            surf_vel: Vector3::new(o * self.pos.pos().z, 0.0, -o * self.pos.pos().x),
+            elev: with_elevation.then(|| pos_to_lat_lon_elev(pos_earth.pos()).2),
+        }
+    }
+
+    fn coriolis_counteraction(&self, ctx: &ForceContext) -> Vector3<f64> {
+        let up = surface_normal(&ctx.pos_earth.pos());
+        let coriolis = ctx.vel_earth.coriolis(&ctx.pos_earth);
         let horizontal_coriolis = coriolis - up * up.dot(&coriolis);
 
-        pos.dir_to_omega(-horizontal_coriolis, self.pos.omega())
+        ctx.pos_earth
+            .dir_to_omega(-horizontal_coriolis, self.pos.omega())
     }
 
-    fn friction(&self, friction: f64) -> Vector3<f64> {
-        let o = OMEGA - self.pos.omega();
-        let vel = self.vel.to_omega(self.pos, self.pos.omega()).vel();
-        let surf_vel = Vector3::new(o * self.pos.pos().z, 0.0, -o * self.pos.pos().x);
-        friction * (surf_vel - vel)
+    fn friction(&self, ctx: &ForceContext, friction: f64) -> Vector3<f64> {
+        friction * (ctx.surf_vel - ctx.vel)
     }
 
-    fn drag(&self, drag_coeff: f64) -> Vector3<f64> {
-        let o = OMEGA - self.pos.omega();
-        let (_, _, elev) = pos_to_lat_lon_elev(self.pos.to_omega(OMEGA).pos());
-        let density = air_density(elev);
-        let vel = self.vel.to_omega(self.pos, self.pos.omega()).vel();
-        let surf_vel = Vector3::new(o * self.pos.pos().z, 0.0, -o * self.pos.pos().x);
-        let vel_diff = surf_vel - vel;
+    /// Lorentz force per unit mass from the geomagnetic field. The field is static in the
+    /// Earth's frame, so there is no electric field there and only the velocity relative to
+    /// the Earth matters.
+    fn lorentz(&self, ctx: &ForceContext, charge_to_mass: f64) -> Vector3<f64> {
+        if charge_to_mass == 0.0 {
+            return Vector3::zeros();
+        }
+        let pos = ctx.pos_earth.pos();
+        let force = charge_to_mass * ctx.vel_earth.vel().cross(&dipole_field(&pos));
+        ctx.pos_earth.dir_to_omega(force, self.pos.omega())
+    }
+
+    /// Gravity of the other body of a two-body system, including the acceleration of the
+    /// Earth towards it
+    fn third_body(&self, system: Option<TwoBodySystem>) -> Vector3<f64> {
+        let system = match system {
+            Some(system) => system,
+            None => return Vector3::zeros(),
+        };
+        let pos = self.pos.to_omega(system.mean_motion());
+        let force = system.perturbation(&pos.pos());
+        pos.dir_to_omega(force, self.pos.omega())
+    }
+
+    fn drag(&self, ctx: &ForceContext, drag_coeff: f64) -> Vector3<f64> {
+        let elev = match ctx.elev {
+            Some(elev) if drag_coeff != 0.0 => elev,
+            _ => return Vector3::zeros(),
+        };
+        let density = ctx.env.air_density(elev);
+        let mut vel_diff = ctx.surf_vel - ctx.vel;
+        // the air moves with the wind relative to the surface
+        if let Some(wind) = ctx
+            .env
+            .wind_velocity(&ctx.pos_earth.pos(), ctx.pos_earth.t())
+        {
+            vel_diff += ctx.pos_earth.dir_to_omega(wind, self.pos.omega());
+        }
         drag_coeff * density * vel_diff.norm() * vel_diff
     }
+
+    /// The upthrust of the displaced air per unit mass, opposite to the effective gravity;
+    /// `volume_per_mass` is the volume of the object divided by its mass, in m³/kg
+    fn buoyancy(&self, ctx: &ForceContext, volume_per_mass: f64, gm: f64) -> Vector3<f64> {
+        let elev = match ctx.elev {
+            Some(elev) if volume_per_mass != 0.0 => elev,
+            _ => return Vector3::zeros(),
+        };
+        let gravity = ctx.pos_earth.grav(gm) + ctx.pos_earth.centrifugal();
+        let force = -ctx.env.air_density(elev) * volume_per_mass * gravity;
+        ctx.pos_earth.dir_to_omega(force, self.pos.omega())
+    }
+
+    /// The push of the pressure systems at the elevation `elev`, in the object's own frame
+    fn pressure_gradient(&self, ctx: &ForceContext, elev: f64) -> Vector3<f64> {
+        match ctx.env.pressure_gradient(&ctx.pos_earth.pos(), elev) {
+            Some(acc) => ctx.pos_earth.dir_to_omega(acc, self.pos.omega()),
+            None => Vector3::zeros(),
+        }
+    }
+}
+
+/// Quantities used by several force terms within a single evaluation of the derivative
+struct ForceContext<'a> {
+    env: &'a Environment,
+    pos_earth: Position,
+    vel_earth: Velocity,
+    /// Velocity in the object's own frame
+    vel: Vector3<f64>,
+    /// Velocity of the Earth's surface at the object's position, in the object's own frame
+    surf_vel: Vector3<f64>,
+    elev: Option<f64>,
+}
+
+/// Specific mechanical energy of an object in the Earth's rotating frame, in J/kg
+#[derive(Debug, Clone, Copy)]
+pub struct Energy {
+    pub t: f64,
+    pub kinetic: f64,
+    pub gravitational: f64,
+    pub rotational: f64,
+}
+
+impl Energy {
+    pub fn total(&self) -> f64 {
+        self.kinetic + self.gravitational + self.rotational
+    }
+}
+
+/// Which turning point of the distance from the Earth's center an apsis is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApsisKind {
+    /// The highest point of a suborbital flight
+    Apex,
+    Apoapsis,
+    Periapsis,
+}
+
+/// A turning point of the distance of a freely moving object from the Earth's center
+#[derive(Debug, Clone, Copy)]
+pub struct Apsis {
+    pub kind: ApsisKind,
+    pub state: SimState,
+}
+
+/// Breakdown of the acceleration of an object in the Earth's frame at a single moment, in m/s²
+#[derive(Debug, Clone, Copy)]
+pub struct Forces {
+    pub t: f64,
+    pub gravity: Vector3<f64>,
+    pub centrifugal: Vector3<f64>,
+    pub coriolis: Vector3<f64>,
+    /// Present while the rotation of the Earth speeds up or slows down
+    pub euler: Vector3<f64>,
+    /// Everything else: drag, friction, the ground reaction, other bodies etc.
+    pub other: Vector3<f64>,
+}
+
+/// A velocity or force arrow drawn at an object
+#[derive(Debug, Clone, Copy)]
+pub struct Arrow {
+    /// The point the arrow starts at, in the frame of the view
+    pub origin: Vector3<f64>,
+    /// The arrow as drawn, i.e. scaled for display
+    pub vector: Vector3<f64>,
+    /// The actual magnitude of the velocity or the acceleration, in m/s or m/s²
+    pub magnitude: f64,
+    pub unit: &'static str,
+    pub color: [f32; 3],
+}
+
+/// Totals over the path of an object in the Earth's frame, for comparing it with the straight
+/// route between its ends
+#[derive(Debug, Clone, Copy)]
+pub struct PathStatistics {
+    /// Distance traveled in 3D, in meters
+    pub length: f64,
+    /// Length of the ground track, in meters
+    pub ground_length: f64,
+    /// Distance along the great circle between the ends of the path, in meters
+    pub straight_distance: f64,
+    /// Average speed along the path, in m/s
+    pub average_speed: f64,
+    /// Sum of the changes of the direction of motion, in degrees
+    pub turning: f64,
+}
+
+impl PathStatistics {
+    /// How much longer the ground track is than the great circle, in percent
+    pub fn excess_length(&self) -> Option<f64> {
+        (self.straight_distance > 0.0)
+            .then(|| (self.ground_length / self.straight_distance - 1.0) * 100.0)
+    }
+}
+
+/// Magnitudes of the accelerations of an object in the Earth's frame at a single moment, in
+/// m/s², for comparing their sizes
+#[derive(Debug, Clone, Copy)]
+pub struct ForceMagnitudes {
+    pub gravity: f64,
+    pub centrifugal: f64,
+    pub coriolis: f64,
+    pub drag: f64,
+    pub friction: f64,
+    pub pressure_gradient: f64,
+    pub buoyancy: f64,
+}
+
+/// Contributions to the apparent weight per unit mass of an object on the surface, in m/s²,
+/// as components along the local vertical (positive = downwards)
+#[derive(Debug, Clone, Copy)]
+pub struct WeightTerms {
+    pub gravity: f64,
+    pub centrifugal: f64,
+    /// The vertical component of the Coriolis force
+    pub eotvos: f64,
+    /// The centrifugal effect of moving along the curved surface
+    pub curvature: f64,
+}
+
+impl WeightTerms {
+    pub fn total(&self) -> f64 {
+        self.gravity + self.centrifugal + self.eotvos + self.curvature
+    }
 }
 
 #[derive(Clone)]
@@ -71,12 +398,52 @@ pub struct Object {
     color: (f32, f32, f32),
     radius: f32,
     path: VecDeque<SimState>,
+    /// The forces acting in each of the states in `path`
+    path_forces: VecDeque<Forces>,
+    /// Periodic copies of the state, for rewinding
+    checkpoints: Vec<Checkpoint>,
+    checkpoint_interval: f64,
     gm: f64,
     drag_coeff: f64,
     friction: f64,
-    attractor: Option<Rc<Box<dyn Fn(Position) -> Vector3<f64>>>>,
+    charge_to_mass: f64,
+    /// The volume of the air displaced per kilogram of the object, in m³/kg, for the buoyancy;
+    /// 0 for objects much denser than the air
+    volume_per_mass: f64,
+    attractor: Option<Arc<Attractor>>,
+    /// The acceleration from the springs attached to the object during the current step, in
+    /// the inertial frame
+    link_acceleration: Vector3<f64>,
     counteract_coriolis: bool,
     state: ObjectState,
+    group: usize,
+    /// Exaggeration factor for drawing the plumb-line deviation, if the object is a plumb bob
+    plumb_line: Option<f64>,
+    weighing_scale: bool,
+    third_body: Option<TwoBodySystem>,
+    /// When the object hit the ground after having been in free flight, for rewinding
+    landed_at: Option<f64>,
+    surface_interaction: SurfaceInteraction,
+    name: Option<String>,
+    trail_frames: TrailFrames,
+    /// How far back the trail reaches, in seconds; the whole path if `None`
+    trail_duration: Option<f64>,
+    /// Fade the trail out towards its tail
+    trail_fade: bool,
+    /// Hidden by the user to declutter the scene; still simulated
+    hidden: bool,
+    path_hidden: bool,
+    /// Set when the integration blew up; the object is stopped in its last valid state
+    blowup: Option<Blowup>,
+    start_time: f64,
+    completion: Completion,
+    /// The direction the object was fired in, in degrees clockwise from north, and where from
+    /// (in the Earth's frame), for measuring how far it lands off that line
+    launch: Option<(f64, Vector3<f64>)>,
+    /// The time and the position in the Earth's frame of the first landing
+    first_impact: Option<(f64, Vector3<f64>)>,
+    /// Shared with the rest of the simulation
+    environment: Arc<Environment>,
 }
 
 impl Object {
@@ -86,12 +453,36 @@ impl Object {
             color: (1.0, 0.0, 0.0),
             radius: 200e3,
             path: VecDeque::new(),
+            path_forces: VecDeque::new(),
+            checkpoints: vec![],
+            checkpoint_interval: CHECKPOINT_INTERVAL,
             gm: GM,
             drag_coeff: 0.0,
             friction: 0.0,
+            charge_to_mass: 0.0,
+            volume_per_mass: 0.0,
             attractor: None,
+            link_acceleration: Vector3::zeros(),
             counteract_coriolis: false,
             state: ObjectState::FreeFlight,
+            group: 0,
+            plumb_line: None,
+            weighing_scale: false,
+            third_body: None,
+            landed_at: None,
+            surface_interaction: SurfaceInteraction::Stick,
+            name: None,
+            trail_frames: Default::default(),
+            trail_duration: None,
+            trail_fade: false,
+            hidden: false,
+            path_hidden: false,
+            blowup: None,
+            start_time: pos.t(),
+            completion: Default::default(),
+            launch: None,
+            first_impact: None,
+            environment: Default::default(),
         }
     }
 
@@ -121,6 +512,79 @@ impl Object {
         Self { friction, ..self }
     }
 
+    /// Makes the object a plumb bob fixed to the Earth, drawing the radial and effective gravity
+    /// directions with the deviation between them exaggerated by `exaggeration`
+    pub fn with_plumb_line(self, exaggeration: f64) -> Self {
+        Self {
+            state: ObjectState::Anchored,
+            plumb_line: Some(exaggeration),
+            ..self
+        }
+    }
+
+    pub fn with_weighing_scale(self) -> Self {
+        Self {
+            weighing_scale: true,
+            ..self
+        }
+    }
+
+    pub fn is_weighing_scale(&self) -> bool {
+        self.weighing_scale
+    }
+
+    pub fn with_charge(self, charge_to_mass: f64) -> Self {
+        Self {
+            charge_to_mass,
+            ..self
+        }
+    }
+
+    /// Makes the object buoyant, with `volume` m³ of it displacing the air and the total mass
+    /// of `mass` kg
+    pub fn with_buoyancy(self, volume: f64, mass: f64) -> Self {
+        Self {
+            volume_per_mass: volume / mass,
+            ..self
+        }
+    }
+
+    /// Starts the object's clock at the time `t` instead of 0. The position and velocity stay
+    /// the same in the object's frame, so an object given in the Earth's frame starts from the
+    /// same place on the Earth, whatever the time.
+    pub fn with_start_time(mut self, t: f64) -> Self {
+        self.sim_state.pos.increase_time(t - self.time());
+        self.start_time = t;
+        self
+    }
+
+    /// Marks the object as fired from its current position in the direction `azimuth`
+    pub fn with_launch_azimuth(self, azimuth: f64) -> Self {
+        let launch_pos = self.pos().to_omega(OMEGA).pos();
+        Self {
+            launch: Some((azimuth, launch_pos)),
+            ..self
+        }
+    }
+
+    pub fn with_completion(self, completion: Completion) -> Self {
+        Self { completion, ..self }
+    }
+
+    pub fn with_environment(mut self, environment: Arc<Environment>) -> Self {
+        self.set_environment(environment);
+        self
+    }
+
+    /// Moves the object into another environment, e.g. when the wind changes during the run
+    pub fn set_environment(&mut self, environment: Arc<Environment>) {
+        let spin = environment.spin;
+        for state in iter::once(&mut self.sim_state).chain(&mut self.path) {
+            state.pos = state.pos.with_spin(spin);
+        }
+        self.environment = environment;
+    }
+
     pub fn with_const_alt(self, alt: f64) -> Self {
         Self {
             state: ObjectState::ConstantAltitude(alt),
@@ -130,18 +594,18 @@ impl Object {
 
     pub fn as_pendulum(self, coeff: f64) -> Self {
         let pos0 = self.sim_state.pos;
-        let boxed_closure: Box<dyn Fn(Position) -> Vector3<f64>> =
+        let boxed_closure: Box<Attractor> =
             Box::new(move |pos: Position| coeff * (pos0.to_omega(pos.omega()).pos() - pos.pos()));
-        let attractor = Rc::new(boxed_closure);
+        let attractor = Arc::from(boxed_closure);
         Self {
             attractor: Some(attractor),
             ..self
         }
     }
 
-    pub fn with_attractor(self, attractor: Box<dyn Fn(Position) -> Vector3<f64>>) -> Self {
+    pub fn with_attractor(self, attractor: Box<Attractor>) -> Self {
         Self {
-            attractor: Some(Rc::new(attractor)),
+            attractor: Some(Arc::from(attractor)),
             ..self
         }
     }
@@ -153,10 +617,255 @@ impl Object {
         }
     }
 
+    pub fn with_third_body(self, system: Option<TwoBodySystem>) -> Self {
+        Self {
+            third_body: system,
+            ..self
+        }
+    }
+
+    pub fn with_surface_interaction(self, surface_interaction: SurfaceInteraction) -> Self {
+        Self {
+            surface_interaction,
+            ..self
+        }
+    }
+
+    pub fn with_group(self, group: usize) -> Self {
+        Self { group, ..self }
+    }
+
+    /// Sets the name of the object; an empty name leaves it anonymous
+    pub fn with_name(self, name: &str) -> Self {
+        Self {
+            name: (!name.is_empty()).then(|| name.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_trail_frames(self, trail_frames: TrailFrames) -> Self {
+        Self {
+            trail_frames,
+            ..self
+        }
+    }
+
+    /// Limits the trail to the last `duration` seconds of the path, optionally fading it out
+    /// towards its tail
+    pub fn with_trail_length(self, duration: Option<f64>, fade: bool) -> Self {
+        Self {
+            trail_duration: duration,
+            trail_fade: fade,
+            ..self
+        }
+    }
+
+    /// Hides the object or only its path, as remembered in its description
+    pub fn with_visibility(self, hidden: bool, path_hidden: bool) -> Self {
+        Self {
+            hidden,
+            path_hidden,
+            ..self
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub fn is_path_hidden(&self) -> bool {
+        self.path_hidden
+    }
+
+    pub fn set_path_hidden(&mut self, path_hidden: bool) {
+        self.path_hidden = path_hidden;
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The name of the object, or a generic label with its index
+    pub fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("Object {}", index),
+        }
+    }
+
+    /// Whether the object is done according to its completion rules
+    pub fn is_finished(&self) -> bool {
+        let landed = self
+            .completion
+            .after_landing
+            .zip(self.landed_at)
+            .is_some_and(|(delay, landed_at)| self.time() >= landed_at + delay);
+        let escaped = self.completion.max_elevation.is_some_and(|max_elevation| {
+            pos_to_lat_lon_elev(self.pos().to_omega(OMEGA).pos()).2 > max_elevation
+        });
+        landed || escaped
+    }
+
+    /// Whether the trail is drawn after the object is removed
+    pub fn keeps_trail(&self) -> bool {
+        self.completion.keep_trail
+    }
+
+    /// Whether the object moved freely at the time `t`, pulled only by gravity and maybe slowed
+    /// by the air, so that its trajectory can be described as a (perturbed) orbit
+    fn is_ballistic(&self, t: f64) -> bool {
+        let in_flight = matches!(self.state, ObjectState::FreeFlight)
+            || self.landed_at.is_some_and(|landed_at| t < landed_at);
+        in_flight
+            && self.attractor.is_none()
+            && !self.counteract_coriolis
+            && self.plumb_line.is_none()
+            && self.gm > 0.0
+    }
+
+    /// The orbit through the given state if the object moves freely
+    pub fn orbit_prediction(&self, state: &SimState) -> Option<OrbitPrediction> {
+        if !self.is_ballistic(state.pos.t()) {
+            return None;
+        }
+        let pos = state.pos.to_omega(0.0);
+        let vel = state.vel.to_omega(state.pos, 0.0);
+        Some(OrbitPrediction::new(&pos.pos(), &vel.vel(), self.gm))
+    }
+
+    /// The apsides of the free flight up to the time `max_t`, at the stored samples
+    pub fn apsides(&self, max_t: f64) -> Vec<Apsis> {
+        let states: Vec<_> = self.states_until(max_t).collect();
+        let mut apsides = vec![];
+        for window in states.windows(3) {
+            let [prev, state, next] = [window[0], window[1], window[2]];
+            if !self.is_ballistic(state.pos.t()) {
+                continue;
+            }
+            let r = |state: &SimState| state.pos.pos().norm();
+            let is_max = r(&state) > r(&prev) && r(&state) >= r(&next);
+            let is_min = r(&state) < r(&prev) && r(&state) <= r(&next);
+            if !is_max && !is_min {
+                continue;
+            }
+            let suborbital = self
+                .orbit_prediction(&state)
+                .is_some_and(|orbit| orbit.class == OrbitClass::Suborbital);
+            let kind = match (is_max, suborbital) {
+                (true, true) => ApsisKind::Apex,
+                (true, false) => ApsisKind::Apoapsis,
+                (false, false) => ApsisKind::Periapsis,
+                // the lowest point of a suborbital flight is where it hits the ground
+                (false, true) => continue,
+            };
+            apsides.push(Apsis { kind, state });
+        }
+        apsides
+    }
+
+    /// The length of the path up to the time `max_t` and the related totals; `None` if the
+    /// object hasn't moved yet
+    pub fn path_statistics(&self, max_t: f64) -> Option<PathStatistics> {
+        let states: Vec<_> = self
+            .states_until(max_t)
+            .chain(iter::once(self.state_at(max_t)))
+            .map(|state| {
+                (
+                    state.pos.t(),
+                    state.pos.to_omega(OMEGA).pos(),
+                    state.vel.to_omega(state.pos, OMEGA).vel(),
+                )
+            })
+            .collect();
+        let (t_start, start, _) = *states.first()?;
+        let (t_end, end, _) = *states.last()?;
+        if t_end <= t_start {
+            return None;
+        }
+
+        let mut length = 0.0;
+        let mut ground_length = 0.0;
+        let mut turning = 0.0;
+        for ((_, pos1, vel1), (_, pos2, vel2)) in states.iter().zip(&states[1..]) {
+            length += (pos2 - pos1).norm();
+            ground_length += great_circle(pos1, pos2).0;
+            if vel1.norm() > 0.0 && vel2.norm() > 0.0 {
+                turning += vel1.angle(vel2).to_degrees();
+            }
+        }
+
+        Some(PathStatistics {
+            length,
+            ground_length,
+            straight_distance: great_circle(&start, &end).0,
+            average_speed: length / (t_end - t_start),
+            turning,
+        })
+    }
+
+    /// The highest elevation reached in free flight up to the time `max_t` with its time, and
+    /// the distance along the surface from the starting point to the position at `max_t`, all
+    /// in meters; `None` if the object never flew
+    pub fn flight_summary(&self, max_t: f64) -> Option<(f64, f64, f64)> {
+        let (max_elev, t_max_elev) = self
+            .states_until(max_t)
+            .filter(|state| self.is_ballistic(state.pos.t()))
+            .map(|state| {
+                let (_, _, elev) = pos_to_lat_lon_elev(state.pos.to_omega(OMEGA).pos());
+                (elev, state.pos.t())
+            })
+            .max_by(|(elev1, _), (elev2, _)| elev1.total_cmp(elev2))?;
+        if max_elev < MIN_FLIGHT_ELEVATION {
+            return None;
+        }
+
+        let start = self.states_until(0.0).next()?.pos.to_omega(OMEGA).pos();
+        let end = self.state_at(max_t).pos.to_omega(OMEGA).pos();
+        let (downrange, _) = great_circle(&start, &end);
+
+        Some((max_elev, t_max_elev, downrange))
+    }
+
+    /// For an object fired in a given direction that landed by the time `max_t`: the flight
+    /// time in seconds, the distance of the impact from the launch point along the surface and
+    /// how far the impact is to the right of the line of fire, in meters
+    pub fn impact_summary(&self, max_t: f64) -> Option<(f64, f64, f64)> {
+        let (azimuth, launch) = self.launch?;
+        let azimuth = azimuth.to_radians();
+        let (landed_at, impact) = self.first_impact.filter(|(t, _)| *t <= max_t)?;
+        let (range, _) = great_circle(&launch, &impact);
+
+        // the line of fire is the great circle leaving the launch point in the azimuth
+        let up = launch.normalize();
+        let (east, north) = east_north(&launch);
+        let left = up.cross(&(north * azimuth.cos() + east * azimuth.sin()));
+        let deflection = -impact.normalize().dot(&left).asin() * impact.norm();
+
+        Some((landed_at - self.start_time, range, deflection))
+    }
+
+    /// The time of the last landing after a free flight, if any
+    pub fn landed_at(&self) -> Option<f64> {
+        self.landed_at
+    }
+
+    pub fn group(&self) -> usize {
+        self.group
+    }
+
     pub fn time(&self) -> f64 {
         self.sim_state.pos.t()
     }
 
+    /// The time the object was started at; its oldest states may have been dropped since
+    pub fn start_time(&self) -> f64 {
+        self.start_time
+    }
+
     pub fn pos(&self) -> Position {
         self.sim_state.pos
     }
@@ -165,10 +874,73 @@ impl Object {
         self.sim_state.vel
     }
 
+    pub(super) fn is_free_flight(&self) -> bool {
+        matches!(self.state, ObjectState::FreeFlight)
+    }
+
+    /// Expresses the current state in the frame rotating with `omega`
+    pub(super) fn change_frame(&mut self, omega: f64) {
+        self.sim_state.vel = self.sim_state.vel.to_omega(self.sim_state.pos, omega);
+        self.sim_state.pos = self.sim_state.pos.to_omega(omega);
+    }
+
+    /// The position and velocity in the inertial frame
+    pub(super) fn inertial_state(&self) -> (Vector3<f64>, Vector3<f64>) {
+        let pos = self.pos().to_omega(0.0);
+        (pos.pos(), self.vel().to_omega(self.pos(), 0.0).vel())
+    }
+
+    /// Moves the object and changes its velocity by vectors given in the inertial frame
+    pub(super) fn shift_inertial(&mut self, dpos: Vector3<f64>, dvel: Vector3<f64>) {
+        let (omega, axis) = (self.pos().omega(), self.pos().axis());
+        let mut pos = self.pos().to_omega(0.0);
+        let mut vel = self.vel().to_omega(self.pos(), 0.0);
+        pos.increase(dpos);
+        vel.increase(dvel);
+        self.sim_state.vel = vel.to_frame(pos, omega, axis);
+        self.sim_state.pos = pos.to_frame(omega, axis);
+    }
+
+    pub(super) fn set_link_acceleration(&mut self, acc: Vector3<f64>) {
+        self.link_acceleration = acc;
+    }
+
+    /// The acceleration from the springs in the object's frame
+    fn link_force(&self) -> Vector3<f64> {
+        let pos = self.pos();
+        rotation::frame_rotation_about(
+            pos.spin(),
+            &Vector3::y_axis(),
+            0.0,
+            &pos.axis(),
+            pos.omega(),
+            pos.t(),
+        ) * self.link_acceleration
+    }
+
+    /// Whether any of the forces acting on the object depends on its elevation
+    fn needs_elevation(&self) -> bool {
+        self.drag_coeff != 0.0 || self.volume_per_mass != 0.0
+    }
+
     fn derivative_inflight(&self) -> SVector<f64, 7> {
-        let drag = self.sim_state.drag(self.drag_coeff);
+        let ctx = self
+            .sim_state
+            .context(&self.environment, self.needs_elevation());
+        let drag = self.sim_state.drag(&ctx, self.drag_coeff);
+        let buoyancy = self.sim_state.buoyancy(&ctx, self.volume_per_mass, self.gm);
+        let lorentz = self.sim_state.lorentz(&ctx, self.charge_to_mass);
+        let third_body = self.sim_state.third_body(self.third_body);
         let vel = self.vel().to_omega(self.pos(), self.pos().omega());
-        let acc = self.pos().grav(self.gm) + self.pos().centrifugal() + vel.coriolis() + drag;
+        let acc = self.pos().grav(self.gm)
+            + self.pos().centrifugal()
+            + vel.coriolis(&self.pos())
+            + self.pos().euler()
+            + drag
+            + buoyancy
+            + lorentz
+            + third_body
+            + self.link_force();
         let vel = vel.vel();
 
         SVector::<f64, 7>::from_column_slice(&[vel.x, vel.y, vel.z, acc.x, acc.y, acc.z, 1.0])
@@ -183,18 +955,28 @@ impl Object {
     }
 
     fn derivative_const_alt(&self, alt: f64) -> SVector<f64, 7> {
+        // the elevation is known, so there's no need to solve for it
+        let ctx = ForceContext {
+            elev: Some(alt),
+            ..self.sim_state.context(&self.environment, false)
+        };
         let vel = self.vel().to_omega(self.pos(), self.pos().omega());
         let coriolis_counteraction = if self.counteract_coriolis {
-            self.sim_state.coriolis_counteraction()
+            self.sim_state.coriolis_counteraction(&ctx)
         } else {
             Vector3::new(0.0, 0.0, 0.0)
         };
         // gravity, centrifugal and reaction from the ground should yield a net force equal to the
         // centripetal force according to the local radius of curvature of the surface
-        let mut acc = vel.coriolis()
-            + self.sim_state.friction(self.friction)
-            + self.sim_state.drag(self.drag_coeff)
+        let mut acc = vel.coriolis(&self.pos())
+            + self.pos().euler()
+            + self.sim_state.friction(&ctx, self.friction)
+            + self.sim_state.drag(&ctx, self.drag_coeff)
+            + self.sim_state.lorentz(&ctx, self.charge_to_mass)
+            + self.sim_state.third_body(self.third_body)
+            + self.sim_state.pressure_gradient(&ctx, alt)
             + self.attraction_force()
+            + self.link_force()
             + coriolis_counteraction;
         let vel = vel.vel();
 
@@ -213,25 +995,191 @@ impl Object {
         match self.state {
             ObjectState::FreeFlight => self.derivative_inflight(),
             ObjectState::ConstantAltitude(alt) => self.derivative_const_alt(alt),
+            ObjectState::Anchored => {
+                SVector::<f64, 7>::from_column_slice(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0])
+            }
         }
     }
 
-    fn color(&self) -> [f32; 3] {
+    pub fn color(&self) -> [f32; 3] {
         [self.color.0, self.color.1, self.color.2]
     }
 
+    /// The radius of the marker sphere in meters
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn initial_sim_state(&self) -> SimState {
+        self.path.front().copied().unwrap_or(self.sim_state)
+    }
+
+    /// The value of the attribute used for automatic coloring, taken at the start of the
+    /// simulation
+    pub fn color_attribute(&self, mode: ColorMode) -> Option<f64> {
+        let state = self.initial_sim_state();
+        match mode {
+            ColorMode::Manual => None,
+            ColorMode::Azimuth => {
+                let (vel_e, vel_n, _) = state.vel.to_east_north_up(state.pos);
+                Some(vel_e.atan2(vel_n).to_degrees().rem_euclid(360.0))
+            }
+            ColorMode::Latitude => {
+                let (lat, _, _) = pos_to_lat_lon_elev(state.pos.to_omega(OMEGA).pos());
+                Some(lat)
+            }
+            ColorMode::Speed => Some(state.vel.to_omega(state.pos, OMEGA).vel().norm()),
+            ColorMode::Group => Some(self.group as f64),
+        }
+    }
+
+    pub fn energy(&self, state: &SimState) -> Energy {
+        let pos = state.pos.to_omega(OMEGA);
+        let vel = state.vel.to_omega(pos, OMEGA).vel();
+        let pos = pos.pos();
+        Energy {
+            t: state.pos.t(),
+            kinetic: 0.5 * vel.norm_squared(),
+            gravitational: -self.gm / pos.norm(),
+            rotational: -0.5 * OMEGA * OMEGA * (pos.x * pos.x + pos.z * pos.z),
+        }
+    }
+
+    /// Energies at at most `max_samples` evenly spaced moments up to the time `max_t`
+    pub fn energy_history(&self, max_t: f64, max_samples: usize) -> Vec<Energy> {
+        let states: Vec<_> = self.states_until(max_t).collect();
+        thin_out(states, max_samples)
+            .iter()
+            .map(|state| self.energy(state))
+            .collect()
+    }
+
+    /// The latitudes and longitudes of at most about `max_samples` evenly spaced states up to
+    /// `max_t`
+    pub fn lat_lon_history(&self, max_t: f64, max_samples: usize) -> Vec<(f64, f64)> {
+        let states: Vec<_> = self.states_until(max_t).collect();
+        thin_out(states, max_samples)
+            .iter()
+            .map(|state| {
+                let (lat, lon, _) = pos_to_lat_lon_elev(state.pos.to_omega(OMEGA).pos());
+                (lat, lon)
+            })
+            .collect()
+    }
+
+    /// The forces acting on the object in its current state
+    pub fn forces(&self) -> Forces {
+        let derivative = self.derivative();
+        let acc = Vector3::from_column_slice(&derivative.as_ref()[3..6]);
+        let pos = self.pos();
+        let vel = self.vel().to_omega(pos, pos.omega());
+        let other = acc - pos.grav(self.gm) - pos.centrifugal() - vel.coriolis(&pos) - pos.euler();
+
+        let pos_earth = pos.to_omega(OMEGA);
+        let vel_earth = self.vel().to_omega(pos, OMEGA);
+        Forces {
+            t: pos.t(),
+            gravity: pos_earth.grav(self.gm),
+            centrifugal: pos_earth.centrifugal(),
+            coriolis: vel_earth.coriolis(&pos_earth),
+            euler: pos_earth.euler(),
+            other: pos.dir_to_omega(other, OMEGA),
+        }
+    }
+
+    /// The forces at at most `max_samples` evenly spaced moments up to the time `max_t`
+    pub fn force_history(&self, max_t: f64, max_samples: usize) -> Vec<Forces> {
+        let forces = self
+            .samples(OMEGA, max_t)
+            .map(|sample| sample.forces)
+            .collect();
+        thin_out(forces, max_samples)
+    }
+
+    /// The samples of the run up to the time `max_t` (but always at least the first one), in
+    /// the frame rotating with `omega`, ending with the current state if it's before `max_t`
+    pub fn samples(&self, omega: f64, max_t: f64) -> impl Iterator<Item = Sample> + '_ {
+        self.path
+            .iter()
+            .zip(&self.path_forces)
+            .map(|(state, forces)| (*state, *forces))
+            .chain(iter::once_with(|| (self.sim_state, self.forces())))
+            .enumerate()
+            .take_while(move |(i, (state, _))| *i == 0 || state.pos.t() < max_t)
+            .map(move |(_, (state, forces))| Sample::new(&state, &forces, omega))
+    }
+
+    /// The current state of the object as a sample in the frame rotating with `omega`
+    pub fn sample(&self, omega: f64) -> Sample {
+        Sample::new(&self.sim_state, &self.forces(), omega)
+    }
+
+    /// Approximate memory taken by the stored path, in bytes
+    pub fn memory_usage(&self) -> usize {
+        self.path.len() * mem::size_of::<SimState>()
+            + self.path_forces.len() * mem::size_of::<Forces>()
+            + self.checkpoints.len() * mem::size_of::<Checkpoint>()
+    }
+
+    /// Drops every other stored sample (keeping the first one), halving the memory taken by the
+    /// path
+    pub fn decimate_path(&mut self) {
+        let mut index = 0;
+        self.path.retain(|_| {
+            index += 1;
+            index % 2 == 1
+        });
+        let mut index = 0;
+        self.path_forces.retain(|_| {
+            index += 1;
+            index % 2 == 1
+        });
+        self.path.shrink_to_fit();
+        self.path_forces.shrink_to_fit();
+    }
+
     pub fn step(&mut self, integrator: &mut impl Integrator<Self>, dt: f64) {
+        if self.blowup.is_some() {
+            return;
+        }
+        self.record_checkpoint();
+        let previous = self.sim_state;
         self.path.push_back(self.sim_state);
+        self.path_forces.push_back(self.forces());
         if self.path.len() > MAX_PATH_LEN {
             let _ = self.path.pop_front();
+            let _ = self.path_forces.pop_front();
         }
         integrator.propagate_in_place(self, Self::derivative, StepSize::Step(dt));
 
+        if !self.is_sane() {
+            let _ = self.path.pop_back();
+            let _ = self.path_forces.pop_back();
+            self.sim_state = previous;
+            self.blowup = Some(self.diagnose_blowup());
+            return;
+        }
+
         let pos = self.pos().to_omega(OMEGA);
         let r = pos.pos().norm();
         let lat_r_gc = (pos.pos().y / r).asin();
         let earth_r = earth_radius(lat_r_gc);
 
+        if matches!(self.state, ObjectState::FreeFlight) && r < earth_r {
+            match self.surface_interaction {
+                SurfaceInteraction::Stick => (),
+                SurfaceInteraction::Bounce { restitution } => {
+                    if self.bounce(restitution, earth_r / r) {
+                        return;
+                    }
+                }
+                SurfaceInteraction::Stop => {
+                    self.stop_on_surface(earth_r / r);
+                    return;
+                }
+            }
+        }
+
         let maybe_target_r = match self.state {
             ObjectState::FreeFlight if r < earth_r => Some(earth_r),
             ObjectState::ConstantAltitude(alt) => Some(earth_r + alt),
@@ -239,6 +1187,9 @@ impl Object {
         };
 
         if let Some(target_r) = maybe_target_r {
+            if matches!(self.state, ObjectState::FreeFlight) {
+                self.record_landing();
+            }
             self.state = ObjectState::ConstantAltitude(target_r - earth_r);
 
             let mut vel = self.vel().to_omega(self.pos(), 0.0);
@@ -256,16 +1207,279 @@ impl Object {
         }
     }
 
-    pub fn last_sim_state(&self, max_t: f64) -> SimState {
+    /// Whether the current state is finite and within the bounds of the physically possible
+    fn is_sane(&self) -> bool {
+        let pos = self.pos().pos();
+        let vel = self.vel().vel();
+        pos.iter().chain(vel.iter()).all(|x| x.is_finite())
+            && self.time().is_finite()
+            && pos.norm() < MAX_SANE_DISTANCE
+            && vel.norm() < MAX_SANE_SPEED
+    }
+
+    /// The terms of the acceleration in the current state, in the object's own frame
+    fn acceleration_terms(&self) -> Vec<(&'static str, Vector3<f64>)> {
+        let alt = match self.state {
+            ObjectState::ConstantAltitude(alt) => Some(alt),
+            _ => None,
+        };
+        let mut ctx = self
+            .sim_state
+            .context(&self.environment, alt.is_none() && self.needs_elevation());
+        ctx.elev = alt.or(ctx.elev);
+        let vel = self.vel().to_omega(self.pos(), self.pos().omega());
+        let mut terms = vec![
+            ("gravity", self.pos().grav(self.gm)),
+            ("centrifugal", self.pos().centrifugal()),
+            ("Coriolis", vel.coriolis(&self.pos())),
+            ("Euler", self.pos().euler()),
+            ("drag", self.sim_state.drag(&ctx, self.drag_coeff)),
+            ("Lorentz", self.sim_state.lorentz(&ctx, self.charge_to_mass)),
+            ("third body", self.sim_state.third_body(self.third_body)),
+            ("spring", self.link_force()),
+        ];
+        if let Some(alt) = alt {
+            terms.push(("friction", self.sim_state.friction(&ctx, self.friction)));
+            terms.push((
+                "pressure gradient",
+                self.sim_state.pressure_gradient(&ctx, alt),
+            ));
+            terms.push(("attraction", self.attraction_force()));
+        } else {
+            terms.push((
+                "buoyancy",
+                self.sim_state.buoyancy(&ctx, self.volume_per_mass, self.gm),
+            ));
+        }
+        terms
+    }
+
+    /// Finds the term of the acceleration responsible for the blow-up in the last valid state:
+    /// a non-finite one, or else the largest one
+    fn diagnose_blowup(&self) -> Blowup {
+        let terms = self.acceleration_terms();
+        let culprit = terms
+            .iter()
+            .find(|(_, acc)| !acc.norm().is_finite())
+            .or_else(|| {
+                terms
+                    .iter()
+                    .max_by(|(_, a), (_, b)| a.norm().total_cmp(&b.norm()))
+            });
+        let (term, acc) = culprit
+            .copied()
+            .unwrap_or(("integration", Vector3::zeros()));
+        Blowup {
+            t: self.time(),
+            term,
+            magnitude: acc.norm(),
+        }
+    }
+
+    pub fn blowup(&self) -> Option<Blowup> {
+        self.blowup
+    }
+
+    /// Moves the object that got below the surface back onto it by scaling its position by
+    /// `scale`, and reflects its vertical velocity relative to the surface; `false` if the
+    /// bounce would be too weak, leaving the object unchanged
+    fn bounce(&mut self, restitution: f64, scale: f64) -> bool {
+        let frame = self.vel().omega();
+        let normal = surface_normal(&self.pos().to_omega(OMEGA).pos());
+        let v_up = self.vel().to_omega(self.pos(), OMEGA).vel().dot(&normal);
+        if -v_up * restitution < MIN_BOUNCE_SPEED {
+            return false;
+        }
+        self.sim_state.pos.mul(scale);
+        let mut vel = self.vel().to_omega(self.pos(), OMEGA);
+        vel.increase(-(1.0 + restitution) * v_up * normal);
+        self.sim_state.vel = vel.to_omega(self.pos(), frame);
+        true
+    }
+
+    /// Puts the object that got below the surface back onto it by scaling its position by
+    /// `scale`, at rest relative to the Earth
+    fn stop_on_surface(&mut self, scale: f64) {
+        let frame = self.vel().omega();
+        self.record_landing();
+        self.sim_state.pos.mul(scale);
+        let mut vel = self.vel().to_omega(self.pos(), OMEGA);
+        vel.increase(-vel.vel());
+        self.sim_state.vel = vel.to_omega(self.pos(), frame);
+        // the anchored objects stay put in their own frame, so that has to be the Earth's
+        self.state = if self.pos().omega() == OMEGA {
+            ObjectState::Anchored
+        } else {
+            ObjectState::ConstantAltitude(0.0)
+        };
+    }
+
+    /// Notes that the object has just come to the ground
+    fn record_landing(&mut self) {
+        self.landed_at = Some(self.time());
+        if self.first_impact.is_none() {
+            self.first_impact = Some((self.time(), self.pos().to_omega(OMEGA).pos()));
+        }
+    }
+
+    /// A copy of the object in its current state, without the stored path
+    pub fn without_history(&self) -> Self {
+        Self {
+            path: VecDeque::new(),
+            path_forces: VecDeque::new(),
+            checkpoints: vec![],
+            attractor: self.attractor.clone(),
+            name: self.name.clone(),
+            environment: self.environment.clone(),
+            ..*self
+        }
+    }
+
+    /// A copy of the object without the stored path, set off from its current position in free
+    /// flight with the velocity `vel` relative to the Earth (in the Earth's frame)
+    pub fn launched(&self, vel: Vector3<f64>) -> Self {
+        let mut earth_vel = self.vel().to_omega(self.pos(), OMEGA);
+        earth_vel.increase(vel - earth_vel.vel());
+        let mut obj = self.without_history();
+        obj.sim_state.vel = earth_vel.to_omega(self.pos(), self.vel().omega());
+        obj.state = ObjectState::FreeFlight;
+        obj.landed_at = None;
+        obj.first_impact = None;
+        obj
+    }
+
+    /// Saves the current state as a checkpoint if the last one is old enough
+    fn record_checkpoint(&mut self) {
+        let due = self
+            .checkpoints
+            .last()
+            .is_none_or(|last| self.time() - last.sim_state.pos.t() >= self.checkpoint_interval);
+        if !due {
+            return;
+        }
+        self.checkpoints.push(Checkpoint {
+            sim_state: self.sim_state,
+            state: self.state,
+            landed_at: self.landed_at,
+            first_impact: self.first_impact,
+            link_acceleration: self.link_acceleration,
+        });
+        if self.checkpoints.len() > MAX_CHECKPOINTS {
+            let mut index = 0;
+            self.checkpoints.retain(|_| {
+                index += 1;
+                index % 2 == 1
+            });
+            self.checkpoint_interval *= 2.0;
+        }
+    }
+
+    /// Returns the object to its last checkpoint not later than `t`, discarding everything
+    /// after it; integrating it from there up to `t` repeats the original steps, whatever the
+    /// stored path lost to the trimming and the decimation
+    pub fn rewind_to(&mut self, t: f64) {
+        let keep = self
+            .checkpoints
+            .partition_point(|checkpoint| checkpoint.sim_state.pos.t() <= t);
+        let checkpoint = match keep.checked_sub(1) {
+            Some(index) => self.checkpoints[index],
+            None => return,
+        };
+        self.checkpoints.truncate(keep);
+        let t_checkpoint = checkpoint.sim_state.pos.t();
+        let keep = self
+            .path
+            .partition_point(|state| state.pos.t() < t_checkpoint);
+        self.path.truncate(keep);
+        self.path_forces.truncate(keep);
+        self.sim_state = checkpoint.sim_state;
+        self.state = checkpoint.state;
+        self.landed_at = checkpoint.landed_at;
+        self.first_impact = checkpoint.first_impact;
+        self.link_acceleration = checkpoint.link_acceleration;
+        if self.blowup.is_some_and(|blowup| blowup.t >= self.time()) {
+            self.blowup = None;
+        }
+    }
+
+    /// All the states of the object up to the time `max_t` (but always at least the first one)
+    pub fn states_until(&self, max_t: f64) -> impl Iterator<Item = SimState> + '_ {
         self.path
             .iter()
             .copied()
             .chain(iter::once(self.sim_state))
             .enumerate()
-            .take_while(|(i, state)| *i == 0 || state.pos.t() < max_t)
+            .take_while(move |(i, state)| *i == 0 || state.pos.t() < max_t)
             .map(|(_, state)| state)
-            .last()
-            .unwrap()
+    }
+
+    /// The number of states returned by `states_until(max_t)`, found without going through them
+    pub fn count_states_until(&self, max_t: f64) -> usize {
+        let in_path = self.path.partition_point(|state| state.pos.t() < max_t);
+        if in_path == self.path.len() && (self.path.is_empty() || self.time() < max_t) {
+            in_path + 1
+        } else {
+            in_path.max(1)
+        }
+    }
+
+    /// The state with the index `index` in `states_until`: a stored one, or the current state
+    /// past their end
+    pub fn nth_state(&self, index: usize) -> SimState {
+        self.path.get(index).copied().unwrap_or(self.sim_state)
+    }
+
+    /// The state at the time `t`, interpolated between the stored samples; clamped to the
+    /// first and the current state
+    pub fn state_at(&self, t: f64) -> SimState {
+        let next_idx = self.path.partition_point(|state| state.pos.t() <= t);
+        if next_idx == 0 {
+            return self.path.front().copied().unwrap_or(self.sim_state);
+        }
+        let next = self.path.get(next_idx).unwrap_or(&self.sim_state);
+        self.path[next_idx - 1].interpolate(next, t)
+    }
+
+    /// The stored samples of the path, without the current state
+    pub fn path(&self) -> &VecDeque<SimState> {
+        &self.path
+    }
+
+    /// The time the trail reaches back to when drawn up to the time `max_t`
+    pub fn trail_start(&self, max_t: f64) -> f64 {
+        self.trail_duration
+            .map_or(f64::NEG_INFINITY, |duration| max_t - duration)
+    }
+
+    pub fn trail_frames(&self) -> TrailFrames {
+        self.trail_frames
+    }
+
+    pub fn trail_fades(&self) -> bool {
+        self.trail_fade
+    }
+
+    /// The values of the quantity the path is colored by at the stored samples of the drawn
+    /// trail
+    pub fn path_color_values(&self, mode: PathColorMode, max_t: f64) -> Vec<f64> {
+        let trail_start = self.trail_start(max_t);
+        self.states_until(max_t)
+            .chain(iter::once(self.state_at(max_t)))
+            .filter(|state| state.pos.t() >= trail_start)
+            .filter_map(|state| Self::path_attribute(&state, mode))
+            .collect()
+    }
+
+    /// The quantity the path is colored by at the state, unless it has the object's color
+    pub fn path_attribute(state: &SimState, mode: PathColorMode) -> Option<f64> {
+        match mode {
+            PathColorMode::Object => None,
+            PathColorMode::Speed => Some(state.vel.to_omega(state.pos, OMEGA).vel().norm()),
+            PathColorMode::Altitude => {
+                let (_, _, elev) = pos_to_lat_lon_elev(state.pos.to_omega(OMEGA).pos());
+                Some(elev / 1e3)
+            }
+        }
     }
 
     pub fn draw(
@@ -273,74 +1487,223 @@ impl Object {
         painter: &mut Painter<'_, '_, '_, '_, '_>,
         omega: f64,
         matrix: &Matrix4<f32>,
+        color: [f32; 3],
         render_settings: &RenderSettings,
     ) {
-        let states: Vec<_> = self
-            .path
-            .iter()
-            .copied()
-            .chain(iter::once(self.sim_state))
-            .enumerate()
-            .take_while(|(i, state)| *i == 0 || state.pos.t() < render_settings.max_t)
-            .map(|(_, state)| state)
-            .collect();
-
-        let state = states.last().unwrap();
+        // the object was added later in the run
+        if render_settings.max_t < self.start_time() {
+            return;
+        }
+        // the marker is drawn at the exact time, so that it stays smooth when followed
+        let state = &self.state_at(render_settings.max_t);
         let pos = state.pos.to_omega(omega);
-        let vel = state.vel.to_omega(pos, omega);
 
         let matrix_trans = matrix.prepend_translation(&Vector3::new(
             pos.pos().x as f32,
             pos.pos().y as f32,
             pos.pos().z as f32,
         ));
-        let uniforms = uniform! {
-            matrix: *(matrix_trans.prepend_scaling(self.radius)).as_ref(),
-            color: self.color(),
-        };
+        painter.solid_sphere(&matrix_trans.prepend_scaling(self.radius), color);
 
-        painter.solid_sphere(&uniforms);
+        let draw_paths = render_settings.draw_paths && !self.path_hidden;
+        if draw_paths && render_settings.draw_apsides {
+            let trail_start = self.trail_start(render_settings.max_t);
+            for apsis in self
+                .apsides(render_settings.max_t)
+                .into_iter()
+                .filter(|apsis| apsis.state.pos.t() >= trail_start)
+            {
+                let pos = apsis.state.pos.to_omega(omega).pos().cast::<f32>();
+                // highest points lighter, lowest points darker than the object
+                let color = match apsis.kind {
+                    ApsisKind::Apex | ApsisKind::Apoapsis => color.map(|c| 0.5 + 0.5 * c),
+                    ApsisKind::Periapsis => color.map(|c| 0.5 * c),
+                };
+                painter.solid_sphere(
+                    &matrix
+                        .prepend_translation(&pos)
+                        .prepend_scaling(self.radius * APSIS_MARKER_SCALE),
+                    color,
+                );
+            }
+        }
 
-        let uniforms = uniform! {
-            matrix: *matrix.as_ref(),
-            color: self.color(),
-        };
+        for state in self.arrow_states(render_settings) {
+            for arrow in self.arrows(&state, omega, color, render_settings) {
+                let matrix_trans = matrix.prepend_translation(&arrow.origin.cast::<f32>());
+                self.draw_vector(arrow.vector, painter, &matrix_trans, arrow.color);
+            }
+        }
 
-        painter.path(
-            &uniforms,
-            &states
-                .iter()
-                .map(|state| {
-                    let pos = state.pos.to_omega(omega);
-                    Vector3::new(pos.pos().x as f32, pos.pos().y as f32, pos.pos().z as f32)
-                })
-                .collect::<Vec<_>>(),
-        );
+        if let Some(exaggeration) = self.plumb_line {
+            let len = self.radius as f64 * 10.0;
+            let radial = -pos.pos().normalize();
+            let eff_grav = (pos.grav(self.gm) + pos.centrifugal()).normalize();
+            let plumb = (radial + (eff_grav - radial) * exaggeration).normalize();
 
-        if render_settings.draw_velocities {
-            // draw the velocity direction
-            let vel = vel.vel() * render_settings.vel_scale;
+            self.draw_vector(radial * len, painter, &matrix_trans, [1.0, 1.0, 1.0]);
+            self.draw_vector(plumb * len, painter, &matrix_trans, [1.0, 0.5, 0.0]);
+        }
+    }
 
-            self.draw_vector(vel, painter, &matrix_trans, self.color());
+    /// The states at which the velocity and the forces are drawn: the one at `max_t` and, if
+    /// turned on, every few stored samples along the shown part of the path
+    pub fn arrow_states(&self, render_settings: &RenderSettings) -> Vec<SimState> {
+        let max_t = render_settings.max_t;
+        let mut states = vec![self.state_at(max_t)];
+        if let Some(interval) = render_settings
+            .path_arrow_interval
+            .filter(|_| !self.path_hidden)
+        {
+            let trail_start = self.trail_start(max_t);
+            // the arrows at `max_t` are already there
+            states.extend(
+                self.states_until(max_t)
+                    .filter(|state| state.pos.t() >= trail_start)
+                    .step_by(interval.max(1))
+                    .filter(|state| state.pos.t() < max_t),
+            );
+        }
+        states
+    }
+
+    /// The velocity and the forces at the state in the frame rotating with `omega`, as far as
+    /// they are turned on; the velocity has the color `color`
+    pub fn arrows(
+        &self,
+        state: &SimState,
+        omega: f64,
+        color: [f32; 3],
+        render_settings: &RenderSettings,
+    ) -> Vec<Arrow> {
+        let pos = state.pos.to_omega(omega);
+        let vel = state.vel.to_omega(pos, omega);
+        let mut arrows = vec![];
+        let mut push = |vector: Vector3<f64>, scale: f64, unit, color| {
+            arrows.push(Arrow {
+                origin: pos.pos(),
+                vector: vector * scale,
+                magnitude: vector.norm(),
+                unit,
+                color,
+            })
+        };
+
+        if render_settings.draw_velocities {
+            push(vel.vel(), render_settings.vel_scale, "m/s", color);
         }
 
         if render_settings.draw_forces {
-            let grav = pos.grav(self.gm) * render_settings.force_scale;
-            let centri = pos.centrifugal() * render_settings.force_scale;
-            let coriolis = vel.coriolis() * render_settings.force_scale;
+            let scale = render_settings.force_scale;
+            push(pos.grav(self.gm), scale, "m/s²", [0.5, 0.5, 0.0]);
+            push(pos.centrifugal(), scale, "m/s²", [0.3, 1.0, 0.3]);
+            push(vel.coriolis(&pos), scale, "m/s²", [0.0, 1.0, 1.0]);
 
-            self.draw_vector(grav, painter, &matrix_trans, [0.5, 0.5, 0.0]);
-            self.draw_vector(centri, painter, &matrix_trans, [0.3, 1.0, 0.3]);
-            self.draw_vector(coriolis, painter, &matrix_trans, [0.0, 1.0, 1.0]);
+            // the frame may change its rotation both by the Earth's spin ramp and by the user
+            // changing the rotation of the view
+            let alpha = Vector3::new(0.0, render_settings.frame_alpha, 0.0);
+            let euler = pos.euler() - alpha.cross(&pos.pos());
+            if render_settings.draw_euler_force && euler.norm() > 0.0 {
+                push(euler, scale, "m/s²", [1.0, 0.3, 1.0]);
+            }
 
             if self.counteract_coriolis {
-                let force = state
-                    .pos
-                    .dir_to_omega(state.coriolis_counteraction(), omega)
-                    * render_settings.force_scale;
-                self.draw_vector(force, painter, &matrix_trans, [0.0, 0.0, 0.9]);
+                let force = state.pos.dir_to_omega(
+                    state.coriolis_counteraction(&state.context(&self.environment, false)),
+                    omega,
+                );
+                push(force, scale, "m/s²", [0.0, 0.0, 0.9]);
             }
         }
+
+        arrows
+    }
+
+    /// Whether the object moves along the surface (or at a constant altitude) at the time `t`
+    fn on_surface(&self, t: f64) -> bool {
+        matches!(self.state, ObjectState::ConstantAltitude(_))
+            && !self.landed_at.is_some_and(|landed_at| t < landed_at)
+    }
+
+    /// The altitude kept by the object if it moves along a surface of constant altitude at the
+    /// time `t`
+    fn surface_altitude(&self, t: f64) -> Option<f64> {
+        match self.state {
+            ObjectState::ConstantAltitude(alt) if self.on_surface(t) => Some(alt),
+            _ => None,
+        }
+    }
+
+    pub fn force_magnitudes(&self, state: &SimState) -> ForceMagnitudes {
+        let t = state.pos.t();
+        let ctx = state.context(&self.environment, self.needs_elevation());
+        // friction only acts while moving along the surface
+        let on_surface = self.on_surface(t);
+        ForceMagnitudes {
+            gravity: ctx.pos_earth.grav(self.gm).norm(),
+            centrifugal: ctx.pos_earth.centrifugal().norm(),
+            coriolis: ctx.vel_earth.coriolis(&ctx.pos_earth).norm(),
+            drag: state.drag(&ctx, self.drag_coeff).norm(),
+            friction: if on_surface {
+                state.friction(&ctx, self.friction).norm()
+            } else {
+                0.0
+            },
+            pressure_gradient: self
+                .surface_altitude(t)
+                .map_or(0.0, |alt| state.pressure_gradient(&ctx, alt).norm()),
+            buoyancy: state.buoyancy(&ctx, self.volume_per_mass, self.gm).norm(),
+        }
+    }
+
+    /// The horizontal acceleration left over from the Coriolis force and the pressure gradient
+    /// (of the pressure systems, or of an attraction standing in for it), in the Earth's frame,
+    /// with the size of the horizontal Coriolis force; only for objects moving along the surface
+    /// under such a force
+    pub fn ageostrophic_acceleration(&self, state: &SimState) -> Option<(Vector3<f64>, f64)> {
+        let alt = self.surface_altitude(state.pos.t())?;
+        let ctx = state.context(&self.environment, false);
+        let field = self
+            .environment
+            .pressure_gradient(&ctx.pos_earth.pos(), alt);
+        let attraction = self
+            .attractor
+            .as_ref()
+            .map(|attractor| state.pos.dir_to_omega(attractor(state.pos), OMEGA));
+        if field.is_none() && attraction.is_none() {
+            return None;
+        }
+        let up = surface_normal(&ctx.pos_earth.pos());
+        let horizontal = |v: Vector3<f64>| v - up * up.dot(&v);
+        let coriolis = horizontal(ctx.vel_earth.coriolis(&ctx.pos_earth));
+        let pressure_gradient = horizontal(
+            field.unwrap_or_else(Vector3::zeros) + attraction.unwrap_or_else(Vector3::zeros),
+        );
+        Some((coriolis + pressure_gradient, coriolis.norm()))
+    }
+
+    pub fn weight_terms(&self, state: &SimState) -> WeightTerms {
+        let pos = state.pos.to_omega(OMEGA);
+        let vel = state.vel.to_omega(pos, OMEGA);
+        let up = surface_normal(&pos.pos());
+        let r = r_curv(&pos.pos()) + pos_to_lat_lon_elev(pos.pos()).2;
+        let v_horiz = vel.vel() - up * up.dot(&vel.vel());
+
+        WeightTerms {
+            gravity: -pos.grav(self.gm).dot(&up),
+            centrifugal: -pos.centrifugal().dot(&up),
+            eotvos: -vel.coriolis(&pos).dot(&up),
+            curvature: -v_horiz.norm_squared() / r,
+        }
+    }
+
+    /// Angle between the radial direction and the effective gravity in the frame rotating with
+    /// `omega`, in degrees
+    fn plumb_line_deviation(&self, pos: Position, omega: f64) -> f64 {
+        let pos = pos.to_omega(omega);
+        let radial = -pos.pos().normalize();
+        let eff_grav = (pos.grav(self.gm) + pos.centrifugal()).normalize();
+        radial.dot(&eff_grav).clamp(-1.0, 1.0).acos().to_degrees()
     }
 
     fn draw_vector(
@@ -365,22 +1728,17 @@ impl Object {
 
         let matrix = matrix * rot_y * rot_x * scale * scale2;
 
-        let uniforms = uniform! { matrix: *matrix.as_ref(), color: color };
-        painter.arrow(&uniforms);
+        painter.arrow(&matrix, color);
     }
 
-    pub fn status(&self, omega: f64, render_settings: &RenderSettings) -> Vec<String> {
-        let state = if render_settings.max_t < self.time() {
-            self.path
-                .iter()
-                .rev()
-                .skip_while(|state| state.pos.t() > render_settings.max_t)
-                .next()
-                .cloned()
-                .unwrap_or_else(|| self.sim_state)
-        } else {
-            self.sim_state
-        };
+    pub fn status(
+        &self,
+        omega: f64,
+        sun: &Sun,
+        celestial: &CelestialFrame,
+        render_settings: &RenderSettings,
+    ) -> Vec<String> {
+        let state = self.state_at(render_settings.max_t);
 
         let pos_rot = state.pos.to_omega(OMEGA);
         let (lat, lon, elev) = pos_to_lat_lon_elev(pos_rot.pos());
@@ -389,10 +1747,100 @@ impl Object {
         let vel_o = state.vel.to_omega(state.pos, omega);
         let vel_s = format!("Speed: {:4.1} m/s", vel_o.vel().norm());
 
-        let mut status = vec![pos_s, vel_s];
+        let sun_s = sun.status(
+            &pos_rot.pos(),
+            state.pos.t(),
+            self.environment.spin,
+            celestial,
+        );
+
+        let mut status = vec![pos_s, vel_s, sun_s];
+
+        if let Some(launch) = self.states_until(0.0).next() {
+            let launch = launch.pos.to_omega(OMEGA).pos();
+            let (distance, bearing) = great_circle(&launch, &pos_rot.pos());
+            status.push(format!(
+                "From the launch point: {}",
+                format_range(distance, bearing)
+            ));
+        }
+        if let Some((ref_lat, ref_lon)) = render_settings.reference_point {
+            let reference = lat_lon_elev_to_vec3(ref_lat, ref_lon, 0.0);
+            let (distance, bearing) = great_circle(&reference, &pos_rot.pos());
+            status.push(format!(
+                "From the reference point: {}",
+                format_range(distance, bearing)
+            ));
+        }
+
+        if let Some(orbit) = self.orbit_prediction(&state) {
+            status.push(orbit.status());
+        }
+        if let Some((max_elev, t_max_elev, downrange)) = self.flight_summary(render_settings.max_t)
+        {
+            status.push(format!(
+                "Max altitude: {:.2} km at t = {:.0} s",
+                max_elev / 1e3,
+                t_max_elev
+            ));
+            status.push(format!("Downrange distance: {:.1} km", downrange / 1e3));
+        }
+        if let Some((flight_time, range, deflection)) = self.impact_summary(render_settings.max_t) {
+            status.push(format!(
+                "Impact after {:.1} s, {:.2} km away, {:.1} m {} of the line of fire",
+                flight_time,
+                range / 1e3,
+                deflection.abs(),
+                if deflection >= 0.0 { "right" } else { "left" }
+            ));
+        }
+        if let Some(stats) = self.path_statistics(render_settings.max_t) {
+            status.push(format!(
+                "Path length: {:.1} km, along the ground {:.1} km{}",
+                stats.length / 1e3,
+                stats.ground_length / 1e3,
+                stats
+                    .excess_length()
+                    .map_or_else(String::new, |excess| format!(
+                        " ({:+.2}% vs. the great circle)",
+                        excess
+                    ))
+            ));
+            status.push(format!(
+                "Average speed: {:.1} m/s, total turning: {:.1}°",
+                stats.average_speed, stats.turning
+            ));
+        }
+
+        if self.weighing_scale {
+            let terms = self.weight_terms(&state);
+            status.push(format!("Apparent weight: {:.5} m/s²", terms.total()));
+        }
+
+        if self.plumb_line.is_some() {
+            let deviation = self.plumb_line_deviation(state.pos, omega);
+            status.push(format!("Plumb-line deviation: {:.4}°", deviation));
+        }
+
+        if let Some((residual, coriolis)) = self
+            .ageostrophic_acceleration(&state)
+            .filter(|(_, coriolis)| *coriolis > 0.0)
+        {
+            let relative = residual.norm() / coriolis;
+            status.push(format!(
+                "{}: ageostrophic acceleration {:.3e} m/s² ({:.1}% of the Coriolis force)",
+                if relative <= GEOSTROPHIC_TOLERANCE {
+                    "Geostrophic"
+                } else {
+                    "Not geostrophic"
+                },
+                residual.norm(),
+                relative * 100.0
+            ));
+        }
 
         if self.counteract_coriolis {
-            let force = state.coriolis_counteraction();
+            let force = state.coriolis_counteraction(&state.context(&self.environment, false));
             let grav_plus_cfg = state.pos.grav(self.gm) + state.pos.centrifugal();
             let grav_plus_cfg_mag = grav_plus_cfg.norm();
             let counteraction_mag = force.norm();
@@ -411,6 +1859,15 @@ impl Object {
     }
 }
 
+/// Distance in meters below 10 km and in kilometers above, followed by the bearing
+fn format_range(distance: f64, bearing: f64) -> String {
+    if distance < 1e4 {
+        format!("{:.1} m, bearing {:.1}°", distance, bearing)
+    } else {
+        format!("{:.2} km, bearing {:.1}°", distance / 1e3, bearing)
+    }
+}
+
 impl State for Object {
     type Derivative = SVector<f64, 7>;
 
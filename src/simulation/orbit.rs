@@ -0,0 +1,157 @@
+use std::fmt;
+
+use nalgebra::{Rotation3, Vector3};
+
+use super::{
+    pos_to_lat_lon_elev, velocity::east_north_up_basis, Position, Velocity, GM, OMEGA, R_EQU,
+};
+
+/// Keplerian elements of an orbit around the Earth, referred to the equator and the vernal
+/// equinox at t=0
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    /// Semi-major axis in meters
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    /// Inclination in degrees
+    pub inclination: f64,
+    /// Right ascension of the ascending node in degrees
+    pub raan: f64,
+    /// Argument of perigee in degrees
+    pub arg_perigee: f64,
+    /// True anomaly at t=0 in degrees
+    pub true_anomaly: f64,
+}
+
+impl OrbitalElements {
+    /// Position and velocity at t=0 in inertial coordinates (which coincide with the coordinates
+    /// of every rotating frame at t=0)
+    pub fn to_inertial(self) -> (Vector3<f64>, Vector3<f64>) {
+        let e = self.eccentricity;
+        let nu = self.true_anomaly.to_radians();
+        let p = self.semi_major_axis * (1.0 - e * e);
+        let r = p / (1.0 + e * nu.cos());
+
+        // position and velocity in the orbital plane, x towards the perigee
+        let pos = Vector3::new(r * nu.cos(), r * nu.sin(), 0.0);
+        let vel_coeff = (GM / p).sqrt();
+        let vel = Vector3::new(-vel_coeff * nu.sin(), vel_coeff * (e + nu.cos()), 0.0);
+
+        let rot = Rotation3::from_axis_angle(&Vector3::z_axis(), self.raan.to_radians())
+            * Rotation3::from_axis_angle(&Vector3::x_axis(), self.inclination.to_radians())
+            * Rotation3::from_axis_angle(&Vector3::z_axis(), self.arg_perigee.to_radians());
+        let (pos, vel) = (rot * pos, rot * vel);
+
+        // equatorial coordinates: x - vernal equinox, z - north pole; in the simulation the
+        // vernal equinox is +Z, the north pole is +Y and +X is 90° east
+        let to_sim = |v: Vector3<f64>| Vector3::new(v.y, v.z, v.x);
+        (to_sim(pos), to_sim(vel))
+    }
+
+    /// The initial state of an object on this orbit, in the Earth's frame
+    pub fn initial_state(self) -> (Position, Velocity) {
+        let (pos, vel) = self.to_inertial();
+        state_from_inertial(pos, vel)
+    }
+}
+
+/// Converts a position and velocity at t=0 in inertial coordinates to a state in the Earth's
+/// frame
+pub fn state_from_inertial(pos: Vector3<f64>, vel: Vector3<f64>) -> (Position, Velocity) {
+    let (lat, lon, elev) = pos_to_lat_lon_elev(pos);
+    let position = Position::from_lat_lon_elev(lat, lon, elev);
+
+    // velocity relative to the rotating surface
+    let vel_rel = vel - Vector3::new(0.0, OMEGA, 0.0).cross(&pos);
+    let (east, north, up) = east_north_up_basis(position);
+    let velocity = Velocity::from_east_north_up(
+        position,
+        vel_rel.dot(&east),
+        vel_rel.dot(&north),
+        vel_rel.dot(&up),
+    );
+
+    (position, velocity)
+}
+
+/// The kind of trajectory of an object moving only under the Earth's gravity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitClass {
+    /// The orbit intersects the Earth
+    Suborbital,
+    /// A closed orbit clearing the Earth
+    Orbital,
+    /// An open orbit, with enough energy to leave the Earth for good
+    Escape,
+}
+
+impl fmt::Display for OrbitClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrbitClass::Suborbital => write!(f, "suborbital"),
+            OrbitClass::Orbital => write!(f, "orbital"),
+            OrbitClass::Escape => write!(f, "escape"),
+        }
+    }
+}
+
+/// The Keplerian orbit through a state of an object, ignoring everything but the gravity of the
+/// Earth
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitPrediction {
+    pub class: OrbitClass,
+    /// Distance of the periapsis from the Earth's center, in meters
+    pub periapsis: f64,
+    /// Distance of the apoapsis from the Earth's center, in meters; `None` for open orbits
+    pub apoapsis: Option<f64>,
+}
+
+impl OrbitPrediction {
+    /// Predicts the orbit from a position and velocity in an inertial frame, for the
+    /// gravitational parameter `gm`
+    pub fn new(pos: &Vector3<f64>, vel: &Vector3<f64>, gm: f64) -> Self {
+        let h = pos.cross(vel);
+        let ecc_vector = vel.cross(&h) / gm - pos.normalize();
+        let e = ecc_vector.norm();
+        // the semi-latus rectum
+        let p = h.norm_squared() / gm;
+
+        let periapsis = p / (1.0 + e);
+        let apoapsis = (e < 1.0).then(|| p / (1.0 - e));
+        let class = if e >= 1.0 {
+            OrbitClass::Escape
+        } else if periapsis < R_EQU {
+            OrbitClass::Suborbital
+        } else {
+            OrbitClass::Orbital
+        };
+
+        Self {
+            class,
+            periapsis,
+            apoapsis,
+        }
+    }
+
+    /// A description of the orbit, with the apsides as altitudes above the equatorial radius
+    pub fn status(&self) -> String {
+        let altitude = |r: f64| (r - R_EQU) / 1e3;
+        match (self.class, self.apoapsis) {
+            (OrbitClass::Suborbital, Some(apoapsis)) => format!(
+                "Trajectory: suborbital, apoapsis {:.1} km",
+                altitude(apoapsis)
+            ),
+            (class, Some(apoapsis)) => format!(
+                "Trajectory: {}, periapsis {:.1} km, apoapsis {:.1} km",
+                class,
+                altitude(self.periapsis),
+                altitude(apoapsis)
+            ),
+            (class, None) => format!(
+                "Trajectory: {}, periapsis {:.1} km",
+                class,
+                altitude(self.periapsis)
+            ),
+        }
+    }
+}
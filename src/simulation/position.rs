@@ -1,6 +1,10 @@
-use nalgebra::Vector3;
+use nalgebra::{Unit, Vector3};
 
-use super::{lat_lon_elev_to_vec3, OMEGA};
+use super::{
+    lat_lon_elev_to_vec3, rotation,
+    spin::{frame_acceleration, frame_rate},
+    SpinRamp, OMEGA,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Position {
@@ -8,6 +12,11 @@ pub struct Position {
     pos: Vector3<f64>,
     // angular velocity of the frame of reference
     omega: f64,
+    // the axis the frame of reference rotates about
+    axis: Unit<Vector3<f64>>,
+    // how the Earth's rotation changes in the simulation the position belongs to, for the
+    // conversions from and to the Earth's frame
+    spin: Option<SpinRamp>,
 }
 
 impl Position {
@@ -17,32 +26,45 @@ impl Position {
             t: 0.0,
             pos,
             omega: OMEGA,
+            axis: Vector3::y_axis(),
+            spin: None,
         }
     }
 
+    /// The same position in a simulation where the Earth's rotation follows `spin`
+    pub fn with_spin(self, spin: Option<SpinRamp>) -> Self {
+        Self { spin, ..self }
+    }
+
+    /// Transforms the position to the frame rotating with `omega` about the Y axis
     pub fn to_omega(self, omega: f64) -> Self {
-        if self.omega == omega {
+        self.to_frame(omega, Vector3::y_axis())
+    }
+
+    /// Transforms the position to the frame rotating with `omega` about `axis`
+    pub fn to_frame(self, omega: f64, axis: Unit<Vector3<f64>>) -> Self {
+        if self.omega == omega && self.axis == axis {
             return self;
         }
-        let Position {
-            t,
-            pos,
-            omega: omega_old,
-        } = self;
-        let wt = (omega_old - omega) * t;
-        let s = wt.sin();
-        let c = wt.cos();
-        let pos = Vector3::new(pos.x * c + pos.z * s, pos.y, -pos.x * s + pos.z * c);
-        Position { t, pos, omega }
+        let rot =
+            rotation::frame_rotation_about(self.spin, &self.axis, self.omega, &axis, omega, self.t);
+        Position {
+            pos: rot * self.pos,
+            omega,
+            axis,
+            ..self
+        }
     }
 
     pub fn dir_to_omega(&self, vec: Vector3<f64>, omega: f64) -> Vector3<f64> {
-        let w = omega - self.omega;
-        let wt = w * self.t;
-        let s = wt.sin();
-        let c = wt.cos();
-
-        Vector3::new(vec.x * c - vec.z * s, vec.y, vec.x * s + vec.z * c)
+        rotation::frame_rotation_about(
+            self.spin,
+            &self.axis,
+            self.omega,
+            &Vector3::y_axis(),
+            omega,
+            self.t,
+        ) * vec
     }
 
     pub fn grav(&self, gm: f64) -> Vector3<f64> {
@@ -51,8 +73,20 @@ impl Position {
     }
 
     pub fn centrifugal(&self) -> Vector3<f64> {
-        let r_xz = Vector3::new(self.pos.x, 0.0, self.pos.z);
-        r_xz * self.omega * self.omega
+        let r_perp = if self.axis == Vector3::y_axis() {
+            Vector3::new(self.pos.x, 0.0, self.pos.z)
+        } else {
+            self.pos - self.axis.into_inner() * self.axis.dot(&self.pos)
+        };
+        let omega = frame_rate(self.spin, self.omega, self.t);
+        r_perp * omega * omega
+    }
+
+    /// The Euler force per unit mass, present while the rotation of the frame speeds up or
+    /// slows down
+    pub fn euler(&self) -> Vector3<f64> {
+        let alpha = self.axis.into_inner() * frame_acceleration(self.spin, self.omega, self.t);
+        -alpha.cross(&self.pos)
     }
 
     pub fn t(&self) -> f64 {
@@ -63,6 +97,14 @@ impl Position {
         self.omega
     }
 
+    pub fn axis(&self) -> Unit<Vector3<f64>> {
+        self.axis
+    }
+
+    pub fn spin(&self) -> Option<SpinRamp> {
+        self.spin
+    }
+
     pub fn pos(&self) -> Vector3<f64> {
         self.pos
     }
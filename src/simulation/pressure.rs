@@ -0,0 +1,35 @@
+use nalgebra::Vector3;
+
+use super::{great_circle, lat_lon_elev_to_vec3, surface_normal};
+
+/// A low (or a high) of the air pressure with a Gaussian profile, centered at a point on the
+/// surface and the same at every altitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureSystem {
+    pub lat: f64,
+    pub lon: f64,
+    /// The pressure at the center relative to the surroundings, in Pa; negative for a low
+    pub depth: f64,
+    /// The distance from the center at which the difference drops to 1/e of `depth`, in meters
+    pub radius: f64,
+}
+
+impl PressureSystem {
+    /// The pressure-gradient acceleration −∇p/ρ along the surface at the point given in the
+    /// Earth's frame, where the air has the density `density`, in m/s²
+    pub fn acceleration(&self, pos: &Vector3<f64>, density: f64) -> Vector3<f64> {
+        let center = lat_lon_elev_to_vec3(self.lat, self.lon, 0.0);
+        let (distance, _) = great_circle(pos, &center);
+        let up = surface_normal(pos);
+        let towards = center - pos;
+        let towards = towards - up * up.dot(&towards);
+        if distance == 0.0 || towards.norm() == 0.0 {
+            return Vector3::zeros();
+        }
+        // dp/dr of p = depth * exp(-(r/radius)²)
+        let slope = -2.0 * self.depth * distance / (self.radius * self.radius)
+            * (-(distance / self.radius).powi(2)).exp();
+        // the air is pushed down the slope, i.e. towards the center of a low
+        slope / density * towards.normalize()
+    }
+}
@@ -0,0 +1,64 @@
+use nalgebra::{Matrix4, RealField, Unit, UnitQuaternion, Vector3};
+
+use super::{spin::frame_angle, SpinRamp};
+
+/// Rotation by `angle` radians about the given axis
+pub fn about_axis<T: RealField>(axis: &Unit<Vector3<T>>, angle: T) -> UnitQuaternion<T> {
+    UnitQuaternion::from_axis_angle(axis, angle)
+}
+
+/// Rotation by `angle` radians about the X axis
+pub fn about_x<T: RealField>(angle: T) -> UnitQuaternion<T> {
+    UnitQuaternion::from_axis_angle(&Vector3::x_axis(), angle)
+}
+
+/// Rotation by `angle` radians about the Y axis (the Earth's rotation axis)
+pub fn about_y<T: RealField>(angle: T) -> UnitQuaternion<T> {
+    UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle)
+}
+
+/// The rotation transforming coordinates in the frame rotating with `omega_from` to coordinates
+/// in the frame rotating with `omega_to` at time `t`; all the frames coincide at t=0. The
+/// Earth's frame follows `spin`, if any.
+pub fn frame_rotation(
+    spin: Option<SpinRamp>,
+    omega_from: f64,
+    omega_to: f64,
+    t: f64,
+) -> UnitQuaternion<f64> {
+    about_y(frame_angle(spin, omega_from, t) - frame_angle(spin, omega_to, t))
+}
+
+/// The rotation transforming coordinates in the frame rotating with `omega_from` about
+/// `axis_from` to coordinates in the frame rotating with `omega_to` about `axis_to` at time `t`
+pub fn frame_rotation_about(
+    spin: Option<SpinRamp>,
+    axis_from: &Unit<Vector3<f64>>,
+    omega_from: f64,
+    axis_to: &Unit<Vector3<f64>>,
+    omega_to: f64,
+    t: f64,
+) -> UnitQuaternion<f64> {
+    if axis_from == axis_to {
+        if *axis_from == Vector3::y_axis() {
+            return frame_rotation(spin, omega_from, omega_to, t);
+        }
+        let angle = frame_angle(spin, omega_from, t) - frame_angle(spin, omega_to, t);
+        return UnitQuaternion::from_axis_angle(axis_from, angle);
+    }
+    // through the inertial frame, with which all the frames coincide at t=0
+    UnitQuaternion::from_axis_angle(axis_to, -frame_angle(spin, omega_to, t))
+        * UnitQuaternion::from_axis_angle(axis_from, frame_angle(spin, omega_from, t))
+}
+
+/// The rotation as a homogeneous matrix for rendering
+pub fn to_matrix(rot: &UnitQuaternion<f64>) -> Matrix4<f32> {
+    rot.cast::<f32>().to_homogeneous()
+}
+
+/// Interpolates between two orientations along the shortest arc; `s` goes from 0 to 1
+pub fn slerp(from: &UnitQuaternion<f64>, to: &UnitQuaternion<f64>, s: f64) -> UnitQuaternion<f64> {
+    // opposite orientations have no unique shortest arc, so just switch halfway
+    from.try_slerp(to, s, 1e-9)
+        .unwrap_or(if s < 0.5 { *from } else { *to })
+}
@@ -0,0 +1,68 @@
+//! The recorded run of an object as a sequence of samples in a chosen frame. The analysis
+//! panels and the exports read the runs through this, rather than through the stored path.
+
+use super::{pos_to_lat_lon_elev, Forces, Position, SimState, Velocity, OMEGA};
+
+/// The state of an object and the accelerations acting on it at a single moment, all in the
+/// frame rotating with `omega` about the Earth's axis
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub t: f64,
+    /// In the frame of the sample, whose angular velocity is `pos.omega()`: 0 for the inertial
+    /// frame, `OMEGA` for the Earth's
+    pub pos: Position,
+    pub vel: Velocity,
+    /// The breakdown of the acceleration in the frame of the sample, in m/s²; the fictitious
+    /// forces are the ones of that frame, so they all vanish in the inertial one
+    pub forces: Forces,
+}
+
+impl Sample {
+    /// `forces` are the ones stored with the state, i.e. in the Earth's frame
+    pub(super) fn new(state: &SimState, forces: &Forces, omega: f64) -> Self {
+        let t = state.pos().t();
+        let pos = state.pos().to_omega(omega);
+        let vel = state.vel().to_omega(*state.pos(), omega);
+        // gravity and the real forces are the same in every frame, only their directions turn
+        let earth = state.pos().to_omega(OMEGA);
+        Self {
+            t,
+            pos,
+            vel,
+            forces: Forces {
+                t,
+                gravity: earth.dir_to_omega(forces.gravity, omega),
+                centrifugal: pos.centrifugal(),
+                coriolis: vel.coriolis(&pos),
+                euler: pos.euler(),
+                other: earth.dir_to_omega(forces.other, omega),
+            },
+        }
+    }
+
+    /// The latitude and longitude in degrees and the elevation in meters
+    pub fn lat_lon_elev(&self) -> (f64, f64, f64) {
+        pos_to_lat_lon_elev(self.pos.to_omega(OMEGA).pos())
+    }
+
+    /// The eastward, northward and upward velocity relative to the Earth's surface, in m/s
+    pub fn east_north_up(&self) -> (f64, f64, f64) {
+        self.vel.to_east_north_up(self.pos)
+    }
+}
+
+/// At most about `max_samples` evenly spaced items, always including the first and the last one
+pub fn thin_out<T: Copy>(items: Vec<T>, max_samples: usize) -> Vec<T> {
+    let stride = (items.len() / max_samples.max(1)).max(1);
+    if stride == 1 {
+        return items;
+    }
+    let mut thinned: Vec<_> = items.iter().step_by(stride).copied().collect();
+    if let Some(&last) = items
+        .last()
+        .filter(|_| !(items.len() - 1).is_multiple_of(stride))
+    {
+        thinned.push(last);
+    }
+    thinned
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use super::OMEGA;
+
+/// A change of the Earth's rotation over the run: the angular velocity changes linearly from
+/// the real one to `final_rate` times the real one between the times `start` and `end`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpinRamp {
+    /// The rotation rate after the ramp, as a multiple of the real one
+    pub final_rate: f64,
+    /// When the rate starts changing, in seconds
+    pub start: f64,
+    /// When the rate reaches the final value, in seconds
+    pub end: f64,
+}
+
+impl Default for SpinRamp {
+    fn default() -> Self {
+        Self {
+            final_rate: 0.0,
+            start: 0.0,
+            end: 86400.0,
+        }
+    }
+}
+
+impl SpinRamp {
+    fn duration(&self) -> f64 {
+        (self.end - self.start).max(0.0)
+    }
+
+    /// The angular velocity of the Earth at the time `t`
+    pub fn omega(&self, t: f64) -> f64 {
+        let factor = if t <= self.start {
+            1.0
+        } else if t >= self.end {
+            self.final_rate
+        } else {
+            1.0 + (self.final_rate - 1.0) * (t - self.start) / self.duration()
+        };
+        OMEGA * factor
+    }
+
+    /// The angular acceleration of the Earth at the time `t`
+    pub fn alpha(&self, t: f64) -> f64 {
+        if t > self.start && t < self.end {
+            OMEGA * (self.final_rate - 1.0) / self.duration()
+        } else {
+            0.0
+        }
+    }
+
+    /// The angle the Earth has turned by since t=0
+    pub fn angle(&self, t: f64) -> f64 {
+        let before = t.min(self.start);
+        let during = (t.min(self.end) - self.start).max(0.0);
+        let after = (t - self.end.max(self.start)).max(0.0);
+        let ramp = if self.duration() > 0.0 {
+            during + (self.final_rate - 1.0) * during * during / 2.0 / self.duration()
+        } else {
+            0.0
+        };
+        OMEGA * (before + ramp + self.final_rate * after)
+    }
+}
+
+/// The ramp followed by the Earth's frame, the one rotating with `OMEGA`, if `spin` has one
+fn earth_spin(spin: Option<SpinRamp>, omega: f64) -> Option<SpinRamp> {
+    spin.filter(|_| omega == OMEGA)
+}
+
+/// The angle the frame rotating with `omega` has turned by at the time `t`. All frames rotate
+/// uniformly, except for the Earth's, which follows the spin ramp, if any.
+pub fn frame_angle(spin: Option<SpinRamp>, omega: f64, t: f64) -> f64 {
+    match earth_spin(spin, omega) {
+        Some(ramp) => ramp.angle(t),
+        None => omega * t,
+    }
+}
+
+/// The angular velocity at the time `t` of the frame rotating with `omega`
+pub(super) fn frame_rate(spin: Option<SpinRamp>, omega: f64, t: f64) -> f64 {
+    match earth_spin(spin, omega) {
+        Some(ramp) => ramp.omega(t),
+        None => omega,
+    }
+}
+
+/// The angular acceleration at the time `t` of the frame rotating with `omega`
+pub(super) fn frame_acceleration(spin: Option<SpinRamp>, omega: f64, t: f64) -> f64 {
+    earth_spin(spin, omega).map_or(0.0, |ramp| ramp.alpha(t))
+}
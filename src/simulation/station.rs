@@ -0,0 +1,101 @@
+use nalgebra::Vector3;
+
+use super::{lat_lon_elev_to_vec3, surface_normal, Object, OMEGA};
+
+/// A ground station fixed to the Earth's surface, able to see objects above a minimum elevation
+#[derive(Debug, Clone, Copy)]
+pub struct GroundStation {
+    pub lat: f64,
+    pub lon: f64,
+    /// Minimum elevation above the horizon at which an object is visible, in degrees
+    pub min_elevation: f64,
+}
+
+impl Default for GroundStation {
+    fn default() -> Self {
+        Self {
+            lat: 52.0,
+            lon: 21.0,
+            min_elevation: 10.0,
+        }
+    }
+}
+
+/// A single interval of time during which an object is visible from a station
+#[derive(Debug, Clone, Copy)]
+pub struct Pass {
+    pub start: f64,
+    pub end: f64,
+    /// The highest elevation reached during the pass, in degrees
+    pub max_elevation: f64,
+    pub max_elevation_t: f64,
+}
+
+impl GroundStation {
+    /// Position of the station in the Earth's frame
+    pub fn pos(&self) -> Vector3<f64> {
+        lat_lon_elev_to_vec3(self.lat, self.lon, 0.0)
+    }
+
+    /// Elevation above the station's horizon of a point given in the Earth's frame, in degrees
+    pub fn elevation(&self, pos: &Vector3<f64>) -> f64 {
+        let station = self.pos();
+        let up = surface_normal(&station);
+        let dir = pos - station;
+        let dist = dir.norm();
+        if dist < 1e-9 {
+            return 90.0;
+        }
+        (up.dot(&dir) / dist).asin().to_degrees()
+    }
+
+    /// The rim of the visibility cone at the slant range `range`, as `n_points` points in the
+    /// Earth's frame
+    pub fn cone_rim(&self, range: f64, n_points: usize) -> Vec<Vector3<f64>> {
+        let station = self.pos();
+        let up = surface_normal(&station);
+        let lon = self.lon.to_radians();
+        let east = Vector3::new(lon.cos(), 0.0, -lon.sin());
+        let north = up.cross(&east);
+
+        let half_angle = (90.0 - self.min_elevation).to_radians();
+        let (axial, radial) = (range * half_angle.cos(), range * half_angle.sin());
+
+        (0..=n_points)
+            .map(|i| {
+                let azimuth = 2.0 * std::f64::consts::PI * i as f64 / n_points as f64;
+                station + up * axial + (east * azimuth.sin() + north * azimuth.cos()) * radial
+            })
+            .collect()
+    }
+
+    /// Finds the passes of the object over the station along its calculated path up to the time
+    /// `max_t`
+    pub fn passes(&self, obj: &Object, max_t: f64) -> Vec<Pass> {
+        let mut passes = vec![];
+        let mut current: Option<Pass> = None;
+
+        for state in obj.states_until(max_t) {
+            let t = state.pos().t();
+            let elevation = self.elevation(&state.pos().to_omega(OMEGA).pos());
+            if elevation >= self.min_elevation {
+                let pass = current.get_or_insert(Pass {
+                    start: t,
+                    end: t,
+                    max_elevation: elevation,
+                    max_elevation_t: t,
+                });
+                pass.end = t;
+                if elevation > pass.max_elevation {
+                    pass.max_elevation = elevation;
+                    pass.max_elevation_t = t;
+                }
+            } else if let Some(pass) = current.take() {
+                passes.push(pass);
+            }
+        }
+        passes.extend(current);
+
+        passes
+    }
+}
@@ -0,0 +1,110 @@
+use nalgebra::Vector3;
+
+use super::{pos_to_lat_lon_elev, surface_normal, CelestialFrame, SpinRamp, OMEGA, YEAR};
+
+/// The Sun's mass multiplied by G in m³/s²
+pub const SUN_GM: f64 = 1.32712440018e20;
+/// Mean distance between the Earth and the Sun in meters
+pub const SUN_DISTANCE: f64 = 1.495978707e11;
+
+/// A simple model of the Sun: moving uniformly along the ecliptic
+#[derive(Debug, Clone, Copy)]
+pub struct Sun {
+    /// Ecliptic longitude of the Sun at t=0 in degrees (0 - March equinox, 90 - June solstice)
+    pub ecliptic_lon: f64,
+    /// Whether the Sun moves along the ecliptic over the year
+    pub annual_motion: bool,
+}
+
+impl Default for Sun {
+    fn default() -> Self {
+        Self {
+            ecliptic_lon: 0.0,
+            annual_motion: true,
+        }
+    }
+}
+
+impl Sun {
+    /// Ecliptic longitude of the Sun at time `t` in degrees
+    pub fn ecliptic_lon(&self, t: f64) -> f64 {
+        if self.annual_motion {
+            self.ecliptic_lon + 360.0 * t / YEAR
+        } else {
+            self.ecliptic_lon
+        }
+    }
+
+    /// Unit vector pointing towards the Sun at time `t` in the frame rotating with `omega`, with
+    /// the Earth's rotation following `spin`
+    pub fn direction(
+        &self,
+        t: f64,
+        omega: f64,
+        spin: Option<SpinRamp>,
+        frame: &CelestialFrame,
+    ) -> Vector3<f64> {
+        let dir = CelestialFrame::ecliptic_direction(self.ecliptic_lon(t), 0.0);
+        frame.ecliptic_to_rotating(t, omega, spin) * dir
+    }
+
+    /// Latitude and longitude of the subsolar point at time `t`, in degrees
+    pub fn subsolar_point(
+        &self,
+        t: f64,
+        spin: Option<SpinRamp>,
+        frame: &CelestialFrame,
+    ) -> (f64, f64) {
+        let dir = self.direction(t, OMEGA, spin, frame);
+        (dir.y.asin().to_degrees(), dir.x.atan2(dir.z).to_degrees())
+    }
+
+    /// Local solar time in hours at the given longitude
+    pub fn local_solar_time(
+        &self,
+        lon: f64,
+        t: f64,
+        spin: Option<SpinRamp>,
+        frame: &CelestialFrame,
+    ) -> f64 {
+        let (_, sub_lon) = self.subsolar_point(t, spin, frame);
+        (12.0 + (lon - sub_lon) / 15.0).rem_euclid(24.0)
+    }
+
+    /// Elevation of the Sun above the local horizon in degrees; `pos` has to be in the Earth's
+    /// frame
+    pub fn elevation(
+        &self,
+        pos: &Vector3<f64>,
+        t: f64,
+        spin: Option<SpinRamp>,
+        frame: &CelestialFrame,
+    ) -> f64 {
+        let up = surface_normal(pos);
+        up.dot(&self.direction(t, OMEGA, spin, frame))
+            .asin()
+            .to_degrees()
+    }
+
+    /// Solar time, elevation and day/night status at a position in the Earth's frame
+    pub fn status(
+        &self,
+        pos: &Vector3<f64>,
+        t: f64,
+        spin: Option<SpinRamp>,
+        frame: &CelestialFrame,
+    ) -> String {
+        let (_, lon, _) = pos_to_lat_lon_elev(*pos);
+        let solar_time = self.local_solar_time(lon, t, spin, frame);
+        let hours = solar_time.floor();
+        let minutes = ((solar_time - hours) * 60.0).floor();
+        let elevation = self.elevation(pos, t, spin, frame);
+        format!(
+            "Solar time: {:02}:{:02}, Sun elevation: {:3.1}° ({})",
+            hours,
+            minutes,
+            elevation,
+            if elevation > 0.0 { "day" } else { "night" }
+        )
+    }
+}
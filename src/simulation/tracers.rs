@@ -0,0 +1,121 @@
+use std::f64::consts::PI;
+
+use nalgebra::Vector3;
+
+use super::{
+    lat_lon_elev_to_vec3,
+    spin::{frame_acceleration, frame_rate},
+    Environment, OMEGA,
+};
+
+/// A large number of passive particles sliding without friction along a sphere in the Earth's
+/// frame, deflected only by the Coriolis force (and the Euler force while the Earth's rotation
+/// changes).
+///
+/// The coordinates are stored as separate arrays, so that the update loop works on contiguous
+/// memory and can be vectorized by the compiler.
+#[derive(Debug, Clone, Default)]
+pub struct Tracers {
+    px: Vec<f64>,
+    py: Vec<f64>,
+    pz: Vec<f64>,
+    vx: Vec<f64>,
+    vy: Vec<f64>,
+    vz: Vec<f64>,
+}
+
+impl Tracers {
+    /// `count` tracers evenly filling a disc of the given radius (in meters) around a point,
+    /// all moving with the same velocity relative to the local east and north
+    pub fn disc(lat: f64, lon: f64, radius: f64, count: usize, vel_e: f64, vel_n: f64) -> Self {
+        let center = lat_lon_elev_to_vec3(lat, lon, 0.0);
+        let r = center.norm();
+        let (east, north) = horizontal_basis(&center);
+
+        let mut tracers = Self::default();
+        // a sunflower pattern fills the disc evenly without randomness
+        let golden_angle = PI * (3.0 - 5.0_f64.sqrt());
+        for i in 0..count {
+            let dist = radius * ((i as f64 + 0.5) / count as f64).sqrt();
+            let ang = golden_angle * i as f64;
+            let pos = (center + east * dist * ang.cos() + north * dist * ang.sin()).normalize() * r;
+            let (east, north) = horizontal_basis(&pos);
+            let vel = east * vel_e + north * vel_n;
+            tracers.px.push(pos.x);
+            tracers.py.push(pos.y);
+            tracers.pz.push(pos.z);
+            tracers.vx.push(vel.x);
+            tracers.vy.push(vel.y);
+            tracers.vz.push(vel.z);
+        }
+        tracers
+    }
+
+    pub fn len(&self) -> usize {
+        self.px.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.px.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Advances the tracers from the time `t` by `dt`, projecting them back onto their sphere
+    /// afterwards. The Coriolis acceleration turns the velocity about the rotation axis without
+    /// changing its size, so it is applied as an exact rotation, which keeps the tracers from
+    /// gaining speed over long runs.
+    pub fn step(&mut self, env: &Environment, t: f64, dt: f64) {
+        let (sin_k, cos_k) = (2.0 * frame_rate(env.spin, OMEGA, t) * dt).sin_cos();
+        let alpha_dt = frame_acceleration(env.spin, OMEGA, t) * dt;
+        let n = self.len();
+        let (px, py, pz) = (&mut self.px[..n], &mut self.py[..n], &mut self.pz[..n]);
+        let (vx, vy, vz) = (&mut self.vx[..n], &mut self.vy[..n], &mut self.vz[..n]);
+        for i in 0..n {
+            // Coriolis acceleration -2Ω×v, rotating (vx, vz) by 2Ω·dt, and Euler acceleration
+            // -dΩ/dt×r with Ω along +Y
+            let r2 = px[i] * px[i] + py[i] * py[i] + pz[i] * pz[i];
+            let r = r2.sqrt();
+
+            let mut nvx = vx[i] * cos_k - vz[i] * sin_k - alpha_dt * pz[i];
+            let mut nvy = vy[i];
+            let mut nvz = vx[i] * sin_k + vz[i] * cos_k + alpha_dt * px[i];
+            // keep the velocity horizontal, which also supplies the centripetal force
+            let radial = (nvx * px[i] + nvy * py[i] + nvz * pz[i]) / r2;
+            nvx -= radial * px[i];
+            nvy -= radial * py[i];
+            nvz -= radial * pz[i];
+
+            let nx = px[i] + nvx * dt;
+            let ny = py[i] + nvy * dt;
+            let nz = pz[i] + nvz * dt;
+            let scale = r / (nx * nx + ny * ny + nz * nz).sqrt();
+
+            px[i] = nx * scale;
+            py[i] = ny * scale;
+            pz[i] = nz * scale;
+            vx[i] = nvx;
+            vy[i] = nvy;
+            vz[i] = nvz;
+        }
+    }
+
+    /// Positions of the tracers in the Earth's frame
+    pub fn positions(&self) -> impl Iterator<Item = Vector3<f64>> + '_ {
+        self.px
+            .iter()
+            .zip(&self.py)
+            .zip(&self.pz)
+            .map(|((x, y), z)| Vector3::new(*x, *y, *z))
+    }
+}
+
+/// The local east and north unit vectors on a sphere
+fn horizontal_basis(pos: &Vector3<f64>) -> (Vector3<f64>, Vector3<f64>) {
+    let up = pos.normalize();
+    let lon = pos.x.atan2(pos.z);
+    let east = Vector3::new(lon.cos(), 0.0, -lon.sin());
+    (east, up.cross(&east))
+}
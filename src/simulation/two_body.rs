@@ -0,0 +1,110 @@
+use std::fmt;
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use super::{GM, MOON_DISTANCE, MOON_GM, SUN_DISTANCE, SUN_GM};
+
+/// A pair of bodies orbiting each other, one of which is the Earth. The simulation coordinates
+/// stay centered on the Earth; in the frame co-rotating with the pair, the other body rests on
+/// the +Z axis in the plane of the equator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TwoBodySystem {
+    EarthMoon,
+    SunEarth,
+}
+
+impl fmt::Display for TwoBodySystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TwoBodySystem::EarthMoon => write!(f, "Earth-Moon"),
+            TwoBodySystem::SunEarth => write!(f, "Sun-Earth"),
+        }
+    }
+}
+
+impl TwoBodySystem {
+    pub const ALL: [TwoBodySystem; 2] = [TwoBodySystem::EarthMoon, TwoBodySystem::SunEarth];
+
+    /// GM of the body other than the Earth
+    pub fn other_gm(&self) -> f64 {
+        match self {
+            TwoBodySystem::EarthMoon => MOON_GM,
+            TwoBodySystem::SunEarth => SUN_GM,
+        }
+    }
+
+    pub fn distance(&self) -> f64 {
+        match self {
+            TwoBodySystem::EarthMoon => MOON_DISTANCE,
+            TwoBodySystem::SunEarth => SUN_DISTANCE,
+        }
+    }
+
+    /// Angular velocity of the co-rotating frame
+    pub fn mean_motion(&self) -> f64 {
+        ((GM + self.other_gm()) / self.distance().powi(3)).sqrt()
+    }
+
+    /// Position of the other body in the co-rotating frame
+    pub fn other_pos(&self) -> Vector3<f64> {
+        Vector3::new(0.0, 0.0, self.distance())
+    }
+
+    /// Acceleration caused by the other body in the co-rotating frame, relative to the Earth.
+    /// Includes the acceleration of the Earth itself towards the other body, which together with
+    /// the centrifugal force about the Earth's center is equivalent to the centrifugal force
+    /// about the barycenter.
+    pub fn perturbation(&self, pos: &Vector3<f64>) -> Vector3<f64> {
+        let other = self.other_pos();
+        let diff = other - pos;
+        let dist = diff.norm();
+        let gm = self.other_gm();
+        gm * diff / dist.powi(3) - gm * other / self.distance().powi(3)
+    }
+
+    /// The five Lagrange points in the co-rotating frame
+    pub fn lagrange_points(&self) -> [Vector3<f64>; 5] {
+        let d = self.distance();
+        let n2 = self.mean_motion().powi(2);
+        let gm2 = self.other_gm();
+        // net acceleration along the Z axis at rest in the co-rotating frame
+        let acc = |z: f64| {
+            -GM * z / z.abs().powi(3) + gm2 * (d - z) / (d - z).abs().powi(3) - gm2 / d / d + n2 * z
+        };
+        let eps = d * 1e-6;
+        let between = find_root(&acc, eps, d - eps);
+        let beyond_other = find_root(&acc, d + eps, 3.0 * d);
+        let beyond_earth = find_root(&acc, -3.0 * d, -eps);
+
+        // L2 lies beyond the lighter body and L3 beyond the heavier one; L4 leads the lighter
+        // body in its orbit by 60° and L5 trails it
+        let (sin, cos) = 60.0_f64.to_radians().sin_cos();
+        let (l2, l3, leading_x) = if self.other_gm() < GM {
+            (beyond_other, beyond_earth, d * sin)
+        } else {
+            (beyond_earth, beyond_other, -d * sin)
+        };
+        [
+            Vector3::new(0.0, 0.0, between),
+            Vector3::new(0.0, 0.0, l2),
+            Vector3::new(0.0, 0.0, l3),
+            Vector3::new(leading_x, 0.0, d * cos),
+            Vector3::new(-leading_x, 0.0, d * cos),
+        ]
+    }
+}
+
+/// Finds a root of `f` in `[a, b]` by bisection; `f(a)` and `f(b)` must have opposite signs
+fn find_root(f: &impl Fn(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let fa_positive = f(a) > 0.0;
+    for _ in 0..200 {
+        let mid = 0.5 * (a + b);
+        if (f(mid) > 0.0) == fa_positive {
+            a = mid;
+        } else {
+            b = mid;
+        }
+    }
+    0.5 * (a + b)
+}
@@ -1,59 +1,76 @@
-use nalgebra::Vector3;
+use nalgebra::{Unit, Vector3};
 
-use super::{Position, GM, OMEGA};
+use super::{rotation, spin::frame_rate, Position, GM, OMEGA};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Velocity {
     vel: Vector3<f64>,
     // angular velocity of the frame of reference
     omega: f64,
+    // the axis the frame of reference rotates about
+    axis: Unit<Vector3<f64>>,
 }
 
 impl Velocity {
     pub fn from_east_north_up(pos: Position, e: f64, n: f64, u: f64) -> Self {
-        let old_omega = pos.omega();
+        let (old_omega, old_axis) = (pos.omega(), pos.axis());
         let pos = pos.to_omega(OMEGA);
-        let eff_grav = pos.grav(GM) + pos.centrifugal();
-        let up = -eff_grav / eff_grav.norm();
-        let lon = pos.pos().x.atan2(pos.pos().z);
-        let east = Vector3::new(lon.cos(), 0.0, -lon.sin());
-        let north = up.cross(&east);
+        let (east, north, up) = east_north_up_basis(pos);
 
         let vel = Self {
             vel: e * east + n * north + u * up,
             omega: OMEGA,
+            axis: Vector3::y_axis(),
         };
 
-        vel.to_omega(pos, old_omega)
+        vel.to_frame(pos, old_omega, old_axis)
     }
 
+    /// Returns the east, north and up components of the velocity relative to the Earth's surface
+    pub fn to_east_north_up(self, pos: Position) -> (f64, f64, f64) {
+        let pos = pos.to_omega(OMEGA);
+        let vel = self.to_omega(pos, OMEGA).vel();
+        let (east, north, up) = east_north_up_basis(pos);
+
+        (vel.dot(&east), vel.dot(&north), vel.dot(&up))
+    }
+
+    /// Transforms the velocity to the frame rotating with `omega` about the Y axis
     pub fn to_omega(self, pos: Position, omega: f64) -> Self {
-        if self.omega == omega {
+        self.to_frame(pos, omega, Vector3::y_axis())
+    }
+
+    /// Transforms the velocity to the frame rotating with `omega` about `axis`
+    pub fn to_frame(self, pos: Position, omega: f64, axis: Unit<Vector3<f64>>) -> Self {
+        if self.omega == omega && self.axis == axis {
             return self;
         }
-        let Velocity {
-            vel,
-            omega: omega_old,
-        } = self;
 
-        let pos = pos.to_omega(omega_old);
+        let pos = pos.to_frame(self.omega, self.axis);
         let t = pos.t();
-        let pos = pos.pos();
-
-        let dw = omega - omega_old;
-        let wt = dw * t;
-        let s = wt.sin();
-        let c = wt.cos();
+        let spin = pos.spin();
+        let rot = rotation::frame_rotation_about(spin, &self.axis, self.omega, &axis, omega, t);
 
-        let z2 = vel.z + pos.x * dw;
-        let x2 = vel.x - pos.z * dw;
-        let vel = Vector3::new(x2 * c - z2 * s, vel.y, x2 * s + z2 * c);
+        let vel = if self.axis == axis {
+            // the velocity of the old frame relative to the new one at this point, then rotated
+            let dw = self.axis.into_inner()
+                * (frame_rate(spin, self.omega, t) - frame_rate(spin, omega, t));
+            rot * (self.vel + dw.cross(&pos.pos()))
+        } else {
+            // through the inertial frame
+            let omega_old = self.axis.into_inner() * frame_rate(spin, self.omega, t);
+            let omega_new = axis.into_inner() * frame_rate(spin, omega, t);
+            let pos_new = rot * pos.pos();
+            rot * (self.vel + omega_old.cross(&pos.pos())) - omega_new.cross(&pos_new)
+        };
 
-        Velocity { vel, omega }
+        Velocity { vel, omega, axis }
     }
 
-    pub fn coriolis(&self) -> Vector3<f64> {
-        let omega_v = Vector3::new(0.0, self.omega, 0.0);
+    /// The Coriolis force per unit mass at the position `pos`, which sets the time and how the
+    /// Earth's rotation changes
+    pub fn coriolis(&self, pos: &Position) -> Vector3<f64> {
+        let omega_v = self.axis.into_inner() * frame_rate(pos.spin(), self.omega, pos.t());
         -2.0 * omega_v.cross(&self.vel)
     }
 
@@ -73,3 +90,13 @@ impl Velocity {
         self.vel *= x;
     }
 }
+
+/// The local east, north and up unit vectors; `pos` has to be in the Earth's frame
+pub(super) fn east_north_up_basis(pos: Position) -> (Vector3<f64>, Vector3<f64>, Vector3<f64>) {
+    let eff_grav = pos.grav(GM) + pos.centrifugal();
+    let up = -eff_grav / eff_grav.norm();
+    let lon = pos.pos().x.atan2(pos.pos().z);
+    let east = Vector3::new(lon.cos(), 0.0, -lon.sin());
+    let north = up.cross(&east);
+    (east, north, up)
+}
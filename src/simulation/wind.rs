@@ -0,0 +1,137 @@
+use nalgebra::Vector3;
+
+use super::{pos_to_lat_lon_elev, surface_normal};
+
+/// Wind velocities relative to the Earth's surface on a grid of times, heights, latitudes and
+/// longitudes, e.g. from a weather reanalysis
+#[derive(Debug, Clone)]
+pub struct WindField {
+    /// The coordinates of the grid, each monotonic in either direction: latitudes and
+    /// longitudes in degrees, heights above the sea level in meters and times in seconds from
+    /// the start of the simulation. The longitudes have to be evenly spaced.
+    lats: Vec<f64>,
+    lons: Vec<f64>,
+    heights: Vec<f64>,
+    times: Vec<f64>,
+    /// The eastward and northward components in m/s, indexed by time, height, latitude and
+    /// longitude, with the longitude changing fastest; NaN where unknown
+    east: Vec<f32>,
+    north: Vec<f32>,
+}
+
+impl WindField {
+    /// `None` if the grid is empty or the numbers of the values don't match it
+    pub fn new(
+        lats: Vec<f64>,
+        lons: Vec<f64>,
+        heights: Vec<f64>,
+        times: Vec<f64>,
+        east: Vec<f32>,
+        north: Vec<f32>,
+    ) -> Option<Self> {
+        let size = lats.len() * lons.len() * heights.len() * times.len();
+        (size > 0 && east.len() == size && north.len() == size).then_some(Self {
+            lats,
+            lons,
+            heights,
+            times,
+            east,
+            north,
+        })
+    }
+
+    /// The size and the extent of the grid, for the user
+    pub fn summary(&self) -> String {
+        let (first, last) = (self.times[0], self.times[self.times.len() - 1]);
+        format!(
+            "{} × {} points, {} level(s), {} time(s) over {:.0} h",
+            self.lats.len(),
+            self.lons.len(),
+            self.heights.len(),
+            self.times.len(),
+            (last - first).abs() / 3600.0
+        )
+    }
+
+    /// The eastward and northward wind in m/s, interpolated linearly between the grid points;
+    /// outside of the grid the values at its edges are used
+    pub fn wind(&self, lat: f64, lon: f64, height: f64, t: f64) -> (f64, f64) {
+        let (n_lat, n_lon) = (self.lats.len(), self.lons.len());
+        let n_height = self.heights.len();
+        let (mut east, mut north, mut total_weight) = (0.0, 0.0, 0.0);
+        for (i_t, w_t) in bracket(&self.times, t) {
+            for (i_h, w_h) in bracket(&self.heights, height) {
+                for (i_lat, w_lat) in bracket(&self.lats, lat) {
+                    for (i_lon, w_lon) in self.lon_bracket(lon) {
+                        let weight = w_t * w_h * w_lat * w_lon;
+                        let index = ((i_t * n_height + i_h) * n_lat + i_lat) * n_lon + i_lon;
+                        let (e, n) = (self.east[index] as f64, self.north[index] as f64);
+                        // the unknown values are left out
+                        if weight > 0.0 && e.is_finite() && n.is_finite() {
+                            east += weight * e;
+                            north += weight * n;
+                            total_weight += weight;
+                        }
+                    }
+                }
+            }
+        }
+        if total_weight > 0.0 {
+            (east / total_weight, north / total_weight)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    /// The wind at the point given in the Earth's frame at the time `t`, as a vector in that
+    /// frame
+    pub fn velocity(&self, pos: &Vector3<f64>, t: f64) -> Vector3<f64> {
+        let (lat, lon, elev) = pos_to_lat_lon_elev(*pos);
+        let (east, north) = self.wind(lat, lon, elev, t);
+        let up = surface_normal(pos);
+        let east_dir = Vector3::y().cross(&up).normalize();
+        let north_dir = up.cross(&east_dir);
+        east * east_dir + north * north_dir
+    }
+
+    /// Like `bracket`, but for the longitudes, which wrap around
+    fn lon_bracket(&self, lon: f64) -> [(usize, f64); 2] {
+        let n = self.lons.len();
+        if n < 2 {
+            return [(0, 1.0), (0, 0.0)];
+        }
+        let first = self.lons[0];
+        let step = (self.lons[n - 1] - first) / (n - 1) as f64;
+        // in steps along the grid from the first longitude
+        let along = ((lon - first) * step.signum()).rem_euclid(360.0) / step.abs();
+        let last = (n - 1) as f64;
+        if along <= last {
+            let i = (along.floor() as usize).min(n - 2);
+            let s = along - i as f64;
+            [(i, 1.0 - s), (i + 1, s)]
+        } else if n as f64 * step.abs() >= 360.0 - 1e-6 {
+            // a global grid: between the last longitude and the first one
+            let s = along - last;
+            [(n - 1, 1.0 - s), (0, s)]
+        } else if along - last < 360.0 / step.abs() - along {
+            [(n - 1, 1.0), (0, 0.0)]
+        } else {
+            [(0, 1.0), (n - 1, 0.0)]
+        }
+    }
+}
+
+/// The indices of the coordinates around `x` with the weights for the linear interpolation
+fn bracket(coords: &[f64], x: f64) -> [(usize, f64); 2] {
+    let n = coords.len();
+    if n < 2 {
+        return [(0, 1.0), (0, 0.0)];
+    }
+    let ascending = coords[n - 1] > coords[0];
+    let i = coords
+        .partition_point(|&c| if ascending { c <= x } else { c >= x })
+        .clamp(1, n - 1);
+    let (a, b) = (coords[i - 1], coords[i]);
+    let s = ((x - a) / (b - a)).clamp(0.0, 1.0);
+    [(i - 1, 1.0 - s), (i, s)]
+}
@@ -1,8 +1,18 @@
 use std::fmt;
 
-use crate::simulation::{Object, Position, Velocity, GM};
+use serde::{Deserialize, Serialize};
 
-use super::utils::*;
+use crate::simulation::{
+    Atmosphere, Completion, Constraint, Link, Object, OrbitalElements, Position, PressureSystem,
+    SpinRamp, SurfaceInteraction, TwoBodySystem, Velocity, GM,
+};
+
+use super::{numbers::parse_number, utils::*, Preset};
+
+/// Restoring acceleration of the Foucault pendulum per meter of displacement, in 1/s²
+pub(super) const PENDULUM_COEFF: f64 = 2e-6;
+/// The density of helium relative to the air at the same pressure and temperature
+const HELIUM_TO_AIR: f64 = 0.138;
 
 #[derive(Clone, PartialEq)]
 pub enum ObjectKindTag {
@@ -11,6 +21,12 @@ pub enum ObjectKindTag {
     Anticyclone,
     Foucault,
     Plane,
+    ConvergingRing,
+    PlumbBob,
+    WeighingScale,
+    Satellite,
+    Balloon,
+    Projectile,
 }
 
 impl fmt::Display for ObjectKindTag {
@@ -21,11 +37,17 @@ impl fmt::Display for ObjectKindTag {
             ObjectKindTag::Anticyclone => write!(f, "Anticyclone"),
             ObjectKindTag::Foucault => write!(f, "Foucault Pendulum"),
             ObjectKindTag::Plane => write!(f, "Plane"),
+            ObjectKindTag::ConvergingRing => write!(f, "Converging ring"),
+            ObjectKindTag::PlumbBob => write!(f, "Plumb bob"),
+            ObjectKindTag::WeighingScale => write!(f, "Weighing scale"),
+            ObjectKindTag::Satellite => write!(f, "Satellite"),
+            ObjectKindTag::Balloon => write!(f, "Balloon"),
+            ObjectKindTag::Projectile => write!(f, "Projectile"),
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum ObjectKind {
     Free {
         vel_n: String,
@@ -34,15 +56,28 @@ pub enum ObjectKind {
         gravity: String,
         friction: String,
         drag: String,
+        charge: String,
     },
     Cyclone {
         n_particles: String,
         radius: String,
         vel: String,
+        /// How much lower the pressure is at the center of the low, in hPa
+        #[serde(default = "default_pressure_difference")]
+        depth: String,
+        /// The radius of the low, in km
+        #[serde(default = "default_pressure_radius")]
+        low_radius: String,
     },
     Anticyclone {
         n_particles: String,
         vel: String,
+        /// How much higher the pressure is at the center of the high, in hPa
+        #[serde(default = "default_pressure_difference")]
+        excess: String,
+        /// The radius of the high, in km
+        #[serde(default = "default_pressure_radius")]
+        high_radius: String,
     },
     Foucault {
         vel: String,
@@ -52,6 +87,54 @@ pub enum ObjectKind {
         vel: String,
         azim: String,
     },
+    ConvergingRing {
+        n_particles: String,
+        radius: String,
+        accel: String,
+    },
+    PlumbBob {
+        exaggeration: String,
+    },
+    WeighingScale {
+        vel_e: String,
+        vel_n: String,
+    },
+    /// An object on a Keplerian orbit; the latitude, longitude and elevation of the description
+    /// are not used
+    Satellite {
+        semi_major_axis: String,
+        eccentricity: String,
+        inclination: String,
+        raan: String,
+        arg_perigee: String,
+        true_anomaly: String,
+    },
+    /// A sealed balloon filled with helium at the launch site, rising until the air gets too
+    /// thin to carry it
+    Balloon {
+        /// The volume of the envelope, in m³
+        volume: String,
+        /// The mass of the envelope and the payload, without the helium, in kg
+        payload: String,
+        /// The drag coefficient times the cross-section, in m²
+        drag_area: String,
+    },
+    /// A shell fired from a gun, given by its muzzle velocity
+    Projectile {
+        speed: String,
+        azim: String,
+        /// The angle of the barrel above the horizon, in degrees
+        angle: String,
+        drag: String,
+    },
+}
+
+fn default_pressure_difference() -> String {
+    "10".to_string()
+}
+
+fn default_pressure_radius() -> String {
+    "1000".to_string()
 }
 
 impl ObjectKind {
@@ -63,6 +146,7 @@ impl ObjectKind {
             gravity: "1".to_string(),
             friction: "0".to_string(),
             drag: "0".to_string(),
+            charge: "0".to_string(),
         }
     }
 
@@ -71,6 +155,8 @@ impl ObjectKind {
             n_particles: "8".to_string(),
             radius: "1000".to_string(),
             vel: "100".to_string(),
+            depth: default_pressure_difference(),
+            low_radius: default_pressure_radius(),
         }
     }
 
@@ -78,6 +164,8 @@ impl ObjectKind {
         Self::Anticyclone {
             n_particles: "8".to_string(),
             vel: "100".to_string(),
+            excess: default_pressure_difference(),
+            high_radius: default_pressure_radius(),
         }
     }
 
@@ -95,6 +183,67 @@ impl ObjectKind {
         }
     }
 
+    pub fn default_converging_ring() -> Self {
+        Self::ConvergingRing {
+            n_particles: "16".to_string(),
+            radius: "1500".to_string(),
+            accel: "0.001".to_string(),
+        }
+    }
+
+    pub fn default_plumb_bob() -> Self {
+        Self::PlumbBob {
+            exaggeration: "100".to_string(),
+        }
+    }
+
+    pub fn default_weighing_scale() -> Self {
+        Self::WeighingScale {
+            vel_e: "0".to_string(),
+            vel_n: "0".to_string(),
+        }
+    }
+
+    pub fn default_satellite() -> Self {
+        Self::Satellite {
+            semi_major_axis: "6778".to_string(),
+            eccentricity: "0".to_string(),
+            inclination: "51.6".to_string(),
+            raan: "0".to_string(),
+            arg_perigee: "0".to_string(),
+            true_anomaly: "0".to_string(),
+        }
+    }
+
+    pub fn default_balloon() -> Self {
+        Self::Balloon {
+            volume: "1000".to_string(),
+            payload: "100".to_string(),
+            drag_area: "50".to_string(),
+        }
+    }
+
+    pub fn default_projectile() -> Self {
+        Self::Projectile {
+            speed: "800".to_string(),
+            azim: "0".to_string(),
+            angle: "45".to_string(),
+            drag: "0".to_string(),
+        }
+    }
+
+    /// A satellite on the orbit given by the elements
+    pub fn satellite(elements: &OrbitalElements) -> Self {
+        Self::Satellite {
+            semi_major_axis: (elements.semi_major_axis / 1e3).to_string(),
+            eccentricity: elements.eccentricity.to_string(),
+            inclination: elements.inclination.to_string(),
+            raan: elements.raan.to_string(),
+            arg_perigee: elements.arg_perigee.to_string(),
+            true_anomaly: elements.true_anomaly.to_string(),
+        }
+    }
+
     pub fn as_tag(&self) -> ObjectKindTag {
         match self {
             ObjectKind::Free { .. } => ObjectKindTag::Free,
@@ -102,45 +251,332 @@ impl ObjectKind {
             ObjectKind::Anticyclone { .. } => ObjectKindTag::Anticyclone,
             ObjectKind::Foucault { .. } => ObjectKindTag::Foucault,
             ObjectKind::Plane { .. } => ObjectKindTag::Plane,
+            ObjectKind::ConvergingRing { .. } => ObjectKindTag::ConvergingRing,
+            ObjectKind::PlumbBob { .. } => ObjectKindTag::PlumbBob,
+            ObjectKind::WeighingScale { .. } => ObjectKindTag::WeighingScale,
+            ObjectKind::Satellite { .. } => ObjectKindTag::Satellite,
+            ObjectKind::Balloon { .. } => ObjectKindTag::Balloon,
+            ObjectKind::Projectile { .. } => ObjectKindTag::Projectile,
         }
     }
 }
 
-#[derive(Clone)]
+/// The frames in which an object's trail is drawn. Trails in frames other than the one the scene
+/// is viewed in are turned so that they end at the object's current position, which shows the
+/// same trajectory as seen from different frames side by side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrailFrames {
+    /// The frame the scene is viewed in
+    pub view: bool,
+    pub inertial: bool,
+    pub earth: bool,
+}
+
+impl Default for TrailFrames {
+    fn default() -> Self {
+        Self {
+            view: true,
+            inertial: false,
+            earth: false,
+        }
+    }
+}
+
+/// What an object does when it falls onto the surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SurfaceMode {
+    Stick,
+    Bounce,
+    Stop,
+}
+
+impl SurfaceMode {
+    pub const ALL: [SurfaceMode; 3] = [SurfaceMode::Stick, SurfaceMode::Bounce, SurfaceMode::Stop];
+}
+
+impl fmt::Display for SurfaceMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SurfaceMode::Stick => write!(f, "Slide along"),
+            SurfaceMode::Bounce => write!(f, "Bounce"),
+            SurfaceMode::Stop => write!(f, "Stop"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ObjectDescription {
+    /// Shown in the object list and as a label in the scene; empty for anonymous objects
+    pub name: String,
     pub lat: String,
     pub lon: String,
     pub elev: String,
     pub color: [f32; 3],
+    pub trail_frames: TrailFrames,
+    /// How far back the trail reaches, in seconds; empty for the whole path
+    pub trail_duration: String,
+    pub trail_fade: bool,
+    /// Remove the object this many seconds after it came to the ground; empty to keep it
+    pub remove_after_landing: String,
+    /// Remove the object once it gets higher than this elevation, in km; empty to keep it
+    pub remove_above: String,
+    /// Keep drawing the trail of the removed object
+    pub keep_trail: bool,
+    pub surface_mode: SurfaceMode,
+    /// The fraction of the vertical velocity kept in a bounce
+    pub restitution: String,
     pub kind: ObjectKind,
+    /// Hidden from the scene by the user
+    pub hidden: bool,
+    /// The path isn't drawn, only the object
+    pub path_hidden: bool,
 }
 
 impl Default for ObjectDescription {
     fn default() -> Self {
         Self {
+            name: String::new(),
             lat: "0".to_string(),
             lon: "0".to_string(),
             elev: "0".to_string(),
             color: [1.0, 0.0, 0.0],
+            trail_frames: Default::default(),
+            trail_duration: String::new(),
+            trail_fade: false,
+            remove_after_landing: String::new(),
+            remove_above: String::new(),
+            keep_trail: false,
+            surface_mode: SurfaceMode::Stick,
+            restitution: "0.5".to_string(),
             kind: ObjectKind::default_free(),
+            hidden: false,
+            path_hidden: false,
         }
     }
 }
 
 impl ObjectDescription {
-    fn lat_f(&self) -> f64 {
-        self.lat.parse().unwrap_or(0.0)
+    /// The parameters of the object as (label, value, unit), in the order of the editor
+    pub fn parameters(&self) -> Vec<(&'static str, &str, &'static str)> {
+        let mut params = vec![];
+        if self.kind.as_tag() != ObjectKindTag::Satellite {
+            params.push(("Latitude", self.lat.as_str(), "°"));
+            params.push(("Longitude", self.lon.as_str(), "°"));
+            params.push(("Elevation", self.elev.as_str(), "m"));
+        }
+        if self.surface_mode == SurfaceMode::Bounce {
+            params.push(("Restitution coefficient", self.restitution.as_str(), ""));
+        }
+        match &self.kind {
+            ObjectKind::Free {
+                vel_e,
+                vel_n,
+                vel_u,
+                friction,
+                drag,
+                gravity,
+                charge,
+            } => {
+                params.push(("Velocity east", vel_e, "m/s"));
+                params.push(("Velocity north", vel_n, "m/s"));
+                params.push(("Velocity up", vel_u, "m/s"));
+                params.push(("Strength of gravity", gravity, "g"));
+                params.push(("Friction coefficient", friction, ""));
+                params.push(("Drag coefficient", drag, ""));
+                params.push(("Charge to mass ratio", charge, "C/kg"));
+            }
+            ObjectKind::Cyclone {
+                n_particles,
+                radius,
+                vel,
+                depth,
+                low_radius,
+            } => {
+                params.push(("Number of particles", n_particles, ""));
+                params.push(("Radius", radius, "km"));
+                params.push(("Velocity", vel, "m/s"));
+                params.push(("Depth of the low", depth, "hPa"));
+                params.push(("Radius of the low", low_radius, "km"));
+            }
+            ObjectKind::ConvergingRing {
+                n_particles,
+                radius,
+                accel,
+            } => {
+                params.push(("Number of particles", n_particles, ""));
+                params.push(("Radius", radius, "km"));
+                params.push(("Inward acceleration", accel, "m/s²"));
+            }
+            ObjectKind::WeighingScale { vel_e, vel_n } => {
+                params.push(("Velocity east", vel_e, "m/s"));
+                params.push(("Velocity north", vel_n, "m/s"));
+            }
+            ObjectKind::Satellite {
+                semi_major_axis,
+                eccentricity,
+                inclination,
+                raan,
+                arg_perigee,
+                true_anomaly,
+            } => {
+                params.push(("Semi-major axis", semi_major_axis, "km"));
+                params.push(("Eccentricity", eccentricity, ""));
+                params.push(("Inclination", inclination, "°"));
+                params.push(("Right ascension of the ascending node", raan, "°"));
+                params.push(("Argument of perigee", arg_perigee, "°"));
+                params.push(("True anomaly", true_anomaly, "°"));
+            }
+            ObjectKind::PlumbBob { exaggeration } => {
+                params.push(("Deviation exaggeration", exaggeration, "×"));
+            }
+            ObjectKind::Balloon {
+                volume,
+                payload,
+                drag_area,
+            } => {
+                params.push(("Volume", volume, "m³"));
+                params.push(("Payload mass", payload, "kg"));
+                params.push(("Drag area", drag_area, "m²"));
+            }
+            ObjectKind::Projectile {
+                speed,
+                azim,
+                angle,
+                drag,
+            } => {
+                params.push(("Muzzle velocity", speed, "m/s"));
+                params.push(("Azimuth", azim, "°"));
+                params.push(("Elevation angle", angle, "°"));
+                params.push(("Drag coefficient", drag, ""));
+            }
+            ObjectKind::Anticyclone {
+                n_particles,
+                vel,
+                excess,
+                high_radius,
+            } => {
+                params.push(("Number of particles", n_particles, ""));
+                params.push(("Velocity", vel, "m/s"));
+                params.push(("Excess pressure of the high", excess, "hPa"));
+                params.push(("Radius of the high", high_radius, "km"));
+            }
+            ObjectKind::Foucault { azim, vel } | ObjectKind::Plane { azim, vel } => {
+                params.push(("Starting azimuth", azim, "°"));
+                params.push(("Starting velocity", vel, "m/s"));
+            }
+        }
+        params
     }
 
-    fn lon_f(&self) -> f64 {
-        self.lon.parse().unwrap_or(0.0)
+    /// Whether the objects created from both descriptions move the same way, i.e. the
+    /// descriptions differ at most in color, name and the look of the trails
+    pub fn same_motion(&self, other: &ObjectDescription) -> bool {
+        self.lat == other.lat
+            && self.lon == other.lon
+            && self.elev == other.elev
+            && self.kind == other.kind
+            && self.surface_interaction() == other.surface_interaction()
     }
 
-    fn elev_f(&self) -> f64 {
-        self.elev.parse().unwrap_or(0.0)
+    pub(super) fn surface_interaction(&self) -> SurfaceInteraction {
+        match self.surface_mode {
+            SurfaceMode::Stick => SurfaceInteraction::Stick,
+            SurfaceMode::Bounce => SurfaceInteraction::Bounce {
+                restitution: self.restitution_f(),
+            },
+            SurfaceMode::Stop => SurfaceInteraction::Stop,
+        }
+    }
+
+    pub(super) fn restitution_f(&self) -> f64 {
+        parse_number(&self.restitution).unwrap_or(0.5)
+    }
+
+    /// The duration of the trail, or `None` for the whole path (also if the field isn't a
+    /// valid duration)
+    pub(super) fn trail_duration_f(&self) -> Option<f64> {
+        parse_number(&self.trail_duration).filter(|duration: &f64| *duration >= 0.0)
+    }
+
+    /// When the object is taken out of the simulation; the fields that aren't valid numbers
+    /// never remove it
+    pub(super) fn completion(&self) -> Completion {
+        Completion {
+            after_landing: parse_number(&self.remove_after_landing)
+                .filter(|delay: &f64| *delay >= 0.0),
+            max_elevation: parse_number(&self.remove_above).map(|km: f64| km * 1e3),
+            keep_trail: self.keep_trail,
+        }
+    }
+
+    pub(super) fn lat_f(&self) -> f64 {
+        parse_number(&self.lat).unwrap_or(0.0)
+    }
+
+    pub(super) fn lon_f(&self) -> f64 {
+        parse_number(&self.lon).unwrap_or(0.0)
+    }
+
+    pub(super) fn elev_f(&self) -> f64 {
+        parse_number(&self.elev).unwrap_or(0.0)
+    }
+
+    /// The low at the center of a cyclone, which the particles at a constant altitude flow
+    /// around
+    pub(super) fn pressure_system(&self) -> Option<PressureSystem> {
+        match &self.kind {
+            ObjectKind::Cyclone {
+                depth, low_radius, ..
+            } => Some(PressureSystem {
+                lat: self.lat_f(),
+                lon: self.lon_f(),
+                depth: -parse_number(depth).unwrap_or(10.0) * 100.0,
+                radius: parse_number(low_radius).unwrap_or(1000.0) * 1000.0,
+            }),
+            ObjectKind::Anticyclone {
+                excess,
+                high_radius,
+                ..
+            } => Some(PressureSystem {
+                lat: self.lat_f(),
+                lon: self.lon_f(),
+                depth: parse_number(excess).unwrap_or(10.0) * 100.0,
+                radius: parse_number(high_radius).unwrap_or(1000.0) * 1000.0,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The volume in m³, the total mass with the helium in kg and the drag area in m² of a
+    /// balloon in the given atmosphere
+    pub(super) fn balloon(&self, atmosphere: Atmosphere) -> Option<(f64, f64, f64)> {
+        match &self.kind {
+            ObjectKind::Balloon {
+                volume,
+                payload,
+                drag_area,
+            } => {
+                // the values rejected by the validation are replaced with the defaults
+                let volume = parse_number(volume)
+                    .filter(|volume: &f64| *volume > 0.0)
+                    .unwrap_or(1000.0);
+                let payload = parse_number(payload)
+                    .filter(|payload: &f64| *payload >= 0.0)
+                    .unwrap_or(100.0);
+                let drag_area = parse_number(drag_area)
+                    .filter(|area: &f64| *area >= 0.0)
+                    .unwrap_or(50.0);
+                let helium = HELIUM_TO_AIR * atmosphere.air_density(self.elev_f()) * volume;
+                Some((volume, payload + helium, drag_area))
+            }
+            _ => None,
+        }
     }
 
-    pub fn into_objects(&self) -> Vec<Object> {
+    /// The objects described, in the given atmosphere
+    pub fn into_objects(&self, atmosphere: Atmosphere) -> Vec<Object> {
         match &self.kind {
             ObjectKind::Free {
                 vel_n,
@@ -149,13 +585,15 @@ impl ObjectDescription {
                 gravity,
                 friction,
                 drag,
+                charge,
             } => {
-                let vel_e = vel_e.parse().unwrap_or(0.0);
-                let vel_n = vel_n.parse().unwrap_or(0.0);
-                let vel_u = vel_u.parse().unwrap_or(0.0);
-                let gravity = gravity.parse().unwrap_or(1.0);
-                let friction = friction.parse().unwrap_or(0.0);
-                let drag = drag.parse().unwrap_or(0.0);
+                let vel_e = parse_number(vel_e).unwrap_or(0.0);
+                let vel_n = parse_number(vel_n).unwrap_or(0.0);
+                let vel_u = parse_number(vel_u).unwrap_or(0.0);
+                let gravity = parse_number(gravity).unwrap_or(1.0);
+                let friction = parse_number(friction).unwrap_or(0.0);
+                let drag = parse_number(drag).unwrap_or(0.0);
+                let charge = parse_number(charge).unwrap_or(0.0);
                 vec![create_object(
                     self.lat_f(),
                     self.lon_f(),
@@ -167,31 +605,36 @@ impl ObjectDescription {
                 .with_color(self.color[0], self.color[1], self.color[2])
                 .with_gm(GM * gravity)
                 .with_friction(friction)
-                .with_drag(drag)]
+                .with_drag(drag)
+                .with_charge(charge)]
             }
             ObjectKind::Cyclone {
                 n_particles,
                 radius,
                 vel,
+                ..
             } => {
-                let n_particles = n_particles.parse().unwrap_or(0);
-                let radius = radius.parse().unwrap_or(0.0);
-                let vel = vel.parse().unwrap_or(0.0);
+                let n_particles = parse_number(n_particles).unwrap_or(0);
+                let radius = parse_number(radius).unwrap_or(0.0);
+                let vel = parse_number(vel).unwrap_or(0.0);
+                // the particles are pulled in by the low, see `pressure_system`
                 cyclone(
                     self.lat_f(),
                     self.lon_f(),
                     self.elev_f(),
                     radius * 1000.0_f64,
-                    2e4,
                     vel,
                     0.0,
                     n_particles,
                     (self.color[0], self.color[1], self.color[2]),
                 )
             }
-            ObjectKind::Anticyclone { n_particles, vel } => {
-                let n_particles = n_particles.parse().unwrap_or(0);
-                let vel = vel.parse().unwrap_or(0.0);
+            ObjectKind::Anticyclone {
+                n_particles, vel, ..
+            } => {
+                let n_particles = parse_number(n_particles).unwrap_or(0);
+                let vel = parse_number(vel).unwrap_or(0.0);
+                // the particles are pushed out by the high, see `pressure_system`
                 anticyclone(
                     self.lat_f(),
                     self.lon_f(),
@@ -203,19 +646,19 @@ impl ObjectDescription {
                 )
             }
             ObjectKind::Foucault { vel, azim } => {
-                let azim = azim.parse().unwrap_or(0.0f64).to_radians();
-                let vel = vel.parse().unwrap_or(0.0);
+                let azim = parse_number(azim).unwrap_or(0.0f64).to_radians();
+                let vel = parse_number(vel).unwrap_or(0.0);
                 let vel_e = vel * azim.sin();
                 let vel_n = vel * azim.cos();
                 vec![
                     create_object(self.lat_f(), self.lon_f(), self.elev_f(), vel_e, vel_n, 0.0)
                         .with_color(self.color[0], self.color[1], self.color[2])
-                        .as_pendulum(2e-6),
+                        .as_pendulum(PENDULUM_COEFF),
                 ]
             }
             ObjectKind::Plane { vel, azim } => {
-                let azim = azim.parse().unwrap_or(0.0f64).to_radians();
-                let vel = vel.parse().unwrap_or(0.0);
+                let azim = parse_number(azim).unwrap_or(0.0f64).to_radians();
+                let vel = parse_number(vel).unwrap_or(0.0);
                 let vel_e = vel * azim.sin();
                 let vel_n = vel * azim.cos();
                 vec![
@@ -225,6 +668,170 @@ impl ObjectDescription {
                         .with_const_alt(self.elev_f()),
                 ]
             }
+            ObjectKind::ConvergingRing {
+                n_particles,
+                radius,
+                accel,
+            } => {
+                let n_particles = parse_number(n_particles).unwrap_or(0);
+                let radius = parse_number(radius).unwrap_or(0.0);
+                let accel = parse_number(accel).unwrap_or(0.0);
+                converging_ring(
+                    self.lat_f(),
+                    self.lon_f(),
+                    self.elev_f(),
+                    radius * 1000.0_f64,
+                    accel,
+                    n_particles,
+                    (self.color[0], self.color[1], self.color[2]),
+                )
+            }
+            ObjectKind::PlumbBob { exaggeration } => {
+                let exaggeration = parse_number(exaggeration).unwrap_or(1.0);
+                vec![
+                    create_object(self.lat_f(), self.lon_f(), self.elev_f(), 0.0, 0.0, 0.0)
+                        .with_color(self.color[0], self.color[1], self.color[2])
+                        .with_radius(50e3)
+                        .with_plumb_line(exaggeration),
+                ]
+            }
+            ObjectKind::WeighingScale { vel_e, vel_n } => {
+                let vel_e = parse_number(vel_e).unwrap_or(0.0);
+                let vel_n = parse_number(vel_n).unwrap_or(0.0);
+                vec![
+                    create_object(self.lat_f(), self.lon_f(), self.elev_f(), vel_e, vel_n, 0.0)
+                        .with_color(self.color[0], self.color[1], self.color[2])
+                        .counteract_coriolis(true)
+                        .with_const_alt(self.elev_f())
+                        .with_weighing_scale(),
+                ]
+            }
+            ObjectKind::Satellite {
+                semi_major_axis,
+                eccentricity,
+                inclination,
+                raan,
+                arg_perigee,
+                true_anomaly,
+            } => {
+                let elements = OrbitalElements {
+                    semi_major_axis: parse_number(semi_major_axis).unwrap_or(6778.0) * 1e3,
+                    eccentricity: parse_number(eccentricity).unwrap_or(0.0),
+                    inclination: parse_number(inclination).unwrap_or(0.0),
+                    raan: parse_number(raan).unwrap_or(0.0),
+                    arg_perigee: parse_number(arg_perigee).unwrap_or(0.0),
+                    true_anomaly: parse_number(true_anomaly).unwrap_or(0.0),
+                };
+                let (pos, vel) = elements.initial_state();
+                vec![Object::new(pos, vel)
+                    .with_color(self.color[0], self.color[1], self.color[2])
+                    .with_radius(100e3)]
+            }
+            ObjectKind::Balloon { .. } => {
+                let (volume, mass, drag_area) = self.balloon(atmosphere).unwrap();
+                vec![
+                    create_object(self.lat_f(), self.lon_f(), self.elev_f(), 0.0, 0.0, 0.0)
+                        .with_color(self.color[0], self.color[1], self.color[2])
+                        .with_drag(0.5 * drag_area / mass)
+                        .with_buoyancy(volume, mass),
+                ]
+            }
+            ObjectKind::Projectile {
+                speed,
+                azim,
+                angle,
+                drag,
+            } => {
+                let speed = parse_number(speed).unwrap_or(0.0);
+                let azim = parse_number(azim).unwrap_or(0.0f64);
+                let angle = parse_number(angle).unwrap_or(45.0f64).to_radians();
+                let drag = parse_number(drag).unwrap_or(0.0);
+                let vel_e = speed * angle.cos() * azim.to_radians().sin();
+                let vel_n = speed * angle.cos() * azim.to_radians().cos();
+                let vel_u = speed * angle.sin();
+                vec![create_object(
+                    self.lat_f(),
+                    self.lon_f(),
+                    self.elev_f(),
+                    vel_e,
+                    vel_n,
+                    vel_u,
+                )
+                .with_color(self.color[0], self.color[1], self.color[2])
+                .with_drag(drag)
+                .with_launch_azimuth(azim)]
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkKind {
+    Spring,
+    Tether,
+}
+
+impl LinkKind {
+    pub const ALL: [LinkKind; 2] = [LinkKind::Spring, LinkKind::Tether];
+}
+
+impl fmt::Display for LinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkKind::Spring => write!(f, "Spring"),
+            LinkKind::Tether => write!(f, "Tether"),
+        }
+    }
+}
+
+/// A spring or a tether between the first objects of two descriptions
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkDescription {
+    /// Indices of the linked object descriptions
+    pub from: usize,
+    pub to: usize,
+    pub kind: LinkKind,
+    /// The rest length of the spring or the length of the tether, in meters; empty for the
+    /// initial distance of the objects
+    pub length: String,
+    /// The spring's restoring acceleration per meter of stretching, in 1/s²
+    pub stiffness: String,
+    /// The spring's damping acceleration per m/s of stretching, in 1/s
+    pub damping: String,
+}
+
+impl Default for LinkDescription {
+    fn default() -> Self {
+        Self {
+            from: 0,
+            to: 1,
+            kind: LinkKind::Spring,
+            length: String::new(),
+            stiffness: "1e-3".to_string(),
+            damping: "0".to_string(),
+        }
+    }
+}
+
+impl LinkDescription {
+    /// The link between the objects with the given indices; `distance` is their initial
+    /// distance
+    pub(super) fn constraint(&self, a: usize, b: usize, distance: f64) -> Constraint {
+        let link = match self.kind {
+            LinkKind::Spring => Link::Spring {
+                stiffness: parse_number(&self.stiffness).unwrap_or(1e-3),
+                damping: parse_number(&self.damping).unwrap_or(0.0),
+            },
+            LinkKind::Tether => Link::Tether,
+        };
+        Constraint {
+            a,
+            b,
+            length: parse_number(&self.length)
+                .filter(|length: &f64| *length >= 0.0)
+                .unwrap_or(distance),
+            link,
         }
     }
 }
@@ -233,6 +840,19 @@ impl ObjectDescription {
 pub struct InitialStateDefinition {
     pub selected_kind: ObjectKindTag,
     pub objects: Vec<ObjectDescription>,
+    pub links: Vec<LinkDescription>,
+    /// The preset the objects were loaded from, if any
+    pub preset: Option<Preset>,
+    /// If set, the other body of the system attracts the objects
+    pub two_body: Option<TwoBodySystem>,
+    /// If set, the Earth's rotation speeds up or slows down during the run
+    pub spin_ramp: Option<SpinRamp>,
+    /// How the density of the air falls off with the elevation
+    pub atmosphere: Atmosphere,
+    /// A two-line element set pasted by the user
+    pub tle: String,
+    /// The outcome of the last attempt to import `tle`
+    pub tle_message: Option<String>,
 }
 
 impl Default for InitialStateDefinition {
@@ -240,6 +860,55 @@ impl Default for InitialStateDefinition {
         Self {
             selected_kind: ObjectKindTag::Free,
             objects: vec![],
+            links: vec![],
+            preset: None,
+            two_body: None,
+            spin_ramp: None,
+            atmosphere: Atmosphere::default(),
+            tle: String::new(),
+            tle_message: None,
+        }
+    }
+}
+
+impl InitialStateDefinition {
+    /// Removes the object description along with its links
+    pub fn remove_object(&mut self, index: usize) {
+        self.objects.remove(index);
+        self.links
+            .retain(|link| link.from != index && link.to != index);
+        for link in &mut self.links {
+            for end in [&mut link.from, &mut link.to] {
+                if *end > index {
+                    *end -= 1;
+                }
+            }
+        }
+    }
+
+    /// Inserts an object description, keeping the links on the objects they were between
+    pub fn insert_object(&mut self, index: usize, obj: ObjectDescription) {
+        self.objects.insert(index, obj);
+        for link in &mut self.links {
+            for end in [&mut link.from, &mut link.to] {
+                if *end >= index {
+                    *end += 1;
+                }
+            }
+        }
+    }
+
+    /// Swaps two object descriptions, keeping the links on the objects they were between
+    pub fn swap_objects(&mut self, i: usize, j: usize) {
+        self.objects.swap(i, j);
+        for link in &mut self.links {
+            for end in [&mut link.from, &mut link.to] {
+                if *end == i {
+                    *end = j;
+                } else if *end == j {
+                    *end = i;
+                }
+            }
         }
     }
 }
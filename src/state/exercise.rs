@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use super::ObjectDescription;
+use crate::simulation::{pos_to_lat_lon_elev, Object, OMEGA};
+
+/// A question about the outcome of a simulation, answered before it runs
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Exercise {
+    pub question: String,
+    pub answers: Vec<String>,
+    /// How the correct answer is determined
+    pub check: Check,
+    /// How long the simulation runs before the answer is checked, in seconds
+    pub duration: f64,
+    /// Objects to simulate; the scenario's objects are used if empty
+    #[serde(default)]
+    pub objects: Vec<ObjectDescription>,
+    /// Shown after the answer has been checked
+    #[serde(default)]
+    pub explanation: Option<String>,
+}
+
+/// The ways of determining the correct answer. The variants other than `Fixed` read the state of
+/// an object at the end of the run and expect two answers, in the order given in the comments.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Check {
+    /// The index of the correct answer
+    Fixed(usize),
+    /// East, west of the given longitude
+    EastOrWest { object: usize, lon: f64 },
+    /// North, south of the given latitude
+    NorthOrSouth { object: usize, lat: f64 },
+    /// Left, right of the initial direction of motion
+    LeftOrRight { object: usize },
+}
+
+impl Check {
+    /// The index of the correct answer, given the simulated objects
+    pub fn correct_answer(&self, objects: &[Object]) -> Option<usize> {
+        let final_lat_lon = |object: usize| {
+            let obj = objects.get(object)?;
+            let (lat, lon, _) = pos_to_lat_lon_elev(obj.pos().to_omega(OMEGA).pos());
+            Some((lat, lon))
+        };
+        match *self {
+            Check::Fixed(answer) => Some(answer),
+            Check::EastOrWest { object, lon } => {
+                let (_, final_lon) = final_lat_lon(object)?;
+                let diff = (final_lon - lon + 540.0).rem_euclid(360.0) - 180.0;
+                Some(if diff > 0.0 { 0 } else { 1 })
+            }
+            Check::NorthOrSouth { object, lat } => {
+                let (final_lat, _) = final_lat_lon(object)?;
+                Some(if final_lat > lat { 0 } else { 1 })
+            }
+            Check::LeftOrRight { object } => {
+                let obj = objects.get(object)?;
+                let start = obj.states_until(0.0).next()?;
+                let start_pos = start.pos().to_omega(OMEGA).pos();
+                let start_vel = start.vel().to_omega(*start.pos(), OMEGA).vel();
+                let displacement = obj.pos().to_omega(OMEGA).pos() - start_pos;
+                // looking from above, a positive component along the local up means a turn to
+                // the left
+                let left = start_vel.cross(&displacement).dot(&start_pos) > 0.0;
+                Some(if left { 0 } else { 1 })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuizPhase {
+    Asking,
+    /// The simulation is running to check the chosen answer
+    Running(usize),
+    Answered {
+        chosen: usize,
+        correct: Option<usize>,
+    },
+    Finished,
+}
+
+/// Progress through the exercises of a scenario
+pub struct Quiz {
+    pub exercises: Vec<Exercise>,
+    pub current: usize,
+    pub phase: QuizPhase,
+    pub score: usize,
+}
+
+impl Quiz {
+    pub fn new(exercises: Vec<Exercise>) -> Self {
+        Self {
+            exercises,
+            current: 0,
+            phase: QuizPhase::Asking,
+            score: 0,
+        }
+    }
+
+    pub fn exercise(&self) -> Option<&Exercise> {
+        self.exercises.get(self.current)
+    }
+}
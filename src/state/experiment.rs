@@ -0,0 +1,155 @@
+use std::{fmt, ops::Range};
+
+use super::{ObjectDescription, ObjectKind};
+use crate::{
+    renderer::colormap,
+    simulation::{pos_to_lat_lon_elev, Object, OMEGA},
+};
+
+/// The parameter of the objects varied between the runs of an experiment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariedParameter {
+    Latitude,
+    /// Only free objects have drag
+    Drag,
+}
+
+impl VariedParameter {
+    pub const ALL: [VariedParameter; 2] = [VariedParameter::Latitude, VariedParameter::Drag];
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            VariedParameter::Latitude => "°",
+            VariedParameter::Drag => "",
+        }
+    }
+
+    /// Sets the parameter of the object; returns whether the object has it
+    fn apply(&self, obj: &mut ObjectDescription, value: f64) -> bool {
+        match (self, &mut obj.kind) {
+            (VariedParameter::Latitude, ObjectKind::Satellite { .. }) => false,
+            (VariedParameter::Latitude, _) => {
+                obj.lat = value.to_string();
+                true
+            }
+            (
+                VariedParameter::Drag,
+                ObjectKind::Free { drag, .. } | ObjectKind::Projectile { drag, .. },
+            ) => {
+                *drag = value.to_string();
+                true
+            }
+            (VariedParameter::Drag, _) => false,
+        }
+    }
+}
+
+impl fmt::Display for VariedParameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VariedParameter::Latitude => write!(f, "Latitude"),
+            VariedParameter::Drag => write!(f, "Drag"),
+        }
+    }
+}
+
+/// Settings of the wizard cloning the current objects into a series of runs
+#[derive(Debug, Clone)]
+pub struct ExperimentWizard {
+    pub parameter: VariedParameter,
+    pub runs: usize,
+    pub from: f64,
+    pub to: f64,
+    /// Why the last experiment couldn't be created
+    pub message: Option<String>,
+}
+
+impl Default for ExperimentWizard {
+    fn default() -> Self {
+        Self {
+            parameter: VariedParameter::Latitude,
+            runs: 5,
+            from: 15.0,
+            to: 75.0,
+            message: None,
+        }
+    }
+}
+
+impl ExperimentWizard {
+    /// Clones the objects once per run, with the parameter spread evenly between `from` and
+    /// `to` and every run in its own color. Returns `None` if none of the objects has the
+    /// parameter.
+    pub fn create(
+        &self,
+        objects: &[ObjectDescription],
+    ) -> Option<(Vec<ObjectDescription>, Experiment)> {
+        let runs = self.runs.max(2);
+        let mut descriptions = Vec::with_capacity(objects.len() * runs);
+        let mut experiment = Experiment {
+            parameter: self.parameter,
+            runs: Vec::with_capacity(runs),
+        };
+        let mut any_varied = false;
+
+        for i in 0..runs {
+            let frac = i as f64 / (runs - 1) as f64;
+            let value = self.from + (self.to - self.from) * frac;
+            let color = colormap(frac);
+            let start = descriptions.len();
+            for obj in objects {
+                let mut obj = obj.clone();
+                any_varied |= self.parameter.apply(&mut obj, value);
+                obj.color = color;
+                descriptions.push(obj);
+            }
+            experiment.runs.push(ExperimentRun {
+                value,
+                color,
+                groups: start..descriptions.len(),
+            });
+        }
+
+        any_varied.then_some((descriptions, experiment))
+    }
+}
+
+/// A series of runs of the same objects differing in a single parameter
+#[derive(Debug, Clone)]
+pub struct Experiment {
+    pub parameter: VariedParameter,
+    pub runs: Vec<ExperimentRun>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExperimentRun {
+    pub value: f64,
+    pub color: [f32; 3],
+    /// The groups (indices of the object descriptions) belonging to the run
+    pub groups: Range<usize>,
+}
+
+/// Where the first object of a run ended up
+#[derive(Debug, Clone, Copy)]
+pub struct RunOutcome {
+    pub lat: f64,
+    pub lon: f64,
+    /// Straight-line distance from the starting point in the Earth's frame, in meters
+    pub displacement: f64,
+}
+
+impl ExperimentRun {
+    pub fn outcome(&self, objects: &[Object], max_t: f64) -> Option<RunOutcome> {
+        let obj = objects
+            .iter()
+            .find(|obj| self.groups.contains(&obj.group()))?;
+        let start = obj.states_until(0.0).next()?.pos().to_omega(OMEGA).pos();
+        let end = obj.state_at(max_t).pos().to_omega(OMEGA).pos();
+        let (lat, lon, _) = pos_to_lat_lon_elev(end);
+        Some(RunOutcome {
+            lat,
+            lon,
+            displacement: (end - start).norm(),
+        })
+    }
+}
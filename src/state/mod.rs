@@ -1,26 +1,281 @@
 mod description;
+mod exercise;
+mod experiment;
+mod numbers;
+mod precompute;
+mod presets;
+mod scenario;
 mod utils;
+mod validation;
 
-use std::fmt;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
 
 use egui::Vec2;
 use glium::glutin;
+use nalgebra::{Isometry3, Vector3};
+use numeric_algs::integration::Integrator;
+use serde::{Deserialize, Serialize};
 
-use crate::simulation::Object;
+use crate::{
+    io::{self, InteractionLog, Recording, WindError},
+    renderer::colormap,
+    simulation::{
+        intercept, lat_lon_elev_to_vec3, pos_to_lat_lon_elev, step_linked, surface_normal,
+        AimError, BenchmarkResult, CelestialFrame, Constraint, Environment, GroundStation,
+        IntegrationMethod, Interception, Launcher, Moon, Object, Shot, Sun, Tracers, WindField, GM,
+        MIN_FLIGHT_ELEVATION, OMEGA,
+    },
+};
 
-pub use description::{InitialStateDefinition, ObjectDescription, ObjectKind, ObjectKindTag};
+pub use description::{
+    InitialStateDefinition, LinkDescription, LinkKind, ObjectDescription, ObjectKind,
+    ObjectKindTag, SurfaceMode, TrailFrames,
+};
+pub use exercise::{Exercise, Quiz, QuizPhase};
+pub use experiment::{Experiment, ExperimentWizard, VariedParameter};
+pub use numbers::DecimalSeparator;
+pub use precompute::Precompute;
+pub use presets::Preset;
+pub use scenario::Scenario;
+pub use validation::{fix_all, validate, InputWarning};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Manual,
+    Azimuth,
+    Latitude,
+    Speed,
+    Group,
+}
+
+impl ColorMode {
+    pub const ALL: [ColorMode; 5] = [
+        ColorMode::Manual,
+        ColorMode::Azimuth,
+        ColorMode::Latitude,
+        ColorMode::Speed,
+        ColorMode::Group,
+    ];
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            ColorMode::Manual | ColorMode::Group => "",
+            ColorMode::Azimuth | ColorMode::Latitude => "°",
+            ColorMode::Speed => "m/s",
+        }
+    }
+}
+
+impl fmt::Display for ColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorMode::Manual => write!(f, "Manual"),
+            ColorMode::Azimuth => write!(f, "Initial azimuth"),
+            ColorMode::Latitude => write!(f, "Initial latitude"),
+            ColorMode::Speed => write!(f, "Initial speed"),
+            ColorMode::Group => write!(f, "Group index"),
+        }
+    }
+}
+
+/// The quantity shown by the color along the objects' paths
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathColorMode {
+    /// The color of the object
+    Object,
+    Speed,
+    Altitude,
+}
+
+impl PathColorMode {
+    pub const ALL: [PathColorMode; 3] = [
+        PathColorMode::Object,
+        PathColorMode::Speed,
+        PathColorMode::Altitude,
+    ];
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            PathColorMode::Object => "",
+            PathColorMode::Speed => "m/s",
+            PathColorMode::Altitude => "km",
+        }
+    }
+}
+
+impl fmt::Display for PathColorMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathColorMode::Object => write!(f, "Object color"),
+            PathColorMode::Speed => write!(f, "Speed relative to the Earth"),
+            PathColorMode::Altitude => write!(f, "Altitude"),
+        }
+    }
+}
+
+/// What is drawn behind the scene
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    Stars,
+    Black,
+    /// Solid green, for chroma keying
+    ChromaKey,
+    /// Fully transparent, if supported by the window system
+    Transparent,
+}
+
+impl Background {
+    pub const ALL: [Background; 4] = [
+        Background::Stars,
+        Background::Black,
+        Background::ChromaKey,
+        Background::Transparent,
+    ];
+
+    /// The color the frame is cleared with
+    pub fn clear_color(&self) -> (f32, f32, f32, f32) {
+        match self {
+            Background::Stars => (0.0, 0.0, 0.02, 1.0),
+            Background::Black => (0.0, 0.0, 0.0, 1.0),
+            Background::ChromaKey => (0.0, 1.0, 0.0, 1.0),
+            Background::Transparent => (0.0, 0.0, 0.0, 0.0),
+        }
+    }
+}
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Background::Stars => write!(f, "Stars"),
+            Background::Black => write!(f, "Black"),
+            Background::ChromaKey => write!(f, "Chroma key (green)"),
+            Background::Transparent => write!(f, "Transparent"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    Perspective,
+    /// Without foreshortening, for figures; the scale matches the perspective one at the
+    /// distance the camera is focused on
+    Orthographic,
+}
+
+impl Projection {
+    pub const ALL: [Projection; 2] = [Projection::Perspective, Projection::Orthographic];
+}
+
+impl fmt::Display for Projection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Projection::Perspective => write!(f, "Perspective"),
+            Projection::Orthographic => write!(f, "Orthographic"),
+        }
+    }
+}
+
+/// How the 2D map shows the surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapProjection {
+    Equirectangular,
+    /// Azimuthal equidistant, centered on the north pole
+    NorthPolar,
+    /// Azimuthal equidistant, centered on the south pole
+    SouthPolar,
+}
+
+impl MapProjection {
+    pub const ALL: [MapProjection; 3] = [
+        MapProjection::Equirectangular,
+        MapProjection::NorthPolar,
+        MapProjection::SouthPolar,
+    ];
+
+    /// The coordinates on the map of a point given in degrees; the polar maps are scaled in
+    /// degrees of latitude from the pole and have the Greenwich meridian pointing down
+    pub fn project(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let (sin, cos) = lon.to_radians().sin_cos();
+        match self {
+            MapProjection::Equirectangular => (lon, lat),
+            MapProjection::NorthPolar => ((90.0 - lat) * sin, -(90.0 - lat) * cos),
+            MapProjection::SouthPolar => ((90.0 + lat) * sin, (90.0 + lat) * cos),
+        }
+    }
+
+    /// The inverse of `project`; `None` for points off the map
+    pub fn unproject(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        match self {
+            MapProjection::Equirectangular => {
+                ((-90.0..=90.0).contains(&y) && (-180.0..=180.0).contains(&x)).then_some((y, x))
+            }
+            MapProjection::NorthPolar => {
+                let dist = x.hypot(y);
+                (dist <= 180.0).then(|| (90.0 - dist, x.atan2(-y).to_degrees()))
+            }
+            MapProjection::SouthPolar => {
+                let dist = x.hypot(y);
+                (dist <= 180.0).then(|| (dist - 90.0, x.atan2(y).to_degrees()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for MapProjection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapProjection::Equirectangular => write!(f, "Equirectangular"),
+            MapProjection::NorthPolar => write!(f, "North polar"),
+            MapProjection::SouthPolar => write!(f, "South polar"),
+        }
+    }
+}
 
 pub struct RenderSettings {
     pub fov: f32,
     pub draw_grid: bool,
     pub draw_solid_surface: bool,
+    pub draw_paths: bool,
+    /// Mark the highest and lowest points of free flights and orbits along the paths
+    pub draw_apsides: bool,
+    /// Draw the paths projected onto the surface
+    pub draw_ground_tracks: bool,
+    pub draw_group_paths: bool,
     pub use_texture: bool,
     pub draw_velocities: bool,
     pub draw_forces: bool,
+    /// Also draw the velocities and forces at every this many stored samples along the paths
+    pub path_arrow_interval: Option<usize>,
+    /// Print the magnitudes of the velocities and forces next to their arrows
+    pub draw_arrow_labels: bool,
+    pub draw_sun: bool,
+    pub draw_field_lines: bool,
+    pub draw_station_cones: bool,
+    /// Draw the names of the named objects next to them
+    pub draw_labels: bool,
+    pub draw_moon: bool,
+    pub draw_tides: bool,
+    pub tide_exaggeration: f64,
     pub vel_scale: f64,
     pub force_scale: f64,
     pub max_t: f64,
-    pub sky_rotation: f64,
+    /// Draw the Euler force along with the other forces while the frame's rotation changes
+    pub draw_euler_force: bool,
+    /// Angular acceleration of the viewing frame caused by changing its rotation during the
+    /// run, in rad/s²
+    pub frame_alpha: f64,
+    pub color_mode: ColorMode,
+    pub path_color_mode: PathColorMode,
+    pub background: Background,
+    pub projection: Projection,
+    /// Latitude and longitude in degrees of the point the objects' ranges and bearings are
+    /// reported from, besides their launch points
+    pub reference_point: Option<(f64, f64)>,
 }
 
 impl Default for RenderSettings {
@@ -29,17 +284,472 @@ impl Default for RenderSettings {
             fov: 45.0,
             draw_grid: true,
             draw_solid_surface: true,
+            draw_paths: true,
+            draw_apsides: true,
+            draw_ground_tracks: false,
+            draw_group_paths: false,
             use_texture: true,
             draw_velocities: false,
             draw_forces: false,
+            path_arrow_interval: None,
+            draw_arrow_labels: false,
+            draw_sun: true,
+            draw_field_lines: false,
+            draw_station_cones: true,
+            draw_labels: true,
+            draw_moon: true,
+            draw_tides: false,
+            tide_exaggeration: 1e6,
             vel_scale: 1e4,
             force_scale: 1e4,
             max_t: 0.0,
-            sky_rotation: 0.0,
+            draw_euler_force: true,
+            frame_alpha: 0.0,
+            color_mode: ColorMode::Manual,
+            path_color_mode: PathColorMode::Object,
+            background: Background::Stars,
+            projection: Projection::Perspective,
+            reference_point: None,
         }
     }
 }
 
+/// Inputs of the analytic Coriolis calculator
+#[derive(Debug, Clone, Copy)]
+pub struct Calculator {
+    pub lat: f64,
+    pub speed: f64,
+    pub flight_time: f64,
+    /// Direction of motion, in degrees clockwise from north
+    pub azimuth: f64,
+}
+
+impl Default for Calculator {
+    fn default() -> Self {
+        Self {
+            lat: 45.0,
+            speed: 100.0,
+            flight_time: 3600.0,
+            azimuth: 0.0,
+        }
+    }
+}
+
+/// The naive shot and the corrected one with the number of iterations, or why there is none
+type AimResult = Result<(Shot, Shot, usize), AimError>;
+
+/// Inputs and the last result of the tool finding the aim that hits a target on the ground
+#[derive(Debug)]
+pub struct AimingTool {
+    pub launcher: Launcher,
+    pub target_lat: f64,
+    pub target_lon: f64,
+    pub result: Option<AimResult>,
+    /// The search running on a background thread
+    solving: Option<JoinHandle<AimResult>>,
+}
+
+impl Default for AimingTool {
+    fn default() -> Self {
+        Self {
+            launcher: Launcher {
+                lat: 45.0,
+                lon: 0.0,
+                speed: 1000.0,
+                drag: 0.0,
+            },
+            target_lat: 45.5,
+            target_lon: 0.5,
+            result: None,
+            solving: None,
+        }
+    }
+}
+
+impl AimingTool {
+    pub fn target(&self) -> Vector3<f64> {
+        lat_lon_elev_to_vec3(self.target_lat, self.target_lon, 0.0)
+    }
+
+    /// Starts searching for the aim on a background thread, as it takes hundreds of simulated
+    /// shots; `poll` picks the result up
+    pub fn solve(&mut self) {
+        let launcher = self.launcher;
+        let target = self.target();
+        self.result = None;
+        self.solving = Some(thread::spawn(move || {
+            let naive = launcher.naive(&target)?;
+            let (corrected, iterations) = launcher.correct(&naive, &target)?;
+            Ok((naive, corrected, iterations))
+        }));
+    }
+
+    pub fn is_solving(&self) -> bool {
+        self.solving.is_some()
+    }
+
+    /// Takes the result of the search once the background thread is done
+    pub fn poll(&mut self) {
+        if !self.solving.as_ref().is_some_and(JoinHandle::is_finished) {
+            return;
+        }
+        let solving = self.solving.take().unwrap();
+        self.result = Some(solving.join().expect("aiming thread panicked"));
+    }
+
+    /// Objects flying the naive and the corrected shots and one marking the target, all
+    /// stopping where they land
+    pub fn objects(&self, naive: &Shot, corrected: &Shot) -> Vec<ObjectDescription> {
+        let launcher = self.launcher;
+        let shot = |name: &str, shot: &Shot, color: [f32; 3]| {
+            let (az, el) = (shot.azimuth.to_radians(), shot.elevation.to_radians());
+            ObjectDescription {
+                name: name.to_string(),
+                lat: launcher.lat.to_string(),
+                lon: launcher.lon.to_string(),
+                color,
+                surface_mode: SurfaceMode::Stop,
+                kind: ObjectKind::Free {
+                    vel_e: (launcher.speed * el.cos() * az.sin()).to_string(),
+                    vel_n: (launcher.speed * el.cos() * az.cos()).to_string(),
+                    vel_u: (launcher.speed * el.sin()).to_string(),
+                    gravity: "1".to_string(),
+                    friction: "0".to_string(),
+                    drag: launcher.drag.to_string(),
+                    charge: "0".to_string(),
+                },
+                ..Default::default()
+            }
+        };
+        vec![
+            shot("Naive aim", naive, [1.0, 0.5, 0.0]),
+            shot("Corrected aim", corrected, [0.0, 1.0, 0.5]),
+            ObjectDescription {
+                name: "Target".to_string(),
+                lat: self.target_lat.to_string(),
+                lon: self.target_lon.to_string(),
+                color: [1.0, 1.0, 1.0],
+                surface_mode: SurfaceMode::Stop,
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+/// Inputs and the last result of the tool finding launches of one object that meet another one
+#[derive(Debug)]
+pub struct InterceptTool {
+    /// Indices of the launched object and of the one it has to meet
+    pub chaser: usize,
+    pub target: usize,
+    /// The range of the flight times tried, in seconds
+    pub min_time: f64,
+    pub max_time: f64,
+    pub candidates: usize,
+    pub result: Vec<Interception>,
+    /// Why there is no result
+    pub message: Option<String>,
+    /// The search running on a background thread
+    solving: Option<JoinHandle<Vec<Interception>>>,
+}
+
+impl Default for InterceptTool {
+    fn default() -> Self {
+        Self {
+            chaser: 0,
+            target: 1,
+            min_time: 600.0,
+            max_time: 3600.0,
+            candidates: 6,
+            result: vec![],
+            message: None,
+            solving: None,
+        }
+    }
+}
+
+impl InterceptTool {
+    /// Starts searching for the launches on a background thread, from copies of the current
+    /// states of the objects; every candidate flies the object many times, which would freeze
+    /// the interface. `poll` picks the result up.
+    pub fn solve(&mut self, objects: &[Object]) {
+        self.discard();
+        let (chaser, target) = match (objects.get(self.chaser), objects.get(self.target)) {
+            (Some(chaser), Some(target)) if self.chaser != self.target => {
+                (chaser.without_history(), target.without_history())
+            }
+            _ => {
+                self.message = Some("Choose two different objects".to_string());
+                return;
+            }
+        };
+        let window = (self.min_time, self.max_time.max(self.min_time));
+        let candidates = self.candidates;
+        self.solving = Some(thread::spawn(move || {
+            intercept(&chaser, &target, window, candidates)
+        }));
+        self.message = Some("Searching...".to_string());
+    }
+
+    pub fn is_solving(&self) -> bool {
+        self.solving.is_some()
+    }
+
+    /// Takes the result of the search once the background thread is done
+    pub fn poll(&mut self) {
+        if !self.solving.as_ref().is_some_and(JoinHandle::is_finished) {
+            return;
+        }
+        let solving = self.solving.take().unwrap();
+        self.result = solving.join().expect("intercept thread panicked");
+        self.message = self
+            .result
+            .is_empty()
+            .then(|| "None of the flight times led to a solution".to_string());
+    }
+
+    /// Forgets the result and the search in progress, which start from states that are gone
+    pub fn discard(&mut self) {
+        self.result.clear();
+        self.solving = None;
+        self.message = None;
+    }
+
+    /// The index of the interception with the lowest launch speed
+    pub fn best(&self) -> Option<usize> {
+        (0..self.result.len()).min_by(|&i, &j| {
+            let speed = |k: usize| self.result[k].velocity.norm();
+            speed(i).total_cmp(&speed(j))
+        })
+    }
+}
+
+/// How fast the simulation advances when it's not locked to the frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedMode {
+    /// A fixed number of time steps per rendered frame
+    StepsPerFrame,
+    /// A fixed number of simulated seconds per second of wall-clock time
+    TimeFactor,
+}
+
+impl SpeedMode {
+    pub const ALL: [SpeedMode; 2] = [SpeedMode::StepsPerFrame, SpeedMode::TimeFactor];
+}
+
+impl fmt::Display for SpeedMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpeedMode::StepsPerFrame => write!(f, "Steps per frame"),
+            SpeedMode::TimeFactor => write!(f, "Simulated seconds per second"),
+        }
+    }
+}
+
+/// Simulation speed, independent of the accuracy of the integration
+#[derive(Debug, Clone, Copy)]
+pub struct SimulationSpeed {
+    pub mode: SpeedMode,
+    pub steps_per_frame: u32,
+    pub time_factor: f64,
+    /// Simulated seconds per second of wall-clock time actually achieved, smoothed over the
+    /// last frames
+    pub measured: f64,
+    /// Reduce the speed around impacts and close approaches of the objects
+    pub slow_motion: bool,
+    /// The fraction of the normal speed the simulation runs at around the events
+    pub slow_motion_factor: f64,
+    /// The event the simulation is currently slowed down for
+    pub slowed_for: Option<SlowMotionEvent>,
+    /// Draw the scene a fraction of a time step back, interpolated between the last two
+    /// states, so that the motion is smooth when there are fewer steps than frames
+    pub interpolate: bool,
+    /// Simulated time owed to the wall clock in the time factor mode or in slow motion
+    pending: f64,
+    /// How far behind the simulation the scene is drawn
+    lag: f64,
+}
+
+impl Default for SimulationSpeed {
+    fn default() -> Self {
+        Self {
+            mode: SpeedMode::TimeFactor,
+            steps_per_frame: 1,
+            time_factor: 600.0,
+            measured: 0.0,
+            slow_motion: false,
+            slow_motion_factor: 0.1,
+            slowed_for: None,
+            interpolate: true,
+            pending: 0.0,
+            lag: 0.0,
+        }
+    }
+}
+
+/// An event the simulation is slowed down around, with the indices of the objects involved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlowMotionEvent {
+    Impact(usize),
+    ClosestApproach(usize, usize),
+}
+
+impl SlowMotionEvent {
+    pub fn describe(&self, objects: &[Object]) -> String {
+        let label = |index: usize| {
+            objects
+                .get(index)
+                .map_or_else(|| format!("Object {}", index), |obj| obj.label(index))
+        };
+        match *self {
+            SlowMotionEvent::Impact(index) => format!("impact of {}", label(index)),
+            SlowMotionEvent::ClosestApproach(i, j) => {
+                format!("closest approach of {} and {}", label(i), label(j))
+            }
+        }
+    }
+}
+
+/// An object taken out of the simulation by its completion rule, kept so that rewinding to
+/// before its removal brings it back
+#[derive(Clone)]
+pub struct RemovedObject {
+    /// The time of the removal
+    pub t: f64,
+    /// The position of the object among the ones of its group at that time
+    index_in_group: usize,
+    /// The constraints dropped with the object, numbered as before the removal
+    constraints: Vec<Constraint>,
+    pub obj: Object,
+}
+
+/// Time constant of the smoothing of the measured simulation speed, in seconds
+const SPEED_SMOOTHING_TIME: f64 = 0.5;
+/// Simulated seconds per second of wall-clock time offered as presets
+pub const SPEED_PRESETS: [f64; 6] = [0.1, 1.0, 10.0, 100.0, 1000.0, 10000.0];
+/// How long before and after an event, in seconds of wall-clock time at the normal speed,
+/// the simulation runs in slow motion
+const SLOW_MOTION_LEAD: f64 = 2.0;
+/// The largest miss distance, in meters, of a closest approach worth slowing down for
+const SLOW_MOTION_APPROACH_DISTANCE: f64 = 50e3;
+/// Above this number of objects, their pairs are not checked for close approaches
+const MAX_SLOW_MOTION_OBJECTS: usize = 100;
+
+impl SimulationSpeed {
+    /// How far behind the simulated time the scene is drawn, in seconds
+    pub fn lag(&self) -> f64 {
+        self.lag
+    }
+
+    /// Updates the measured speed after a frame in which the simulation advanced by `sim_dt`
+    pub fn measure(&mut self, sim_dt: f64, wall_dt: f64) {
+        if wall_dt <= 0.0 {
+            return;
+        }
+        let weight = (wall_dt / SPEED_SMOOTHING_TIME).min(1.0);
+        self.measured += (sim_dt / wall_dt - self.measured) * weight;
+    }
+}
+
+/// Maximum number of steps taken in a single frame in the time factor mode, so that a slow
+/// frame doesn't make the following ones even slower
+const MAX_STEPS_PER_FRAME: u32 = 1000;
+
+/// Parameters of a new cloud of passive tracers
+#[derive(Debug, Clone, Copy)]
+pub struct TracerSettings {
+    pub count: usize,
+    pub lat: f64,
+    pub lon: f64,
+    /// Radius of the cloud in km
+    pub radius: f64,
+    pub vel_e: f64,
+    pub vel_n: f64,
+}
+
+impl Default for TracerSettings {
+    fn default() -> Self {
+        Self {
+            count: 10000,
+            lat: 45.0,
+            lon: 0.0,
+            radius: 500.0,
+            vel_e: 20.0,
+            vel_n: 0.0,
+        }
+    }
+}
+
+/// Replaying the stored paths by animating the rendered time range while the simulation is
+/// paused
+#[derive(Debug, Clone, Copy)]
+pub struct Playback {
+    /// +1 when playing forwards, -1 backwards, 0 when stopped
+    pub direction: f64,
+    /// Simulated seconds per second of wall-clock time
+    pub rate: f64,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            direction: 0.0,
+            rate: 3600.0,
+        }
+    }
+}
+
+/// Settings for recording videos: every rendered frame advances the simulation by exactly the
+/// same amount of simulated time, regardless of how long the frame took to render
+#[derive(Debug, Clone, Copy)]
+pub struct FramePacing {
+    pub enabled: bool,
+    /// Simulated seconds per output frame; split into substeps no longer than the time step
+    pub seconds_per_frame: f64,
+    /// Number of frames rendered since the last reset
+    pub frame: u64,
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seconds_per_frame: 60.0,
+            frame: 0,
+        }
+    }
+}
+
+/// Limit on the memory taken by the stored paths
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryBudget {
+    pub megabytes: f64,
+    /// Whether to decimate the paths when the budget is exceeded, instead of just warning
+    pub auto_decimate: bool,
+    /// How many times the paths were decimated since the last reset
+    pub decimations: u32,
+    pub exceeded: bool,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        Self {
+            megabytes: 1024.0,
+            auto_decimate: true,
+            decimations: 0,
+            exceeded: false,
+        }
+    }
+}
+
+/// The closest the external camera gets to the Earth's center, in meters
+pub const MIN_CAMERA_DISTANCE: f32 = 6378e3;
+/// The farthest the external camera gets from the Earth's center, in meters
+pub const MAX_CAMERA_DISTANCE: f32 = 2e9;
+
+/// How long the camera takes to move to the new view after switching, in seconds, by default
+const CAMERA_TRANSITION_TIME: f64 = 1.0;
+
 #[derive(Debug, Clone, Copy)]
 pub struct ExternalState {
     pub lat: f32,
@@ -47,11 +757,205 @@ pub struct ExternalState {
     pub tilt: f32,
     pub turn: f32,
     pub distance: f32,
+    /// Keep the camera fixed relative to the stars instead of rotating with the frame
+    pub inertial: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct FollowingState {
     pub obj: usize,
+    /// Distance of the camera from the object, in meters
+    pub distance: f32,
+    /// Angle of the camera around the local vertical, measured from behind the object
+    pub azimuth: f32,
+    /// Angle of the camera above the local horizontal
+    pub elevation: f32,
+    /// View from the object itself, looking forward
+    pub first_person: bool,
+}
+
+impl Default for FollowingState {
+    fn default() -> Self {
+        Self {
+            obj: 0,
+            distance: 2e6,
+            azimuth: 0.0,
+            elevation: 0.4,
+            first_person: false,
+        }
+    }
+}
+
+/// A point typed by the user for the external camera to move above
+#[derive(Debug, Clone, Copy)]
+pub struct CameraJump {
+    /// In degrees
+    pub lat: f64,
+    pub lon: f64,
+    /// Height of the camera above the surface, in kilometers
+    pub altitude: f64,
+}
+
+impl Default for CameraJump {
+    fn default() -> Self {
+        Self {
+            lat: 0.0,
+            lon: 0.0,
+            altitude: 20000.0,
+        }
+    }
+}
+
+/// How long the automatic orbit waits after the user last moved the camera before resuming, in
+/// seconds
+const AUTO_ORBIT_RESUME_DELAY: f64 = 5.0;
+/// How long the automatic orbit takes to speed up to or slow down from its full rate, in seconds
+const AUTO_ORBIT_EASE_TIME: f64 = 2.0;
+
+/// A slow, continuous orbit of the camera around the Earth or the followed object, e.g. for
+/// background footage
+#[derive(Debug, Clone, Copy)]
+pub struct AutoOrbit {
+    pub enabled: bool,
+    /// In degrees per second of wall time
+    pub rate: f32,
+    /// Distance of the camera from the center of the orbit, in kilometers
+    pub radius: f32,
+    /// Fraction of the full rate the camera currently moves at, eased between 0 and 1
+    speed: f32,
+    /// Wall time since the user last moved the camera by hand, in seconds
+    idle: f64,
+}
+
+impl Default for AutoOrbit {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rate: 5.0,
+            radius: 40000.0,
+            speed: 0.0,
+            idle: AUTO_ORBIT_RESUME_DELAY,
+        }
+    }
+}
+
+/// A saved setting of the external camera; the angles are in degrees and the distance in
+/// kilometers, so that bookmarks can be written by hand in scenario files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub lat: f32,
+    pub lon: f32,
+    pub tilt: f32,
+    pub turn: f32,
+    pub distance: f32,
+}
+
+impl Default for CameraBookmark {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            lat: 0.0,
+            lon: 0.0,
+            tilt: 0.0,
+            turn: 0.0,
+            distance: 60e3,
+        }
+    }
+}
+
+impl CameraBookmark {
+    pub fn new(name: String, external: &ExternalState) -> Self {
+        Self {
+            name,
+            lat: external.lat.to_degrees(),
+            lon: external.lon.to_degrees(),
+            tilt: external.tilt.to_degrees(),
+            turn: external.turn.to_degrees(),
+            distance: external.distance / 1e3,
+        }
+    }
+
+    /// The external camera set as in the bookmark
+    fn apply(&self, external: &ExternalState) -> ExternalState {
+        ExternalState {
+            lat: self.lat.to_radians(),
+            lon: self.lon.to_radians(),
+            tilt: self.tilt.to_radians(),
+            turn: self.turn.to_radians(),
+            distance: self.distance * 1e3,
+            inertial: external.inertial,
+        }
+    }
+}
+
+/// The rotation rates of the reference frame always offered on the slider
+pub const BUILTIN_FRAME_ROTATIONS: [(&str, f64); 2] = [("Inertial", 0.0), ("Earth", 1.0)];
+/// How close to a named rotation rate the slider has to be dragged to snap to it
+const FRAME_ROTATION_SNAP: f64 = 0.01;
+/// The change of the rotation rate of the frame with a key press, and with Shift held
+pub const FRAME_ROTATION_NUDGE: f64 = 0.01;
+pub const FRAME_ROTATION_FINE_NUDGE: f64 = 0.001;
+
+/// A rotation rate of the reference frame saved under a name, as a fraction of the Earth's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameRotationStop {
+    pub name: String,
+    pub omega: f64,
+}
+
+impl Default for FrameRotationStop {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            omega: 1.0,
+        }
+    }
+}
+
+/// A smooth move of the external camera between two settings, e.g. to a bookmark
+#[derive(Debug, Clone, Copy)]
+pub struct CameraAnimation {
+    pub from: ExternalState,
+    pub to: ExternalState,
+    /// Goes from 0 to 1 over the duration of the animation
+    pub progress: f64,
+}
+
+impl CameraAnimation {
+    /// The camera settings at the current point of the animation, eased in and out; the
+    /// longitude goes the shorter way around and the distance changes geometrically, so that
+    /// zooming feels uniform
+    fn current(&self) -> ExternalState {
+        let p = self.progress.clamp(0.0, 1.0) as f32;
+        let s = p * p * (3.0 - 2.0 * p);
+        let lerp = |a: f32, b: f32| a + (b - a) * s;
+        let mut d_lon = (self.to.lon - self.from.lon) % std::f32::consts::TAU;
+        if d_lon > std::f32::consts::PI {
+            d_lon -= std::f32::consts::TAU;
+        } else if d_lon < -std::f32::consts::PI {
+            d_lon += std::f32::consts::TAU;
+        }
+        ExternalState {
+            lat: lerp(self.from.lat, self.to.lat),
+            lon: self.from.lon + d_lon * s,
+            tilt: lerp(self.from.tilt, self.to.tilt),
+            turn: lerp(self.from.turn, self.to.turn),
+            distance: lerp(self.from.distance.ln(), self.to.distance.ln()).exp(),
+            inertial: self.to.inertial,
+        }
+    }
+}
+
+/// A smooth change of the view after switching the camera
+#[derive(Debug, Clone, Copy)]
+pub struct CameraTransition {
+    /// The view at the moment of switching
+    pub from: Isometry3<f64>,
+    /// Goes from 0 to 1 over the duration of the transition
+    pub progress: f64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -65,6 +969,14 @@ pub struct CameraState {
     pub tag: StateTag,
     pub external: ExternalState,
     pub following: FollowingState,
+    pub transition: Option<CameraTransition>,
+    pub animation: Option<CameraAnimation>,
+    /// Duration of the transitions and animations of the camera, in seconds
+    pub animation_time: f64,
+    /// Keep the external camera's up direction towards the north pole, so that tilting and
+    /// turning it doesn't roll the view
+    pub north_up: bool,
+    pub auto_orbit: AutoOrbit,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -83,14 +995,36 @@ impl fmt::Display for CameraStateDef {
 }
 
 impl CameraState {
+    /// Moving the camera by hand ends the animation and pauses the automatic orbit
+    fn take_over(&mut self) {
+        self.animation = None;
+        self.auto_orbit.idle = 0.0;
+    }
+
     pub fn drag(&mut self, drag_delta: Vec2) {
-        if self.tag == StateTag::External {
-            self.external.lat = (self.external.lat + drag_delta.y * 0.01).clamp(-1.57, 1.57);
-            self.external.lon = (self.external.lon - drag_delta.x * 0.01) % 6.2831853;
+        if drag_delta == Vec2::ZERO {
+            return;
+        }
+        self.take_over();
+        match self.tag {
+            StateTag::External => {
+                self.external.lat = (self.external.lat + drag_delta.y * 0.01).clamp(-1.57, 1.57);
+                self.external.lon = (self.external.lon - drag_delta.x * 0.01) % 6.2831853;
+            }
+            StateTag::Following => {
+                self.following.elevation =
+                    (self.following.elevation + drag_delta.y * 0.01).clamp(-1.5, 1.5);
+                self.following.azimuth =
+                    (self.following.azimuth - drag_delta.x * 0.01) % std::f32::consts::TAU;
+            }
         }
     }
 
     pub fn shift_drag(&mut self, drag_delta: Vec2) {
+        if drag_delta == Vec2::ZERO {
+            return;
+        }
+        self.take_over();
         if self.tag == StateTag::External {
             self.external.tilt = (self.external.tilt + drag_delta.y * 0.01).clamp(-1.57, 1.57);
             self.external.turn = (self.external.turn + drag_delta.x * 0.01).clamp(-3.14, 3.14);
@@ -99,13 +1033,19 @@ impl CameraState {
 
     pub fn scroll(&mut self, scroll: glutin::event::MouseScrollDelta) {
         use glutin::event::MouseScrollDelta::*;
+        self.take_over();
         match scroll {
-            LineDelta(_x, y) => {
-                if self.tag == StateTag::External {
-                    self.external.distance =
-                        (self.external.distance / 2.0_f32.powf(y as f32 * 0.2)).clamp(6378e3, 2e9);
+            LineDelta(_x, y) => match self.tag {
+                StateTag::External => {
+                    self.external.distance = (self.external.distance
+                        / 2.0_f32.powf(y as f32 * 0.2))
+                    .clamp(MIN_CAMERA_DISTANCE, MAX_CAMERA_DISTANCE);
                 }
-            }
+                StateTag::Following => {
+                    self.following.distance =
+                        (self.following.distance / 2.0_f32.powf(y * 0.2)).clamp(1e3, 5e7);
+                }
+            },
             PixelDelta(pos) => {
                 println!("PixelDelta({:?})", pos);
             }
@@ -119,6 +1059,129 @@ impl CameraState {
         }
     }
 
+    /// Whether the object is not drawn, because the camera is inside it
+    pub fn hides_object(&self, index: usize) -> bool {
+        self.tag == StateTag::Following
+            && self.following.first_person
+            && self.following.obj == index
+    }
+
+    /// Switches the camera, moving smoothly from the view `from` it had until now
+    pub fn switch_to(&mut self, cam_def: CameraStateDef, from: Isometry3<f64>) {
+        if cam_def == self.as_def() {
+            return;
+        }
+        self.set_from_def(cam_def);
+        self.start_transition(from);
+    }
+
+    /// Makes the camera move smoothly from the view `from` to the one of its current settings
+    pub fn start_transition(&mut self, from: Isometry3<f64>) {
+        self.transition = Some(CameraTransition {
+            from,
+            progress: 0.0,
+        });
+    }
+
+    /// Moves the camera smoothly to the bookmarked setting
+    pub fn go_to_bookmark(&mut self, bookmark: &CameraBookmark, from: Isometry3<f64>) {
+        self.animate_to(bookmark.apply(&self.external), from);
+    }
+
+    /// Moves the external camera smoothly to the given setting; `from` is the current view, in
+    /// case the camera has to be switched to the external one first
+    pub fn animate_to(&mut self, to: ExternalState, from: Isometry3<f64>) {
+        if self.tag == StateTag::External {
+            self.animation = Some(CameraAnimation {
+                from: self.external,
+                to,
+                progress: 0.0,
+            });
+        } else {
+            self.tag = StateTag::External;
+            self.external = to;
+            self.start_transition(from);
+        }
+    }
+
+    /// Moves the camera smoothly back to looking straight at the Earth or the followed object
+    pub fn reset_orientation(&mut self, from: Isometry3<f64>) {
+        match self.tag {
+            StateTag::External => {
+                let to = ExternalState {
+                    tilt: 0.0,
+                    turn: 0.0,
+                    ..self.external
+                };
+                self.animate_to(to, from);
+            }
+            StateTag::Following => {
+                let default = FollowingState::default();
+                self.following.azimuth = default.azimuth;
+                self.following.elevation = default.elevation;
+                self.start_transition(from);
+            }
+        }
+    }
+
+    pub fn update_transition(&mut self, wall_dt: f64) {
+        let step = wall_dt / self.animation_time.max(1e-3);
+        if let Some(transition) = self.transition.as_mut() {
+            transition.progress += step;
+            if transition.progress >= 1.0 {
+                self.transition = None;
+            }
+        }
+        if let Some(animation) = self.animation.as_mut() {
+            animation.progress += step;
+            self.external = animation.current();
+            // switching away from the external camera ends the animation
+            if animation.progress >= 1.0 || self.tag != StateTag::External {
+                self.animation = None;
+            }
+        }
+        self.update_auto_orbit(wall_dt);
+    }
+
+    /// Turns the camera around the Earth or the followed object while the automatic orbit is
+    /// on, easing in when it starts or resumes after the user moved the camera, and out when it
+    /// is turned off or the user takes over
+    fn update_auto_orbit(&mut self, wall_dt: f64) {
+        let orbit = &mut self.auto_orbit;
+        orbit.idle += wall_dt;
+        let target = if orbit.enabled && orbit.idle >= AUTO_ORBIT_RESUME_DELAY {
+            1.0
+        } else {
+            0.0
+        };
+        let ease = (wall_dt / AUTO_ORBIT_EASE_TIME) as f32;
+        orbit.speed = if orbit.speed < target {
+            (orbit.speed + ease).min(target)
+        } else {
+            (orbit.speed - ease).max(target)
+        };
+        if orbit.speed <= 0.0 || self.animation.is_some() {
+            return;
+        }
+        let s = orbit.speed * orbit.speed * (3.0 - 2.0 * orbit.speed);
+        let turn = orbit.rate.to_radians() * s * wall_dt as f32;
+        // the distance approaches the radius exponentially, on the same time scale as the easing
+        let approach = s * (1.0 - (-wall_dt / AUTO_ORBIT_EASE_TIME).exp() as f32);
+        let radius = orbit.radius * 1e3;
+        match self.tag {
+            StateTag::External => {
+                self.external.lon = (self.external.lon + turn) % std::f32::consts::TAU;
+                let distance = radius.clamp(MIN_CAMERA_DISTANCE, MAX_CAMERA_DISTANCE);
+                self.external.distance += (distance - self.external.distance) * approach;
+            }
+            StateTag::Following => {
+                self.following.azimuth = (self.following.azimuth + turn) % std::f32::consts::TAU;
+                let distance = radius.clamp(1e3, 5e7);
+                self.following.distance += (distance - self.following.distance) * approach;
+            }
+        }
+    }
+
     pub fn set_from_def(&mut self, cam_def: CameraStateDef) {
         match cam_def {
             CameraStateDef::External => {
@@ -139,10 +1202,93 @@ pub struct State {
     pub camera_state: CameraState,
     pub running: bool,
     pub time_step: f64,
+    pub integration: IntegrationMethod,
     pub objects: Vec<Object>,
+    /// The objects removed by their completion rules, in the order of the removals; they
+    /// aren't simulated anymore, but their trails may still be drawn
+    pub removed_objects: Vec<RemovedObject>,
+    /// The springs and tethers between the objects
+    pub constraints: Vec<Constraint>,
+    /// The object picked in the scene with the mouse
+    pub selected_object: Option<usize>,
+    pub tracers: Tracers,
+    /// The Earth's spin, the wind, the pressure systems and the atmosphere, shared by the
+    /// objects and the tracers
+    pub environment: Arc<Environment>,
+    pub tracer_settings: TracerSettings,
     pub current_state_def: InitialStateDefinition,
     pub new_state_def: Option<InitialStateDefinition>,
+    /// Problems found in `new_state_def` when the user tried to apply it
+    pub input_warnings: Vec<InputWarning>,
     pub render_settings: RenderSettings,
+    pub show_ring_diagnostics: bool,
+    pub show_ground_stations: bool,
+    pub show_calculator: bool,
+    pub show_aiming_tool: bool,
+    pub aiming_tool: AimingTool,
+    pub show_intercept_tool: bool,
+    pub intercept_tool: InterceptTool,
+    /// Show the ground tracks on a 2D map of the Earth
+    pub show_map: bool,
+    pub map_projection: MapProjection,
+    /// Hide all the windows and render only the scene
+    pub hide_ui: bool,
+    /// Shown even when the rest of the interface is hidden
+    pub show_status_bar: bool,
+    /// Show the heading and the artificial horizon in the cameras following objects
+    pub show_attitude: bool,
+    pub frame_pacing: FramePacing,
+    pub speed: SimulationSpeed,
+    pub playback: Playback,
+    pub memory_budget: MemoryBudget,
+    pub calculator: Calculator,
+    pub quiz: Option<Quiz>,
+    pub experiment_wizard: ExperimentWizard,
+    /// The runs created by the wizard, shown in the comparison window
+    pub experiment: Option<Experiment>,
+    /// Simulated time up to which the "precompute" action integrates
+    pub precompute_until: f64,
+    pub precompute: Option<Precompute>,
+    /// Results of the last run of the micro-benchmarks
+    pub benchmarks: Vec<BenchmarkResult>,
+    /// Where the paths are exported to in the KML format
+    pub kml_path: String,
+    pub camera_jump: CameraJump,
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    /// The name for the next saved bookmark, as typed by the user
+    pub bookmark_name: String,
+    /// Rotation rates of the frame saved by the user, next to the built-in ones
+    pub frame_rotation_stops: Vec<FrameRotationStop>,
+    /// The name for the next saved rotation rate, as typed by the user
+    pub frame_rotation_name: String,
+    /// Where the HTML report of the run is exported
+    pub report_path: String,
+    /// Result of the last export, shown to the user
+    pub export_message: Option<String>,
+    /// Which object blew up in the integration and why; the simulation is paused when it
+    /// happens
+    pub blowup_message: Option<String>,
+    /// Start the objects added or changed during a run at the current time, instead of
+    /// simulating them from the start; a reset still starts all of them at 0
+    pub soft_start: bool,
+    /// How the numbers are displayed to the user
+    pub decimal_separator: DecimalSeparator,
+    /// The GRIB2 or NetCDF file the wind is loaded from
+    pub wind_path: String,
+    /// What was loaded from the wind file, or why it failed
+    pub wind_message: Option<String>,
+    /// The wind file being read on a background thread, with its path
+    pub wind_loading: Option<(String, JoinHandle<Result<WindField, WindError>>)>,
+    /// Save the next rendered frame of the scene to a PNG file
+    pub screenshot_requested: bool,
+    pub interaction_log: InteractionLog,
+    pub recording: Recording,
+    pub celestial: CelestialFrame,
+    pub sun: Sun,
+    pub moon: Moon,
+    pub stations: Vec<GroundStation>,
+    /// The simulation time and the rotation of the viewing frame when it was last measured
+    last_frame_omega: (f64, f64),
 }
 
 impl Default for State {
@@ -159,30 +1305,1156 @@ impl Default for State {
                     tilt: 0.0,
                     turn: 0.0,
                     distance: 60e6,
+                    inertial: false,
                 },
-                following: FollowingState { obj: 0 },
+                following: Default::default(),
+                transition: None,
+                animation: None,
+                animation_time: CAMERA_TRANSITION_TIME,
+                north_up: false,
+                auto_orbit: Default::default(),
             },
             running: false,
             time_step: 10.0,
+            integration: IntegrationMethod::RungeKutta4,
             objects: vec![],
+            removed_objects: vec![],
+            constraints: vec![],
+            selected_object: None,
+            tracers: Default::default(),
+            environment: Default::default(),
+            tracer_settings: Default::default(),
             current_state_def: Default::default(),
             new_state_def: None,
+            input_warnings: vec![],
             render_settings: Default::default(),
+            show_ring_diagnostics: false,
+            show_ground_stations: false,
+            show_calculator: false,
+            show_aiming_tool: false,
+            aiming_tool: Default::default(),
+            show_intercept_tool: false,
+            intercept_tool: Default::default(),
+            show_map: false,
+            map_projection: MapProjection::Equirectangular,
+            hide_ui: false,
+            show_status_bar: true,
+            show_attitude: true,
+            frame_pacing: Default::default(),
+            speed: Default::default(),
+            playback: Default::default(),
+            memory_budget: Default::default(),
+            calculator: Default::default(),
+            quiz: None,
+            experiment_wizard: Default::default(),
+            experiment: None,
+            precompute_until: 7.0 * 86400.0,
+            precompute: None,
+            benchmarks: vec![],
+            kml_path: "paths.kml".to_string(),
+            camera_jump: Default::default(),
+            camera_bookmarks: vec![],
+            bookmark_name: String::new(),
+            frame_rotation_stops: vec![FrameRotationStop {
+                // the sidereal day of Mars is 88642.66 s
+                name: "Mars rate".to_string(),
+                omega: 0.97204,
+            }],
+            frame_rotation_name: String::new(),
+            report_path: "report.html".to_string(),
+            export_message: None,
+            blowup_message: None,
+            soft_start: false,
+            decimal_separator: DecimalSeparator::from_locale(),
+            wind_path: "wind.grib2".to_string(),
+            wind_message: None,
+            wind_loading: None,
+            screenshot_requested: false,
+            interaction_log: Default::default(),
+            recording: Default::default(),
+            celestial: Default::default(),
+            sun: Default::default(),
+            moon: Default::default(),
+            stations: vec![],
+            last_frame_omega: (0.0, 0.0),
         }
     }
 }
 
 impl State {
+    /// Whether the object is drawn: neither hidden by the user nor containing the camera
+    pub fn shows_object(&self, index: usize) -> bool {
+        !self.camera_state.hides_object(index)
+            && self.objects.get(index).is_some_and(|obj| !obj.is_hidden())
+    }
+
+    /// Shows or hides an object and its path, and remembers it in the description of its
+    /// group (and in the one being edited), so that it survives rebuilding the objects
+    pub fn set_visibility(&mut self, index: usize, shown: bool, path_shown: bool) {
+        let obj = match self.objects.get_mut(index) {
+            Some(obj) => obj,
+            None => return,
+        };
+        obj.set_hidden(!shown);
+        obj.set_path_hidden(!path_shown);
+        let group = obj.group();
+        let edited = self.new_state_def.as_mut().map(|def| &mut def.objects);
+        for objects in std::iter::once(&mut self.current_state_def.objects).chain(edited) {
+            if let Some(def) = objects.get_mut(group) {
+                def.hidden = !shown;
+                def.path_hidden = !path_shown;
+            }
+        }
+    }
+
     pub fn reset_state(&mut self) {
         self.t = 0.0;
         self.ang = 0.0;
         self.omega = 1.0;
         self.render_settings.max_t = 0.0;
+        self.render_settings.frame_alpha = 0.0;
+        self.last_frame_omega = (0.0, 0.0);
+        self.frame_pacing.frame = 0;
+        self.memory_budget.decimations = 0;
+        self.memory_budget.exceeded = false;
+        // the objects being precomputed are replaced, so the result would be stale; dropping
+        // it stops the threads
+        self.precompute = None;
 
         self.objects = vec![];
-        for object_def in self.current_state_def.objects.iter() {
-            let objects = object_def.into_objects();
+        self.removed_objects = vec![];
+        self.constraints = vec![];
+        self.selected_object = None;
+        // the interceptions start from the discarded states
+        self.intercept_tool.discard();
+        self.blowup_message = None;
+        self.environment = Arc::new(self.described_environment());
+        self.tracers.clear();
+        let two_body = self.current_state_def.two_body;
+        for (group, object_def) in self.current_state_def.objects.iter().enumerate() {
+            let objects = object_def
+                .into_objects(self.environment.atmosphere)
+                .into_iter()
+                .map(|obj| {
+                    obj.with_group(group)
+                        .with_third_body(two_body)
+                        .with_name(&object_def.name)
+                        .with_trail_frames(object_def.trail_frames)
+                        .with_trail_length(object_def.trail_duration_f(), object_def.trail_fade)
+                        .with_surface_interaction(object_def.surface_interaction())
+                        .with_completion(object_def.completion())
+                        .with_visibility(object_def.hidden, object_def.path_hidden)
+                        .with_environment(self.environment.clone())
+                });
+            self.objects.extend(objects);
+        }
+
+        // the links are between the first objects of the descriptions
+        let mut first_objects = vec![None; self.current_state_def.objects.len()];
+        for (index, obj) in self.objects.iter().enumerate().rev() {
+            first_objects[obj.group()] = Some(index);
+        }
+        for link in &self.current_state_def.links {
+            let ends = (
+                first_objects.get(link.from).copied().flatten(),
+                first_objects.get(link.to).copied().flatten(),
+            );
+            if let (Some(a), Some(b)) = ends {
+                if a == b {
+                    continue;
+                }
+                let distance = (self.objects[b].pos().to_omega(0.0).pos()
+                    - self.objects[a].pos().to_omega(0.0).pos())
+                .norm();
+                self.constraints.push(link.constraint(a, b, distance));
+            }
+        }
+    }
+
+    /// Applies an edited initial state. Objects whose descriptions didn't change (other than in
+    /// color) keep their trajectories; only the new and modified ones are integrated from the
+    /// start up to the current time, on background threads, or start at the current time with
+    /// `soft_start`.
+    pub fn apply_state_def(&mut self, new_def: InitialStateDefinition) {
+        let old_def = std::mem::replace(&mut self.current_state_def, new_def);
+        // the runs no longer correspond to the objects
+        self.experiment = None;
+        // the linked objects can't be integrated separately
+        if self.t == 0.0
+            || self.precompute.is_some()
+            || !old_def.links.is_empty()
+            || !self.current_state_def.links.is_empty()
+            || old_def.two_body != self.current_state_def.two_body
+            || old_def.spin_ramp != self.current_state_def.spin_ramp
+            || old_def.atmosphere != self.current_state_def.atmosphere
+        {
+            self.reset_state();
+            return;
+        }
+
+        // the cyclones and anticyclones may have been added, moved or removed
+        self.set_environment(self.described_environment());
+
+        let mut old_groups: Vec<Option<Vec<Object>>> =
+            old_def.objects.iter().map(|_| Some(vec![])).collect();
+        // the objects may end up in a different order
+        self.selected_object = None;
+        for obj in std::mem::take(&mut self.objects) {
+            if let Some(Some(group)) = old_groups.get_mut(obj.group()) {
+                group.push(obj);
+            }
+        }
+        // the removed objects stay removed, and follow their groups
+        let mut old_removed = std::mem::take(&mut self.removed_objects);
+
+        let two_body = self.current_state_def.two_body;
+        for (group, object_def) in self.current_state_def.objects.iter().enumerate() {
+            let reusable = old_def.objects.iter().enumerate().position(|(i, old)| {
+                // the objects started later than the time rewound to are gone
+                old_groups[i].as_ref().is_some_and(|group| {
+                    !group.is_empty() || old_removed.iter().any(|removed| removed.obj.group() == i)
+                }) && old.same_motion(object_def)
+            });
+            let objects: Vec<_> = match reusable {
+                Some(i) => {
+                    let [r, g, b] = object_def.color;
+                    let update = |obj: Object| {
+                        obj.with_group(group)
+                            .with_color(r, g, b)
+                            .with_name(&object_def.name)
+                            .with_trail_frames(object_def.trail_frames)
+                            .with_trail_length(object_def.trail_duration_f(), object_def.trail_fade)
+                            .with_completion(object_def.completion())
+                            .with_visibility(object_def.hidden, object_def.path_hidden)
+                    };
+                    let (removed, rest) = old_removed
+                        .into_iter()
+                        .partition(|removed| removed.obj.group() == i);
+                    old_removed = rest;
+                    self.removed_objects.extend(removed.into_iter().map(
+                        |removed: RemovedObject| RemovedObject {
+                            obj: update(removed.obj),
+                            ..removed
+                        },
+                    ));
+                    old_groups[i]
+                        .take()
+                        .unwrap()
+                        .into_iter()
+                        .map(update)
+                        .collect()
+                }
+                None => object_def
+                    .into_objects(self.environment.atmosphere)
+                    .into_iter()
+                    .map(|obj| {
+                        let mut obj = obj
+                            .with_group(group)
+                            .with_third_body(two_body)
+                            .with_name(&object_def.name)
+                            .with_trail_frames(object_def.trail_frames)
+                            .with_trail_length(object_def.trail_duration_f(), object_def.trail_fade)
+                            .with_surface_interaction(object_def.surface_interaction())
+                            .with_completion(object_def.completion())
+                            .with_visibility(object_def.hidden, object_def.path_hidden)
+                            .with_environment(self.environment.clone());
+                        if self.soft_start {
+                            obj = obj.with_start_time(self.t);
+                        }
+                        obj
+                    })
+                    .collect(),
+            };
             self.objects.extend(objects);
         }
+        self.removed_objects.sort_by(|a, b| a.t.total_cmp(&b.t));
+        self.integrate_in_background(self.t);
+    }
+
+    /// The environment given by the current initial state, with the wind kept
+    fn described_environment(&self) -> Environment {
+        let def = &self.current_state_def;
+        Environment {
+            spin: def.spin_ramp,
+            wind: self.environment.wind.clone(),
+            pressure_systems: def
+                .objects
+                .iter()
+                .filter_map(ObjectDescription::pressure_system)
+                .collect(),
+            atmosphere: def.atmosphere,
+        }
+    }
+
+    /// Replaces the environment of the simulation from now on, for all the objects
+    fn set_environment(&mut self, environment: Environment) {
+        self.environment = Arc::new(environment);
+        let removed = self
+            .removed_objects
+            .iter_mut()
+            .map(|removed| &mut removed.obj);
+        for obj in self.objects.iter_mut().chain(removed) {
+            obj.set_environment(self.environment.clone());
+        }
+    }
+
+    /// Sets the wind blowing in the simulation from now on; `None` for still air
+    pub fn set_wind_field(&mut self, wind: Option<Arc<WindField>>) {
+        self.set_environment(Environment {
+            wind,
+            ..(*self.environment).clone()
+        });
+    }
+
+    /// Rewinds the simulation itself to the time `t`: the objects return to their last
+    /// checkpoints before that moment, the later parts of their paths are discarded, and they
+    /// are integrated again up to `t` on background threads
+    pub fn rewind_to(&mut self, t: f64) {
+        if self.precompute.is_some() || t >= self.t {
+            return;
+        }
+        self.restore_removed(t);
+        // the objects added later in the run didn't exist yet
+        self.remove_objects(|obj| obj.start_time() > t);
+        for obj in &mut self.objects {
+            obj.rewind_to(t);
+        }
+        if self.failed_objects() == 0 {
+            self.blowup_message = None;
+        }
+        // the tracers keep no history to go back to
+        self.tracers.clear();
+        let new_t = self
+            .objects
+            .iter()
+            .map(Object::time)
+            .fold(t, f64::min)
+            .max(0.0);
+        self.ang -= self.frame_omega() * (self.t - new_t);
+        self.t = new_t;
+        self.render_settings.max_t = new_t;
+        self.playback.direction = 0.0;
+        self.integrate_in_background(t);
+    }
+
+    /// Advances all the objects by a single time step
+    pub fn step(&mut self, integrator: &mut impl Integrator<Object>) {
+        self.advance(integrator, self.time_step);
+    }
+
+    /// Advances the simulation for a single rendered frame, which took `wall_dt` seconds: by
+    /// the configured simulation speed, or exactly the configured amount of simulated time if
+    /// frame pacing is enabled
+    pub fn step_frame(&mut self, integrator: &mut impl Integrator<Object>, wall_dt: f64) {
+        if !self.frame_pacing.enabled {
+            // simulated time per frame at the normal speed
+            let frame_time = match self.speed.mode {
+                SpeedMode::StepsPerFrame => self.speed.steps_per_frame as f64 * self.time_step,
+                SpeedMode::TimeFactor => wall_dt * self.speed.time_factor,
+            };
+            self.speed.slowed_for = if self.speed.slow_motion && wall_dt > 0.0 {
+                self.nearby_event(frame_time / wall_dt * SLOW_MOTION_LEAD)
+            } else {
+                None
+            };
+            let steps = match (self.speed.mode, self.speed.slowed_for) {
+                (SpeedMode::StepsPerFrame, None) => {
+                    self.speed.lag = 0.0;
+                    self.speed.steps_per_frame
+                }
+                (_, slowed_for) => {
+                    let factor = if slowed_for.is_some() {
+                        self.speed.slow_motion_factor
+                    } else {
+                        1.0
+                    };
+                    self.speed.pending += frame_time * factor;
+                    let steps = (self.speed.pending / self.time_step).floor() as u32;
+                    let steps = steps.min(MAX_STEPS_PER_FRAME);
+                    self.speed.pending =
+                        (self.speed.pending - steps as f64 * self.time_step).min(self.time_step);
+                    // the wall clock is at `t + pending`; one step back, the scene can be
+                    // interpolated between the stored states
+                    self.speed.lag = if self.speed.interpolate {
+                        self.time_step - self.speed.pending
+                    } else {
+                        0.0
+                    };
+                    steps
+                }
+            };
+            for _ in 0..steps {
+                self.step(integrator);
+            }
+            return;
+        }
+        self.speed.lag = 0.0;
+        let duration = self.frame_pacing.seconds_per_frame;
+        let substeps = (duration / self.time_step).ceil().max(1.0) as usize;
+        let dt = duration / substeps as f64;
+        for _ in 0..substeps {
+            self.advance(integrator, dt);
+        }
+        self.frame_pacing.frame += 1;
+    }
+
+    /// Describes an impact or a close approach of the objects happening less than `window`
+    /// simulated seconds before or after the current time, if there is one
+    fn nearby_event(&self, window: f64) -> Option<SlowMotionEvent> {
+        // positions and velocities in the Earth's frame
+        let states: Vec<_> = self
+            .objects
+            .iter()
+            .map(|obj| {
+                let pos = obj.pos().to_omega(OMEGA).pos();
+                let vel = obj.vel().to_omega(obj.pos(), OMEGA).vel();
+                (pos, vel)
+            })
+            .collect();
+
+        for (index, (obj, (pos, vel))) in self.objects.iter().zip(&states).enumerate() {
+            let landed_recently = obj
+                .landed_at()
+                .is_some_and(|t| t > 0.0 && self.t - t < window);
+            let (_, _, elev) = pos_to_lat_lon_elev(*pos);
+            let up = surface_normal(pos);
+            let descent = -vel.dot(&up);
+            // gravity less the centrifugal effect of the horizontal motion, which turns the
+            // descent of an orbiting object around before it gets to the ground
+            let horizontal = vel + up * descent;
+            let r = pos.norm();
+            let fall_accel = GM / (r * r) - horizontal.norm_squared() / r;
+            let landing_soon = elev > MIN_FLIGHT_ELEVATION
+                && descent > 0.0
+                && time_to_fall(elev, descent, fall_accel).is_some_and(|t| t < window);
+            if landed_recently || landing_soon {
+                return Some(SlowMotionEvent::Impact(index));
+            }
+        }
+
+        if self.objects.len() > MAX_SLOW_MOTION_OBJECTS {
+            return None;
+        }
+        for (i, (pos1, vel1)) in states.iter().enumerate() {
+            for (j, (pos2, vel2)) in states.iter().enumerate().skip(i + 1) {
+                // the objects of a group, like the particles of a ring, stay close together
+                if self.objects[i].group() == self.objects[j].group() {
+                    continue;
+                }
+                let rel_pos = pos2 - pos1;
+                let rel_vel = vel2 - vel1;
+                // only the pairs that get from far apart to close within the window are
+                // approaching, not the ones drifting near each other
+                if rel_vel.norm() * window < SLOW_MOTION_APPROACH_DISTANCE {
+                    continue;
+                }
+                let t_closest = -rel_pos.dot(&rel_vel) / rel_vel.norm_squared();
+                let miss = (rel_pos + rel_vel * t_closest).norm();
+                if t_closest.abs() < window && miss < SLOW_MOTION_APPROACH_DISTANCE {
+                    return Some(SlowMotionEvent::ClosestApproach(i, j));
+                }
+            }
+        }
+        None
+    }
+
+    fn advance(&mut self, integrator: &mut impl Integrator<Object>, dt: f64) {
+        let failed = self.failed_objects();
+        step_linked(&mut self.objects, &self.constraints, integrator, dt);
+        if self.failed_objects() > failed {
+            self.report_blowup();
+        }
+        self.tracers.step(&self.environment, self.t, dt);
+        self.t += dt;
+        self.ang += self.frame_omega() * dt;
+        self.remove_finished();
+        self.enforce_memory_budget();
+    }
+
+    /// Takes the objects done according to their completion rules out of the simulation,
+    /// keeping them for a rewind
+    fn remove_finished(&mut self) {
+        let objects = &self.objects;
+        let finished = |index: usize| objects.get(index).is_some_and(Object::is_finished);
+        let mut constraints: Vec<_> = self
+            .constraints
+            .iter()
+            .filter(|constraint| finished(constraint.a) || finished(constraint.b))
+            .cloned()
+            .collect();
+        let mut group_sizes = BTreeMap::new();
+        let mut index_in_group = vec![];
+        for obj in objects {
+            let size = group_sizes.entry(obj.group()).or_insert(0);
+            index_in_group.push(*size);
+            *size += 1;
+        }
+
+        let removed = self.remove_objects(Object::is_finished);
+        let last = removed.len().saturating_sub(1);
+        for (i, (index, obj)) in removed.into_iter().enumerate() {
+            self.removed_objects.push(RemovedObject {
+                t: self.t,
+                index_in_group: index_in_group[index],
+                // restored after all the objects removed together
+                constraints: if i == last {
+                    std::mem::take(&mut constraints)
+                } else {
+                    vec![]
+                },
+                obj,
+            });
+        }
+    }
+
+    /// Brings back the objects removed after the time `t`, in their former places, undoing
+    /// the latest removals first
+    fn restore_removed(&mut self, t: f64) {
+        let first = self
+            .removed_objects
+            .partition_point(|removed| removed.t <= t);
+        let mut restored = self.removed_objects.split_off(first);
+        // the objects removed together go back in their original order
+        restored.sort_by(|a, b| b.t.total_cmp(&a.t));
+        for removed in restored {
+            let group = removed.obj.group();
+            let start = self.objects.partition_point(|obj| obj.group() < group);
+            let end = self.objects.partition_point(|obj| obj.group() <= group);
+            let position = (start + removed.index_in_group).min(end);
+            let new_indices: Vec<_> = (0..self.objects.len())
+                .map(|i| Some(if i < position { i } else { i + 1 }))
+                .collect();
+            self.objects.insert(position, removed.obj);
+            self.renumber_objects(&new_indices);
+            self.constraints.extend(removed.constraints);
+        }
+    }
+
+    /// Takes the objects for which `remove` is true out of the simulation and renumbers
+    /// everything referring to the rest; returns the removed ones with their former indices
+    fn remove_objects(&mut self, remove: impl Fn(&Object) -> bool) -> Vec<(usize, Object)> {
+        if !self.objects.iter().any(&remove) {
+            return vec![];
+        }
+        let mut new_indices = vec![];
+        let mut kept = vec![];
+        let mut removed = vec![];
+        for (index, obj) in std::mem::take(&mut self.objects).into_iter().enumerate() {
+            if remove(&obj) {
+                new_indices.push(None);
+                removed.push((index, obj));
+            } else {
+                new_indices.push(Some(kept.len()));
+                kept.push(obj);
+            }
+        }
+        self.objects = kept;
+        self.renumber_objects(&new_indices);
+        removed
+    }
+
+    /// Updates everything referring to the objects by their indices after they were
+    /// reordered: `new_indices[i]` is the new index of the object that had the index `i`, or
+    /// `None` if it is gone. The constraints and the results involving the gone ones are
+    /// dropped.
+    fn renumber_objects(&mut self, new_indices: &[Option<usize>]) {
+        self.constraints.retain_mut(|constraint| {
+            match (new_indices[constraint.a], new_indices[constraint.b]) {
+                (Some(a), Some(b)) => {
+                    constraint.a = a;
+                    constraint.b = b;
+                    true
+                }
+                _ => false,
+            }
+        });
+        let new_index = |index: usize| new_indices.get(index).copied().flatten();
+        self.selected_object = self.selected_object.and_then(new_index);
+        if self.camera_state.tag == StateTag::Following {
+            match new_index(self.camera_state.following.obj) {
+                Some(index) => self.camera_state.following.obj = index,
+                None => self.camera_state.set_from_def(CameraStateDef::External),
+            }
+        }
+        // recomputed on the next frame
+        self.speed.slowed_for = None;
+
+        let tool = &mut self.intercept_tool;
+        let gone = |index: usize| new_indices.get(index).is_some_and(Option::is_none);
+        if gone(tool.chaser) || gone(tool.target) {
+            tool.discard();
+            tool.message = Some("The objects were removed from the simulation".to_string());
+        }
+        tool.chaser = new_index(tool.chaser).unwrap_or(tool.chaser);
+        tool.target = new_index(tool.target).unwrap_or(tool.target);
+    }
+
+    fn failed_objects(&self) -> usize {
+        self.objects
+            .iter()
+            .filter(|obj| obj.blowup().is_some())
+            .count()
+    }
+
+    /// Pauses the simulation and tells the user which object blew up, so that it doesn't
+    /// silently fly off to infinity
+    fn report_blowup(&mut self) {
+        let failed: Vec<_> = self
+            .objects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, obj)| obj.blowup().map(|blowup| (i, blowup)))
+            .collect();
+        let (index, blowup) = match failed.iter().max_by(|a, b| a.1.t.total_cmp(&b.1.t)) {
+            Some(&latest) => latest,
+            None => return,
+        };
+        self.running = false;
+        self.blowup_message = Some(format!(
+            "{} blew up and was stopped: {}{}",
+            self.objects[index].label(index),
+            blowup.describe(),
+            match failed.len() {
+                1 => String::new(),
+                n => format!(" ({} objects stopped in total)", n),
+            }
+        ));
+    }
+
+    /// Starts integrating copies of the objects up to `precompute_until` on background
+    /// threads; the objects stay as they are, for display, until `poll_precompute` replaces
+    /// them
+    pub fn start_precompute(&mut self) {
+        if self.precompute.is_some() || self.precompute_until <= self.t {
+            return;
+        }
+        self.integrate_in_background(self.precompute_until);
+    }
+
+    /// Starts advancing copies of the objects that are behind the time `until` up to it on
+    /// background threads; the simulation is paused until `poll_precompute` collects them
+    fn integrate_in_background(&mut self, until: f64) {
+        if !self.objects.iter().any(|obj| obj.time() < until) {
+            return;
+        }
+        self.running = false;
+        self.precompute = Some(Precompute::start(
+            self.objects.clone(),
+            self.constraints.clone(),
+            until,
+            self.time_step,
+            self.integration,
+        ));
+    }
+
+    /// Replaces the objects with the runs of the experiment set up in the wizard and starts
+    /// precomputing them
+    pub fn start_experiment(&mut self) {
+        let wizard = &mut self.experiment_wizard;
+        match wizard.create(&self.current_state_def.objects) {
+            Some((objects, experiment)) => {
+                wizard.message = None;
+                self.current_state_def.objects = objects;
+                self.current_state_def.preset = None;
+                self.experiment = Some(experiment);
+                self.reset_state();
+                self.start_precompute();
+            }
+            None => {
+                wizard.message = Some(format!(
+                    "None of the objects has the parameter \"{}\"",
+                    wizard.parameter
+                ));
+            }
+        }
+    }
+
+    /// Collects the objects if the precomputation has finished
+    pub fn poll_precompute(&mut self) {
+        if !self
+            .precompute
+            .as_ref()
+            .is_some_and(Precompute::is_finished)
+        {
+            return;
+        }
+        let precompute = self.precompute.take().unwrap();
+        let until = precompute.until;
+        let failed = precompute.failed_at_start;
+        let snapshot = std::mem::replace(&mut self.objects, precompute.join());
+        for (obj, shown) in self.objects.iter_mut().zip(&snapshot) {
+            // the environment and the visibility may have changed while they were being
+            // integrated
+            obj.set_environment(self.environment.clone());
+            obj.set_hidden(shown.is_hidden());
+            obj.set_path_hidden(shown.is_path_hidden());
+        }
+        self.ang += self.frame_omega() * (until - self.t);
+        self.t = until;
+        self.render_settings.max_t = self.t;
+        self.enforce_memory_budget();
+        if self.failed_objects() > failed {
+            self.report_blowup();
+        }
+    }
+
+    /// Starts reading the wind from the file `wind_path` on a background thread; the large
+    /// GRIB2 and NetCDF files take a while to parse. `poll_wind` picks the result up.
+    pub fn start_loading_wind(&mut self) {
+        let path = self.wind_path.clone();
+        let thread_path = path.clone();
+        let handle = thread::spawn(move || io::load_wind_field(thread_path));
+        self.wind_loading = Some((path, handle));
+        self.wind_message = Some("Loading the wind...".to_string());
+    }
+
+    /// Starts blowing the wind once the background thread has read it
+    pub fn poll_wind(&mut self) {
+        if !self
+            .wind_loading
+            .as_ref()
+            .is_some_and(|(_, handle)| handle.is_finished())
+        {
+            return;
+        }
+        self.wait_for_wind();
+    }
+
+    /// Blocks until the wind being loaded is read, and starts blowing it
+    pub fn wait_for_wind(&mut self) {
+        let (path, handle) = match self.wind_loading.take() {
+            Some(loading) => loading,
+            None => return,
+        };
+        let message = match handle.join().expect("wind loading thread panicked") {
+            Ok(field) => {
+                let summary = field.summary();
+                self.set_wind_field(Some(Arc::new(field)));
+                format!("Wind loaded from {}: {}", path, summary)
+            }
+            Err(err) => format!("Loading the wind failed: {}", err),
+        };
+        self.wind_message = Some(message);
+    }
+
+    /// The time the scene is drawn at while the simulation runs
+    pub fn render_time(&self) -> f64 {
+        (self.t - self.speed.lag()).max(0.0)
+    }
+
+    /// Estimates the angular acceleration of the viewing frame from the changes of its rotation
+    /// made while the simulation runs; changes made while paused are instantaneous and ignored
+    pub fn update_frame_alpha(&mut self) {
+        let omega = self.frame_omega();
+        let (last_t, last_omega) = self.last_frame_omega;
+        if self.t > last_t {
+            self.render_settings.frame_alpha = (omega - last_omega) / (self.t - last_t);
+        } else if omega != last_omega {
+            self.render_settings.frame_alpha = 0.0;
+        } else {
+            return;
+        }
+        self.last_frame_omega = (self.t, omega);
+    }
+
+    /// Moves the rendered time range if the playback is on; `wall_dt` is the duration of the
+    /// last frame
+    pub fn update_playback(&mut self, wall_dt: f64) {
+        if self.running || self.playback.direction == 0.0 {
+            return;
+        }
+        let max_t =
+            self.render_settings.max_t + self.playback.direction * self.playback.rate * wall_dt;
+        if max_t <= 0.0 || max_t >= self.t {
+            self.playback.direction = 0.0;
+        }
+        self.render_settings.max_t = max_t.clamp(0.0, self.t);
+    }
+
+    pub fn spawn_tracers(&mut self) {
+        let settings = self.tracer_settings;
+        self.tracers = Tracers::disc(
+            settings.lat,
+            settings.lon,
+            settings.radius * 1e3,
+            settings.count,
+            settings.vel_e,
+            settings.vel_n,
+        );
+    }
+
+    /// Total memory taken by the paths of all the objects, in bytes
+    pub fn path_memory(&self) -> usize {
+        self.objects
+            .iter()
+            .chain(self.removed_objects.iter().map(|removed| &removed.obj))
+            .map(Object::memory_usage)
+            .sum()
+    }
+
+    fn enforce_memory_budget(&mut self) {
+        let budget = (self.memory_budget.megabytes * 1024.0 * 1024.0) as usize;
+        self.memory_budget.exceeded = self.path_memory() > budget;
+        if self.memory_budget.exceeded && self.memory_budget.auto_decimate {
+            let removed = self
+                .removed_objects
+                .iter_mut()
+                .map(|removed| &mut removed.obj);
+            for obj in self.objects.iter_mut().chain(removed) {
+                obj.decimate_path();
+            }
+            self.memory_budget.decimations += 1;
+            self.memory_budget.exceeded = false;
+        }
+    }
+
+    /// Angular velocity of the reference frame the scene is viewed in: a fraction of the
+    /// Earth's rotation, or of the co-rotating frame of the two-body system if one is active
+    pub fn frame_omega(&self) -> f64 {
+        let base = match self.current_state_def.two_body {
+            Some(system) => system.mean_motion(),
+            None => OMEGA,
+        };
+        self.omega * base
+    }
+
+    /// The named rotation rates of the frame: the built-in ones, then the ones saved by the user
+    pub fn named_frame_rotations(&self) -> Vec<(String, f64)> {
+        BUILTIN_FRAME_ROTATIONS
+            .iter()
+            .map(|(name, omega)| (name.to_string(), *omega))
+            .chain(
+                self.frame_rotation_stops
+                    .iter()
+                    .map(|stop| (stop.name.clone(), stop.omega)),
+            )
+            .collect()
+    }
+
+    /// Moves the rotation rate of the frame to the nearest named one, if it is close enough
+    pub fn snap_frame_rotation(&mut self) {
+        let nearest = self
+            .named_frame_rotations()
+            .into_iter()
+            .map(|(_, omega)| omega)
+            .min_by(|a, b| (a - self.omega).abs().total_cmp(&(b - self.omega).abs()));
+        if let Some(omega) =
+            nearest.filter(|omega| (omega - self.omega).abs() < FRAME_ROTATION_SNAP)
+        {
+            self.omega = omega;
+        }
+    }
+
+    /// Changes the rotation rate of the frame by `step`, landing on a multiple of the step
+    pub fn nudge_frame_rotation(&mut self, step: f64) {
+        self.omega = ((self.omega + step) / step.abs()).round() * step.abs();
+    }
+
+    /// The current values of the user-facing controls, for the interaction log
+    pub fn control_values(&self) -> Vec<(&'static str, String)> {
+        let settings = &self.render_settings;
+        let mut values = vec![
+            ("running", self.running.to_string()),
+            ("frame_rotation", format!("{:.3}", self.omega)),
+            ("time_step", format!("{:.1}", self.time_step)),
+            ("speed_mode", format!("{}", self.speed.mode)),
+            ("slow_motion", self.speed.slow_motion.to_string()),
+            ("integration", format!("{}", self.integration)),
+            ("decimal_separator", format!("{}", self.decimal_separator)),
+            ("frame_pacing", self.frame_pacing.enabled.to_string()),
+            ("camera", format!("{}", self.camera_state.as_def())),
+            ("north_up", self.camera_state.north_up.to_string()),
+            (
+                "auto_orbit",
+                self.camera_state.auto_orbit.enabled.to_string(),
+            ),
+            ("editing_state", self.new_state_def.is_some().to_string()),
+            ("soft_start", self.soft_start.to_string()),
+            ("num_objects", self.objects.len().to_string()),
+            ("fov", format!("{:.0}", settings.fov)),
+            ("draw_grid", settings.draw_grid.to_string()),
+            (
+                "draw_solid_surface",
+                settings.draw_solid_surface.to_string(),
+            ),
+            ("draw_paths", settings.draw_paths.to_string()),
+            ("draw_apsides", settings.draw_apsides.to_string()),
+            (
+                "draw_ground_tracks",
+                settings.draw_ground_tracks.to_string(),
+            ),
+            ("draw_group_paths", settings.draw_group_paths.to_string()),
+            ("use_texture", settings.use_texture.to_string()),
+            ("draw_velocities", settings.draw_velocities.to_string()),
+            ("draw_forces", settings.draw_forces.to_string()),
+            ("draw_arrow_labels", settings.draw_arrow_labels.to_string()),
+            (
+                "path_arrow_interval",
+                format!("{:?}", settings.path_arrow_interval),
+            ),
+            ("draw_sun", settings.draw_sun.to_string()),
+            ("draw_moon", settings.draw_moon.to_string()),
+            ("draw_tides", settings.draw_tides.to_string()),
+            ("draw_field_lines", settings.draw_field_lines.to_string()),
+            ("color_mode", format!("{}", settings.color_mode)),
+            ("path_color_mode", format!("{}", settings.path_color_mode)),
+            ("projection", format!("{}", settings.projection)),
+            (
+                "reference_point",
+                settings
+                    .reference_point
+                    .map_or("none".to_string(), |(lat, lon)| format!("{}, {}", lat, lon)),
+            ),
+            ("obliquity", format!("{:.1}", self.celestial.obliquity)),
+            (
+                "sky_rotation",
+                format!("{:.0}", self.celestial.sky_rotation),
+            ),
+            ("precession", self.celestial.precession.to_string()),
+            (
+                "show_ring_diagnostics",
+                self.show_ring_diagnostics.to_string(),
+            ),
+            (
+                "show_ground_stations",
+                self.show_ground_stations.to_string(),
+            ),
+            ("show_calculator", self.show_calculator.to_string()),
+            ("show_aiming_tool", self.show_aiming_tool.to_string()),
+            ("show_intercept_tool", self.show_intercept_tool.to_string()),
+            ("show_map", self.show_map.to_string()),
+            ("map_projection", format!("{}", self.map_projection)),
+        ];
+        // the time range follows the simulation while it runs
+        if !self.running {
+            values.push(("time_range", format!("{:.0}", settings.max_t)));
+        }
+        values
+    }
+
+    /// Replaces the initial state with the scenario and restarts the simulation
+    pub fn load_scenario(&mut self, scenario: Scenario) {
+        self.current_state_def.objects = scenario.objects;
+        self.current_state_def.links = scenario.links;
+        self.current_state_def.preset = None;
+        self.experiment = None;
+        self.current_state_def.two_body = scenario.two_body;
+        self.current_state_def.spin_ramp = scenario.spin_ramp;
+        self.current_state_def.atmosphere = scenario.atmosphere;
+        self.reset_state();
+        if let Some(time_step) = scenario.time_step {
+            self.time_step = time_step;
+        }
+        self.integration = scenario.integration;
+        if let Some(omega) = scenario.omega {
+            self.omega = omega;
+        }
+        self.running = scenario.autostart;
+        if !scenario.camera_bookmarks.is_empty() {
+            self.camera_bookmarks = scenario.camera_bookmarks;
+        }
+        if !scenario.frame_rotation_stops.is_empty() {
+            self.frame_rotation_stops = scenario.frame_rotation_stops;
+        }
+        let mut exercises = scenario.exercises;
+        for exercise in &mut exercises {
+            if exercise.objects.is_empty() {
+                exercise.objects = self.current_state_def.objects.clone();
+            }
+        }
+        self.quiz = if exercises.is_empty() {
+            None
+        } else {
+            Some(Quiz::new(exercises))
+        };
+        self.start_exercise();
+    }
+
+    /// Prepares the objects of the current exercise and waits for the answer
+    pub fn start_exercise(&mut self) {
+        let exercise = match self.quiz.as_mut() {
+            Some(quiz) => {
+                quiz.phase = QuizPhase::Asking;
+                match quiz.exercise() {
+                    Some(exercise) => exercise.clone(),
+                    None => {
+                        quiz.phase = QuizPhase::Finished;
+                        return;
+                    }
+                }
+            }
+            None => return,
+        };
+        self.current_state_def.objects = exercise.objects;
+        self.reset_state();
+        self.running = false;
+    }
+
+    /// Records the answer and runs the simulation to check it
+    pub fn answer_exercise(&mut self, answer: usize) {
+        if let Some(quiz) = self.quiz.as_mut() {
+            quiz.phase = QuizPhase::Running(answer);
+            self.running = true;
+        }
+    }
+
+    /// Stops the simulation and scores the answer once the exercise's duration has passed
+    pub fn update_quiz(&mut self) {
+        let quiz = match self.quiz.as_mut() {
+            Some(quiz) => quiz,
+            None => return,
+        };
+        let chosen = match quiz.phase {
+            QuizPhase::Running(chosen) => chosen,
+            _ => return,
+        };
+        let exercise = match quiz.exercises.get(quiz.current) {
+            Some(exercise) => exercise,
+            None => return,
+        };
+        if self.t < exercise.duration {
+            return;
+        }
+        self.running = false;
+        let correct = exercise.check.correct_answer(&self.objects);
+        if correct == Some(chosen) {
+            quiz.score += 1;
+        }
+        quiz.phase = QuizPhase::Answered { chosen, correct };
+    }
+
+    /// Moves on to the next exercise
+    pub fn next_exercise(&mut self) {
+        if let Some(quiz) = self.quiz.as_mut() {
+            quiz.current += 1;
+        }
+        self.start_exercise();
+    }
+
+    /// A sphere containing the objects with the given indices and their paths up to the
+    /// displayed time, in the frame the scene is viewed in; `None` if there are no such objects
+    pub fn bounding_sphere(&self, indices: &[usize]) -> Option<(Vector3<f64>, f64)> {
+        let omega = self.frame_omega();
+        let max_t = self.render_settings.max_t;
+        let objects: Vec<&Object> = indices
+            .iter()
+            .filter_map(|i| self.objects.get(*i))
+            .collect();
+        let points = || {
+            objects.iter().flat_map(move |obj| {
+                obj.states_until(max_t)
+                    .chain(std::iter::once(obj.state_at(max_t)))
+                    .map(move |state| state.pos().to_omega(omega).pos())
+            })
+        };
+
+        let (min, max) = points().fold(None, |bounds, point| match bounds {
+            None => Some((point, point)),
+            Some((min, max)) => Some((min.inf(&point), max.sup(&point))),
+        })?;
+        let center = (min + max) / 2.0;
+        let radius = points()
+            .map(|point| (point - center).norm())
+            .fold(0.0, f64::max);
+        let marker = objects
+            .iter()
+            .map(|obj| obj.radius() as f64)
+            .fold(0.0, f64::max);
+        Some((center, radius + marker))
+    }
+
+    /// Indices of the objects, grouped by the object description that created them
+    pub fn groups(&self) -> BTreeMap<usize, Vec<usize>> {
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, obj) in self.objects.iter().enumerate() {
+            groups.entry(obj.group()).or_default().push(index);
+        }
+        groups
+    }
+
+    /// The groups of objects forming cyclone or anticyclone rings
+    pub fn ring_groups(&self) -> Vec<(usize, Vec<&Object>)> {
+        self.groups()
+            .into_iter()
+            .filter(|(group, members)| {
+                members.len() > 2
+                    && matches!(
+                        self.current_state_def
+                            .objects
+                            .get(*group)
+                            .map(|def| &def.kind),
+                        Some(ObjectKind::Cyclone { .. })
+                            | Some(ObjectKind::Anticyclone { .. })
+                            | Some(ObjectKind::ConvergingRing { .. })
+                    )
+            })
+            .map(|(group, members)| {
+                let objects = members.iter().map(|i| &self.objects[*i]).collect();
+                (group, objects)
+            })
+            .collect()
+    }
+
+    /// The range of the attribute selected for automatic coloring across all objects
+    pub fn color_range(&self) -> Option<(f64, f64)> {
+        let mode = self.render_settings.color_mode;
+        self.objects
+            .iter()
+            .filter_map(|obj| obj.color_attribute(mode))
+            .fold(None, |range, value| match range {
+                None => Some((value, value)),
+                Some((min, max)) => Some((value.min(min), value.max(max))),
+            })
+    }
+
+    /// The range of the quantity the paths are colored by, along the drawn paths of the shown
+    /// objects
+    pub fn path_color_range(&self) -> Option<(f64, f64)> {
+        let mode = self.render_settings.path_color_mode;
+        if mode == PathColorMode::Object {
+            return None;
+        }
+        let max_t = self.render_settings.max_t;
+        self.objects
+            .iter()
+            .enumerate()
+            .filter(|(index, obj)| self.shows_object(*index) && !obj.is_path_hidden())
+            .flat_map(|(_, obj)| obj.path_color_values(mode, max_t))
+            .fold(None, |range, value| match range {
+                None => Some((value, value)),
+                Some((min, max)) => Some((value.min(min), value.max(max))),
+            })
+    }
+
+    /// The colors with which the objects should be drawn, according to the color mode
+    pub fn object_colors(&self) -> Vec<[f32; 3]> {
+        let mode = self.render_settings.color_mode;
+        let range = self.color_range();
+        self.objects
+            .iter()
+            .map(|obj| match (obj.color_attribute(mode), range) {
+                // flagged whatever the color mode
+                _ if obj.blowup().is_some() => [1.0, 0.0, 0.0],
+                (Some(value), Some((min, max))) if max > min => {
+                    colormap((value - min) / (max - min))
+                }
+                (Some(_), Some(_)) => colormap(0.5),
+                _ => obj.color(),
+            })
+            .collect()
+    }
+}
+
+/// The time it takes to fall from the elevation `elev`, starting with the downward speed
+/// `descent` and accelerating downwards at `accel`; `None` if the descent stops before the
+/// ground
+fn time_to_fall(elev: f64, descent: f64, accel: f64) -> Option<f64> {
+    if accel == 0.0 {
+        return Some(elev / descent);
+    }
+    let discriminant = descent * descent + 2.0 * accel * elev;
+    if discriminant < 0.0 {
+        return None;
     }
+    Some((discriminant.sqrt() - descent) / accel)
 }
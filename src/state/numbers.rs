@@ -0,0 +1,121 @@
+//! Numbers typed by the user and shown to them, with either a decimal point or a decimal comma
+
+use std::{env, fmt, str::FromStr};
+
+/// The languages writing the decimal separator as a comma
+const COMMA_LANGUAGES: [&str; 33] = [
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl", "hr", "hu", "id", "is",
+    "it", "lt", "lv", "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sr", "sv", "tr",
+    "uk",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecimalSeparator {
+    Point,
+    Comma,
+}
+
+impl DecimalSeparator {
+    pub const ALL: [DecimalSeparator; 2] = [DecimalSeparator::Point, DecimalSeparator::Comma];
+
+    /// The separator of the locale set in the environment (`LC_ALL`, `LC_NUMERIC` or `LANG`);
+    /// the point if there is none
+    pub fn from_locale() -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .find_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default();
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default();
+        if COMMA_LANGUAGES.contains(&language) {
+            DecimalSeparator::Comma
+        } else {
+            DecimalSeparator::Point
+        }
+    }
+
+    /// The number `x`, to be formatted with this separator
+    pub fn number(self, x: f64) -> Localized {
+        Localized(x, self)
+    }
+}
+
+impl fmt::Display for DecimalSeparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecimalSeparator::Point => write!(f, "Point (1.5)"),
+            DecimalSeparator::Comma => write!(f, "Comma (1,5)"),
+        }
+    }
+}
+
+/// A number written with a chosen decimal separator. It is formatted like the `f64` inside,
+/// with `{}` or `{:e}` and the same precision, sign and width, so only the number itself is
+/// changed and not the text around it.
+#[derive(Debug, Clone, Copy)]
+pub struct Localized(f64, DecimalSeparator);
+
+impl Localized {
+    fn write(&self, f: &mut fmt::Formatter, text: String) -> fmt::Result {
+        let text = match self.1 {
+            DecimalSeparator::Point => text,
+            DecimalSeparator::Comma => text.replace('.', ","),
+        };
+        match f.width() {
+            Some(width) => write!(f, "{:>width$}", text, width = width),
+            None => f.write_str(&text),
+        }
+    }
+}
+
+impl fmt::Display for Localized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match (f.precision(), f.sign_plus()) {
+            (Some(precision), false) => format!("{:.*}", precision, self.0),
+            (Some(precision), true) => format!("{:+.*}", precision, self.0),
+            (None, false) => format!("{}", self.0),
+            (None, true) => format!("{:+}", self.0),
+        };
+        self.write(f, text)
+    }
+}
+
+impl fmt::LowerExp for Localized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match (f.precision(), f.sign_plus()) {
+            (Some(precision), false) => format!("{:.*e}", precision, self.0),
+            (Some(precision), true) => format!("{:+.*e}", precision, self.0),
+            (None, false) => format!("{:e}", self.0),
+            (None, true) => format!("{:+e}", self.0),
+        };
+        self.write(f, text)
+    }
+}
+
+/// Parses a number typed by the user, whatever the displayed separator: both "52.23" and
+/// "52,23" are accepted, as well as the scientific notation ("1.5e-3"), spaces or apostrophes
+/// grouping the digits and the minus sign. If both a point and a comma appear, the last one
+/// separates the decimals, and a lone comma is always taken as a decimal comma. `None` if it
+/// isn't a number.
+pub fn parse_number<T: FromStr>(text: &str) -> Option<T> {
+    let mut number: String = text
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\'' | '\u{a0}' | '\u{202f}'))
+        .map(|c| if c == '−' { '-' } else { c })
+        .collect();
+    match (number.rfind('.'), number.rfind(',')) {
+        (Some(point), Some(comma)) if comma > point => {
+            number = number.replace('.', "").replace(',', ".");
+        }
+        (Some(_), Some(_)) => number = number.replace(',', ""),
+        (None, Some(_)) if number.matches(',').count() == 1 => {
+            number = number.replace(',', ".");
+        }
+        (None, Some(_)) => number = number.replace(',', ""),
+        _ => (),
+    }
+    number.parse().ok()
+}
@@ -0,0 +1,126 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::simulation::{step_linked, Constraint, IntegrationMethod, Object, ObjectIntegrator};
+
+/// Integration of all the objects up to a given time, running on background threads
+pub struct Precompute {
+    /// Simulated time the objects are being advanced to
+    pub until: f64,
+    /// How many of the objects had already blown up at the start
+    pub failed_at_start: usize,
+    total_work: usize,
+    progress: Arc<AtomicUsize>,
+    /// Set when the result isn't needed anymore, to stop the threads early
+    cancelled: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<Vec<Object>>>,
+}
+
+impl Precompute {
+    /// Starts advancing the objects up to the time `until` in steps of at most `dt`, splitting
+    /// them between as many threads as there are cores. The objects may start at different
+    /// times, e.g. when only some of them were rewound or edited. Linked objects depend on each
+    /// other, so if there are any links, all the objects are advanced together on a single
+    /// thread.
+    pub fn start(
+        objects: Vec<Object>,
+        constraints: Vec<Constraint>,
+        until: f64,
+        dt: f64,
+        method: IntegrationMethod,
+    ) -> Self {
+        let threads = if constraints.is_empty() {
+            thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            1
+        };
+        let chunk_size = objects.len().div_ceil(threads).max(1);
+        let steps = move |obj: &Object| ((until - obj.time()) / dt).ceil().max(0.0) as usize;
+        let total_work = if constraints.is_empty() {
+            objects.iter().map(steps).sum()
+        } else {
+            objects.first().map_or(0, steps) * objects.len()
+        };
+        let failed_at_start = objects.iter().filter(|obj| obj.blowup().is_some()).count();
+        let progress = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let mut objects = objects.into_iter().peekable();
+        let mut handles = vec![];
+        while objects.peek().is_some() {
+            let mut chunk: Vec<Object> = objects.by_ref().take(chunk_size).collect();
+            let progress = progress.clone();
+            let cancelled = cancelled.clone();
+            let constraints = constraints.clone();
+            handles.push(thread::spawn(move || {
+                let mut integrator = ObjectIntegrator::new(dt);
+                integrator.method = method;
+                if constraints.is_empty() {
+                    for obj in &mut chunk {
+                        for _ in 0..steps(obj) {
+                            let step = dt.min(until - obj.time());
+                            if step <= 0.0 || cancelled.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            obj.step(&mut integrator, step);
+                            progress.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                } else {
+                    // the linked objects are always at the same time
+                    for _ in 0..chunk.first().map_or(0, steps) {
+                        let step = dt.min(until - chunk[0].time());
+                        if step <= 0.0 || cancelled.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        step_linked(&mut chunk, &constraints, &mut integrator, step);
+                        progress.fetch_add(chunk.len(), Ordering::Relaxed);
+                    }
+                }
+                chunk
+            }));
+        }
+
+        Self {
+            until,
+            failed_at_start,
+            total_work,
+            progress,
+            cancelled,
+            handles,
+        }
+    }
+
+    /// Fraction of the work done, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        if self.total_work == 0 {
+            return 1.0;
+        }
+        self.progress.load(Ordering::Relaxed) as f32 / self.total_work as f32
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handles.iter().all(JoinHandle::is_finished)
+    }
+
+    /// Waits for the threads and returns the objects in their original order
+    pub fn join(mut self) -> Vec<Object> {
+        std::mem::take(&mut self.handles)
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("precompute thread panicked"))
+            .collect()
+    }
+}
+
+impl Drop for Precompute {
+    /// Stops the threads if the result is discarded; they finish their current steps on
+    /// their own
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+
+use nalgebra::Vector3;
+
+use crate::simulation::{
+    pos_to_lat_lon_elev, state_from_inertial, OrbitalElements, Position, TwoBodySystem, Velocity,
+    R_EQU,
+};
+
+use super::{ObjectDescription, ObjectKind};
+
+/// Ready-made scenarios that can be loaded in the state editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    ConvergingRing,
+    Geostationary,
+    Molniya,
+    NearSunSynchronous,
+    PolarLeo,
+    EarthMoonLagrange,
+    SunEarthLagrange,
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Preset::ConvergingRing => write!(f, "Converging ring (angular momentum)"),
+            Preset::Geostationary => write!(f, "Geostationary orbit"),
+            Preset::Molniya => write!(f, "Molniya orbit"),
+            Preset::NearSunSynchronous => write!(f, "Polar near-SSO orbit"),
+            Preset::PolarLeo => write!(f, "Polar low Earth orbit"),
+            Preset::EarthMoonLagrange => write!(f, "Earth-Moon Lagrange points"),
+            Preset::SunEarthLagrange => write!(f, "Sun-Earth Lagrange points"),
+        }
+    }
+}
+
+impl Preset {
+    pub const ALL: [Preset; 7] = [
+        Preset::ConvergingRing,
+        Preset::Geostationary,
+        Preset::Molniya,
+        Preset::NearSunSynchronous,
+        Preset::PolarLeo,
+        Preset::EarthMoonLagrange,
+        Preset::SunEarthLagrange,
+    ];
+
+    pub fn objects(&self) -> Vec<ObjectDescription> {
+        match self {
+            Preset::ConvergingRing => vec![ObjectDescription {
+                lat: "45".to_string(),
+                kind: ObjectKind::default_converging_ring(),
+                color: [0.2, 0.6, 1.0],
+                ..Default::default()
+            }],
+            Preset::Geostationary => vec![orbiting_object(
+                OrbitalElements {
+                    semi_major_axis: 42_164e3,
+                    eccentricity: 0.0,
+                    inclination: 0.0,
+                    raan: 0.0,
+                    arg_perigee: 0.0,
+                    true_anomaly: 0.0,
+                },
+                [1.0, 0.8, 0.2],
+            )],
+            Preset::Molniya => vec![orbiting_object(
+                OrbitalElements {
+                    semi_major_axis: 26_600e3,
+                    eccentricity: 0.74,
+                    inclination: 63.4,
+                    raan: 0.0,
+                    arg_perigee: 270.0,
+                    true_anomaly: 0.0,
+                },
+                [1.0, 0.3, 0.3],
+            )],
+            Preset::NearSunSynchronous => vec![orbiting_object(
+                OrbitalElements {
+                    semi_major_axis: R_EQU + 700e3,
+                    eccentricity: 0.0,
+                    inclination: 98.19,
+                    raan: 90.0,
+                    arg_perigee: 0.0,
+                    true_anomaly: 0.0,
+                },
+                [0.3, 1.0, 0.4],
+            )],
+            Preset::PolarLeo => vec![orbiting_object(
+                OrbitalElements {
+                    semi_major_axis: R_EQU + 500e3,
+                    eccentricity: 0.0,
+                    inclination: 90.0,
+                    raan: 0.0,
+                    arg_perigee: 0.0,
+                    true_anomaly: 0.0,
+                },
+                [0.3, 0.7, 1.0],
+            )],
+            Preset::EarthMoonLagrange => lagrange_objects(TwoBodySystem::EarthMoon),
+            Preset::SunEarthLagrange => lagrange_objects(TwoBodySystem::SunEarth),
+        }
+    }
+
+    /// The two-body system whose co-rotating frame the scenario uses
+    pub fn two_body(&self) -> Option<TwoBodySystem> {
+        match self {
+            Preset::EarthMoonLagrange => Some(TwoBodySystem::EarthMoon),
+            Preset::SunEarthLagrange => Some(TwoBodySystem::SunEarth),
+            _ => None,
+        }
+    }
+
+    /// A time step suitable for the scenario, if it differs from whatever the user has chosen
+    pub fn time_step(&self) -> Option<f64> {
+        match self {
+            Preset::ConvergingRing => None,
+            Preset::Geostationary => Some(500.0),
+            Preset::Molniya => Some(100.0),
+            Preset::NearSunSynchronous | Preset::PolarLeo => Some(10.0),
+            Preset::EarthMoonLagrange | Preset::SunEarthLagrange => Some(1000.0),
+        }
+    }
+
+    /// An explanation of what the scenario demonstrates
+    pub fn notes(&self) -> Option<&'static str> {
+        match self {
+            Preset::ConvergingRing => None,
+            Preset::Geostationary => Some(
+                "A geostationary satellite orbits once per sidereal day in the plane of the \
+                 equator, in the same direction as the Earth rotates. In the frame rotating with \
+                 the Earth its angular velocity is exactly cancelled, so it hangs motionless over \
+                 one point: gravity is balanced by the centrifugal force and, since the satellite \
+                 doesn't move, there is no Coriolis force. Lower the rotation of the reference \
+                 frame to see the actual orbit.",
+            ),
+            Preset::Molniya => Some(
+                "A highly eccentric orbit with a period of half a sidereal day, inclined at 63.4°. \
+                 The satellite spends most of its time near the apogee above high northern \
+                 latitudes, tracing loops there in the rotating frame.",
+            ),
+            Preset::NearSunSynchronous => Some(
+                "A near-polar, slightly retrograde orbit with the height and inclination of a \
+                 sun-synchronous one. For a real satellite the Earth's oblateness turns the \
+                 orbital plane once per year, keeping it fixed relative to the Sun; the \
+                 simulation uses point-mass gravity, so here the plane stays fixed in inertial \
+                 space and the orbit isn't actually sun-synchronous.",
+            ),
+            Preset::PolarLeo => Some(
+                "A polar orbit 500 km above the surface. The orbital plane is fixed in inertial \
+                 space, so in the rotating frame the ground track shifts westwards by about 24° \
+                 of longitude every orbit.",
+            ),
+            Preset::EarthMoonLagrange | Preset::SunEarthLagrange => Some(
+                "Objects placed at rest at the five Lagrange points, in the frame co-rotating \
+                 with the two bodies. There the gravity of both bodies is balanced by the \
+                 centrifugal force. L1, L2 and L3 are unstable, so the objects slowly drift \
+                 away; near L4 and L5 the Coriolis force turns the drift into loops around \
+                 the points. Set the rotation of the reference frame to 1 to view the scene \
+                 in the co-rotating frame.",
+            ),
+        }
+    }
+}
+
+/// A satellite placed on the given orbit at t=0
+fn orbiting_object(elements: OrbitalElements, color: [f32; 3]) -> ObjectDescription {
+    ObjectDescription {
+        kind: ObjectKind::satellite(&elements),
+        color,
+        ..Default::default()
+    }
+}
+
+/// Free objects at rest at the Lagrange points of the system, in its co-rotating frame
+fn lagrange_objects(system: TwoBodySystem) -> Vec<ObjectDescription> {
+    let omega = Vector3::new(0.0, system.mean_motion(), 0.0);
+    system
+        .lagrange_points()
+        .iter()
+        .enumerate()
+        .map(|(i, point)| {
+            let (pos, vel) = state_from_inertial(*point, omega.cross(point));
+            ObjectDescription {
+                name: format!("L{}", i + 1),
+                ..free_object(pos, vel, [0.8, 0.8, 0.8])
+            }
+        })
+        .collect()
+}
+
+fn free_object(pos: Position, vel: Velocity, color: [f32; 3]) -> ObjectDescription {
+    let (lat, lon, elev) = pos_to_lat_lon_elev(pos.pos());
+    let (vel_e, vel_n, vel_u) = vel.to_east_north_up(pos);
+    ObjectDescription {
+        name: String::new(),
+        lat: lat.to_string(),
+        lon: lon.to_string(),
+        elev: elev.to_string(),
+        kind: ObjectKind::Free {
+            vel_e: vel_e.to_string(),
+            vel_n: vel_n.to_string(),
+            vel_u: vel_u.to_string(),
+            gravity: "1".to_string(),
+            friction: "0".to_string(),
+            drag: "0".to_string(),
+            charge: "0".to_string(),
+        },
+        color,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,78 @@
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::{CameraBookmark, Exercise, FrameRotationStop, LinkDescription, ObjectDescription};
+use crate::simulation::{Atmosphere, IntegrationMethod, SpinRamp, TwoBodySystem};
+
+/// An initial configuration of the simulation that can be loaded from a RON file
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub objects: Vec<ObjectDescription>,
+    /// Springs and tethers between the objects
+    #[serde(default)]
+    pub links: Vec<LinkDescription>,
+    /// Two-body system whose co-rotating frame is used
+    #[serde(default)]
+    pub two_body: Option<TwoBodySystem>,
+    /// Change of the Earth's rotation rate over the run
+    #[serde(default)]
+    pub spin_ramp: Option<SpinRamp>,
+    /// Model of the air density used for the drag
+    #[serde(default)]
+    pub atmosphere: Atmosphere,
+    /// Time step to use instead of the default one
+    #[serde(default)]
+    pub time_step: Option<f64>,
+    /// Numerical method for advancing the objects
+    #[serde(default)]
+    pub integration: IntegrationMethod,
+    /// Angular velocity of the reference frame, as a fraction of the Earth's
+    #[serde(default)]
+    pub omega: Option<f64>,
+    /// Whether to start the simulation right after loading
+    #[serde(default)]
+    pub autostart: bool,
+    /// Settings of the external camera to move between during a demonstration
+    #[serde(default)]
+    pub camera_bookmarks: Vec<CameraBookmark>,
+    /// Named rotation rates of the frame, offered next to the built-in ones
+    #[serde(default)]
+    pub frame_rotation_stops: Vec<FrameRotationStop>,
+    /// Questions to be answered by the student before running the simulation
+    #[serde(default)]
+    pub exercises: Vec<Exercise>,
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    /// The time step isn't a positive number of seconds
+    InvalidTimeStep(f64),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScenarioError::Io(err) => write!(f, "couldn't read the scenario file: {}", err),
+            ScenarioError::Parse(err) => write!(f, "couldn't parse the scenario: {}", err),
+            ScenarioError::InvalidTimeStep(time_step) => {
+                write!(f, "the time step must be positive, not {}", time_step)
+            }
+        }
+    }
+}
+
+impl Scenario {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScenarioError> {
+        let contents = fs::read_to_string(path).map_err(ScenarioError::Io)?;
+        let scenario: Self = ron::from_str(&contents).map_err(ScenarioError::Parse)?;
+        match scenario.time_step {
+            Some(time_step) if !(time_step.is_finite() && time_step > 0.0) => {
+                Err(ScenarioError::InvalidTimeStep(time_step))
+            }
+            _ => Ok(scenario),
+        }
+    }
+}
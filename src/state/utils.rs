@@ -23,6 +23,8 @@ fn get_coords_at_dist(lat: f64, lon: f64, dir: f64, dist: f64) -> (f64, f64) {
     (new_lat, new_lon)
 }
 
+/// Particles at a constant altitude, flowing out of the high at the point they start from; the
+/// high itself is a `PressureSystem` of the simulation
 pub fn anticyclone(
     lat: f64,
     lon: f64,
@@ -43,22 +45,23 @@ pub fn anticyclone(
             Object::new(pos, vel)
                 .with_color(color.0, color.1, color.2)
                 .with_radius(100e3)
+                .with_const_alt(elev)
         })
         .collect()
 }
 
+/// A ring of particles at a constant altitude, flowing into the low at its center; the low
+/// itself is a `PressureSystem` of the simulation
 pub fn cyclone(
     lat: f64,
     lon: f64,
     elev: f64,
     radius: f64,
-    attractor_coeff: f64,
     vel: f64,
     vel_up: f64,
     num_objects: usize,
     color: (f32, f32, f32),
 ) -> Vec<Object> {
-    let center_pos = Position::from_lat_lon_elev(lat, lon, elev);
     (0..num_objects)
         .into_iter()
         .map(|index| {
@@ -71,10 +74,37 @@ pub fn cyclone(
             Object::new(pos, vel)
                 .with_color(color.0, color.1, color.2)
                 .with_radius(100e3)
+                .with_const_alt(elev)
+        })
+        .collect()
+}
+
+/// A ring of particles initially at rest relative to the surface, pulled towards the center with
+/// a constant acceleration
+pub fn converging_ring(
+    lat: f64,
+    lon: f64,
+    elev: f64,
+    radius: f64,
+    accel: f64,
+    num_objects: usize,
+    color: (f32, f32, f32),
+) -> Vec<Object> {
+    let center_pos = Position::from_lat_lon_elev(lat, lon, elev);
+    (0..num_objects)
+        .map(|index| {
+            let azim = 2.0 * PI / (num_objects as f64) * (index as f64);
+            let (nlat, nlon) = get_coords_at_dist(lat, lon, azim.to_degrees(), radius);
+            let pos = Position::from_lat_lon_elev(nlat, nlon, elev);
+            let vel = Velocity::from_east_north_up(pos, 0.0, 0.0, 0.0);
+            Object::new(pos, vel)
+                .with_color(color.0, color.1, color.2)
+                .with_radius(50e3)
+                .with_const_alt(elev)
                 .with_attractor(Box::new(move |pos| {
                     let pos_diff = center_pos.to_omega(pos.omega()).pos() - pos.pos();
-                    let pos_norm = pos_diff.norm();
-                    pos_diff / pos_norm / pos_norm * attractor_coeff
+                    // no direction to pull in once the object has reached the center
+                    pos_diff.try_normalize(1e-9).unwrap_or_else(Vector3::zeros) * accel
                 }))
         })
         .collect()
@@ -0,0 +1,406 @@
+use crate::simulation::{Atmosphere, Position, GM, R_POL};
+
+use super::{
+    description::PENDULUM_COEFF, numbers::parse_number, DecimalSeparator, InitialStateDefinition,
+    ObjectDescription, ObjectKind, SurfaceMode,
+};
+
+/// The pendulum has to swing at least this many time steps per period to be simulated
+/// accurately
+const MIN_STEPS_PER_SWING: f64 = 20.0;
+/// A projectile has to fly at least this many time steps, so that the impact is placed
+/// accurately
+const MIN_STEPS_PER_FLIGHT: f64 = 100.0;
+/// Speeds above the escape velocity are fixed to this fraction of it
+const ESCAPE_FIX_FRACTION: f64 = 0.99;
+
+/// A suspicious input in the state editor, which would make the objects behave unphysically
+#[derive(Debug, Clone)]
+pub struct InputWarning {
+    pub message: String,
+    fix: Option<Fix>,
+}
+
+/// A correction of the input that makes the warning go away
+#[derive(Debug, Clone, Copy)]
+enum Fix {
+    /// Put the object on the surface
+    Elevation(usize),
+    /// Scale the velocity of the object by the factor
+    Speed(usize, f64),
+    /// Use the default number of particles
+    Particles(usize),
+    /// Use the given time step
+    TimeStep(f64),
+    /// Bring the restitution coefficient into the range from 0 to 1
+    Restitution(usize),
+    /// Use the default size and payload of the balloon where they are out of range
+    Balloon(usize),
+}
+
+impl InputWarning {
+    fn new(obj: &ObjectDescription, index: usize, message: String, fix: Option<Fix>) -> Self {
+        let name = if obj.name.is_empty() {
+            format!("Object {}", index)
+        } else {
+            obj.name.clone()
+        };
+        Self {
+            message: format!("{}: {}", name, message),
+            fix,
+        }
+    }
+
+    pub fn can_fix(&self) -> bool {
+        self.fix.is_some()
+    }
+
+    fn apply_fix(&self, def: &mut InitialStateDefinition, time_step: &mut f64) {
+        match self.fix {
+            Some(Fix::Elevation(index)) => {
+                def.objects[index].elev = "0".to_string();
+            }
+            Some(Fix::Speed(index, factor)) => {
+                let scale = |value: &mut String| {
+                    let scaled = parse_number(value).unwrap_or(0.0) * factor;
+                    *value = format!("{:.1}", scaled);
+                };
+                match &mut def.objects[index].kind {
+                    ObjectKind::Free {
+                        vel_e,
+                        vel_n,
+                        vel_u,
+                        ..
+                    } => {
+                        scale(vel_e);
+                        scale(vel_n);
+                        scale(vel_u);
+                    }
+                    ObjectKind::WeighingScale { vel_e, vel_n } => {
+                        scale(vel_e);
+                        scale(vel_n);
+                    }
+                    ObjectKind::Cyclone { vel, .. }
+                    | ObjectKind::Anticyclone { vel, .. }
+                    | ObjectKind::Foucault { vel, .. }
+                    | ObjectKind::Plane { vel, .. } => scale(vel),
+                    ObjectKind::Projectile { speed, .. } => scale(speed),
+                    _ => (),
+                }
+            }
+            Some(Fix::Particles(index)) => match &mut def.objects[index].kind {
+                ObjectKind::Cyclone { n_particles, .. } => {
+                    if let ObjectKind::Cyclone {
+                        n_particles: default,
+                        ..
+                    } = ObjectKind::default_cyclone()
+                    {
+                        *n_particles = default;
+                    }
+                }
+                ObjectKind::Anticyclone { n_particles, .. } => {
+                    if let ObjectKind::Anticyclone {
+                        n_particles: default,
+                        ..
+                    } = ObjectKind::default_anticyclone()
+                    {
+                        *n_particles = default;
+                    }
+                }
+                ObjectKind::ConvergingRing { n_particles, .. } => {
+                    if let ObjectKind::ConvergingRing {
+                        n_particles: default,
+                        ..
+                    } = ObjectKind::default_converging_ring()
+                    {
+                        *n_particles = default;
+                    }
+                }
+                _ => (),
+            },
+            Some(Fix::TimeStep(new_step)) => {
+                *time_step = time_step.min(new_step);
+            }
+            Some(Fix::Restitution(index)) => {
+                let obj = &mut def.objects[index];
+                obj.restitution = obj.restitution_f().clamp(0.0, 1.0).to_string();
+            }
+            Some(Fix::Balloon(index)) => {
+                if let (
+                    ObjectKind::Balloon {
+                        volume,
+                        payload,
+                        drag_area,
+                    },
+                    ObjectKind::Balloon {
+                        volume: default_volume,
+                        payload: default_payload,
+                        drag_area: default_drag_area,
+                    },
+                ) = (&mut def.objects[index].kind, ObjectKind::default_balloon())
+                {
+                    let valid = |value: &str, in_range: fn(f64) -> bool| {
+                        parse_number::<f64>(value).is_some_and(in_range)
+                    };
+                    if !valid(volume, |volume| volume > 0.0) {
+                        *volume = default_volume;
+                    }
+                    if !valid(payload, |payload| payload >= 0.0) {
+                        *payload = default_payload;
+                    }
+                    if !valid(drag_area, |area| area >= 0.0) {
+                        *drag_area = default_drag_area;
+                    }
+                }
+            }
+            None => (),
+        }
+    }
+}
+
+/// Looks for inputs that would produce unphysical or broken objects; the numbers in the
+/// messages are written with `separator`
+pub fn validate(
+    def: &InitialStateDefinition,
+    time_step: f64,
+    separator: DecimalSeparator,
+) -> Vec<InputWarning> {
+    let mut warnings = vec![];
+    for (index, obj) in def.objects.iter().enumerate() {
+        validate_object(
+            obj,
+            index,
+            def.atmosphere,
+            time_step,
+            separator,
+            &mut warnings,
+        );
+    }
+    warnings
+}
+
+/// Applies the fixes of all the warnings that have one
+pub fn fix_all(warnings: &[InputWarning], def: &mut InitialStateDefinition, time_step: &mut f64) {
+    for warning in warnings {
+        warning.apply_fix(def, time_step);
+    }
+}
+
+fn validate_object(
+    obj: &ObjectDescription,
+    index: usize,
+    atmosphere: Atmosphere,
+    time_step: f64,
+    separator: DecimalSeparator,
+    warnings: &mut Vec<InputWarning>,
+) {
+    let number = |x: f64| separator.number(x);
+    let elev = obj.elev_f();
+    let mut warn = |message: String, fix: Option<Fix>| {
+        warnings.push(InputWarning::new(obj, index, message, fix));
+    };
+
+    // such values would silently become the defaults
+    for (label, value, _) in obj.parameters() {
+        if parse_number::<f64>(value).is_none() {
+            warn(
+                format!("{} \"{}\" isn't a number", label.to_lowercase(), value),
+                None,
+            );
+        }
+    }
+
+    if obj.surface_mode == SurfaceMode::Bounce && !(0.0..=1.0).contains(&obj.restitution_f()) {
+        warn(
+            format!(
+                "the restitution coefficient {} would make the bounces gain energy or pass \
+                 through the surface",
+                number(obj.restitution_f())
+            ),
+            Some(Fix::Restitution(index)),
+        );
+    }
+
+    let parse = |value: &str| parse_number::<f64>(value).unwrap_or(0.0);
+    let (speed, gravity, n_particles) = match &obj.kind {
+        ObjectKind::Free {
+            vel_e,
+            vel_n,
+            vel_u,
+            gravity,
+            ..
+        } => {
+            let speed = (parse(vel_e).powi(2) + parse(vel_n).powi(2) + parse(vel_u).powi(2)).sqrt();
+            (Some(speed), parse_number(gravity).unwrap_or(1.0), None)
+        }
+        ObjectKind::WeighingScale { vel_e, vel_n } => {
+            let speed = (parse(vel_e).powi(2) + parse(vel_n).powi(2)).sqrt();
+            (Some(speed), 1.0, None)
+        }
+        ObjectKind::Cyclone {
+            n_particles, vel, ..
+        }
+        | ObjectKind::Anticyclone {
+            n_particles, vel, ..
+        } => (Some(parse(vel).abs()), 1.0, Some(n_particles)),
+        ObjectKind::Foucault { vel, .. } | ObjectKind::Plane { vel, .. } => {
+            (Some(parse(vel).abs()), 1.0, None)
+        }
+        ObjectKind::Projectile { speed, angle, .. } => {
+            // the flight time without drag, from the launch back down to the same elevation
+            let flight_time =
+                2.0 * parse(speed).abs() * parse(angle).to_radians().sin() / (GM / (R_POL * R_POL));
+            let max_step = (flight_time / MIN_STEPS_PER_FLIGHT).floor();
+            if max_step > 0.0 && time_step > max_step {
+                warn(
+                    format!(
+                        "the time step of {} s is too long to place the impact of a flight \
+                         lasting about {:.0} s",
+                        number(time_step),
+                        number(flight_time)
+                    ),
+                    Some(Fix::TimeStep(max_step)),
+                );
+            }
+            (Some(parse(speed).abs()), 1.0, None)
+        }
+        ObjectKind::ConvergingRing { n_particles, .. } => (None, 1.0, Some(n_particles)),
+        ObjectKind::PlumbBob { .. } | ObjectKind::Balloon { .. } => (None, 1.0, None),
+        ObjectKind::Satellite {
+            semi_major_axis,
+            eccentricity,
+            ..
+        } => {
+            let a = parse_number(semi_major_axis).unwrap_or(6778.0) * 1e3;
+            let e = parse_number(eccentricity).unwrap_or(0.0);
+            if !(0.0..1.0).contains(&e) {
+                warn(
+                    format!("eccentricity {} doesn't describe a closed orbit", number(e)),
+                    None,
+                );
+            } else if a * (1.0 - e) < R_POL {
+                warn(
+                    format!(
+                        "the perigee is {:.0} km below the surface",
+                        number((R_POL - a * (1.0 - e)) / 1e3)
+                    ),
+                    None,
+                );
+            }
+            // the position follows from the orbit
+            return;
+        }
+    };
+
+    if elev < 0.0 {
+        warn(
+            format!("the elevation of {} m is below the surface", number(elev)),
+            Some(Fix::Elevation(index)),
+        );
+    }
+
+    if let Some(speed) = speed.filter(|_| gravity > 0.0) {
+        let r = Position::from_lat_lon_elev(obj.lat_f(), obj.lon_f(), elev.max(0.0))
+            .pos()
+            .norm();
+        let escape = (2.0 * GM * gravity / r).sqrt();
+        if speed > escape {
+            warn(
+                format!(
+                    "the speed of {:.0} m/s is above the escape velocity of {:.0} m/s",
+                    number(speed),
+                    number(escape)
+                ),
+                Some(Fix::Speed(index, ESCAPE_FIX_FRACTION * escape / speed)),
+            );
+        }
+    }
+
+    if let Some(n_particles) = n_particles {
+        if parse_number::<usize>(n_particles).unwrap_or(0) == 0 {
+            warn(
+                "there are no particles".to_string(),
+                Some(Fix::Particles(index)),
+            );
+        }
+    }
+
+    if let ObjectKind::Balloon {
+        volume,
+        payload,
+        drag_area,
+    } = &obj.kind
+    {
+        let (volume, payload, drag_area) = (parse(volume), parse(payload), parse(drag_area));
+        let limits = [
+            (volume, "volume", "m³", volume <= 0.0),
+            (payload, "payload", "kg", payload < 0.0),
+            (drag_area, "drag area", "m²", drag_area < 0.0),
+        ];
+        // such a balloon can't be simulated, so the other checks are pointless
+        let mut invalid = false;
+        for (value, label, unit, out_of_range) in limits {
+            if out_of_range {
+                invalid = true;
+                warn(
+                    format!(
+                        "the balloon's {} of {} {} is impossible",
+                        label,
+                        number(value),
+                        unit
+                    ),
+                    Some(Fix::Balloon(index)),
+                );
+            }
+        }
+        if invalid {
+            return;
+        }
+    }
+
+    if let Some((volume, mass, drag_area)) = obj.balloon(atmosphere) {
+        let density = atmosphere.air_density(elev.max(0.0));
+        let lift = density * volume;
+        if lift <= mass {
+            warn(
+                format!(
+                    "the balloon weighs {:.0} kg with the helium, but only lifts {:.0} kg",
+                    number(mass),
+                    number(lift)
+                ),
+                None,
+            );
+        } else if drag_area > 0.0 {
+            // the drag brings the balloon to its terminal speed within about 1/rate seconds
+            let net = GM / (R_POL * R_POL) * (lift / mass - 1.0);
+            let rate = 2.0 * (net * 0.5 * drag_area / mass * density).sqrt();
+            let max_step = ((10.0 / rate).floor() / 10.0).max(0.1);
+            if time_step > max_step {
+                warn(
+                    format!(
+                        "the time step of {} s is too long for the balloon, which reaches its \
+                         rising speed within {:.1} s",
+                        number(time_step),
+                        number(1.0 / rate)
+                    ),
+                    Some(Fix::TimeStep(max_step)),
+                );
+            }
+        }
+    }
+
+    if matches!(obj.kind, ObjectKind::Foucault { .. }) {
+        let period = std::f64::consts::TAU / PENDULUM_COEFF.sqrt();
+        let max_step = (period / MIN_STEPS_PER_SWING).floor();
+        if time_step > max_step {
+            warn(
+                format!(
+                    "the time step of {} s is too long for the pendulum's period of {:.0} s",
+                    number(time_step),
+                    number(period)
+                ),
+                Some(Fix::TimeStep(max_step)),
+            );
+        }
+    }
+}